@@ -27,7 +27,7 @@ fn get_autostart_dir() -> Option<PathBuf> {
 }
 
 /// Get the path to the autostart desktop file
-fn get_autostart_file() -> Option<PathBuf> {
+pub(crate) fn get_autostart_file() -> Option<PathBuf> {
     get_autostart_dir().map(|p| p.join("win11-clipboard-history.desktop"))
 }
 
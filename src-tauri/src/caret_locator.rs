@@ -0,0 +1,345 @@
+//! Caret Locator Module
+//! Finds the text caret of the focused application through AT-SPI, so the window can open
+//! next to it. The accessibility tree is walked from the active window down to the focused
+//! accessible, whose Text interface reports the caret offset and its screen extents.
+
+use crate::config_manager::ScreenRect;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::OwnedObjectPath;
+
+const A11Y_BUS_NAME: &str = "org.a11y.Bus";
+const A11Y_BUS_PATH: &str = "/org/a11y/bus";
+const REGISTRY_BUS_NAME: &str = "org.a11y.atspi.Registry";
+const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+const ACCESSIBLE_INTERFACE: &str = "org.a11y.atspi.Accessible";
+const TEXT_INTERFACE: &str = "org.a11y.atspi.Text";
+
+/// AtspiStateType bits used while walking the tree
+const STATE_ACTIVE: u32 = 1;
+const STATE_FOCUSED: u32 = 12;
+const STATE_SHOWING: u32 = 25;
+const STATE_MANAGES_DESCENDANTS: u32 = 31;
+
+/// ATSPI_COORD_TYPE_SCREEN
+const COORD_TYPE_SCREEN: u32 = 0;
+
+/// Upper bound on accessibles visited, so huge trees (browsers) can't stall the window
+const MAX_VISITED: usize = 1500;
+/// How long the whole lookup may take before the window opens without a caret
+const LOOKUP_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// An accessible object: owning bus name and object path
+type ObjectRef = (String, OwnedObjectPath);
+
+/// Accessibility bus connection, reused across lookups
+static A11Y_CONNECTION: Lazy<Mutex<Option<Connection>>> = Lazy::new(|| Mutex::new(None));
+
+/// A caret lookup running on its own thread, so a hung application can't block the
+/// window from opening. Dropping it stops the walk.
+pub struct CaretLookup {
+    rx: mpsc::Receiver<Option<ScreenRect>>,
+    cancelled: Arc<AtomicBool>,
+    deadline: Instant,
+}
+
+impl CaretLookup {
+    /// Starts looking for the text caret in the focused application
+    pub fn start() -> Self {
+        let (tx, rx) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let stop = cancelled.clone();
+        thread::spawn(move || {
+            let rect = a11y_connection()
+                .map_err(|e| eprintln!("[CaretLocator] Accessibility bus unavailable: {}", e))
+                .ok()
+                .and_then(|conn| find_caret(&conn, &stop));
+            let _ = tx.send(rect);
+        });
+        Self {
+            rx,
+            cancelled,
+            deadline: Instant::now() + LOOKUP_TIMEOUT,
+        }
+    }
+
+    /// Screen rectangle of the caret, if the application exposes one in time
+    pub fn wait(self) -> Option<ScreenRect> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        self.rx.recv_timeout(left).ok().flatten()
+    }
+}
+
+impl Drop for CaretLookup {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The cached accessibility bus connection, reconnecting if the bus went away
+fn a11y_connection() -> Result<Connection, String> {
+    let mut cached = A11Y_CONNECTION.lock();
+    if let Some(conn) = cached.as_ref().filter(|conn| is_alive(conn)) {
+        return Ok(conn.clone());
+    }
+    let conn = connect_a11y_bus()?;
+    *cached = Some(conn.clone());
+    Ok(conn)
+}
+
+fn is_alive(conn: &Connection) -> bool {
+    conn.call_method(
+        Some("org.freedesktop.DBus"),
+        "/org/freedesktop/DBus",
+        Some("org.freedesktop.DBus.Peer"),
+        "Ping",
+        &(),
+    )
+    .is_ok()
+}
+
+/// Connects to the accessibility bus announced on the session bus. Calls time out with
+/// the lookup, so a hung application can't keep the lookup thread around.
+fn connect_a11y_bus() -> Result<Connection, String> {
+    let session = Connection::session().map_err(|e| e.to_string())?;
+    let address: String = session
+        .call_method(
+            Some(A11Y_BUS_NAME),
+            A11Y_BUS_PATH,
+            Some(A11Y_BUS_NAME),
+            "GetAddress",
+            &(),
+        )
+        .and_then(|reply| reply.body().deserialize())
+        .map_err(|e| e.to_string())?;
+    zbus::blocking::connection::Builder::address(address.as_str())
+        .and_then(|builder| builder.method_timeout(LOOKUP_TIMEOUT).build())
+        .map_err(|e| e.to_string())
+}
+
+fn children(conn: &Connection, (name, path): &ObjectRef) -> Vec<ObjectRef> {
+    conn.call_method(
+        Some(name.as_str()),
+        path.as_str(),
+        Some(ACCESSIBLE_INTERFACE),
+        "GetChildren",
+        &(),
+    )
+    .and_then(|reply| reply.body().deserialize())
+    .unwrap_or_default()
+}
+
+/// Whether the state set (two 32-bit words) has the state
+fn has_state(states: &[u32], state: u32) -> bool {
+    states
+        .get((state / 32) as usize)
+        .is_some_and(|word| word & (1 << (state % 32)) != 0)
+}
+
+fn states(conn: &Connection, (name, path): &ObjectRef) -> Vec<u32> {
+    conn.call_method(
+        Some(name.as_str()),
+        path.as_str(),
+        Some(ACCESSIBLE_INTERFACE),
+        "GetState",
+        &(),
+    )
+    .and_then(|reply| reply.body().deserialize())
+    .unwrap_or_default()
+}
+
+/// The focused accessible inside the active window of any application. Gives up once
+/// `stop` is set.
+fn focused_accessible(conn: &Connection, stop: &AtomicBool) -> Option<ObjectRef> {
+    let stopped = || stop.load(Ordering::Relaxed);
+    let root = (
+        REGISTRY_BUS_NAME.to_string(),
+        OwnedObjectPath::try_from(ROOT_PATH).ok()?,
+    );
+    let active_window = children(conn, &root)
+        .iter()
+        .flat_map(|app| children(conn, app))
+        .take_while(|_| !stopped())
+        .find(|window| has_state(&states(conn, window), STATE_ACTIVE))?;
+
+    // Depth-first, skipping hidden subtrees and containers that manage their own
+    // descendants (tables, lists), which can hold thousands of children
+    let mut stack = vec![active_window];
+    let mut visited = HashSet::new();
+    while let Some(object) = stack.pop() {
+        if stopped() {
+            return None;
+        }
+        if visited.len() >= MAX_VISITED || !visited.insert(object.clone()) {
+            continue;
+        }
+        let states = states(conn, &object);
+        if has_state(&states, STATE_FOCUSED) {
+            return Some(object);
+        }
+        if has_state(&states, STATE_SHOWING) && !has_state(&states, STATE_MANAGES_DESCENDANTS) {
+            stack.extend(children(conn, &object).into_iter().rev());
+        }
+    }
+    None
+}
+
+/// Caret extents of the focused accessible, when it implements Text
+fn find_caret(conn: &Connection, stop: &AtomicBool) -> Option<ScreenRect> {
+    let (name, path) = focused_accessible(conn, stop)?;
+    let text = Proxy::new(conn, name.as_str(), path.as_str(), TEXT_INTERFACE).ok()?;
+    let offset: i32 = text.get_property("CaretOffset").ok()?;
+
+    let extents = |offset: i32| -> Option<ScreenRect> {
+        let (x, y, width, height): (i32, i32, i32, i32) = text
+            .call("GetCharacterExtents", &(offset, COORD_TYPE_SCREEN))
+            .ok()?;
+        (width > 0 || height > 0).then_some(ScreenRect {
+            x,
+            y,
+            width,
+            height,
+        })
+    };
+
+    // Past the last character there is nothing to measure: use the right edge of the
+    // character before the caret
+    extents(offset).or_else(|| {
+        let before = extents(offset.checked_sub(1).filter(|o| *o >= 0)?)?;
+        Some(ScreenRect {
+            x: before.x + before.width,
+            width: 0,
+            ..before
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portal::test_bus::PrivateBus;
+
+    /// Mock accessible with fixed children and states
+    struct MockAccessible {
+        children: Vec<&'static str>,
+        states: Vec<u32>,
+    }
+
+    fn state_set(states: &[u32]) -> Vec<u32> {
+        let mut words = vec![0, 0];
+        for state in states {
+            words[(state / 32) as usize] |= 1 << (state % 32);
+        }
+        words
+    }
+
+    #[zbus::interface(name = "org.a11y.atspi.Accessible")]
+    impl MockAccessible {
+        fn get_children(&self) -> Vec<(String, OwnedObjectPath)> {
+            self.children
+                .iter()
+                .map(|path| {
+                    (
+                        REGISTRY_BUS_NAME.to_string(),
+                        OwnedObjectPath::try_from(*path).unwrap(),
+                    )
+                })
+                .collect()
+        }
+
+        fn get_state(&self) -> Vec<u32> {
+            self.states.clone()
+        }
+    }
+
+    /// Mock text entry with the caret after the third and last character
+    struct MockText;
+
+    #[zbus::interface(name = "org.a11y.atspi.Text")]
+    impl MockText {
+        #[zbus(property)]
+        fn caret_offset(&self) -> i32 {
+            3
+        }
+
+        fn get_character_extents(&self, offset: i32, _coord_type: u32) -> (i32, i32, i32, i32) {
+            if offset < 3 {
+                (100 + offset * 8, 200, 8, 16)
+            } else {
+                (0, 0, 0, 0)
+            }
+        }
+    }
+
+    #[test]
+    fn test_has_state() {
+        let states = state_set(&[STATE_FOCUSED, STATE_MANAGES_DESCENDANTS]);
+        assert!(has_state(&states, STATE_FOCUSED));
+        assert!(has_state(&states, STATE_MANAGES_DESCENDANTS));
+        assert!(!has_state(&states, STATE_ACTIVE));
+        assert!(!has_state(&[], STATE_FOCUSED));
+    }
+
+    #[test]
+    #[ignore = "needs dbus-daemon"]
+    fn test_find_caret_with_mock_tree() {
+        let bus = PrivateBus::start().expect("failed to start dbus-daemon");
+
+        let node = |children: Vec<&'static str>, states: &[u32]| MockAccessible {
+            children,
+            states: state_set(states),
+        };
+        let _server = bus
+            .connect()
+            .name(REGISTRY_BUS_NAME)
+            .unwrap()
+            .serve_at(ROOT_PATH, node(vec!["/app"], &[]))
+            .unwrap()
+            .serve_at("/app", node(vec!["/app/inactive", "/app/window"], &[]))
+            .unwrap()
+            .serve_at("/app/inactive", node(vec![], &[STATE_SHOWING]))
+            .unwrap()
+            .serve_at(
+                "/app/window",
+                node(
+                    vec!["/app/window/label", "/app/window/entry"],
+                    &[STATE_ACTIVE, STATE_SHOWING],
+                ),
+            )
+            .unwrap()
+            .serve_at("/app/window/label", node(vec![], &[STATE_SHOWING]))
+            .unwrap()
+            .serve_at(
+                "/app/window/entry",
+                node(vec![], &[STATE_SHOWING, STATE_FOCUSED]),
+            )
+            .unwrap()
+            .serve_at("/app/window/entry", MockText)
+            .unwrap()
+            .build()
+            .unwrap();
+        let client = bus.connect().build().unwrap();
+
+        let running = AtomicBool::new(false);
+        let (_, focused) = focused_accessible(&client, &running).unwrap();
+        assert_eq!(focused.as_str(), "/app/window/entry");
+        assert_eq!(
+            find_caret(&client, &running),
+            Some(ScreenRect {
+                x: 124,
+                y: 200,
+                width: 0,
+                height: 16,
+            })
+        );
+
+        // A stopped lookup walks no further
+        assert_eq!(focused_accessible(&client, &AtomicBool::new(true)), None);
+    }
+}
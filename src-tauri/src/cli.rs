@@ -0,0 +1,432 @@
+//! CLI Module
+//! Parses command-line subcommands. They are executed by the running instance:
+//! a second launch forwards its arguments through the single-instance plugin.
+//! History subcommands (export/import) go through our own D-Bus channel instead,
+//! so their result can be printed by the process that was invoked.
+
+use crate::clipboard_importers::ImportSource;
+use crate::history_transfer::{parse_date, ExportOptions, ImportMode};
+use crate::shortcut_chord::TABS;
+use std::path::{Path, PathBuf};
+
+/// A subcommand that acts on the running instance
+#[derive(Debug, Clone)]
+pub enum CliCommand {
+    /// `--export-history <FILE>`
+    ExportHistory {
+        path: PathBuf,
+        options: ExportOptions,
+    },
+    /// `--import-history <FILE>`
+    ImportHistory { path: PathBuf, mode: ImportMode },
+    /// `--import-from <SOURCE> [FILE]`
+    ImportFrom {
+        source: ImportSource,
+        path: Option<PathBuf>,
+        mode: ImportMode,
+    },
+    /// `--abort-typing`
+    AbortTyping,
+    /// `--paste-item <N>`
+    PasteItem { index: usize },
+    /// `--paste-plain`
+    PastePlain,
+    /// `--open-tab <TAB>`
+    OpenTab { tab: String },
+    /// `--toggle-pause`
+    TogglePause,
+}
+
+impl CliCommand {
+    /// Export/import: the invoking process waits for these and reports their result
+    pub fn reports_result(&self) -> bool {
+        matches!(
+            self,
+            Self::ExportHistory { .. } | Self::ImportHistory { .. } | Self::ImportFrom { .. }
+        )
+    }
+}
+
+/// Help text for the subcommands, printed by `--help`
+pub const SUBCOMMAND_HELP: &str = r#"HISTORY:
+        --export-history <FILE>  Export history to FILE (JSON, or zip with --bundle)
+            --pinned-only        Only export pinned items
+            --since <DATE>       Only export items copied on/after DATE (YYYY-MM-DD or RFC 3339)
+            --until <DATE>       Only export items copied on/before DATE
+            --no-images          Skip image items
+            --bundle             Write a zip bundle with images as PNG files
+        --import-history <FILE>  Import history from an export file or bundle
+            --replace            Replace current history instead of merging
+        --import-from <SOURCE> [FILE]
+                                 Import history from another clipboard manager
+                                 (copyq, gpaste, klipper, cliphist, clipman).
+                                 FILE overrides the default history location;
+                                 --replace is also accepted
+
+PASTING:
+        --abort-typing           Stop an ongoing type-out paste (bind this to a key
+                                 on Wayland, where the abort hotkey can't be grabbed)
+        --paste-item <N>         Paste the Nth history item into the focused window
+                                 without opening the history
+        --paste-plain            Paste the most recent text item as plain text
+        --open-tab <TAB>         Open the history on a tab
+                                 (clipboard, emoji, gifs, kaomoji, symbols)
+
+RECORDING:
+        --toggle-pause           Pause or resume recording the clipboard"#;
+
+/// Returns the value following `flag`, if the flag is present.
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<Result<&'a str, String>> {
+    let idx = args.iter().position(|a| a == flag)?;
+    Some(
+        args.get(idx + 1)
+            .filter(|v| !v.starts_with("--"))
+            .map(String::as_str)
+            .ok_or_else(|| format!("{} requires a value", flag)),
+    )
+}
+
+fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().any(|a| a == flag)
+}
+
+/// Resolves a path given on the command line against the caller's working directory.
+fn resolve_path(value: &str, cwd: &Path) -> PathBuf {
+    let path = PathBuf::from(value);
+    if path.is_absolute() {
+        path
+    } else {
+        cwd.join(path)
+    }
+}
+
+/// Parses a subcommand from `args`.
+/// Returns `None` when no subcommand is present (normal startup / toggle).
+pub fn parse_cli_command(args: &[String], cwd: &Path) -> Option<Result<CliCommand, String>> {
+    if let Some(value) = flag_value(args, "--export-history") {
+        return Some(value.and_then(|path| {
+            let since = flag_value(args, "--since")
+                .map(|v| v.and_then(|d| parse_date(d, false)))
+                .transpose()?;
+            let until = flag_value(args, "--until")
+                .map(|v| v.and_then(|d| parse_date(d, true)))
+                .transpose()?;
+
+            Ok(CliCommand::ExportHistory {
+                path: resolve_path(path, cwd),
+                options: ExportOptions {
+                    pinned_only: has_flag(args, "--pinned-only"),
+                    since,
+                    until,
+                    include_images: !has_flag(args, "--no-images"),
+                    bundle: has_flag(args, "--bundle"),
+                },
+            })
+        }));
+    }
+
+    if let Some(value) = flag_value(args, "--import-history") {
+        return Some(value.map(|path| CliCommand::ImportHistory {
+            path: resolve_path(path, cwd),
+            mode: if has_flag(args, "--replace") {
+                ImportMode::Replace
+            } else {
+                ImportMode::Merge
+            },
+        }));
+    }
+
+    if let Some(value) = flag_value(args, "--import-from") {
+        return Some(value.and_then(|name| {
+            let source = ImportSource::parse(name)
+                .ok_or_else(|| format!("Unknown import source '{}'", name))?;
+            let idx = args.iter().position(|a| a == "--import-from").unwrap_or(0);
+            let path = args
+                .get(idx + 2)
+                .filter(|v| !v.starts_with("--"))
+                .map(|v| resolve_path(v, cwd));
+
+            Ok(CliCommand::ImportFrom {
+                source,
+                path,
+                mode: if has_flag(args, "--replace") {
+                    ImportMode::Replace
+                } else {
+                    ImportMode::Merge
+                },
+            })
+        }));
+    }
+
+    if has_flag(args, "--abort-typing") {
+        return Some(Ok(CliCommand::AbortTyping));
+    }
+
+    if let Some(value) = flag_value(args, "--paste-item") {
+        return Some(value.and_then(|n| {
+            n.parse::<usize>()
+                .ok()
+                .filter(|&index| index >= 1)
+                .map(|index| CliCommand::PasteItem { index })
+                .ok_or_else(|| format!("Invalid item number '{}'", n))
+        }));
+    }
+
+    if has_flag(args, "--paste-plain") {
+        return Some(Ok(CliCommand::PastePlain));
+    }
+
+    if let Some(value) = flag_value(args, "--open-tab") {
+        return Some(value.and_then(|tab| {
+            let tab = tab.to_lowercase();
+            if TABS.contains(&tab.as_str()) {
+                Ok(CliCommand::OpenTab { tab })
+            } else {
+                Err(format!("Unknown tab '{}'", tab))
+            }
+        }));
+    }
+
+    if has_flag(args, "--toggle-pause") {
+        return Some(Ok(CliCommand::TogglePause));
+    }
+
+    None
+}
+
+#[cfg(target_os = "linux")]
+pub use channel::{forward, serve, CliHandler};
+
+/// D-Bus channel between a second launch and the running instance
+#[cfg(target_os = "linux")]
+mod channel {
+    use super::{parse_cli_command, CliCommand};
+    use std::path::Path;
+    use std::sync::Arc;
+    use zbus::blocking::{fdo::DBusProxy, Connection};
+    use zbus::names::BusName;
+
+    const CLI_BUS_NAME: &str = "dev.gustavosett.ClipboardHistory.Cli";
+    const CLI_PATH: &str = "/dev/gustavosett/ClipboardHistory/Cli";
+    const CLI_INTERFACE: &str = "dev.gustavosett.ClipboardHistory.Cli";
+
+    /// Runs a subcommand in the running instance; returns the message to print
+    pub type CliHandler = Arc<dyn Fn(CliCommand) -> Result<String, String> + Send + Sync>;
+
+    struct CliService {
+        handler: CliHandler,
+    }
+
+    #[zbus::interface(name = "dev.gustavosett.ClipboardHistory.Cli")]
+    impl CliService {
+        /// Returns whether the command succeeded and the message to print
+        fn run(&self, argv: Vec<String>, cwd: String) -> (bool, String) {
+            let result = match parse_cli_command(&argv, Path::new(&cwd)) {
+                Some(Ok(command)) => (self.handler)(command),
+                Some(Err(e)) => Err(e),
+                None => Err("No subcommand given".to_string()),
+            };
+            match result {
+                Ok(message) => (true, message),
+                Err(e) => (false, e),
+            }
+        }
+    }
+
+    /// Serves subcommands on the session bus. Keep the connection alive while running.
+    pub fn serve(handler: CliHandler) -> Result<Connection, String> {
+        serve_on(zbus::blocking::connection::Builder::session(), handler)
+    }
+
+    fn serve_on(
+        builder: zbus::Result<zbus::blocking::connection::Builder<'static>>,
+        handler: CliHandler,
+    ) -> Result<Connection, String> {
+        builder
+            .and_then(|b| b.name(CLI_BUS_NAME))
+            .and_then(|b| b.serve_at(CLI_PATH, CliService { handler }))
+            .and_then(|b| b.build())
+            .map_err(|e| format!("Failed to serve CLI commands: {}", e))
+    }
+
+    /// Runs `args` in the running instance and returns its result.
+    /// `None` when no instance is running.
+    pub fn forward(args: &[String], cwd: &Path) -> Option<Result<String, String>> {
+        forward_on(&Connection::session().ok()?, args, cwd)
+    }
+
+    fn forward_on(
+        conn: &Connection,
+        args: &[String],
+        cwd: &Path,
+    ) -> Option<Result<String, String>> {
+        let name = BusName::try_from(CLI_BUS_NAME).ok()?;
+        if !DBusProxy::new(conn).ok()?.name_has_owner(name).ok()? {
+            return None;
+        }
+
+        let reply = conn
+            .call_method(
+                Some(CLI_BUS_NAME),
+                CLI_PATH,
+                Some(CLI_INTERFACE),
+                "Run",
+                &(args, cwd.to_string_lossy().as_ref()),
+            )
+            .and_then(|reply| reply.body().deserialize::<(bool, String)>());
+        Some(match reply {
+            Ok((true, message)) => Ok(message),
+            Ok((false, error)) => Err(error),
+            Err(e) => Err(format!("The running instance did not answer: {}", e)),
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::portal::test_bus::PrivateBus;
+
+        #[test]
+        #[ignore = "needs dbus-daemon"]
+        fn test_forward_returns_result() {
+            let bus = PrivateBus::start().expect("failed to start dbus-daemon");
+            let client = bus.connect().build().unwrap();
+            let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+            // Nothing serves the channel yet
+            assert!(
+                forward_on(&client, &args(&["app", "--toggle-pause"]), Path::new("/")).is_none()
+            );
+
+            let handler: CliHandler = Arc::new(|command| match command {
+                CliCommand::ExportHistory { path, .. } => {
+                    Ok(format!("Exported to {}", path.display()))
+                }
+                _ => Err("Not supported".to_string()),
+            });
+            let _server = serve_on(Ok(bus.connect()), handler).unwrap();
+
+            assert_eq!(
+                forward_on(
+                    &client,
+                    &args(&["app", "--export-history", "h.json"]),
+                    Path::new("/tmp")
+                ),
+                Some(Ok("Exported to /tmp/h.json".to_string()))
+            );
+            assert_eq!(
+                forward_on(
+                    &client,
+                    &args(&["app", "--paste-item", "x"]),
+                    Path::new("/")
+                ),
+                Some(Err("Invalid item number 'x'".to_string()))
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_export() {
+        let cmd = parse_cli_command(
+            &args(&[
+                "app",
+                "--export-history",
+                "out.zip",
+                "--bundle",
+                "--pinned-only",
+            ]),
+            Path::new("/home/user"),
+        )
+        .unwrap()
+        .unwrap();
+
+        match cmd {
+            CliCommand::ExportHistory { path, options } => {
+                assert_eq!(path, PathBuf::from("/home/user/out.zip"));
+                assert!(options.bundle && options.pinned_only && options.include_images);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_import_and_errors() {
+        let cmd = parse_cli_command(
+            &args(&["app", "--import-history", "/tmp/h.json", "--replace"]),
+            Path::new("/"),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(matches!(
+            cmd,
+            CliCommand::ImportHistory {
+                mode: ImportMode::Replace,
+                ..
+            }
+        ));
+
+        assert!(
+            parse_cli_command(&args(&["app", "--export-history"]), Path::new("/"))
+                .unwrap()
+                .is_err()
+        );
+        assert!(parse_cli_command(&args(&["app", "--background"]), Path::new("/")).is_none());
+        assert!(matches!(
+            parse_cli_command(&args(&["app", "--abort-typing"]), Path::new("/")),
+            Some(Ok(CliCommand::AbortTyping))
+        ));
+
+        let cmd = parse_cli_command(
+            &args(&["app", "--import-from", "CopyQ", "tab.dat"]),
+            Path::new("/home/user"),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(matches!(
+            cmd,
+            CliCommand::ImportFrom {
+                source: ImportSource::CopyQ,
+                path: Some(ref p),
+                mode: ImportMode::Merge,
+            } if p == Path::new("/home/user/tab.dat")
+        ));
+        assert!(
+            parse_cli_command(&args(&["app", "--import-from", "nope"]), Path::new("/"))
+                .unwrap()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_shortcut_actions() {
+        let parse = |list: &[&str]| parse_cli_command(&args(list), Path::new("/")).unwrap();
+
+        assert!(matches!(
+            parse(&["app", "--paste-item", "3"]),
+            Ok(CliCommand::PasteItem { index: 3 })
+        ));
+        assert!(parse(&["app", "--paste-item", "0"]).is_err());
+        assert!(matches!(
+            parse(&["app", "--open-tab", "Emoji"]),
+            Ok(CliCommand::OpenTab { ref tab }) if tab == "emoji"
+        ));
+        assert!(parse(&["app", "--open-tab", "settings"]).is_err());
+        assert!(matches!(
+            parse(&["app", "--paste-plain"]),
+            Ok(CliCommand::PastePlain)
+        ));
+        assert!(matches!(
+            parse(&["app", "--toggle-pause"]),
+            Ok(CliCommand::TogglePause)
+        ));
+    }
+}
@@ -0,0 +1,751 @@
+//! Clipboard Importers Module
+//! Reads the on-disk history of other Linux clipboard managers (CopyQ, GPaste, Klipper,
+//! cliphist, Clipman) and converts it into `ClipboardItem`s.
+
+use crate::clipboard_manager::ClipboardItem;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// CopyQ file name of the default "&clipboard" tab (the tab name is base64 encoded)
+const COPYQ_CLIPBOARD_TAB: &str = "copyq_tab_JmNsaXBib2FyZA==.dat";
+
+/// CopyQ shortens common MIME prefixes to a single digit when saving items
+const COPYQ_MIME_PREFIXES: &[(char, &str)] = &[
+    ('0', "application/x-copyq-"),
+    ('1', "text/"),
+    ('2', "application/"),
+    ('3', "image/"),
+];
+
+/// MIME type CopyQ uses to mark pinned items
+const COPYQ_MIME_PINNED: &str = "application/x-copyq-item-pinned";
+
+/// Marker of the new (compressed MIME) CopyQ item format
+const COPYQ_ITEM_V2: i32 = -2;
+
+/// Prefix of the preview cliphist prints for non-text entries
+const CLIPHIST_BINARY_PREVIEW: &str = "[[ binary data";
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// --- Data Structures ---
+
+/// Clipboard managers we can import from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    CopyQ,
+    GPaste,
+    Klipper,
+    Cliphist,
+    Clipman,
+}
+
+/// Import source as shown in the settings UI
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSourceInfo {
+    pub id: ImportSource,
+    pub name: &'static str,
+    /// Where the history is read from when no path is given
+    pub default_path: Option<String>,
+    /// True if the default history file (or the cliphist binary) was found
+    pub available: bool,
+}
+
+/// Content read from another manager, before conversion to a `ClipboardItem`
+#[derive(Debug, Clone, PartialEq)]
+enum EntryContent {
+    Text(String),
+    /// Encoded image (PNG, JPEG, ...)
+    Image(Vec<u8>),
+}
+
+/// One history entry read from another manager
+#[derive(Debug, Clone, PartialEq)]
+struct ImportedEntry {
+    content: EntryContent,
+    timestamp: Option<DateTime<Utc>>,
+    pinned: bool,
+}
+
+impl ImportedEntry {
+    fn text(text: String) -> Self {
+        Self {
+            content: EntryContent::Text(text),
+            timestamp: None,
+            pinned: false,
+        }
+    }
+
+    fn image(bytes: Vec<u8>) -> Self {
+        Self {
+            content: EntryContent::Image(bytes),
+            timestamp: None,
+            pinned: false,
+        }
+    }
+}
+
+impl ImportSource {
+    pub const ALL: [ImportSource; 5] = [
+        ImportSource::CopyQ,
+        ImportSource::GPaste,
+        ImportSource::Klipper,
+        ImportSource::Cliphist,
+        ImportSource::Clipman,
+    ];
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            ImportSource::CopyQ => "CopyQ",
+            ImportSource::GPaste => "GPaste",
+            ImportSource::Klipper => "Klipper",
+            ImportSource::Cliphist => "cliphist",
+            ImportSource::Clipman => "Clipman",
+        }
+    }
+
+    /// Parses a source name given on the command line (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|s| s.display_name().eq_ignore_ascii_case(value))
+    }
+
+    /// Location of the history this manager writes by default
+    pub fn default_path(self) -> Option<PathBuf> {
+        match self {
+            ImportSource::CopyQ => {
+                let dir = dirs::config_dir()?.join("copyq");
+                let tab = dir.join(COPYQ_CLIPBOARD_TAB);
+                if tab.exists() {
+                    return Some(tab);
+                }
+                // Fall back to the first saved tab
+                let mut tabs: Vec<PathBuf> = fs::read_dir(&dir)
+                    .ok()?
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| {
+                        p.file_name()
+                            .and_then(|n| n.to_str())
+                            .is_some_and(|n| n.starts_with("copyq_tab_") && n.ends_with(".dat"))
+                    })
+                    .collect();
+                tabs.sort();
+                tabs.into_iter().next().or(Some(tab))
+            }
+            ImportSource::GPaste => Some(dirs::data_dir()?.join("gpaste").join("history.xml")),
+            ImportSource::Klipper => Some(dirs::data_dir()?.join("klipper").join("history2.lst")),
+            ImportSource::Cliphist => Some(dirs::cache_dir()?.join("cliphist").join("db")),
+            ImportSource::Clipman => Some(dirs::data_dir()?.join("clipman.json")),
+        }
+    }
+}
+
+// --- Public API ---
+
+/// Lists the supported sources and whether their history was found
+pub fn list_sources() -> Vec<ImportSourceInfo> {
+    ImportSource::ALL
+        .into_iter()
+        .map(|source| {
+            let default_path = source.default_path();
+            let available = match source {
+                ImportSource::Cliphist => command_exists("cliphist"),
+                _ => default_path.as_ref().is_some_and(|p| p.exists()),
+            };
+            ImportSourceInfo {
+                id: source,
+                name: source.display_name(),
+                default_path: default_path.map(|p| p.to_string_lossy().to_string()),
+                available,
+            }
+        })
+        .collect()
+}
+
+/// Reads the history of `source` from `path` (or its default location).
+/// Items are returned most recent first.
+pub fn read_source(
+    source: ImportSource,
+    path: Option<&Path>,
+) -> Result<Vec<ClipboardItem>, String> {
+    let default_path = source.default_path();
+    let path = path.or(default_path.as_deref());
+
+    let entries = match source {
+        ImportSource::Cliphist => read_cliphist(path)?,
+        _ => {
+            let path = path.ok_or("Could not determine the history location")?;
+            let data =
+                fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            match source {
+                ImportSource::CopyQ => parse_copyq(&data)?,
+                ImportSource::GPaste => parse_gpaste(&String::from_utf8_lossy(&data))?,
+                ImportSource::Klipper => parse_klipper(&data)?,
+                ImportSource::Clipman => parse_clipman(&data)?,
+                ImportSource::Cliphist => unreachable!(),
+            }
+        }
+    };
+
+    println!(
+        "[Importers] Read {} entries from {}",
+        entries.len(),
+        source.display_name()
+    );
+    Ok(into_items(entries, Utc::now()))
+}
+
+/// Converts entries (most recent first) into history items.
+/// Entries without a timestamp get one second less than the previous entry so order is kept.
+fn into_items(entries: Vec<ImportedEntry>, now: DateTime<Utc>) -> Vec<ClipboardItem> {
+    entries
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            let mut item = match entry.content {
+                EntryContent::Text(text) if text.trim().is_empty() => return None,
+                EntryContent::Text(text) => ClipboardItem::new_text(text),
+                EntryContent::Image(bytes) => match ClipboardItem::from_image_bytes(&bytes) {
+                    Ok(item) => item,
+                    Err(e) => {
+                        eprintln!("[Importers] Skipping image: {}", e);
+                        return None;
+                    }
+                },
+            };
+            item.timestamp = entry
+                .timestamp
+                .unwrap_or_else(|| now - Duration::seconds(index as i64));
+            item.pinned = entry.pinned;
+            Some(item)
+        })
+        .collect()
+}
+
+// --- Qt QDataStream (CopyQ, Klipper) ---
+
+/// Minimal big-endian QDataStream reader
+struct QtStream<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> QtStream<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| format!("Unexpected end of data at offset {}", self.pos))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_i32(&mut self) -> Result<i32, String> {
+        self.read_u32().map(|v| v as i32)
+    }
+
+    fn read_bool(&mut self) -> Result<bool, String> {
+        Ok(self.take(1)?[0] != 0)
+    }
+
+    /// QByteArray: length-prefixed bytes, 0xFFFFFFFF for a null array
+    fn read_bytes(&mut self) -> Result<&'a [u8], String> {
+        match self.read_u32()? {
+            u32::MAX => Ok(&[]),
+            len => self.take(len as usize),
+        }
+    }
+
+    /// QString: byte-length-prefixed UTF-16BE, 0xFFFFFFFF for a null string
+    fn read_string(&mut self) -> Result<String, String> {
+        let bytes = self.read_bytes()?;
+        if bytes.len() % 2 != 0 {
+            return Err(format!("Invalid string at offset {}", self.pos));
+        }
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        Ok(String::from_utf16_lossy(&units))
+    }
+
+    /// QImage: a format marker followed by a PNG file without a length prefix
+    fn read_png(&mut self) -> Result<Option<&'a [u8]>, String> {
+        if self.read_i32()? == 0 {
+            return Ok(None);
+        }
+        let start = self.pos;
+        if self.take(PNG_SIGNATURE.len())? != PNG_SIGNATURE {
+            return Err("Image is not a PNG".to_string());
+        }
+        loop {
+            let len = self.read_u32()? as usize;
+            let kind = self.take(4)?;
+            self.take(len + 4)?; // chunk data + CRC
+            if kind == b"IEND" {
+                return Ok(Some(&self.data[start..self.pos]));
+            }
+        }
+    }
+}
+
+/// Reverses Qt's `qCompress`: a big-endian uncompressed size followed by a zlib stream
+fn q_uncompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 4 {
+        return Ok(Vec::new());
+    }
+    let mut out = Vec::new();
+    ZlibDecoder::new(&data[4..])
+        .read_to_end(&mut out)
+        .map_err(|e| format!("Failed to decompress item: {}", e))?;
+    Ok(out)
+}
+
+// --- CopyQ ---
+
+fn copyq_mime(compressed: &[u8]) -> String {
+    let mime = String::from_utf8_lossy(compressed);
+    let mut chars = mime.chars();
+    match chars
+        .next()
+        .and_then(|c| COPYQ_MIME_PREFIXES.iter().find(|(p, _)| *p == c))
+    {
+        Some((_, prefix)) => format!("{}{}", prefix, chars.as_str()),
+        None => mime.to_string(),
+    }
+}
+
+/// Reads one CopyQ item as a list of (MIME type, data) pairs
+fn read_copyq_item(stream: &mut QtStream) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let marker = stream.read_i32()?;
+    let mut formats = Vec::new();
+
+    if marker == COPYQ_ITEM_V2 {
+        let count = stream.read_i32()?;
+        for _ in 0..count {
+            let mime = copyq_mime(stream.read_bytes()?);
+            let compressed = stream.read_bool()?;
+            let bytes = stream.read_bytes()?;
+            let bytes = if compressed {
+                q_uncompress(bytes)?
+            } else {
+                bytes.to_vec()
+            };
+            formats.push((mime, bytes));
+        }
+    } else if marker >= 0 {
+        // Old format: plain MIME strings, every value compressed
+        for _ in 0..marker {
+            let mime = stream.read_string()?;
+            let bytes = q_uncompress(stream.read_bytes()?)?;
+            formats.push((mime, bytes));
+        }
+    } else {
+        return Err(format!("Unknown CopyQ item format {}", marker));
+    }
+
+    Ok(formats)
+}
+
+/// Parses a CopyQ tab file (`copyq_tab_*.dat`). Items are stored top (newest) first.
+fn parse_copyq(data: &[u8]) -> Result<Vec<ImportedEntry>, String> {
+    let mut stream = QtStream::new(data);
+
+    // Newer versions start with a "CopyQ v..." header string
+    match stream.read_string() {
+        Ok(header) if header.starts_with("CopyQ") => {}
+        _ => stream.pos = 0,
+    }
+
+    let count = stream.read_i32()?;
+    let mut entries = Vec::new();
+
+    for _ in 0..count.max(0) {
+        let formats = read_copyq_item(&mut stream)?;
+        let pinned = formats.iter().any(|(mime, _)| mime == COPYQ_MIME_PINNED);
+
+        let text = formats
+            .iter()
+            .find(|(mime, _)| mime == "text/plain" || mime.starts_with("text/plain;"));
+        let image = formats
+            .iter()
+            .find(|(mime, _)| mime.starts_with("image/") && !mime.contains("svg"));
+
+        let entry = match (text, image) {
+            (Some((_, bytes)), _) => {
+                ImportedEntry::text(String::from_utf8_lossy(bytes).into_owned())
+            }
+            (None, Some((_, bytes))) => ImportedEntry::image(bytes.clone()),
+            (None, None) => continue,
+        };
+        entries.push(ImportedEntry { pinned, ..entry });
+    }
+
+    Ok(entries)
+}
+
+// --- Klipper ---
+
+/// Parses Klipper's `history2.lst`: a CRC and a byte array holding a version string
+/// followed by typed items, most recent first.
+fn parse_klipper(data: &[u8]) -> Result<Vec<ImportedEntry>, String> {
+    let mut outer = QtStream::new(data);
+    let _crc = outer.read_u32()?;
+    let mut stream = QtStream::new(outer.read_bytes()?);
+    let _version = stream.read_bytes()?;
+
+    let mut entries = Vec::new();
+    while !stream.at_end() {
+        let kind = stream.read_string()?;
+        match kind.as_str() {
+            "string" => entries.push(ImportedEntry::text(stream.read_string()?)),
+            "url" => {
+                // The count comes from the file, so it isn't trusted for preallocation
+                let mut urls = Vec::new();
+                for _ in 0..stream.read_u32()? {
+                    urls.push(String::from_utf8_lossy(stream.read_bytes()?).into_owned());
+                }
+                // Metadata map and "cut" flag are not needed
+                for _ in 0..stream.read_u32()? {
+                    stream.read_string()?;
+                    stream.read_string()?;
+                }
+                stream.read_i32()?;
+                entries.push(ImportedEntry::text(urls.join("\n")));
+            }
+            "image" => {
+                if let Some(png) = stream.read_png()? {
+                    entries.push(ImportedEntry::image(png.to_vec()));
+                }
+            }
+            other => {
+                // Item layout is unknown, so nothing after it can be read
+                eprintln!(
+                    "[Importers] Stopping at unknown Klipper item type '{}'",
+                    other
+                );
+                break;
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+// --- GPaste ---
+
+/// Parses GPaste's `history.xml`, most recent first. Password items are skipped.
+fn parse_gpaste(xml: &str) -> Result<Vec<ImportedEntry>, String> {
+    let doc =
+        roxmltree::Document::parse(xml).map_err(|e| format!("Invalid GPaste history: {}", e))?;
+
+    let mut entries = Vec::new();
+    for node in doc
+        .root_element()
+        .children()
+        .filter(|n| n.has_tag_name("item"))
+    {
+        let value: String = node
+            .children()
+            .filter(|n| n.has_tag_name("value"))
+            .flat_map(|v| {
+                v.descendants()
+                    .filter(|d| d.is_text())
+                    .filter_map(|d| d.text())
+            })
+            .collect();
+
+        let mut entry = match node.attribute("kind").unwrap_or("Text") {
+            "Text" | "Uris" => ImportedEntry::text(value),
+            "Image" => match fs::read(value.trim()) {
+                Ok(bytes) => ImportedEntry::image(bytes),
+                Err(e) => {
+                    eprintln!("[Importers] Skipping GPaste image {}: {}", value.trim(), e);
+                    continue;
+                }
+            },
+            _ => continue,
+        };
+
+        entry.timestamp = node
+            .attribute("date")
+            .and_then(|d| d.parse::<i64>().ok())
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single());
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+// --- Clipman ---
+
+/// Parses Clipman's `clipman.json`: a JSON array of strings, oldest first
+fn parse_clipman(data: &[u8]) -> Result<Vec<ImportedEntry>, String> {
+    let history: Vec<String> =
+        serde_json::from_slice(data).map_err(|e| format!("Invalid Clipman history: {}", e))?;
+    Ok(history.into_iter().rev().map(ImportedEntry::text).collect())
+}
+
+// --- cliphist ---
+
+/// Parses `cliphist list` output (`<id>\t<preview>`, newest first)
+fn parse_cliphist_list(list: &str) -> Vec<(&str, &str)> {
+    list.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter(|(id, _)| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .collect()
+}
+
+fn cliphist(db: Option<&Path>) -> Command {
+    let mut cmd = Command::new("cliphist");
+    if let Some(db) = db {
+        cmd.arg("-db-path").arg(db);
+    }
+    cmd
+}
+
+fn cliphist_decode(db: Option<&Path>, line: &str) -> Result<Vec<u8>, String> {
+    let mut child = cliphist(db)
+        .arg("decode")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run cliphist: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(format!("{}\n", line).as_bytes())
+            .map_err(|e| format!("Failed to write to cliphist: {}", e))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("cliphist decode failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("cliphist could not decode entry '{}'", line));
+    }
+    Ok(output.stdout)
+}
+
+/// Reads cliphist history through the `cliphist` binary.
+/// `path` may be a cliphist database or a file holding saved `cliphist list` output.
+fn read_cliphist(path: Option<&Path>) -> Result<Vec<ImportedEntry>, String> {
+    if !command_exists("cliphist") {
+        return Err("cliphist is not installed".to_string());
+    }
+
+    // The default database is used unless a different one is given
+    let saved_list = path
+        .filter(|p| p.exists())
+        .and_then(|p| fs::read_to_string(p).ok())
+        .filter(|text| !parse_cliphist_list(text).is_empty());
+    let db = match (&saved_list, path) {
+        (None, Some(p)) if p.exists() => Some(p),
+        _ => None,
+    };
+
+    let list = match saved_list {
+        Some(list) => list,
+        None => {
+            let output = cliphist(db)
+                .arg("list")
+                .output()
+                .map_err(|e| format!("Failed to run cliphist: {}", e))?;
+            if !output.status.success() {
+                return Err(format!(
+                    "cliphist list failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+    };
+
+    let mut entries = Vec::new();
+    for (id, preview) in parse_cliphist_list(&list) {
+        let bytes = match cliphist_decode(db, &format!("{}\t{}", id, preview)) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!("[Importers] {}", e);
+                continue;
+            }
+        };
+        entries.push(if preview.starts_with(CLIPHIST_BINARY_PREVIEW) {
+            ImportedEntry::image(bytes)
+        } else {
+            ImportedEntry::text(String::from_utf8_lossy(&bytes).into_owned())
+        });
+    }
+
+    Ok(entries)
+}
+
+fn command_exists(cmd: &str) -> bool {
+    Command::new("which")
+        .arg(cmd)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+// --- Commands ---
+
+/// Lists the clipboard managers history can be imported from
+#[tauri::command]
+pub fn list_import_sources() -> Vec<ImportSourceInfo> {
+    list_sources()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal QDataStream writer used to build fixtures
+    #[derive(Default)]
+    struct QtWriter(Vec<u8>);
+
+    impl QtWriter {
+        fn u32(mut self, v: u32) -> Self {
+            self.0.extend_from_slice(&v.to_be_bytes());
+            self
+        }
+        fn bool(mut self, v: bool) -> Self {
+            self.0.push(v as u8);
+            self
+        }
+        fn bytes(mut self, v: &[u8]) -> Self {
+            self = self.u32(v.len() as u32);
+            self.0.extend_from_slice(v);
+            self
+        }
+        fn string(self, v: &str) -> Self {
+            let utf16: Vec<u8> = v.encode_utf16().flat_map(|u| u.to_be_bytes()).collect();
+            self.bytes(&utf16)
+        }
+    }
+
+    #[test]
+    fn test_parse_copyq() {
+        let data = QtWriter::default()
+            .string("CopyQ v3")
+            .u32(2)
+            // Pinned text item
+            .u32(COPYQ_ITEM_V2 as u32)
+            .u32(2)
+            .bytes(b"1plain")
+            .bool(false)
+            .bytes(b"pinned text")
+            .bytes(b"0item-pinned")
+            .bool(false)
+            .bytes(b"")
+            // Item without text or image
+            .u32(COPYQ_ITEM_V2 as u32)
+            .u32(1)
+            .bytes(b"2x-unknown")
+            .bool(false)
+            .bytes(b"?")
+            .0;
+
+        let entries = parse_copyq(&data).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].content, EntryContent::Text("pinned text".into()));
+        assert!(entries[0].pinned);
+    }
+
+    #[test]
+    fn test_parse_klipper() {
+        let payload = QtWriter::default()
+            .bytes(b"0.9.7\0")
+            .string("string")
+            .string("newest")
+            .string("url")
+            .u32(2)
+            .bytes(b"file:///a")
+            .bytes(b"file:///b")
+            .u32(1)
+            .string("key")
+            .string("value")
+            .u32(0)
+            .string("string")
+            .string("oldest")
+            .0;
+        let data = QtWriter::default().u32(0).bytes(&payload).0;
+
+        let entries = parse_klipper(&data).unwrap();
+        let texts: Vec<_> = entries.iter().map(|e| e.content.clone()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                EntryContent::Text("newest".into()),
+                EntryContent::Text("file:///a\nfile:///b".into()),
+                EntryContent::Text("oldest".into()),
+            ]
+        );
+
+        // A corrupt URL count fails on the missing data instead of allocating for it
+        let payload = QtWriter::default()
+            .bytes(b"0.9.7\0")
+            .string("url")
+            .u32(u32::MAX)
+            .bytes(b"file:///a")
+            .0;
+        let data = QtWriter::default().u32(0).bytes(&payload).0;
+        assert!(parse_klipper(&data).is_err());
+    }
+
+    #[test]
+    fn test_parse_text_formats() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<history version="2.0">
+  <item kind="Text" uuid="1" date="1700000000"><value><![CDATA[a & b]]></value></item>
+  <item kind="Password" uuid="2" name="pw"><value><![CDATA[secret]]></value></item>
+  <item kind="Uris" uuid="3"><value>file:///tmp/x</value></item>
+</history>"#;
+        let entries = parse_gpaste(xml).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].content, EntryContent::Text("a & b".into()));
+        assert_eq!(entries[0].timestamp.unwrap().timestamp(), 1_700_000_000);
+
+        let entries = parse_clipman(br#"["old", "new"]"#).unwrap();
+        assert_eq!(entries[0].content, EntryContent::Text("new".into()));
+
+        let list = "12\thello\n11\t[[ binary data 1 KiB png 2x2 ]]\nbad line\n";
+        assert_eq!(
+            parse_cliphist_list(list),
+            vec![("12", "hello"), ("11", "[[ binary data 1 KiB png 2x2 ]]")]
+        );
+
+        let now = Utc::now();
+        let items = into_items(
+            vec![
+                ImportedEntry::text("b".into()),
+                ImportedEntry::text("a".into()),
+            ],
+            now,
+        );
+        assert!(items[0].timestamp > items[1].timestamp);
+    }
+}
@@ -1,12 +1,16 @@
 //! Clipboard Manager Module
 //! Handles clipboard monitoring, history storage, and paste injection
 
+use crate::clipboard_rules::{ClipboardRule, RuleSet};
+use crate::clipboard_snapshot::ClipboardSnapshot;
+use crate::privacy_policy::PrivacySettings;
 use arboard::{Clipboard, ImageData};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use chrono::{DateTime, Utc};
 use image::{DynamicImage, ImageFormat};
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::io::Cursor;
 use std::thread;
@@ -63,6 +67,12 @@ pub struct ClipboardItem {
     pub pinned: bool,
     /// Preview text (for display)
     pub preview: String,
+    /// Collections this item was tagged into by clipboard rules
+    #[serde(default)]
+    pub collections: Vec<String>,
+    /// When this item is automatically removed (secrets, copies from flagged apps)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl ClipboardItem {
@@ -94,6 +104,28 @@ impl ClipboardItem {
         )
     }
 
+    /// Builds an image item from an encoded image (PNG, JPEG, ...).
+    /// The hash matches the one computed for images read from the system clipboard.
+    pub fn from_image_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let rgba = image::load_from_memory(bytes)
+            .map_err(|e| format!("Image load failed: {}", e))?
+            .to_rgba8();
+        let (width, height) = rgba.dimensions();
+        let hash = calculate_hash(rgba.as_raw());
+
+        let mut buffer = Cursor::new(Vec::new());
+        DynamicImage::ImageRgba8(rgba)
+            .write_to(&mut buffer, ImageFormat::Png)
+            .map_err(|e| format!("PNG encode failed: {}", e))?;
+
+        Ok(Self::new_image(
+            BASE64.encode(buffer.get_ref()),
+            width,
+            height,
+            hash,
+        ))
+    }
+
     fn create(content: ClipboardContent, preview: String) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
@@ -101,6 +133,8 @@ impl ClipboardItem {
             timestamp: Utc::now(),
             pinned: false,
             preview,
+            collections: Vec::new(),
+            expires_at: None,
         }
     }
 
@@ -115,6 +149,28 @@ impl ClipboardItem {
             .nth(1)
             .and_then(|h| h.parse::<u64>().ok())
     }
+
+    /// Hash identifying the item's content, used to deduplicate imports.
+    /// Images use the hash stored in the preview, falling back to the encoded data.
+    pub fn content_hash(&self) -> u64 {
+        match &self.content {
+            ClipboardContent::Text(text) => calculate_hash(text),
+            ClipboardContent::Image { base64, .. } => self
+                .extract_image_hash()
+                .unwrap_or_else(|| calculate_hash(base64)),
+        }
+    }
+}
+
+/// Summary of an import into the history
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportSummary {
+    /// Items added to history
+    pub added: usize,
+    /// Items skipped because the same content was already present
+    pub duplicates: usize,
+    /// Items that existed before the import and were removed (replace mode)
+    pub removed: usize,
 }
 
 // --- Manager Logic ---
@@ -127,6 +183,14 @@ pub struct ClipboardManager {
     last_pasted_image_hash: Option<u64>,
     /// Track last added text hash to prevent duplicates from rapid copies
     last_added_text_hash: Option<u64>,
+    /// User-defined rules evaluated before text is recorded
+    rules: RuleSet,
+    /// Incremented on every history change, so the store knows when to save
+    revision: u64,
+    /// Expiry rules for newly copied items
+    privacy: PrivacySettings,
+    /// While set, copies are not recorded
+    paused: bool,
 }
 
 impl Default for ClipboardManager {
@@ -142,9 +206,36 @@ impl ClipboardManager {
             last_pasted_text: None,
             last_pasted_image_hash: None,
             last_added_text_hash: None,
+            rules: RuleSet::default(),
+            revision: 0,
+            privacy: PrivacySettings::default(),
+            paused: false,
         }
     }
 
+    /// Replaces the active clipboard rules (called on startup and when settings change)
+    pub fn set_rules(&mut self, rules: &[ClipboardRule]) {
+        self.rules = RuleSet::new(rules);
+    }
+
+    /// Replaces the active privacy policy (called on startup and when settings change)
+    pub fn set_privacy(&mut self, privacy: &PrivacySettings) {
+        self.privacy = privacy.clone();
+    }
+
+    pub fn privacy(&self) -> &PrivacySettings {
+        &self.privacy
+    }
+
+    /// Pauses or resumes recording; copies made while paused are never added
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
     // --- Monitoring / Reading ---
 
     pub fn get_current_text(&mut self) -> Result<String, arboard::Error> {
@@ -175,8 +266,10 @@ impl ClipboardManager {
 
     // --- Adding Items ---
 
-    pub fn add_text(&mut self, text: String) -> Option<ClipboardItem> {
-        if self.should_skip_text(&text) {
+    /// Records copied text. `source_classes` comes from `PrivacySettings::source_classes`,
+    /// looked up before taking the manager lock.
+    pub fn add_text(&mut self, text: String, source_classes: &[String]) -> Option<ClipboardItem> {
+        if self.paused || self.should_skip_text(&text) {
             return None;
         }
 
@@ -187,6 +280,20 @@ impl ClipboardManager {
             return None;
         }
 
+        // Apply user rules (rewrite / skip / pin / tag) before anything is recorded
+        let outcome = self.rules.apply(&text);
+        if outcome.skip || outcome.text.trim().is_empty() {
+            self.last_added_text_hash = Some(text_hash);
+            return None;
+        }
+        let text = outcome.text;
+
+        // Auto-pinned text that is already pinned moves to the top instead of pinning a copy
+        if outcome.pin && self.move_pinned_text_to_top(&text) {
+            self.last_added_text_hash = Some(text_hash);
+            return None;
+        }
+
         // Check if this exact text is already the most recent non-pinned item
         // If so, skip entirely - no need to add or move
         if self.is_duplicate_text(&text) {
@@ -199,7 +306,16 @@ impl ClipboardManager {
         self.remove_duplicate_text_from_history(&text);
 
         // Create new item and add to history
-        let item = ClipboardItem::new_text(text);
+        let mut item = ClipboardItem::new_text(text);
+        item.pinned = outcome.pin;
+        item.collections = outcome.collections;
+        if !item.pinned {
+            if let ClipboardContent::Text(text) = &item.content {
+                item.expires_at =
+                    self.privacy
+                        .expiry_for(Some(text), source_classes, item.timestamp);
+            }
+        }
         self.insert_item(item.clone());
 
         self.last_added_text_hash = Some(text_hash);
@@ -207,19 +323,27 @@ impl ClipboardManager {
         Some(item)
     }
 
-    pub fn add_image(&mut self, image_data: ImageData<'_>, hash: u64) -> Option<ClipboardItem> {
-        if self.should_skip_image(hash) {
+    pub fn add_image(
+        &mut self,
+        image_data: ImageData<'_>,
+        hash: u64,
+        source_classes: &[String],
+    ) -> Option<ClipboardItem> {
+        if self.paused || self.should_skip_image(hash) {
             return None;
         }
 
         let base64_image = self.convert_image_to_base64(&image_data)?;
 
-        let item = ClipboardItem::new_image(
+        let mut item = ClipboardItem::new_image(
             base64_image,
             image_data.width as u32,
             image_data.height as u32,
             hash,
         );
+        item.expires_at = self
+            .privacy
+            .expiry_for(None, source_classes, item.timestamp);
 
         self.insert_item(item.clone());
         Some(item)
@@ -290,6 +414,19 @@ impl ClipboardManager {
         }
     }
 
+    /// Moves the pinned item holding the text to the top; false if there is none
+    fn move_pinned_text_to_top(&mut self, text: &str) -> bool {
+        let Some(pos) = self.history.iter().position(|item| {
+            item.pinned && matches!(&item.content, ClipboardContent::Text(t) if t == text)
+        }) else {
+            return false;
+        };
+        let item = self.history.remove(pos);
+        self.history.insert(0, item);
+        self.revision += 1;
+        true
+    }
+
     fn convert_image_to_base64(&self, image_data: &ImageData<'_>) -> Option<String> {
         let img = DynamicImage::ImageRgba8(
             image::RgbaImage::from_raw(
@@ -308,6 +445,7 @@ impl ClipboardManager {
         // Insert after pinned items (first non-pinned slot)
         let insert_pos = self.history.iter().position(|i| !i.pinned).unwrap_or(0);
         self.history.insert(insert_pos, item);
+        self.revision += 1;
 
         // Trim history
         self.enforce_history_limit();
@@ -335,20 +473,126 @@ impl ClipboardManager {
         self.history.iter().find(|item| item.id == id)
     }
 
+    /// Nth item (1-based) in the order shown by the history window
+    pub fn item_at(&self, index: usize) -> Option<&ClipboardItem> {
+        index.checked_sub(1).and_then(|i| self.history.get(i))
+    }
+
+    /// Most recently copied text item, pinned or not
+    pub fn latest_text_item(&self) -> Option<&ClipboardItem> {
+        self.history
+            .iter()
+            .filter(|item| matches!(item.content, ClipboardContent::Text(_)))
+            .max_by_key(|item| item.timestamp)
+    }
+
+    /// Current history revision (changes whenever history is modified)
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
     pub fn clear(&mut self) {
         self.history.retain(|item| item.pinned);
+        self.revision += 1;
+    }
+
+    /// Removes every item, including pinned ones (used when history is locked)
+    pub fn clear_all(&mut self) {
+        self.history.clear();
+        self.revision += 1;
     }
 
     pub fn remove_item(&mut self, id: &str) {
         self.history.retain(|item| item.id != id);
+        self.revision += 1;
     }
 
     pub fn toggle_pin(&mut self, id: &str) -> Option<ClipboardItem> {
         let item = self.history.iter_mut().find(|i| i.id == id)?;
         item.pinned = !item.pinned;
+        // Pinned items are kept on purpose, so they never expire
+        if item.pinned {
+            item.expires_at = None;
+        }
+        self.revision += 1;
         Some(item.clone())
     }
 
+    /// Removes items whose expiry has passed and returns them
+    pub fn remove_expired(&mut self, now: DateTime<Utc>) -> Vec<ClipboardItem> {
+        let (expired, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.history)
+            .into_iter()
+            .partition(|item| !item.pinned && item.expires_at.is_some_and(|at| at <= now));
+        self.history = kept;
+        if !expired.is_empty() {
+            // Copying the same secret again should record it again
+            self.last_added_text_hash = None;
+            self.revision += 1;
+        }
+        expired
+    }
+
+    /// Empties the system clipboard if it still holds one of `items`.
+    /// Returns true if the clipboard was cleared.
+    pub fn wipe_system_clipboard_if_holding(&self, items: &[ClipboardItem]) -> bool {
+        let Ok(mut clipboard) = get_system_clipboard() else {
+            return false;
+        };
+
+        let holding = match clipboard.get_text() {
+            Ok(current) => items
+                .iter()
+                .any(|item| matches!(&item.content, ClipboardContent::Text(t) if *t == current)),
+            Err(_) => match clipboard.get_image() {
+                Ok(image) => {
+                    let hash = calculate_hash(&image.bytes);
+                    items
+                        .iter()
+                        .any(|item| item.extract_image_hash() == Some(hash))
+                }
+                Err(_) => false,
+            },
+        };
+
+        holding && clipboard.clear().is_ok()
+    }
+
+    /// Imports items into history.
+    /// In replace mode the current history (including pins) is discarded first;
+    /// otherwise items whose content is already present are skipped.
+    pub fn import_items(&mut self, items: Vec<ClipboardItem>, replace: bool) -> ImportSummary {
+        let mut summary = ImportSummary::default();
+
+        if replace {
+            summary.removed = self.history.len();
+            self.history.clear();
+        }
+
+        let mut known: HashSet<u64> = self.history.iter().map(|i| i.content_hash()).collect();
+        let mut known_ids: HashSet<String> = self.history.iter().map(|i| i.id.clone()).collect();
+
+        for mut item in items {
+            if !known.insert(item.content_hash()) {
+                summary.duplicates += 1;
+                continue;
+            }
+            if !known_ids.insert(item.id.clone()) {
+                item.id = Uuid::new_v4().to_string();
+                known_ids.insert(item.id.clone());
+            }
+            self.history.push(item);
+            summary.added += 1;
+        }
+
+        // Restore the usual ordering: pinned first, then most recent first
+        self.history
+            .sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.timestamp.cmp(&a.timestamp)));
+        self.enforce_history_limit();
+        self.revision += 1;
+
+        summary
+    }
+
     // --- Paste Logic ---
 
     pub fn mark_as_pasted(&mut self, item: &ClipboardItem) {
@@ -366,6 +610,16 @@ impl ClipboardManager {
         }
     }
 
+    /// Mark restored clipboard contents as pasted, so restoring them after a paste
+    /// doesn't record them as a new copy
+    pub fn mark_snapshot_restored(&mut self, snapshot: &ClipboardSnapshot) {
+        self.last_pasted_text = snapshot.text();
+        self.last_pasted_image_hash = snapshot
+            .png()
+            .and_then(|png| image::load_from_memory(png).ok())
+            .map(|img| calculate_hash(img.to_rgba8().as_raw()));
+    }
+
     /// Mark a specific text as pasted (to prevent it from appearing in history)
     /// Used for emojis/special insertions
     pub fn mark_text_as_pasted(&mut self, text: &str) {
@@ -437,3 +691,45 @@ impl ClipboardManager {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard_rules::RuleAction;
+
+    #[test]
+    fn test_auto_pinned_text_is_not_duplicated() {
+        let mut manager = ClipboardManager::new();
+        manager.set_rules(&[ClipboardRule {
+            id: "pin-tickets".to_string(),
+            name: String::new(),
+            enabled: true,
+            pattern: r"^TICKET-\d+$".to_string(),
+            action: RuleAction::AutoPin,
+        }]);
+
+        assert!(manager.add_text("TICKET-1".to_string(), &[]).is_some());
+        assert!(manager.add_text("TICKET-2".to_string(), &[]).is_some());
+        assert!(manager.add_text("other".to_string(), &[]).is_some());
+        // Copied again: the pinned entry moves to the top
+        assert!(manager.add_text("TICKET-1".to_string(), &[]).is_none());
+        assert!(manager.add_text("TICKET-2".to_string(), &[]).is_none());
+
+        let texts: Vec<(bool, String)> = manager
+            .get_history()
+            .iter()
+            .filter_map(|item| match &item.content {
+                ClipboardContent::Text(text) => Some((item.pinned, text.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                (true, "TICKET-2".to_string()),
+                (true, "TICKET-1".to_string()),
+                (false, "other".to_string()),
+            ]
+        );
+    }
+}
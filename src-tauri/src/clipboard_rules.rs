@@ -0,0 +1,351 @@
+//! Clipboard Rules Module
+//! Declarative "regex match → action" rules that rewrite, skip, pin or tag copied text
+//! before it is recorded in history.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Query parameters removed by the `StripTrackingParams` action.
+/// Entries ending in `*` are treated as prefixes.
+const TRACKING_PARAMS: &[&str] = &[
+    "utm_*", "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "mc_cid", "mc_eid",
+    "igshid", "yclid",
+];
+
+/// Matches http(s) URLs embedded anywhere in a piece of text
+const URL_PATTERN: &str = r#"https?://[^\s<>"']+"#;
+
+// --- Data Structures ---
+
+/// A single user-defined rule, stored in the user settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClipboardRule {
+    /// Stable identifier used by the settings UI
+    pub id: String,
+    /// Human-readable label
+    #[serde(default)]
+    pub name: String,
+    /// Disabled rules are kept in settings but never evaluated
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Regular expression tested against the copied text
+    pub pattern: String,
+    /// What to do when the pattern matches
+    pub action: RuleAction,
+}
+
+/// Action executed when a rule's pattern matches
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "data")]
+pub enum RuleAction {
+    /// Remove utm_*, fbclid, gclid and similar parameters from every URL in the text
+    StripTrackingParams,
+    /// Replace every match of the pattern (supports `$1` / `${name}` capture references)
+    Replace { with: String },
+    /// Trim leading and trailing whitespace
+    Trim,
+    /// Do not record the text in history at all
+    SkipRecording,
+    /// Record the item as pinned
+    AutoPin,
+    /// Add the item to a named collection
+    Tag { collection: String },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Final result of running all rules over a piece of text
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RuleOutcome {
+    /// Text after all rewriting actions
+    pub text: String,
+    /// True if a `SkipRecording` rule matched
+    pub skip: bool,
+    /// True if an `AutoPin` rule matched
+    pub pin: bool,
+    /// Collections the item should be tagged with (deduplicated, in rule order)
+    pub collections: Vec<String>,
+}
+
+/// Per-rule report returned by the dry-run command
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleMatchReport {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub matched: bool,
+    /// Set when the pattern is not a valid regular expression
+    pub error: Option<String>,
+    /// Text after this rule was applied (only when it matched)
+    pub text_after: Option<String>,
+}
+
+/// Result of a dry run over a sample text
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleDryRun {
+    pub rules: Vec<RuleMatchReport>,
+    pub outcome: RuleOutcome,
+}
+
+// --- Rule Engine ---
+
+struct CompiledRule {
+    rule: ClipboardRule,
+    regex: Regex,
+}
+
+/// Compiled, ready-to-evaluate set of rules.
+/// Invalid patterns are skipped (and logged) so one typo can't break recording.
+#[derive(Default)]
+pub struct RuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+impl RuleSet {
+    pub fn new(rules: &[ClipboardRule]) -> Self {
+        let rules = rules
+            .iter()
+            .filter(|r| r.enabled)
+            .filter_map(|rule| match Regex::new(&rule.pattern) {
+                Ok(regex) => Some(CompiledRule {
+                    rule: rule.clone(),
+                    regex,
+                }),
+                Err(e) => {
+                    eprintln!(
+                        "[ClipboardRules] Ignoring rule '{}' with invalid pattern: {}",
+                        rule.id, e
+                    );
+                    None
+                }
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Evaluates every rule in order. Each rule sees the text produced by the previous one.
+    /// Evaluation stops as soon as a `SkipRecording` rule matches.
+    pub fn apply(&self, text: &str) -> RuleOutcome {
+        let mut outcome = RuleOutcome {
+            text: text.to_string(),
+            ..Default::default()
+        };
+
+        for compiled in &self.rules {
+            if apply_rule(&compiled.rule, &compiled.regex, &mut outcome) && outcome.skip {
+                break;
+            }
+        }
+
+        outcome
+    }
+}
+
+/// Applies one rule to the outcome. Returns true if the pattern matched.
+fn apply_rule(rule: &ClipboardRule, regex: &Regex, outcome: &mut RuleOutcome) -> bool {
+    if !regex.is_match(&outcome.text) {
+        return false;
+    }
+
+    match &rule.action {
+        RuleAction::StripTrackingParams => {
+            outcome.text = strip_tracking_params(&outcome.text);
+        }
+        RuleAction::Replace { with } => {
+            outcome.text = regex.replace_all(&outcome.text, with.as_str()).into_owned();
+        }
+        RuleAction::Trim => {
+            outcome.text = outcome.text.trim().to_string();
+        }
+        RuleAction::SkipRecording => {
+            outcome.skip = true;
+        }
+        RuleAction::AutoPin => {
+            outcome.pin = true;
+        }
+        RuleAction::Tag { collection } => {
+            let collection = collection.trim();
+            if !collection.is_empty() && !outcome.collections.iter().any(|c| c == collection) {
+                outcome.collections.push(collection.to_string());
+            }
+        }
+    }
+
+    true
+}
+
+/// Runs the rules against a sample without touching history, reporting every rule.
+pub fn dry_run(rules: &[ClipboardRule], sample: &str) -> RuleDryRun {
+    let mut outcome = RuleOutcome {
+        text: sample.to_string(),
+        ..Default::default()
+    };
+    let mut reports = Vec::with_capacity(rules.len());
+
+    for rule in rules {
+        let mut report = RuleMatchReport {
+            id: rule.id.clone(),
+            name: rule.name.clone(),
+            enabled: rule.enabled,
+            matched: false,
+            error: None,
+            text_after: None,
+        };
+
+        match Regex::new(&rule.pattern) {
+            Ok(regex) => {
+                if rule.enabled && !outcome.skip && apply_rule(rule, &regex, &mut outcome) {
+                    report.matched = true;
+                    report.text_after = Some(outcome.text.clone());
+                } else if !rule.enabled || outcome.skip {
+                    // Report whether it *would* match, without applying it
+                    report.matched = regex.is_match(&outcome.text);
+                }
+            }
+            Err(e) => report.error = Some(e.to_string()),
+        }
+
+        reports.push(report);
+    }
+
+    RuleDryRun {
+        rules: reports,
+        outcome,
+    }
+}
+
+// --- URL Cleaning ---
+
+fn is_tracking_param(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    TRACKING_PARAMS.iter().any(|p| match p.strip_suffix('*') {
+        Some(prefix) => key.starts_with(prefix),
+        None => key == *p,
+    })
+}
+
+/// Removes known tracking parameters from every URL found in `text`.
+pub fn strip_tracking_params(text: &str) -> String {
+    // URL_PATTERN is a constant, so compiling it cannot fail
+    let url_regex = Regex::new(URL_PATTERN).expect("valid URL pattern");
+    url_regex
+        .replace_all(text, |caps: &regex::Captures| clean_url(&caps[0]))
+        .into_owned()
+}
+
+fn clean_url(url: &str) -> String {
+    let (without_fragment, fragment) = match url.split_once('#') {
+        Some((base, frag)) => (base, Some(frag)),
+        None => (url, None),
+    };
+
+    let Some((base, query)) = without_fragment.split_once('?') else {
+        return url.to_string();
+    };
+
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| !is_tracking_param(pair.split('=').next().unwrap_or("")))
+        .collect();
+
+    let mut cleaned = base.to_string();
+    if !kept.is_empty() {
+        cleaned.push('?');
+        cleaned.push_str(&kept.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        cleaned.push('#');
+        cleaned.push_str(fragment);
+    }
+    cleaned
+}
+
+// --- Commands ---
+
+/// Shows which rules match a sample text and what would be recorded.
+/// Uses the rules passed by the settings UI, or the saved rules when omitted.
+#[tauri::command]
+pub fn preview_clipboard_rules(sample: String, rules: Option<Vec<ClipboardRule>>) -> RuleDryRun {
+    let rules = rules.unwrap_or_else(|| {
+        crate::user_settings::UserSettingsManager::new()
+            .load()
+            .clipboard_rules
+    });
+    dry_run(&rules, &sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, action: RuleAction) -> ClipboardRule {
+        ClipboardRule {
+            id: pattern.to_string(),
+            name: String::new(),
+            enabled: true,
+            pattern: pattern.to_string(),
+            action,
+        }
+    }
+
+    #[test]
+    fn test_strip_tracking_params() {
+        let text =
+            "see https://example.com/a?utm_source=x&id=5&fbclid=abc#top and https://b.org/?gclid=1";
+        assert_eq!(
+            strip_tracking_params(text),
+            "see https://example.com/a?id=5#top and https://b.org/"
+        );
+    }
+
+    #[test]
+    fn test_rules_apply_in_order() {
+        let rules = RuleSet::new(&[
+            rule(r"^\s", RuleAction::Trim),
+            rule(r"foo", RuleAction::Replace { with: "bar".into() }),
+            rule(
+                r"bar",
+                RuleAction::Tag {
+                    collection: "Bars".into(),
+                },
+            ),
+            rule(r"bar", RuleAction::AutoPin),
+        ]);
+
+        let outcome = rules.apply("  foo  ");
+        assert_eq!(outcome.text, "bar");
+        assert!(outcome.pin);
+        assert!(!outcome.skip);
+        assert_eq!(outcome.collections, vec!["Bars".to_string()]);
+    }
+
+    #[test]
+    fn test_skip_and_invalid_rules() {
+        let mut disabled = rule(r".*", RuleAction::AutoPin);
+        disabled.enabled = false;
+        let rules = [
+            rule(r"(unclosed", RuleAction::Trim),
+            disabled,
+            rule(r"^secret", RuleAction::SkipRecording),
+            rule(r".*", RuleAction::AutoPin),
+        ];
+
+        let outcome = RuleSet::new(&rules).apply("secret token");
+        assert!(outcome.skip);
+        assert!(!outcome.pin);
+
+        let report = dry_run(&rules, "secret token");
+        assert!(report.rules[0].error.is_some());
+        assert!(report.rules[1].matched && !report.rules[1].enabled);
+        assert!(report.rules[2].matched);
+        assert!(report.outcome.skip && !report.outcome.pin);
+    }
+}
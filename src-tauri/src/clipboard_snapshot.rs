@@ -0,0 +1,570 @@
+//! Clipboard Snapshot Module
+//! Captures every format currently offered on the system clipboard and offers them
+//! again later, so a paste from history doesn't destroy what the user had copied.
+//!
+//! Wayland uses the data-control protocol (wlroots, KDE); other compositors and X11 go
+//! through the X11 CLIPBOARD selection (XWayland on Wayland).
+
+use std::time::Duration;
+
+/// Snapshots larger than this are not kept (formats are dropped once the budget is spent)
+const MAX_SNAPSHOT_BYTES: usize = 64 * 1024 * 1024;
+
+/// How long the clipboard owner gets to answer a conversion request
+#[cfg(target_os = "linux")]
+const CONVERT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Time the target app gets to read the pasted content before the clipboard is restored
+pub const RESTORE_DELAY: Duration = Duration::from_millis(400);
+
+/// X11 selection targets that describe the selection itself rather than its content
+#[cfg(target_os = "linux")]
+const X11_META_TARGETS: &[&str] = &[
+    "TARGETS",
+    "TIMESTAMP",
+    "MULTIPLE",
+    "SAVE_TARGETS",
+    "DELETE",
+    "INSERT_SELECTION",
+    "INSERT_PROPERTY",
+    "ATOM_PAIR",
+    "INCR",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Backend {
+    Wayland,
+    X11,
+}
+
+/// Contents of the system clipboard at one point in time, in every offered format
+#[derive(Debug, Clone)]
+pub struct ClipboardSnapshot {
+    backend: Backend,
+    /// (MIME type or X11 target name, data), in the order the owner offered them
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl ClipboardSnapshot {
+    /// Reads all formats currently on the clipboard
+    pub fn capture() -> Result<Self, String> {
+        #[cfg(target_os = "linux")]
+        {
+            if crate::session::is_wayland() {
+                match wayland::capture() {
+                    Ok(entries) => {
+                        return Ok(Self {
+                            backend: Backend::Wayland,
+                            entries,
+                        })
+                    }
+                    Err(e) => eprintln!("[ClipboardSnapshot] Wayland capture failed: {}", e),
+                }
+            }
+            x11::capture().map(|entries| Self {
+                backend: Backend::X11,
+                entries,
+            })
+        }
+        #[cfg(not(target_os = "linux"))]
+        Err("Clipboard snapshots are only supported on Linux".to_string())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Offered formats, in order
+    pub fn formats(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(mime, _)| mime.as_str())
+    }
+
+    /// The plain text representation, if any
+    pub fn text(&self) -> Option<String> {
+        const TEXT_FORMATS: &[&str] = &[
+            "text/plain;charset=utf-8",
+            "UTF8_STRING",
+            "text/plain",
+            "STRING",
+            "TEXT",
+        ];
+        TEXT_FORMATS.iter().find_map(|format| {
+            self.entries
+                .iter()
+                .find(|(mime, _)| mime.eq_ignore_ascii_case(format))
+                .and_then(|(_, data)| String::from_utf8(data.clone()).ok())
+        })
+    }
+
+    /// The PNG representation, if any
+    pub fn png(&self) -> Option<&[u8]> {
+        self.entries
+            .iter()
+            .find(|(mime, _)| mime == "image/png")
+            .map(|(_, data)| data.as_slice())
+    }
+
+    /// Offers the captured formats on the clipboard again.
+    /// An empty snapshot leaves the clipboard untouched.
+    pub fn restore(&self) -> Result<(), String> {
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+        #[cfg(target_os = "linux")]
+        {
+            match self.backend {
+                Backend::Wayland => wayland::restore(&self.entries),
+                Backend::X11 => x11::restore(self.entries.clone()),
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        Err("Clipboard snapshots are only supported on Linux".to_string())
+    }
+}
+
+/// Drops formats once the size budget is exhausted
+fn within_budget(used: &mut usize, len: usize) -> bool {
+    if *used + len > MAX_SNAPSHOT_BYTES {
+        return false;
+    }
+    *used += len;
+    true
+}
+
+#[cfg(target_os = "linux")]
+mod wayland {
+    use super::{within_budget, CONVERT_TIMEOUT, MAX_SNAPSHOT_BYTES};
+    use std::io::{ErrorKind, Read};
+    use std::os::fd::AsRawFd;
+    use std::time::Instant;
+    use wl_clipboard_rs::{copy, paste};
+
+    pub fn capture() -> Result<Vec<(String, Vec<u8>)>, String> {
+        let mime_types = match paste::get_mime_types_ordered(
+            paste::ClipboardType::Regular,
+            paste::Seat::Unspecified,
+        ) {
+            Ok(types) => types,
+            Err(paste::Error::ClipboardEmpty) | Err(paste::Error::NoSeats) => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mut entries = Vec::with_capacity(mime_types.len());
+        let mut used = 0;
+        for mime in mime_types {
+            let (pipe, _) = match paste::get_contents(
+                paste::ClipboardType::Regular,
+                paste::Seat::Unspecified,
+                paste::MimeType::Specific(&mime),
+            ) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    eprintln!("[ClipboardSnapshot] Skipping {}: {}", mime, e);
+                    continue;
+                }
+            };
+
+            match read_bounded(pipe, MAX_SNAPSHOT_BYTES - used) {
+                Ok(data) if within_budget(&mut used, data.len()) => entries.push((mime, data)),
+                Ok(_) => {}
+                Err(e) => eprintln!("[ClipboardSnapshot] Skipping {}: {}", mime, e),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Reads at most `limit + 1` bytes (enough to tell the format is over budget),
+    /// giving up if the owner doesn't finish writing within `CONVERT_TIMEOUT`
+    pub(super) fn read_bounded(pipe: impl Read + AsRawFd, limit: usize) -> Result<Vec<u8>, String> {
+        let fd = pipe.as_raw_fd();
+        // SAFETY: fd is the open pipe owned by `pipe`
+        unsafe {
+            let flags = libc::fcntl(fd, libc::F_GETFL);
+            if flags < 0 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+                return Err(std::io::Error::last_os_error().to_string());
+            }
+        }
+
+        let deadline = Instant::now() + CONVERT_TIMEOUT;
+        let mut reader = pipe.take(limit as u64 + 1);
+        let mut data = Vec::new();
+        let mut buf = [0u8; 64 * 1024];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => return Ok(data),
+                Ok(n) => data.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left.is_zero() {
+                        return Err("timed out".to_string());
+                    }
+                    let mut pollfd = libc::pollfd {
+                        fd,
+                        events: libc::POLLIN,
+                        revents: 0,
+                    };
+                    // SAFETY: pollfd is a valid single-element array for the call
+                    unsafe { libc::poll(&mut pollfd, 1, left.as_millis().max(1) as libc::c_int) };
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+
+    pub fn restore(entries: &[(String, Vec<u8>)]) -> Result<(), String> {
+        let sources = entries
+            .iter()
+            .map(|(mime, data)| copy::MimeSource {
+                source: copy::Source::Bytes(data.clone().into_boxed_slice()),
+                mime_type: copy::MimeType::Specific(mime.clone()),
+            })
+            .collect();
+
+        // Serves paste requests from a background thread until something else is copied
+        copy::Options::new()
+            .copy_multi(sources)
+            .map_err(|e| format!("Wayland restore failed: {}", e))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use super::{within_budget, CONVERT_TIMEOUT, X11_META_TARGETS};
+    use std::time::Instant;
+    use x11rb::connection::{Connection, RequestConnection};
+    use x11rb::protocol::xproto::{
+        Atom, AtomEnum, ConnectionExt, CreateWindowAux, EventMask, PropMode, Property,
+        SelectionNotifyEvent, Window, WindowClass, SELECTION_NOTIFY_EVENT,
+    };
+    use x11rb::protocol::Event;
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::wrapper::ConnectionExt as _;
+    use x11rb::NONE;
+
+    struct Atoms {
+        clipboard: Atom,
+        targets: Atom,
+        incr: Atom,
+        property: Atom,
+    }
+
+    fn intern(conn: &RustConnection, name: &str) -> Result<Atom, String> {
+        Ok(conn
+            .intern_atom(false, name.as_bytes())
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?
+            .atom)
+    }
+
+    /// Connects and creates an unmapped window to own or receive the selection
+    fn setup() -> Result<(RustConnection, Window, Atoms), String> {
+        let (conn, screen_num) =
+            x11rb::connect(None).map_err(|e| format!("X11 connect failed: {}", e))?;
+        let root = conn
+            .setup()
+            .roots
+            .get(screen_num)
+            .ok_or("Failed to get screen")?
+            .root;
+
+        let window = conn.generate_id().map_err(|e| e.to_string())?;
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )
+        .map_err(|e| format!("Failed to create window: {}", e))?;
+
+        let atoms = Atoms {
+            clipboard: intern(&conn, "CLIPBOARD")?,
+            targets: intern(&conn, "TARGETS")?,
+            incr: intern(&conn, "INCR")?,
+            property: intern(&conn, "WIN11_CLIPBOARD_SNAPSHOT")?,
+        };
+        Ok((conn, window, atoms))
+    }
+
+    /// Waits for an event matching `filter`, up to CONVERT_TIMEOUT
+    fn wait_for<T>(
+        conn: &RustConnection,
+        mut filter: impl FnMut(Event) -> Option<T>,
+    ) -> Result<T, String> {
+        let deadline = Instant::now() + CONVERT_TIMEOUT;
+        loop {
+            match conn.poll_for_event().map_err(|e| e.to_string())? {
+                Some(event) => {
+                    if let Some(value) = filter(event) {
+                        return Ok(value);
+                    }
+                }
+                None if Instant::now() >= deadline => {
+                    return Err("Clipboard owner did not respond".to_string())
+                }
+                None => std::thread::sleep(std::time::Duration::from_millis(5)),
+            }
+        }
+    }
+
+    /// Converts the selection to `target` and reads the result (including INCR transfers)
+    fn read_target(
+        conn: &RustConnection,
+        window: Window,
+        atoms: &Atoms,
+        target: Atom,
+    ) -> Result<Vec<u8>, String> {
+        conn.convert_selection(
+            window,
+            atoms.clipboard,
+            target,
+            atoms.property,
+            x11rb::CURRENT_TIME,
+        )
+        .map_err(|e| e.to_string())?;
+        conn.flush().map_err(|e| e.to_string())?;
+
+        let property = wait_for(conn, |event| match event {
+            Event::SelectionNotify(e) if e.requestor == window => Some(e.property),
+            _ => None,
+        })?;
+        if property == NONE {
+            return Err("Conversion refused".to_string());
+        }
+
+        let reply = conn
+            .get_property(true, window, atoms.property, AtomEnum::ANY, 0, u32::MAX)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+        if reply.type_ != atoms.incr {
+            return Ok(reply.value);
+        }
+
+        // INCR: the owner sends chunks each time we delete the property, ending with an empty one
+        let mut data = Vec::new();
+        loop {
+            wait_for(conn, |event| match event {
+                Event::PropertyNotify(e)
+                    if e.window == window
+                        && e.atom == atoms.property
+                        && e.state == Property::NEW_VALUE =>
+                {
+                    Some(())
+                }
+                _ => None,
+            })?;
+            let chunk = conn
+                .get_property(true, window, atoms.property, AtomEnum::ANY, 0, u32::MAX)
+                .map_err(|e| e.to_string())?
+                .reply()
+                .map_err(|e| e.to_string())?;
+            if chunk.value.is_empty() {
+                return Ok(data);
+            }
+            data.extend_from_slice(&chunk.value);
+            if data.len() > super::MAX_SNAPSHOT_BYTES {
+                return Err("Selection too large".to_string());
+            }
+        }
+    }
+
+    fn atom_name(conn: &RustConnection, atom: Atom) -> Option<String> {
+        let reply = conn.get_atom_name(atom).ok()?.reply().ok()?;
+        String::from_utf8(reply.name).ok()
+    }
+
+    pub fn capture() -> Result<Vec<(String, Vec<u8>)>, String> {
+        let (conn, window, atoms) = setup()?;
+
+        let owner = conn
+            .get_selection_owner(atoms.clipboard)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?
+            .owner;
+
+        let mut entries = Vec::new();
+        if owner != NONE {
+            let targets: Vec<Atom> = read_target(&conn, window, &atoms, atoms.targets)?
+                .chunks_exact(4)
+                .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+
+            let mut used = 0;
+            for target in targets {
+                let Some(name) = atom_name(&conn, target) else {
+                    continue;
+                };
+                if X11_META_TARGETS.contains(&name.as_str())
+                    || entries.iter().any(|(n, _)| *n == name)
+                {
+                    continue;
+                }
+                match read_target(&conn, window, &atoms, target) {
+                    Ok(data) if within_budget(&mut used, data.len()) => entries.push((name, data)),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("[ClipboardSnapshot] Skipping {}: {}", name, e),
+                }
+            }
+        }
+
+        let _ = conn.destroy_window(window);
+        let _ = conn.flush();
+        Ok(entries)
+    }
+
+    /// Takes ownership of CLIPBOARD and serves the entries from a background thread
+    /// until another client copies something.
+    pub fn restore(entries: Vec<(String, Vec<u8>)>) -> Result<(), String> {
+        let (conn, window, atoms) = setup()?;
+
+        let mut offered = Vec::with_capacity(entries.len());
+        for (name, data) in entries {
+            offered.push((intern(&conn, &name)?, data));
+        }
+
+        conn.set_selection_owner(window, atoms.clipboard, x11rb::CURRENT_TIME)
+            .map_err(|e| e.to_string())?;
+        let owner = conn
+            .get_selection_owner(atoms.clipboard)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?
+            .owner;
+        if owner != window {
+            return Err("Failed to take clipboard ownership".to_string());
+        }
+
+        std::thread::spawn(move || {
+            // Leave room for the ChangeProperty request header
+            let max_len = conn.maximum_request_bytes().saturating_sub(64);
+            let mut target_list: Vec<Atom> = offered.iter().map(|(atom, _)| *atom).collect();
+            target_list.push(atoms.targets);
+
+            while let Ok(event) = conn.wait_for_event() {
+                match event {
+                    Event::SelectionRequest(req) => {
+                        let mut property = if req.property == NONE {
+                            req.target
+                        } else {
+                            req.property
+                        };
+
+                        let result = if req.target == atoms.targets {
+                            conn.change_property32(
+                                PropMode::REPLACE,
+                                req.requestor,
+                                property,
+                                AtomEnum::ATOM,
+                                &target_list,
+                            )
+                            .map(|_| ())
+                        } else {
+                            match offered.iter().find(|(atom, _)| *atom == req.target) {
+                                // Large data would need an INCR transfer; refuse it instead
+                                Some((atom, data)) if data.len() <= max_len => conn
+                                    .change_property8(
+                                        PropMode::REPLACE,
+                                        req.requestor,
+                                        property,
+                                        *atom,
+                                        data,
+                                    )
+                                    .map(|_| ()),
+                                _ => {
+                                    property = NONE;
+                                    Ok(())
+                                }
+                            }
+                        };
+                        if result.is_err() {
+                            property = NONE;
+                        }
+
+                        let notify = SelectionNotifyEvent {
+                            response_type: SELECTION_NOTIFY_EVENT,
+                            sequence: 0,
+                            time: req.time,
+                            requestor: req.requestor,
+                            selection: req.selection,
+                            target: req.target,
+                            property,
+                        };
+                        let _ = conn.send_event(false, req.requestor, EventMask::NO_EVENT, notify);
+                        let _ = conn.flush();
+                    }
+                    Event::SelectionClear(e) if e.selection == atoms.clipboard => break,
+                    _ => {}
+                }
+            }
+
+            let _ = conn.destroy_window(window);
+            let _ = conn.flush();
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_and_png_lookup() {
+        let snapshot = ClipboardSnapshot {
+            backend: Backend::X11,
+            entries: vec![
+                ("text/html".to_string(), b"<b>hi</b>".to_vec()),
+                ("UTF8_STRING".to_string(), b"hi".to_vec()),
+                ("image/png".to_string(), vec![0x89, b'P', b'N', b'G']),
+            ],
+        };
+
+        assert_eq!(snapshot.text().as_deref(), Some("hi"));
+        assert_eq!(snapshot.png(), Some(&[0x89, b'P', b'N', b'G'][..]));
+        assert_eq!(
+            snapshot.formats().collect::<Vec<_>>(),
+            vec!["text/html", "UTF8_STRING", "image/png"]
+        );
+        assert!(!snapshot.is_empty());
+    }
+
+    #[test]
+    fn test_budget() {
+        let mut used = 0;
+        assert!(within_budget(&mut used, MAX_SNAPSHOT_BYTES - 10));
+        assert!(!within_budget(&mut used, 11));
+        assert!(within_budget(&mut used, 10));
+        assert_eq!(used, MAX_SNAPSHOT_BYTES);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_wayland_read_bounded() {
+        use std::io::Write;
+        use std::os::unix::net::UnixStream;
+
+        // Stops one byte past the limit
+        let (reader, mut writer) = UnixStream::pair().unwrap();
+        writer.write_all(&[7; 100]).unwrap();
+        drop(writer);
+        assert_eq!(wayland::read_bounded(reader, 10).unwrap().len(), 11);
+
+        // An owner that never finishes writing is given up on
+        let (reader, mut writer) = UnixStream::pair().unwrap();
+        writer.write_all(b"partial").unwrap();
+        let started = std::time::Instant::now();
+        assert!(wayland::read_bounded(reader, 1024).is_err());
+        assert!(started.elapsed() >= CONVERT_TIMEOUT);
+    }
+}
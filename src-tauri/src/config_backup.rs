@@ -0,0 +1,765 @@
+//! Config Backup Module
+//! Saves every desktop config file, gsettings key and xfconf property before we change it,
+//! and what we wrote, in a manifest under the data dir, so the changes can be shown as a
+//! diff and rolled back.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Lines of unchanged context around each change in a diff
+const DIFF_CONTEXT: usize = 3;
+
+/// Edit distance after which a diff just replaces the whole file
+const MAX_DIFF_EDITS: usize = 1000;
+
+/// Serializes manifest updates from the registration thread and the UI
+static MANIFEST_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Set while changes aren't recorded, see `ConfigBackups::pause`
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Records changes again when dropped
+pub struct PauseGuard;
+
+impl Drop for PauseGuard {
+    fn drop(&mut self) {
+        PAUSED.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Something we changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BackupTarget {
+    File { path: PathBuf },
+    GSettings { schema: String, key: String },
+    Xfconf { channel: String, property: String },
+}
+
+impl std::fmt::Display for BackupTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File { path } => write!(f, "{}", path.display()),
+            Self::GSettings { schema, key } => write!(f, "gsettings {} {}", schema, key),
+            Self::Xfconf { channel, property } => write!(f, "xfconf {} {}", channel, property),
+        }
+    }
+}
+
+/// The state of a target before one of our changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub target: BackupTarget,
+    /// Unix time the backup was taken
+    pub timestamp: u64,
+    /// What the change was for
+    pub reason: String,
+    /// Copy of the file in the backup dir, or the setting's value; `None` if it didn't exist
+    pub original: Option<String>,
+    /// State right after our change, in the same form as `original`. Missing until the
+    /// change is made, and for backups of older versions.
+    #[serde(default, deserialize_with = "present")]
+    pub written: Option<Option<String>>,
+}
+
+/// Keeps a `null` field apart from a missing one
+fn present<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Option<String>>, D::Error> {
+    Option::deserialize(d).map(Some)
+}
+
+/// Difference between the oldest backup of a target and its current state
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChange {
+    pub target: String,
+    /// Unix time of the oldest backup
+    pub timestamp: u64,
+    /// Reasons of all changes, oldest first
+    pub reasons: Vec<String>,
+    /// Unified diff from the original to the current state; empty when they match
+    pub diff: String,
+}
+
+/// Backups of the desktop configuration we modify
+pub struct ConfigBackups {
+    dir: PathBuf,
+}
+
+impl ConfigBackups {
+    /// Uses the app's data dir (e.g., ~/.local/share/win11-clipboard-history/config-backups/)
+    pub fn new() -> Self {
+        Self::with_dir(
+            dirs::data_local_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("win11-clipboard-history")
+                .join("config-backups"),
+        )
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Stops recording changes until the guard is dropped, for changes that undo our own
+    /// and shouldn't leave backups behind
+    pub fn pause() -> PauseGuard {
+        PAUSED.store(true, Ordering::SeqCst);
+        PauseGuard
+    }
+
+    fn load(&self) -> Vec<BackupEntry> {
+        fs::read_to_string(self.dir.join(MANIFEST_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, entries: &[BackupEntry]) -> Result<(), String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+        let content = serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+        fs::write(self.dir.join(MANIFEST_FILE), content)
+            .map_err(|e| format!("Failed to write backup manifest: {}", e))
+    }
+
+    fn record(
+        &self,
+        target: BackupTarget,
+        reason: &str,
+        original: impl FnOnce(usize) -> Result<Option<String>, String>,
+    ) -> Result<(), String> {
+        if PAUSED.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let _lock = MANIFEST_LOCK.lock();
+        let mut entries = self.load();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let original = original(entries.len())?;
+        entries.push(BackupEntry {
+            target,
+            timestamp,
+            reason: reason.to_string(),
+            original,
+            written: None,
+        });
+        self.save(&entries)
+    }
+
+    /// Copies a file into the backup dir under a new name, `None` if it doesn't exist
+    fn copy_file(
+        &self,
+        path: &Path,
+        suffix: &str,
+        entries: usize,
+    ) -> Result<Option<String>, String> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // The entry count keeps names unique within a second
+        let copy = format!("{}-{}-{}{}", timestamp, entries, name, suffix);
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+        fs::copy(path, self.dir.join(&copy))
+            .map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+        Ok(Some(copy))
+    }
+
+    /// Records the state we left the target in, on its newest backup
+    fn record_written(&self, target: BackupTarget) -> Result<(), String> {
+        if PAUSED.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let _lock = MANIFEST_LOCK.lock();
+        let mut entries = self.load();
+        let count = entries.len();
+        let Some(entry) = entries.iter_mut().rev().find(|e| e.target == target) else {
+            return Ok(());
+        };
+        let state = match &target {
+            BackupTarget::File { path } => self.copy_file(path, ".written", count)?,
+            _ => current_state(&target),
+        };
+        entry.written = Some(state);
+        self.save(&entries)
+    }
+
+    /// Copies `path` into the backup dir before it is changed
+    pub fn backup_file(&self, path: &Path, reason: &str) -> Result<(), String> {
+        let target = BackupTarget::File {
+            path: path.to_path_buf(),
+        };
+        self.record(target, reason, |count| {
+            let copy = self.copy_file(path, "", count)?;
+            if let Some(copy) = &copy {
+                println!("[ConfigBackup] Backed up {} as {}", path.display(), copy);
+            }
+            Ok(copy)
+        })
+    }
+
+    /// Saves a gsettings value before it is changed
+    pub fn backup_gsettings(&self, schema: &str, key: &str, reason: &str) -> Result<(), String> {
+        let value = gsettings_get(schema, key)?;
+        let target = BackupTarget::GSettings {
+            schema: schema.to_string(),
+            key: key.to_string(),
+        };
+        self.record(target, reason, |_| Ok(Some(value)))
+    }
+
+    /// Saves an xfconf property before it is changed
+    pub fn backup_xfconf(&self, channel: &str, property: &str, reason: &str) -> Result<(), String> {
+        let target = BackupTarget::Xfconf {
+            channel: channel.to_string(),
+            property: property.to_string(),
+        };
+        let value = current_state(&target);
+        self.record(target, reason, |_| Ok(value))
+    }
+
+    /// Records the file as we wrote it, after a `backup_file`
+    pub fn file_written(&self, path: &Path) -> Result<(), String> {
+        self.record_written(BackupTarget::File {
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Records the gsettings value we set, after a `backup_gsettings`
+    pub fn gsettings_written(&self, schema: &str, key: &str) -> Result<(), String> {
+        self.record_written(BackupTarget::GSettings {
+            schema: schema.to_string(),
+            key: key.to_string(),
+        })
+    }
+
+    /// Records the xfconf property we set or removed, after a `backup_xfconf`
+    pub fn xfconf_written(&self, channel: &str, property: &str) -> Result<(), String> {
+        self.record_written(BackupTarget::Xfconf {
+            channel: channel.to_string(),
+            property: property.to_string(),
+        })
+    }
+
+    /// Entries grouped by target, oldest first
+    fn by_target(entries: &[BackupEntry]) -> Vec<(&BackupTarget, Vec<&BackupEntry>)> {
+        let mut groups: Vec<(&BackupTarget, Vec<&BackupEntry>)> = Vec::new();
+        for entry in entries {
+            match groups
+                .iter_mut()
+                .find(|(target, _)| **target == entry.target)
+            {
+                Some((_, group)) => group.push(entry),
+                None => groups.push((&entry.target, vec![entry])),
+            }
+        }
+        groups
+    }
+
+    /// What changed in each target since its oldest backup
+    pub fn changes(&self) -> Vec<ConfigChange> {
+        let entries = self.load();
+        Self::by_target(&entries)
+            .into_iter()
+            .map(|(target, group)| {
+                let original = self.stored(target, &group[0].original);
+                let current = current_state(target);
+                ConfigChange {
+                    target: target.to_string(),
+                    timestamp: group[0].timestamp,
+                    reasons: group.iter().map(|e| e.reason.clone()).collect(),
+                    diff: unified_diff(
+                        original.as_deref().unwrap_or_default(),
+                        current.as_deref().unwrap_or_default(),
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    /// Text of a recorded state, reading file copies from the backup dir; `None` if the
+    /// target didn't exist
+    fn stored(&self, target: &BackupTarget, state: &Option<String>) -> Option<String> {
+        match (target, state) {
+            (BackupTarget::File { .. }, Some(copy)) => {
+                Some(fs::read_to_string(self.dir.join(copy)).unwrap_or_default())
+            }
+            (_, state) => state.clone(),
+        }
+    }
+
+    /// Deletes the file copies of an entry
+    fn remove_copies(&self, entry: &BackupEntry) {
+        if let BackupTarget::File { .. } = entry.target {
+            let written = entry.written.clone().flatten();
+            for copy in [&entry.original, &written].into_iter().flatten() {
+                let _ = fs::remove_file(self.dir.join(copy));
+            }
+        }
+    }
+
+    /// Undoes our changes to every target, newest first. A target edited since we wrote
+    /// it is left as is and reported with the diff, so those edits aren't lost. Undone
+    /// changes leave the manifest; the others stay so the rollback can be retried.
+    pub fn rollback(&self) -> Result<Vec<String>, String> {
+        self.rollback_where(|_| true)
+    }
+
+    /// Undoes our changes to settings only, leaving files as they are. Used once our own
+    /// bindings were removed from the files, so edits made since are kept.
+    pub fn rollback_settings(&self) -> Result<Vec<String>, String> {
+        self.rollback_where(|target| !matches!(target, BackupTarget::File { .. }))
+    }
+
+    fn rollback_where(
+        &self,
+        filter: impl Fn(&BackupTarget) -> bool,
+    ) -> Result<Vec<String>, String> {
+        let _lock = MANIFEST_LOCK.lock();
+        let entries = self.load();
+        let mut restored = Vec::new();
+        let mut errors = Vec::new();
+        let mut remaining = Vec::new();
+
+        for (target, group) in Self::by_target(&entries) {
+            if !filter(target) {
+                remaining.extend(group.into_iter().cloned());
+                continue;
+            }
+            let (undone, result) = self.undo(target, &group);
+            match result {
+                Ok(true) => {
+                    println!("[ConfigBackup] Restored {}", target);
+                    restored.push(format!("Restored: {}", target));
+                }
+                Ok(false) => {}
+                Err(e) => errors.push(format!("{}: {}", target, e)),
+            }
+            let (kept, dropped) = group.split_at(group.len() - undone);
+            remaining.extend(kept.iter().map(|e| (*e).clone()));
+            for entry in dropped {
+                self.remove_copies(entry);
+            }
+        }
+
+        self.save(&remaining)?;
+        if errors.is_empty() {
+            Ok(restored)
+        } else {
+            // Blank lines separate the targets, whose errors can hold a diff
+            Err(errors.join("\n\n"))
+        }
+    }
+
+    /// Undoes the group's changes newest first, as long as the target is still the way we
+    /// left it. Returns how many were undone, and whether the target was changed: when it
+    /// is already back to its original state, all of them are dropped as they are.
+    fn undo(&self, target: &BackupTarget, group: &[&BackupEntry]) -> (usize, Result<bool, String>) {
+        let mut current = current_state(target);
+        if current == self.stored(target, &group[0].original) {
+            return (group.len(), Ok(false));
+        }
+
+        for (undone, entry) in group.iter().rev().enumerate() {
+            let Some(written) = &entry.written else {
+                let e = "no record of what was written, left as is".to_string();
+                return (undone, Err(e));
+            };
+            let written = self.stored(target, written);
+            if written != current {
+                let e = format!(
+                    "changed since it was written, left as is:\n{}",
+                    unified_diff(
+                        written.as_deref().unwrap_or_default(),
+                        current.as_deref().unwrap_or_default()
+                    )
+                    .trim_end()
+                );
+                return (undone, Err(e));
+            }
+            if let Err(e) = self.restore(entry) {
+                return (undone, Err(e));
+            }
+            current = current_state(target);
+        }
+        (group.len(), Ok(true))
+    }
+
+    fn restore(&self, entry: &BackupEntry) -> Result<(), String> {
+        match (&entry.target, &entry.original) {
+            (BackupTarget::File { path }, Some(copy)) => fs::copy(self.dir.join(copy), path)
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            // We created the file
+            (BackupTarget::File { path }, None) => match fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+                _ => Ok(()),
+            },
+            (BackupTarget::GSettings { schema, key }, Some(value)) => {
+                gsettings(&["set", schema, key, value]).map(|_| ())
+            }
+            (BackupTarget::GSettings { .. }, None) => Ok(()),
+            (BackupTarget::Xfconf { channel, property }, Some(value)) => xfconf(&[
+                "-c", channel, "-p", property, "-n", "-t", "string", "-s", value,
+            ])
+            .map(|_| ()),
+            (BackupTarget::Xfconf { channel, property }, None) => {
+                xfconf(&["-c", channel, "-p", property, "-r"]).map(|_| ())
+            }
+        }
+    }
+}
+
+impl Default for ConfigBackups {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn gsettings(args: &[&str]) -> Result<String, String> {
+    run("gsettings", args)
+}
+
+fn gsettings_get(schema: &str, key: &str) -> Result<String, String> {
+    gsettings(&["get", schema, key])
+}
+
+fn xfconf(args: &[&str]) -> Result<String, String> {
+    run("xfconf-query", args)
+}
+
+/// What the target holds now; `None` if it doesn't exist or can't be read
+fn current_state(target: &BackupTarget) -> Option<String> {
+    match target {
+        BackupTarget::File { path } => fs::read_to_string(path).ok(),
+        BackupTarget::GSettings { schema, key } => gsettings_get(schema, key).ok(),
+        BackupTarget::Xfconf { channel, property } => xfconf(&["-c", channel, "-p", property]).ok(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// Line edits turning `a` into `b`, using the linear-space variant of Myers' O(ND)
+/// algorithm: each half of the edit script is found around the middle snake
+fn line_edits<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(Edit, &'a str)> {
+    let mut edits = Vec::with_capacity(a.len() + b.len());
+    diff_into(a, b, &mut edits);
+    // Removals before additions within each change, as diffs are usually read
+    for change in edits.split_mut(|(edit, _)| *edit == Edit::Keep) {
+        change.sort_by_key(|(edit, _)| *edit == Edit::Add);
+    }
+    edits
+}
+
+fn diff_into<'a>(a: &[&'a str], b: &[&'a str], edits: &mut Vec<(Edit, &'a str)>) {
+    // Common lines at both ends are kept as they are
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    edits.extend(a[..prefix].iter().map(|line| (Edit::Keep, *line)));
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let common = &a[a.len() - suffix..];
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    let split = if a.is_empty() || b.is_empty() {
+        None
+    } else {
+        middle_snake(a, b)
+    };
+    match split {
+        Some((x, y)) => {
+            diff_into(&a[..x], &b[..y], edits);
+            diff_into(&a[x..], &b[y..], edits);
+        }
+        // One side is empty, or they're too different to be worth a minimal diff
+        _ => {
+            edits.extend(a.iter().map(|line| (Edit::Remove, *line)));
+            edits.extend(b.iter().map(|line| (Edit::Add, *line)));
+        }
+    }
+    edits.extend(common.iter().map(|line| (Edit::Keep, *line)));
+}
+
+/// Point where an optimal edit path crosses the middle of the edit distance, searched
+/// from both ends at once. `None` beyond `MAX_DIFF_EDITS`.
+fn middle_snake(a: &[&str], b: &[&str]) -> Option<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let d_max = (n + m + 1) / 2 + 1;
+    // Furthest x reached on each diagonal k, forward from the start and backward from the end
+    let mut forward = vec![0isize; 2 * d_max as usize + 1];
+    let mut backward = vec![0isize; 2 * d_max as usize + 1];
+    let at = |k: isize| (k + d_max) as usize;
+
+    for d in 0..d_max.min(MAX_DIFF_EDITS as isize / 2 + 1) {
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            let start = (x, y);
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+            if odd && (k - delta).abs() < d && x + backward[at(delta - k)] >= n {
+                return Some((start.0 as usize, start.1 as usize));
+            }
+        }
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+            if !odd && (k - delta).abs() <= d && x + forward[at(delta - k)] >= n {
+                return Some(((n - x) as usize, (m - y) as usize));
+            }
+        }
+    }
+    None
+}
+
+/// Unified diff of two texts with `DIFF_CONTEXT` lines of context; empty if they match
+fn unified_diff(original: &str, current: &str) -> String {
+    let a: Vec<&str> = original.lines().collect();
+    let b: Vec<&str> = current.lines().collect();
+    let edits = line_edits(&a, &b);
+
+    // Line numbers (1-based) in the original and current text before each edit
+    let mut positions = Vec::with_capacity(edits.len());
+    let (mut old_line, mut new_line) = (1, 1);
+    for (edit, _) in &edits {
+        positions.push((old_line, new_line));
+        match edit {
+            Edit::Keep => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Edit::Remove => old_line += 1,
+            Edit::Add => new_line += 1,
+        }
+    }
+
+    // Ranges of edits shown, each change widened by the context and merged when they touch
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, (edit, _)) in edits.iter().enumerate() {
+        if *edit == Edit::Keep {
+            continue;
+        }
+        let start = i.saturating_sub(DIFF_CONTEXT);
+        let end = (i + DIFF_CONTEXT + 1).min(edits.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = String::new();
+    for (start, end) in hunks {
+        let slice = &edits[start..end];
+        let old_count = slice.iter().filter(|(e, _)| *e != Edit::Add).count();
+        let new_count = slice.iter().filter(|(e, _)| *e != Edit::Remove).count();
+        let (old_start, new_start) = positions[start];
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        ));
+        for (edit, line) in slice {
+            let sign = match edit {
+                Edit::Keep => ' ',
+                Edit::Remove => '-',
+                Edit::Add => '+',
+            };
+            out.push(sign);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Lists what we changed in the desktop configuration, as diffs against the backups
+#[tauri::command]
+pub fn get_config_changes() -> Vec<ConfigChange> {
+    ConfigBackups::new().changes()
+}
+
+/// Undoes our changes to config files and settings, including disabled conflicts. Those
+/// edited since are reported with their diff instead.
+#[tauri::command]
+pub fn rollback_config_changes() -> Result<Vec<String>, String> {
+    ConfigBackups::new().rollback()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let original = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl";
+        let current = "a\nb\nc\n# d\ne\nf\ng\nh\ni\nj\nk\nl\nadded";
+        assert_eq!(
+            unified_diff(original, current),
+            "@@ -1,7 +1,7 @@\n a\n b\n c\n-d\n+# d\n e\n f\n g\n\
+             @@ -10,3 +10,4 @@\n j\n k\n l\n+added\n"
+        );
+        assert_eq!(unified_diff(original, original), "");
+        assert_eq!(unified_diff("", "x"), "@@ -1,0 +1,1 @@\n+x\n");
+    }
+
+    #[test]
+    fn test_line_edits() {
+        // Minimal edit counts, checked against the LCS length of small inputs
+        let texts = [
+            "", "a", "ab", "ba", "abcabba", "cbabac", "xaxbxc", "abcdef", "fedcba",
+        ];
+        for old in texts {
+            for new in texts {
+                let a: Vec<&str> = old.split("").filter(|c| !c.is_empty()).collect();
+                let b: Vec<&str> = new.split("").filter(|c| !c.is_empty()).collect();
+                let edits = line_edits(&a, &b);
+                let kept = |side: Edit| {
+                    edits
+                        .iter()
+                        .filter(|(e, _)| *e != side)
+                        .map(|(_, l)| *l)
+                        .collect::<Vec<_>>()
+                };
+                assert_eq!(kept(Edit::Add), a, "{} -> {}", old, new);
+                assert_eq!(kept(Edit::Remove), b, "{} -> {}", old, new);
+
+                let mut lcs = vec![vec![0; b.len() + 1]; a.len() + 1];
+                for i in (0..a.len()).rev() {
+                    for j in (0..b.len()).rev() {
+                        lcs[i][j] = if a[i] == b[j] {
+                            lcs[i + 1][j + 1] + 1
+                        } else {
+                            lcs[i + 1][j].max(lcs[i][j + 1])
+                        };
+                    }
+                }
+                let keeps = edits.iter().filter(|(e, _)| *e == Edit::Keep).count();
+                assert_eq!(keeps, lcs[0][0], "{} -> {}", old, new);
+            }
+        }
+    }
+
+    #[test]
+    fn test_backup_and_rollback() {
+        let dir = std::env::temp_dir().join(format!("config-backup-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let backups = ConfigBackups::with_dir(dir.join("backups"));
+
+        let config = dir.join("config");
+        fs::write(&config, "bindsym $mod+v split vertical").unwrap();
+        backups.backup_file(&config, "Clipboard History").unwrap();
+        fs::write(&config, "# bindsym $mod+v split vertical").unwrap();
+        backups.file_written(&config).unwrap();
+        backups
+            .backup_file(&config, "Clipboard History (Alt)")
+            .unwrap();
+        fs::write(
+            &config,
+            "# bindsym $mod+v split vertical\nbindsym Ctrl+Alt+v exec x",
+        )
+        .unwrap();
+        backups.file_written(&config).unwrap();
+
+        // A file we create is removed on rollback
+        let created = dir.join("created");
+        backups.backup_file(&created, "Clipboard History").unwrap();
+        fs::write(&created, "new").unwrap();
+        backups.file_written(&created).unwrap();
+
+        let changes = backups.changes();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].reasons.len(), 2);
+        assert!(changes[0].diff.contains("-bindsym $mod+v split vertical\n"));
+        assert!(changes[1].diff.contains("+new\n"));
+
+        assert_eq!(backups.rollback().unwrap().len(), 2);
+        assert_eq!(
+            fs::read_to_string(&config).unwrap(),
+            "bindsym $mod+v split vertical"
+        );
+        assert!(!created.exists());
+        assert!(backups.changes().is_empty());
+        assert_eq!(fs::read_dir(dir.join("backups")).unwrap().count(), 1);
+
+        // A file edited since we wrote it is left alone, with the edits shown
+        backups.backup_file(&config, "Clipboard History").unwrap();
+        fs::write(&config, "# bindsym $mod+v split vertical").unwrap();
+        backups.file_written(&config).unwrap();
+        fs::write(
+            &config,
+            "# bindsym $mod+v split vertical\nbindsym $mod+t term",
+        )
+        .unwrap();
+        let error = backups.rollback().unwrap_err();
+        assert!(error.contains("changed since it was written"));
+        assert!(error.contains("+bindsym $mod+t term"));
+        assert!(fs::read_to_string(&config).unwrap().ends_with("term"));
+        assert_eq!(backups.changes().len(), 1);
+
+        // Once back to the original, the backup is just dropped
+        fs::write(&config, "bindsym $mod+v split vertical").unwrap();
+        assert!(backups.rollback().unwrap().is_empty());
+        assert!(backups.changes().is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
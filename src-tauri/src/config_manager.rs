@@ -1,24 +1,227 @@
 //! Config Manager Module
-//! Handles persistence of window state (position, monitor) specifically for Wayland usage.
+//! Handles persistence of window state (position and size per monitor) and decides where
+//! the window opens for each placement mode.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use tauri::{Monitor, PhysicalPosition, PhysicalSize};
 
 const CONFIG_FILE: &str = "window_state.json";
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct WindowState {
-    pub monitor_name: Option<String>,
+/// Schema version written to window_state.json.
+/// v1 (no `version` field): a single `monitor_name`, `x`, `y` in absolute coordinates.
+/// v2: per-monitor positions relative to the monitor, plus the window size.
+/// v3: monitors keyed by name, size and position (see `monitor_key`) instead of name alone.
+pub const WINDOW_STATE_VERSION: u32 = 3;
+
+/// Smallest size a remembered window size is restored at
+const MIN_WINDOW_SIZE: PhysicalSize<u32> = PhysicalSize {
+    width: 300,
+    height: 320,
+};
+
+/// Window geometry remembered on one monitor. The position is relative to the monitor's
+/// top-left corner, so it survives the monitor moving around in the layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedGeometry {
     pub x: i32,
     pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Everything remembered for one monitor
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonitorState {
+    /// Most recent geometry on this monitor, used in layouts it hasn't been part of before
+    #[serde(default)]
+    pub last: Option<SavedGeometry>,
+    /// Geometry per monitor layout (see `layout_fingerprint`) this monitor has been part of
+    #[serde(default)]
+    pub layouts: HashMap<String, SavedGeometry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowState {
+    pub version: u32,
+    /// Keyed by `monitor_key`
+    #[serde(default)]
+    pub monitors: HashMap<String, MonitorState>,
+    /// Absolute position read from a v1 file: (monitor name, x, y). Used until the window is
+    /// moved on that monitor, as v1 didn't record the monitor's origin.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    legacy: Option<(String, i32, i32)>,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        Self {
+            version: WINDOW_STATE_VERSION,
+            monitors: HashMap::new(),
+            legacy: None,
+        }
+    }
+}
+
+/// The single position stored by v1
+#[derive(Deserialize)]
+struct WindowStateV1 {
+    monitor_name: Option<String>,
+    x: i32,
+    y: i32,
+}
+
+impl WindowState {
+    /// Parses any known schema version, migrating older ones
+    fn from_json(content: &str) -> Result<Self, String> {
+        let value: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        match value.get("version").and_then(|v| v.as_u64()) {
+            None => {
+                let v1: WindowStateV1 = serde_json::from_value(value).map_err(|e| e.to_string())?;
+                println!("[ConfigManager] Migrating window state from v1");
+                Ok(Self {
+                    legacy: v1.monitor_name.map(|name| (name, v1.x, v1.y)),
+                    ..Self::default()
+                })
+            }
+            Some(version) if version <= WINDOW_STATE_VERSION as u64 => {
+                let mut state: Self = serde_json::from_value(value).map_err(|e| e.to_string())?;
+                state.version = WINDOW_STATE_VERSION;
+                Ok(state)
+            }
+            Some(version) => Err(format!(
+                "window state version {} is newer than supported ({})",
+                version, WINDOW_STATE_VERSION
+            )),
+        }
+    }
+
+    /// Whether the content was written by a newer version of the app
+    fn is_newer_version(content: &str) -> bool {
+        serde_json::from_str::<serde_json::Value>(content)
+            .ok()
+            .and_then(|value| value.get("version")?.as_u64())
+            .is_some_and(|version| version > WINDOW_STATE_VERSION as u64)
+    }
+
+    /// Geometry remembered for the monitor: for this exact layout, else the most recent one.
+    /// A monitor that moved in the layout since gets the latest geometry of its model,
+    /// unless identical monitors make that ambiguous.
+    pub fn geometry_on(&self, monitor: &MonitorArea, layout: &str) -> Option<SavedGeometry> {
+        if let Some(entry) = self.monitors.get(&monitor_key(monitor)) {
+            return entry.layouts.get(layout).or(entry.last.as_ref()).copied();
+        }
+        let mut same_model = self
+            .monitors
+            .iter()
+            .filter(|(key, _)| is_same_model(key, monitor));
+        match (same_model.next(), same_model.next()) {
+            (Some((_, entry)), None) => entry.last,
+            _ => None,
+        }
+    }
+
+    /// Remembers the window's absolute position and size on the monitor
+    pub fn record(
+        &mut self,
+        monitor: &MonitorArea,
+        layout: &str,
+        position: PhysicalPosition<i32>,
+        size: PhysicalSize<u32>,
+    ) {
+        let geometry = SavedGeometry {
+            x: position.x - monitor.position.x,
+            y: position.y - monitor.position.y,
+            width: size.width,
+            height: size.height,
+        };
+        // v2 kept the monitor under its name alone
+        if let Some(name) = &monitor.name {
+            self.monitors.remove(name);
+        }
+        let entry = self.monitors.entry(monitor_key(monitor)).or_default();
+        entry.last = Some(geometry);
+        entry.layouts.insert(layout.to_string(), geometry);
+        if self.legacy.as_ref().map(|(name, ..)| name) == monitor.name.as_ref() {
+            self.legacy = None;
+        }
+    }
+
+    /// Window size to restore on the monitor, bounded to fit on it
+    pub fn saved_size(&self, monitor: &MonitorArea, layout: &str) -> Option<PhysicalSize<u32>> {
+        let geometry = self.geometry_on(monitor, layout)?;
+        Some(PhysicalSize::new(
+            geometry
+                .width
+                .min(monitor.size.width)
+                .max(MIN_WINDOW_SIZE.width),
+            geometry
+                .height
+                .min(monitor.size.height)
+                .max(MIN_WINDOW_SIZE.height),
+        ))
+    }
+
+    /// Absolute saved position on the monitor, if any
+    fn saved_position(&self, monitor: &MonitorArea, layout: &str) -> Option<PhysicalPosition<i32>> {
+        if let Some(geometry) = self.geometry_on(monitor, layout) {
+            return Some(PhysicalPosition::new(
+                monitor.position.x + geometry.x,
+                monitor.position.y + geometry.y,
+            ));
+        }
+        match &self.legacy {
+            Some((name, x, y)) if monitor.name.as_ref() == Some(name) => {
+                Some(PhysicalPosition::new(*x, *y))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The monitor's name and size, e.g. `DELL U2720Q 3840x2160`. Identical monitors share it.
+fn model_key(monitor: &MonitorArea) -> String {
+    let size = format!("{}x{}", monitor.size.width, monitor.size.height);
+    match &monitor.name {
+        Some(name) => format!("{} {}", name, size),
+        None => size,
+    }
+}
+
+/// Identifies a monitor across sessions: its name and size, plus its place in the layout
+/// to tell identical monitors apart, e.g. `DELL U2720Q 3840x2160@0,0`
+pub fn monitor_key(monitor: &MonitorArea) -> String {
+    format!(
+        "{}@{},{}",
+        model_key(monitor),
+        monitor.position.x,
+        monitor.position.y
+    )
+}
+
+/// Whether a `monitors` key belongs to the monitor's model, at any position.
+/// v2 keys are the bare monitor name.
+fn is_same_model(key: &str, monitor: &MonitorArea) -> bool {
+    key.strip_prefix(model_key(monitor).as_str())
+        .is_some_and(|rest| rest.starts_with('@'))
+        || monitor.name.as_deref() == Some(key)
+}
+
+/// Identifies the set of connected monitors and their arrangement, e.g. docked vs undocked
+pub fn layout_fingerprint(monitors: &[MonitorArea]) -> String {
+    let mut parts: Vec<String> = monitors.iter().map(monitor_key).collect();
+    parts.sort();
+    parts.join(";")
 }
 
 pub struct ConfigManager {
     data_dir: PathBuf,
     state: WindowState,
     dirty: bool, // Tracks if we have unsaved changes in memory
+    /// Set when the file belongs to a newer version of the app, which keeps it as is
+    read_only: bool,
 }
 
 impl ConfigManager {
@@ -27,6 +230,7 @@ impl ConfigManager {
             data_dir,
             state: WindowState::default(),
             dirty: false,
+            read_only: false,
         };
 
         if let Err(e) = manager.load() {
@@ -43,17 +247,21 @@ impl ConfigManager {
         self.state.clone()
     }
 
-    /// Updates the state in memory only. Use sync_to_disk() to flush.
-    pub fn update_state(&mut self, monitor_name: Option<String>, x: i32, y: i32) {
-        self.state.monitor_name = monitor_name;
-        self.state.x = x;
-        self.state.y = y;
+    /// Records the window geometry on a monitor in memory only. Use sync_to_disk() to flush.
+    pub fn update_state(
+        &mut self,
+        monitor: &MonitorArea,
+        layout: &str,
+        position: PhysicalPosition<i32>,
+        size: PhysicalSize<u32>,
+    ) {
+        self.state.record(monitor, layout, position, size);
         self.dirty = true;
     }
 
     /// Flushes changes to disk only if there are unsaved changes.
     pub fn sync_to_disk(&mut self) {
-        if self.dirty {
+        if self.dirty && !self.read_only {
             if let Err(e) = self.save_to_disk() {
                 eprintln!("[ConfigManager] Failed to save config: {}", e);
             } else {
@@ -74,7 +282,8 @@ impl ConfigManager {
             return Ok(());
         }
         let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
-        self.state = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        self.read_only = WindowState::is_newer_version(&content);
+        self.state = WindowState::from_json(&content)?;
         Ok(())
     }
 
@@ -88,68 +297,300 @@ impl ConfigManager {
     }
 }
 
-/// Determines where the window should be placed based on saved state and available monitors.
-pub fn resolve_window_position(
+/// Where the history window opens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowPlacement {
+    /// Mouse cursor on X11, saved position on Wayland
+    #[default]
+    Auto,
+    /// Below the text caret of the focused app, like Windows 11
+    Caret,
+    /// At the mouse cursor
+    Mouse,
+    /// Centre of the active monitor
+    MonitorCenter,
+    /// Where the window was last moved to
+    Saved,
+    /// Bottom-centre of the active monitor
+    BottomCenter,
+}
+
+impl WindowPlacement {
+    /// Resolves `Auto` to the mode used by the session type
+    pub fn for_session(self, wayland: bool) -> Self {
+        match self {
+            Self::Auto if wayland => Self::Saved,
+            Self::Auto => Self::Mouse,
+            other => other,
+        }
+    }
+
+    /// Mode tried next when this one's source isn't available
+    fn fallback(self) -> Option<Self> {
+        match self {
+            Self::Caret => Some(Self::Mouse),
+            Self::Mouse => Some(Self::MonitorCenter),
+            Self::Saved => Some(Self::BottomCenter),
+            Self::Auto | Self::MonitorCenter | Self::BottomCenter => None,
+        }
+    }
+}
+
+/// A monitor's name and bounds, in physical pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorArea {
+    pub name: Option<String>,
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+}
+
+impl From<&Monitor> for MonitorArea {
+    fn from(monitor: &Monitor) -> Self {
+        Self {
+            name: monitor.name().cloned(),
+            position: *monitor.position(),
+            size: *monitor.size(),
+        }
+    }
+}
+
+impl MonitorArea {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.position.x
+            && x < (self.position.x + self.size.width as i32)
+            && y >= self.position.y
+            && y < (self.position.y + self.size.height as i32)
+    }
+
+    /// Whether this is the compositor output with the given layout rectangle. Monitor
+    /// bounds are the logical ones times the monitor's integer scale, so the scale is
+    /// taken from the widths and the rest has to agree with it.
+    fn shows(&self, output: &ScreenRect) -> bool {
+        if output.width <= 0 || output.height <= 0 {
+            return false;
+        }
+        let scale = (self.size.width as f64 / output.width as f64)
+            .round()
+            .max(1.0);
+        let close =
+            |physical: i32, logical: i32| (physical as f64 - logical as f64 * scale).abs() <= scale;
+        close(self.size.width as i32, output.width)
+            && close(self.size.height as i32, output.height)
+            && close(self.position.x, output.x)
+            && close(self.position.y, output.y)
+    }
+}
+
+/// A rectangle in screen coordinates, e.g. the text caret
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// What the placement modes anchor to; `None` where the source isn't available
+#[derive(Debug, Clone, Default)]
+pub struct PlacementSources {
+    pub caret: Option<ScreenRect>,
+    pub cursor: Option<PhysicalPosition<i32>>,
+    /// Bounds of the focused toplevel's output in the compositor's logical layout.
+    /// Compositors name outputs by connector (`DP-2`), which monitors don't report.
+    pub focused_output: Option<ScreenRect>,
+}
+
+/// Where the window opens, and the monitor it opens on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement<'a> {
+    pub position: PhysicalPosition<i32>,
+    pub monitor: &'a MonitorArea,
+}
+
+/// Determines where the window opens, given its size on each monitor. Modes whose source
+/// is missing fall back through `WindowPlacement::fallback`, ending at the bottom-centre
+/// of the active monitor (see `active_monitor`). `monitors` must not be empty.
+pub fn resolve_placement<'a>(
+    mode: WindowPlacement,
     state: &WindowState,
-    available_monitors: &[Monitor],
-    window_size: PhysicalSize<u32>,
-) -> PhysicalPosition<i32> {
-    // 1. Try to restore saved position if monitor exists and position is valid
-    if let Some(saved_monitor_name) = &state.monitor_name {
-        if let Some(monitor) = available_monitors.iter().find(|m| {
-            m.name()
-                .is_some_and(|n| n.as_str() == saved_monitor_name.as_str())
-        }) {
-            if is_position_valid(state.x, state.y, monitor, window_size) {
-                return PhysicalPosition::new(state.x, state.y);
-            }
+    sources: &PlacementSources,
+    monitors: &'a [MonitorArea],
+    window_size: impl Fn(&MonitorArea) -> PhysicalSize<u32>,
+) -> Placement<'a> {
+    let active = active_monitor(sources, monitors);
+
+    let mut mode = Some(mode);
+    while let Some(current) = mode {
+        if let Some(placement) = place(current, state, sources, monitors, active, &window_size) {
+            return placement;
         }
+        mode = current.fallback();
+    }
+    Placement {
+        position: calculate_bottom_center(active, window_size(active)),
+        monitor: active,
     }
+}
+
+/// The monitor the user is on: the one under the cursor, else the one holding the focused
+/// toplevel, else the first of `monitors`
+pub fn active_monitor<'a>(
+    sources: &PlacementSources,
+    monitors: &'a [MonitorArea],
+) -> &'a MonitorArea {
+    sources
+        .cursor
+        .and_then(|c| monitor_at(monitors, c.x, c.y))
+        .or_else(|| {
+            let output = sources.focused_output?;
+            monitors.iter().find(|m| m.shows(&output))
+        })
+        .unwrap_or(&monitors[0])
+}
 
-    // 2. Fallback: Default to Bottom-Center of Primary (or first available)
-    let target_monitor = available_monitors
-        .iter()
-        .find(|m| m.scale_factor() > 0.0) // Just a check to get first valid one
-        .unwrap_or(&available_monitors[0]);
+fn place<'a>(
+    mode: WindowPlacement,
+    state: &WindowState,
+    sources: &PlacementSources,
+    monitors: &'a [MonitorArea],
+    active: &'a MonitorArea,
+    window_size: &impl Fn(&MonitorArea) -> PhysicalSize<u32>,
+) -> Option<Placement<'a>> {
+    let on_active = |position| Placement {
+        position,
+        monitor: active,
+    };
+    match mode {
+        WindowPlacement::Caret => {
+            let caret = sources.caret?;
+            let monitor = monitor_at(monitors, caret.x, caret.y)?;
+            Some(Placement {
+                position: position_at_caret(caret, monitor, window_size(monitor)),
+                monitor,
+            })
+        }
+        WindowPlacement::Mouse => {
+            let cursor = sources.cursor?;
+            let position = clamp_to_monitor(active, cursor.x, cursor.y, window_size(active));
+            Some(on_active(position))
+        }
+        WindowPlacement::MonitorCenter => {
+            Some(on_active(calculate_center(active, window_size(active))))
+        }
+        WindowPlacement::Saved => restore_saved_position(state, monitors, active, window_size),
+        WindowPlacement::Auto | WindowPlacement::BottomCenter => Some(on_active(
+            calculate_bottom_center(active, window_size(active)),
+        )),
+    }
+}
 
-    calculate_bottom_center(target_monitor, window_size)
+fn monitor_at(monitors: &[MonitorArea], x: i32, y: i32) -> Option<&MonitorArea> {
+    monitors.iter().find(|m| m.contains(x, y))
 }
 
-/// Checks if a coordinate is "valid" based on bounds and visibility heuristics.
-fn is_position_valid(x: i32, y: i32, monitor: &Monitor, window_size: PhysicalSize<u32>) -> bool {
-    is_top_left_within_monitor(x, y, monitor)
-        && has_min_vertical_visibility(y, monitor, window_size)
+/// The position saved on the active monitor, else on any other connected monitor, as long
+/// as it is still valid in the current layout
+fn restore_saved_position<'a>(
+    state: &WindowState,
+    monitors: &'a [MonitorArea],
+    active: &'a MonitorArea,
+    window_size: &impl Fn(&MonitorArea) -> PhysicalSize<u32>,
+) -> Option<Placement<'a>> {
+    let layout = layout_fingerprint(monitors);
+    std::iter::once(active)
+        .chain(monitors.iter().filter(|m| *m != active))
+        .find_map(|monitor| {
+            let position = state.saved_position(monitor, &layout)?;
+            is_position_valid(position.x, position.y, monitor, window_size(monitor))
+                .then_some(Placement { position, monitor })
+        })
 }
 
-/// Ensures the window's top-left corner is strictly inside the monitor bounds.
-fn is_top_left_within_monitor(x: i32, y: i32, monitor: &Monitor) -> bool {
-    let m_pos = monitor.position();
-    let m_size = monitor.size();
+/// Below the caret, or above it when there is no room underneath
+fn position_at_caret(
+    caret: ScreenRect,
+    monitor: &MonitorArea,
+    window_size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    const CARET_GAP: i32 = 4;
 
-    x >= m_pos.x
-        && x < (m_pos.x + m_size.width as i32)
-        && y >= m_pos.y
-        && y < (m_pos.y + m_size.height as i32)
+    let monitor_bottom = monitor.position.y + monitor.size.height as i32;
+    let below = caret.y + caret.height + CARET_GAP;
+    let y = if below + window_size.height as i32 <= monitor_bottom {
+        below
+    } else {
+        caret.y - window_size.height as i32 - CARET_GAP
+    };
+    clamp_to_monitor(monitor, caret.x, y, window_size)
+}
+
+/// Keeps the window inside the monitor with 10px padding
+fn clamp_to_monitor(
+    monitor: &MonitorArea,
+    x: i32,
+    y: i32,
+    window_size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    const PADDING: i32 = 10;
+
+    let m_pos = monitor.position;
+    let max_x = m_pos.x + monitor.size.width as i32 - window_size.width as i32 - PADDING;
+    let max_y = m_pos.y + monitor.size.height as i32 - window_size.height as i32 - PADDING;
+
+    // min before max: a window larger than the monitor sticks to the top-left
+    PhysicalPosition::new(
+        x.min(max_x).max(m_pos.x + PADDING),
+        y.min(max_y).max(m_pos.y + PADDING),
+    )
+}
+
+/// Checks if a coordinate is "valid" based on bounds and visibility heuristics.
+fn is_position_valid(
+    x: i32,
+    y: i32,
+    monitor: &MonitorArea,
+    window_size: PhysicalSize<u32>,
+) -> bool {
+    monitor.contains(x, y) && has_min_vertical_visibility(y, monitor, window_size)
 }
 
 /// Ensures at least the top half of the window remains visible on the monitor.
-fn has_min_vertical_visibility(y: i32, monitor: &Monitor, window_size: PhysicalSize<u32>) -> bool {
-    let m_pos = monitor.position();
-    let m_size = monitor.size();
+fn has_min_vertical_visibility(
+    y: i32,
+    monitor: &MonitorArea,
+    window_size: PhysicalSize<u32>,
+) -> bool {
+    let m_pos = monitor.position;
+    let m_size = monitor.size;
     // Ensure y is not so low that the window falls off the bottom entirely.
     // We require the top half (height/2) to be above the bottom edge of the monitor.
     y < (m_pos.y + m_size.height as i32 - (window_size.height as i32 / 2))
 }
 
+/// Calculates a position centred on the monitor.
+fn calculate_center(
+    monitor: &MonitorArea,
+    window_size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    let m_pos = monitor.position;
+    let m_size = monitor.size;
+
+    PhysicalPosition::new(
+        m_pos.x + (m_size.width as i32 / 2) - (window_size.width as i32 / 2),
+        m_pos.y + (m_size.height as i32 / 2) - (window_size.height as i32 / 2),
+    )
+}
+
 /// Calculates a centered position at the bottom of the screen.
 fn calculate_bottom_center(
-    monitor: &Monitor,
+    monitor: &MonitorArea,
     window_size: PhysicalSize<u32>,
 ) -> PhysicalPosition<i32> {
     const PADDING_BOTTOM: i32 = 45;
 
-    let m_pos = monitor.position();
-    let m_size = monitor.size();
+    let m_pos = monitor.position;
+    let m_size = monitor.size;
 
     // X = center horizontally
     let x = m_pos.x + (m_size.width as i32 / 2) - (window_size.width as i32 / 2);
@@ -159,3 +600,388 @@ fn calculate_bottom_center(
 
     PhysicalPosition::new(x, y)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: PhysicalSize<u32> = PhysicalSize {
+        width: 360,
+        height: 480,
+    };
+
+    fn monitors() -> Vec<MonitorArea> {
+        vec![
+            MonitorArea {
+                name: Some("DP-1".to_string()),
+                position: PhysicalPosition::new(0, 0),
+                size: PhysicalSize::new(1920, 1080),
+            },
+            MonitorArea {
+                name: Some("HDMI-1".to_string()),
+                position: PhysicalPosition::new(1920, 0),
+                size: PhysicalSize::new(1280, 1024),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_placement_modes() {
+        let monitors = monitors();
+        let state = WindowState::default();
+        let sources = PlacementSources {
+            caret: Some(ScreenRect {
+                x: 2000,
+                y: 100,
+                width: 2,
+                height: 18,
+            }),
+            cursor: Some(PhysicalPosition::new(500, 500)),
+            focused_output: None,
+        };
+        let place =
+            |mode| resolve_placement(mode, &state, &sources, &monitors, |_| WINDOW).position;
+
+        // Below the caret, on the caret's monitor
+        assert_eq!(
+            place(WindowPlacement::Caret),
+            PhysicalPosition::new(2000, 122)
+        );
+        assert_eq!(
+            place(WindowPlacement::Mouse),
+            PhysicalPosition::new(500, 500)
+        );
+        // The active monitor is the one under the cursor
+        assert_eq!(
+            place(WindowPlacement::MonitorCenter),
+            PhysicalPosition::new(780, 300)
+        );
+        assert_eq!(
+            place(WindowPlacement::BottomCenter),
+            PhysicalPosition::new(780, 555)
+        );
+
+        // A caret near the bottom edge opens the window above it
+        let low_caret = PlacementSources {
+            caret: Some(ScreenRect {
+                x: 100,
+                y: 1000,
+                width: 2,
+                height: 18,
+            }),
+            cursor: None,
+            focused_output: None,
+        };
+        assert_eq!(
+            resolve_placement(
+                WindowPlacement::Caret,
+                &state,
+                &low_caret,
+                &monitors,
+                |_| WINDOW
+            )
+            .position,
+            PhysicalPosition::new(100, 516)
+        );
+    }
+
+    #[test]
+    fn test_placement_fallbacks() {
+        let monitors = monitors();
+        let none = PlacementSources::default();
+        let mut state = WindowState::default();
+
+        // No caret and no cursor: caret and mouse end at the centre of the first monitor
+        for mode in [WindowPlacement::Caret, WindowPlacement::Mouse] {
+            assert_eq!(
+                resolve_placement(mode, &state, &none, &monitors, |_| WINDOW).position,
+                PhysicalPosition::new(780, 300)
+            );
+        }
+
+        // Wayland: no cursor, but the compositor reports the focused output
+        let focused = PlacementSources {
+            focused_output: Some(ScreenRect {
+                x: 1920,
+                y: 0,
+                width: 1280,
+                height: 1024,
+            }),
+            ..PlacementSources::default()
+        };
+        assert_eq!(
+            resolve_placement(WindowPlacement::Mouse, &state, &focused, &monitors, |_| {
+                WINDOW
+            })
+            .position,
+            PhysicalPosition::new(2380, 272)
+        );
+
+        // Saved positions need their monitor and must stay visible
+        let layout = layout_fingerprint(&monitors);
+        state.record(
+            &monitors[1],
+            &layout,
+            PhysicalPosition::new(2100, 200),
+            WINDOW,
+        );
+        let saved = |state: &WindowState| {
+            resolve_placement(WindowPlacement::Saved, state, &none, &monitors, |_| WINDOW).position
+        };
+        assert_eq!(saved(&state), PhysicalPosition::new(2100, 200));
+        state.record(
+            &monitors[1],
+            &layout,
+            PhysicalPosition::new(2100, 900),
+            WINDOW,
+        );
+        assert_eq!(saved(&state), PhysicalPosition::new(780, 555));
+        assert_eq!(
+            resolve_placement(
+                WindowPlacement::Saved,
+                &WindowState::default(),
+                &none,
+                &monitors,
+                |_| WINDOW
+            )
+            .position,
+            PhysicalPosition::new(780, 555)
+        );
+
+        assert_eq!(
+            WindowPlacement::Auto.for_session(true),
+            WindowPlacement::Saved
+        );
+        assert_eq!(
+            WindowPlacement::Auto.for_session(false),
+            WindowPlacement::Mouse
+        );
+    }
+
+    #[test]
+    fn test_focused_output_by_geometry() {
+        // Monitors carry model names and bounds scaled by their integer scale factor
+        let monitors = vec![
+            MonitorArea {
+                name: Some("Samsung Display Corp. 0x4193".to_string()),
+                position: PhysicalPosition::new(0, 0),
+                size: PhysicalSize::new(2880, 1800),
+            },
+            MonitorArea {
+                name: Some("DELL U2720Q".to_string()),
+                position: PhysicalPosition::new(1440, 0),
+                size: PhysicalSize::new(1920, 1080),
+            },
+        ];
+        let output = |x, width, height| PlacementSources {
+            focused_output: Some(ScreenRect {
+                x,
+                y: 0,
+                width,
+                height,
+            }),
+            ..PlacementSources::default()
+        };
+
+        // Scale 2 panel at the origin, external monitor next to it at scale 1
+        assert_eq!(
+            active_monitor(&output(0, 1440, 900), &monitors),
+            &monitors[0]
+        );
+        assert_eq!(
+            active_monitor(&output(1440, 1920, 1080), &monitors),
+            &monitors[1]
+        );
+        // No monitor with that geometry: the first one
+        assert_eq!(
+            active_monitor(&output(5000, 1920, 1080), &monitors),
+            &monitors[0]
+        );
+    }
+
+    #[test]
+    fn test_window_state_per_monitor() {
+        let docked = monitors();
+        let docked_layout = layout_fingerprint(&docked);
+        let mut state = WindowState::default();
+        state.record(
+            &docked[0],
+            &docked_layout,
+            PhysicalPosition::new(100, 200),
+            PhysicalSize::new(400, 600),
+        );
+        state.record(
+            &docked[1],
+            &docked_layout,
+            PhysicalPosition::new(2000, 100),
+            WINDOW,
+        );
+
+        // Each monitor restores its own geometry; the cursor picks the monitor
+        let on = |x, y, state: &WindowState, monitors: &[MonitorArea]| {
+            let sources = PlacementSources {
+                cursor: Some(PhysicalPosition::new(x, y)),
+                ..PlacementSources::default()
+            };
+            resolve_placement(WindowPlacement::Saved, state, &sources, monitors, |_| {
+                WINDOW
+            })
+            .position
+        };
+        assert_eq!(on(10, 10, &state, &docked), PhysicalPosition::new(100, 200));
+        assert_eq!(
+            on(2500, 10, &state, &docked),
+            PhysicalPosition::new(2000, 100)
+        );
+        assert_eq!(
+            state.saved_size(&docked[0], &docked_layout),
+            Some(PhysicalSize::new(400, 600))
+        );
+
+        // Undocked, DP-1 moved to the right of a laptop panel: the position follows it
+        let mut undocked = vec![MonitorArea {
+            name: Some("eDP-1".to_string()),
+            position: PhysicalPosition::new(0, 0),
+            size: PhysicalSize::new(1280, 800),
+        }];
+        undocked.push(MonitorArea {
+            position: PhysicalPosition::new(1280, 0),
+            ..docked[0].clone()
+        });
+        assert_eq!(
+            on(1300, 10, &state, &undocked),
+            PhysicalPosition::new(1380, 200)
+        );
+        // Unknown monitor under the cursor: another monitor's saved position
+        assert_eq!(
+            on(10, 10, &state, &undocked),
+            PhysicalPosition::new(1380, 200)
+        );
+
+        // Sizes are bounded to fit the monitor
+        state.record(
+            &undocked[0],
+            &layout_fingerprint(&undocked),
+            PhysicalPosition::new(0, 0),
+            PhysicalSize::new(100, 5000),
+        );
+        assert_eq!(
+            state.saved_size(&undocked[0], "other layout"),
+            Some(PhysicalSize::new(300, 800))
+        );
+    }
+
+    #[test]
+    fn test_window_state_versions() {
+        let monitors = monitors();
+        let none = PlacementSources::default();
+
+        // v1 positions are used until the window is moved on that monitor
+        let v1 = WindowState::from_json(r#"{"monitor_name":"HDMI-1","x":2100,"y":200}"#).unwrap();
+        assert_eq!(v1.version, WINDOW_STATE_VERSION);
+        assert_eq!(
+            resolve_placement(WindowPlacement::Saved, &v1, &none, &monitors, |_| WINDOW).position,
+            PhysicalPosition::new(2100, 200)
+        );
+        // ... including after the first save
+        let resaved = WindowState::from_json(&serde_json::to_string(&v1).unwrap()).unwrap();
+        assert_eq!(
+            resaved.saved_position(&monitors[1], "layout"),
+            Some(PhysicalPosition::new(2100, 200))
+        );
+
+        // v2 monitors, keyed by name, are still found
+        let v2 = WindowState::from_json(
+            r#"{"version":2,"monitors":{"HDMI-1":{"last":{"x":10,"y":20,"width":400,"height":500}}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            v2.saved_position(&monitors[1], "layout"),
+            Some(PhysicalPosition::new(1930, 20))
+        );
+
+        let mut state = WindowState::default();
+        state.record(&monitors[0], "layout", PhysicalPosition::new(5, 6), WINDOW);
+        let json = serde_json::to_string(&state).unwrap();
+        let reloaded = WindowState::from_json(&json).unwrap();
+        assert_eq!(
+            reloaded.geometry_on(&monitors[0], "layout"),
+            state.geometry_on(&monitors[0], "layout")
+        );
+
+        assert!(WindowState::from_json(r#"{"version":99,"monitors":{}}"#).is_err());
+    }
+
+    #[test]
+    fn test_newer_version_is_kept() {
+        let dir = std::env::temp_dir().join(format!("window-state-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let newer = r#"{"version":99,"monitors":{}}"#;
+        fs::write(dir.join(CONFIG_FILE), newer).unwrap();
+
+        let mut manager = ConfigManager::new(dir.clone());
+        manager.update_state(
+            &monitors()[0],
+            "layout",
+            PhysicalPosition::new(5, 6),
+            WINDOW,
+        );
+        manager.sync_to_disk();
+        assert_eq!(fs::read_to_string(dir.join(CONFIG_FILE)).unwrap(), newer);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_identical_monitors() {
+        let model = |x| MonitorArea {
+            name: Some("DELL U2720Q".to_string()),
+            position: PhysicalPosition::new(x, 0),
+            size: PhysicalSize::new(1920, 1080),
+        };
+        let monitors = vec![model(0), model(1920)];
+        assert_ne!(monitor_key(&monitors[0]), monitor_key(&monitors[1]));
+
+        let layout = layout_fingerprint(&monitors);
+        let mut state = WindowState::default();
+        state.record(
+            &monitors[0],
+            &layout,
+            PhysicalPosition::new(100, 100),
+            PhysicalSize::new(400, 600),
+        );
+        state.record(
+            &monitors[1],
+            &layout,
+            PhysicalPosition::new(2000, 100),
+            WINDOW,
+        );
+        assert_eq!(
+            state.saved_size(&monitors[0], &layout),
+            Some(PhysicalSize::new(400, 600))
+        );
+        assert_eq!(state.saved_size(&monitors[1], &layout), Some(WINDOW));
+        // A third one elsewhere can't tell which of them it matches
+        assert_eq!(state.geometry_on(&model(3840), "other"), None);
+
+        // The window is sized for the monitor its saved position is on, not the active one
+        let sources = PlacementSources {
+            cursor: Some(PhysicalPosition::new(2500, 500)),
+            ..PlacementSources::default()
+        };
+        state.record(
+            &monitors[1],
+            &layout,
+            PhysicalPosition::new(1920, 900),
+            WINDOW,
+        );
+        let placement = resolve_placement(
+            WindowPlacement::Saved,
+            &state,
+            &sources,
+            &monitors,
+            |monitor| state.saved_size(monitor, &layout).unwrap(),
+        );
+        assert_eq!(placement.monitor, &monitors[0]);
+        assert_eq!(placement.position, PhysicalPosition::new(100, 100));
+    }
+}
@@ -1,7 +1,14 @@
 //! Focus Manager Module
-//! Tracks and restores window focus for proper paste injection on X11.
-//! Also provides X11 window activation using EWMH protocols.
+//! Tracks and restores window focus for proper paste injection.
+//! X11 uses the input focus directly; native Wayland sessions go through the compositor
+//! (see `wayland_focus`). Also provides X11 window activation using EWMH protocols.
 
+#[cfg(target_os = "linux")]
+use crate::config_manager::ScreenRect;
+#[cfg(target_os = "linux")]
+use once_cell::sync::Lazy;
+#[cfg(target_os = "linux")]
+use parking_lot::Mutex;
 #[cfg(target_os = "linux")]
 use std::sync::atomic::{AtomicU32, Ordering};
 #[cfg(target_os = "linux")]
@@ -23,8 +30,102 @@ static LAST_FOCUSED_WINDOW: AtomicU32 = AtomicU32::new(0);
 
 // --- Linux Implementation ---
 
+/// Records the active toplevel before our window opens and reactivates it before a paste
+#[cfg(target_os = "linux")]
+pub trait FocusBackend: Send {
+    fn name(&self) -> &'static str;
+    /// Records the currently active toplevel
+    fn save(&mut self) -> Result<(), String>;
+    /// Reactivates the recorded toplevel
+    fn restore(&mut self) -> Result<(), String>;
+    /// Class names (WM_CLASS or Wayland app id) of the recorded toplevel
+    fn saved_class(&self) -> Option<Vec<String>>;
+    /// Class names of the toplevel active right now; the recorded one is left as is
+    fn active_class(&mut self) -> Option<Vec<String>>;
+    /// Layout bounds of the output the user was working on when the toplevel was
+    /// recorded, for compositors that report it
+    fn saved_output(&self) -> Option<ScreenRect> {
+        None
+    }
+}
+
+/// Input focus on the X server; also covers XWayland windows
+#[cfg(target_os = "linux")]
+pub struct X11Focus;
+
+#[cfg(target_os = "linux")]
+impl FocusBackend for X11Focus {
+    fn name(&self) -> &'static str {
+        "X11"
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        x11_save_focused_window();
+        Ok(())
+    }
+
+    fn restore(&mut self) -> Result<(), String> {
+        x11_restore_focused_window()
+    }
+
+    fn saved_class(&self) -> Option<Vec<String>> {
+        let window_id = LAST_FOCUSED_WINDOW.load(Ordering::SeqCst);
+        if window_id == 0 {
+            return None;
+        }
+        let conn = get_x11_connection().ok()?;
+        window_class(&conn, window_id)
+    }
+
+    fn active_class(&mut self) -> Option<Vec<String>> {
+        x11_active_window_class()
+    }
+}
+
+#[cfg(target_os = "linux")]
+static FOCUS_BACKEND: Lazy<Mutex<Box<dyn FocusBackend>>> = Lazy::new(|| {
+    let backend = select_backend();
+    eprintln!("[FocusManager] Using {} focus backend", backend.name());
+    Mutex::new(backend)
+});
+
+/// Native Wayland sessions use the compositor's IPC; X11 (and unknown compositors,
+/// through XWayland) use the X input focus
+#[cfg(target_os = "linux")]
+fn select_backend() -> Box<dyn FocusBackend> {
+    if crate::session::is_wayland() {
+        if let Some(backend) = crate::wayland_focus::backend_for_session() {
+            return backend;
+        }
+    }
+    Box::new(X11Focus)
+}
+
 #[cfg(target_os = "linux")]
 pub fn save_focused_window() {
+    let mut backend = FOCUS_BACKEND.lock();
+    if let Err(e) = backend.save() {
+        eprintln!(
+            "[FocusManager] {} failed to save the focused window: {}",
+            backend.name(),
+            e
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn restore_focused_window() -> Result<(), String> {
+    FOCUS_BACKEND.lock().restore()?;
+
+    // Small delay to ensure the Window Manager processes the focus change
+    // before we attempt to simulate keystrokes
+    thread::sleep(FOCUS_RESTORE_DELAY);
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn x11_save_focused_window() {
     match get_x11_connection() {
         Ok(conn) => match conn.get_input_focus() {
             Ok(cookie) => match cookie.reply() {
@@ -42,7 +143,7 @@ pub fn save_focused_window() {
 }
 
 #[cfg(target_os = "linux")]
-pub fn restore_focused_window() -> Result<(), String> {
+fn x11_restore_focused_window() -> Result<(), String> {
     let window_id = LAST_FOCUSED_WINDOW.load(Ordering::SeqCst);
 
     if window_id == 0 {
@@ -58,10 +159,6 @@ pub fn restore_focused_window() -> Result<(), String> {
 
     conn.flush().map_err(|e| format!("Flush failed: {}", e))?;
 
-    // Small delay to ensure the Window Manager processes the focus change
-    // before we attempt to simulate keystrokes
-    thread::sleep(FOCUS_RESTORE_DELAY);
-
     Ok(())
 }
 
@@ -76,6 +173,78 @@ pub fn get_focused_window() -> Option<u32> {
     Some(reply.focus)
 }
 
+/// Returns the class names of the active window, through the compositor on native
+/// Wayland sessions and `_NET_ACTIVE_WINDOW` otherwise
+#[cfg(target_os = "linux")]
+pub fn active_window_class() -> Option<Vec<String>> {
+    FOCUS_BACKEND.lock().active_class()
+}
+
+/// Returns the WM_CLASS (instance and class names) of the window in `_NET_ACTIVE_WINDOW`.
+/// Works on X11 and for XWayland windows.
+#[cfg(target_os = "linux")]
+fn x11_active_window_class() -> Option<Vec<String>> {
+    let (conn, screen_num) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen_num)?.root;
+
+    let active_atom = conn
+        .intern_atom(false, b"_NET_ACTIVE_WINDOW")
+        .ok()?
+        .reply()
+        .ok()?
+        .atom;
+    let window = conn
+        .get_property(false, root, active_atom, AtomEnum::WINDOW, 0, 1)
+        .ok()?
+        .reply()
+        .ok()?
+        .value32()?
+        .next()
+        .filter(|&w| w != 0)?;
+
+    window_class(&conn, window)
+}
+
+/// Returns the class names of the window saved by `save_focused_window`
+#[cfg(target_os = "linux")]
+pub fn saved_window_class() -> Option<Vec<String>> {
+    FOCUS_BACKEND.lock().saved_class()
+}
+
+/// Returns the output the user was working on when `save_focused_window` ran
+#[cfg(target_os = "linux")]
+pub fn saved_focused_output() -> Option<ScreenRect> {
+    FOCUS_BACKEND.lock().saved_output()
+}
+
+/// Reads WM_CLASS, walking up to the top-level window when the focus is on a child window
+#[cfg(target_os = "linux")]
+fn window_class(conn: &impl Connection, mut window: u32) -> Option<Vec<String>> {
+    loop {
+        let reply = conn
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+            .ok()?
+            .reply()
+            .ok()?;
+        if !reply.value.is_empty() {
+            return Some(
+                reply
+                    .value
+                    .split(|&b| b == 0)
+                    .filter(|part| !part.is_empty())
+                    .map(|part| String::from_utf8_lossy(part).into_owned())
+                    .collect(),
+            );
+        }
+
+        let tree = conn.query_tree(window).ok()?.reply().ok()?;
+        if tree.parent == 0 || tree.parent == tree.root {
+            return None;
+        }
+        window = tree.parent;
+    }
+}
+
 /// Helper to establish X11 connection
 #[cfg(target_os = "linux")]
 fn get_x11_connection() -> Result<impl Connection, String> {
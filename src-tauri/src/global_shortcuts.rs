@@ -0,0 +1,325 @@
+//! Global Shortcuts Portal Module
+//! Binds shortcuts through `org.freedesktop.portal.GlobalShortcuts`, implemented by GNOME 48+,
+//! KDE and Hyprland. The user confirms the bindings in a portal dialog, and presses arrive as
+//! Activated signals on the running instance instead of launching a new process.
+
+use crate::portal::{
+    call_request, new_token, string_value, PortalResults, PORTAL_BUS_NAME, PORTAL_PATH,
+    SESSION_INTERFACE,
+};
+use std::collections::HashMap;
+use zbus::blocking::{Connection, MessageIterator, Proxy};
+use zbus::message::Type;
+use zbus::zvariant::{OwnedObjectPath, Value};
+use zbus::MatchRule;
+
+const GLOBAL_SHORTCUTS_INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+const REGISTRY_INTERFACE: &str = "org.freedesktop.host.portal.Registry";
+
+/// App id the portal shows the shortcuts under (our desktop file name)
+const APP_ID: &str = "win11-clipboard-history";
+
+/// A shortcut to bind
+#[derive(Debug, Clone)]
+pub struct PortalShortcut {
+    pub id: String,
+    pub description: String,
+    /// Preferred trigger in the portal format (`LOGO+v`), see `Chord::portal`
+    pub trigger: String,
+}
+
+/// A shortcut as the portal bound it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundShortcut {
+    pub id: String,
+    /// Trigger the user accepted, as shown by the portal; empty if left unassigned
+    pub trigger: String,
+}
+
+/// Whether the session bus offers the GlobalShortcuts portal
+pub fn available(conn: &Connection) -> bool {
+    Proxy::new(
+        conn,
+        PORTAL_BUS_NAME,
+        PORTAL_PATH,
+        GLOBAL_SHORTCUTS_INTERFACE,
+    )
+    .and_then(|proxy| proxy.get_property::<u32>("version"))
+    .is_ok()
+}
+
+/// Tells the portal which app an unsandboxed connection belongs to. Must come before
+/// any other portal call; older portals don't have the registry, which is fine.
+fn register_app(conn: &Connection) {
+    let options: HashMap<&str, Value> = HashMap::new();
+    if let Err(e) = conn.call_method(
+        Some(PORTAL_BUS_NAME),
+        PORTAL_PATH,
+        Some(REGISTRY_INTERFACE),
+        "Register",
+        &(APP_ID, options),
+    ) {
+        println!("[GlobalShortcuts] App registry unavailable: {}", e);
+    }
+}
+
+/// Reads the `shortcuts` entry of a BindShortcuts or ListShortcuts response
+fn bound_shortcuts(results: &PortalResults) -> Vec<BoundShortcut> {
+    results
+        .get("shortcuts")
+        .and_then(|v| v.try_clone().ok())
+        .and_then(|v| Vec::<(String, PortalResults)>::try_from(v).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(id, properties)| BoundShortcut {
+            id,
+            trigger: string_value(&properties, "trigger_description").unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// A GlobalShortcuts session, closed on drop. Shortcuts stay bound while it is open.
+pub struct GlobalShortcutsSession {
+    conn: Connection,
+    session: OwnedObjectPath,
+}
+
+impl GlobalShortcutsSession {
+    pub fn create(conn: &Connection) -> Result<Self, String> {
+        register_app(conn);
+        let results = call_request(conn, "CreateSession", |token| {
+            let session_token = new_token();
+            let options = HashMap::from([
+                ("handle_token", Value::from(token)),
+                ("session_handle_token", Value::from(session_token.as_str())),
+            ]);
+            conn.call_method(
+                Some(PORTAL_BUS_NAME),
+                PORTAL_PATH,
+                Some(GLOBAL_SHORTCUTS_INTERFACE),
+                "CreateSession",
+                &(options,),
+            )
+            .map(|_| ())
+        })?;
+        let session = string_value(&results, "session_handle")
+            .and_then(|path| OwnedObjectPath::try_from(path).ok())
+            .ok_or("Portal returned no session handle")?;
+
+        Ok(Self {
+            conn: conn.clone(),
+            session,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        self.session.as_str()
+    }
+
+    /// Binds the shortcuts. The portal may ask the user to confirm or pick other triggers.
+    pub fn bind(&self, shortcuts: &[PortalShortcut]) -> Result<Vec<BoundShortcut>, String> {
+        let list: Vec<(&str, HashMap<&str, Value>)> = shortcuts
+            .iter()
+            .map(|s| {
+                (
+                    s.id.as_str(),
+                    HashMap::from([
+                        ("description", Value::from(s.description.as_str())),
+                        ("preferred_trigger", Value::from(s.trigger.as_str())),
+                    ]),
+                )
+            })
+            .collect();
+
+        let results = call_request(&self.conn, "BindShortcuts", |token| {
+            let options = HashMap::from([("handle_token", Value::from(token))]);
+            self.conn
+                .call_method(
+                    Some(PORTAL_BUS_NAME),
+                    PORTAL_PATH,
+                    Some(GLOBAL_SHORTCUTS_INTERFACE),
+                    "BindShortcuts",
+                    &(self.session.as_ref(), list, "", options),
+                )
+                .map(|_| ())
+        })?;
+        Ok(bound_shortcuts(&results))
+    }
+
+    /// Ids of this session's shortcuts, as they are pressed. Watch before binding so
+    /// no press is missed; the iterator ends when the connection closes.
+    pub fn activations(&self) -> Result<impl Iterator<Item = String>, String> {
+        let rule = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .interface(GLOBAL_SHORTCUTS_INTERFACE)
+            .and_then(|b| b.member("Activated"))
+            .and_then(|b| b.path(PORTAL_PATH))
+            .map_err(|e| e.to_string())?
+            .build();
+        let messages = MessageIterator::for_match_rule(rule, &self.conn, None)
+            .map_err(|e| format!("Failed to watch shortcut activations: {}", e))?;
+
+        let session = self.session.clone();
+        Ok(messages.filter_map(Result::ok).filter_map(move |message| {
+            let (handle, id, _timestamp, _options): (OwnedObjectPath, String, u64, PortalResults) =
+                message.body().deserialize().ok()?;
+            (handle == session).then_some(id)
+        }))
+    }
+}
+
+impl Drop for GlobalShortcutsSession {
+    fn drop(&mut self) {
+        let _ = self.conn.call_method(
+            Some(PORTAL_BUS_NAME),
+            self.session.as_ref(),
+            Some(SESSION_INTERFACE),
+            "Close",
+            &(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portal::test_bus::{respond, PrivateBus};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+    use zbus::message::Header;
+    use zbus::zvariant::{ObjectPath, OwnedValue};
+
+    const SESSION_PATH: &str = "/org/freedesktop/portal/desktop/session/1/test";
+
+    /// Mock portal that binds every shortcut to its preferred trigger
+    #[derive(Default)]
+    struct MockPortal {
+        triggers: Arc<Mutex<Vec<(String, String)>>>,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.portal.GlobalShortcuts")]
+    impl MockPortal {
+        async fn create_session(
+            &self,
+            #[zbus(header)] header: Header<'_>,
+            #[zbus(connection)] conn: &zbus::Connection,
+            options: HashMap<String, OwnedValue>,
+        ) -> OwnedObjectPath {
+            let results = HashMap::from([("session_handle", Value::from(SESSION_PATH))]);
+            respond(conn, &header, &options, results).await
+        }
+
+        async fn bind_shortcuts(
+            &self,
+            #[zbus(header)] header: Header<'_>,
+            #[zbus(connection)] conn: &zbus::Connection,
+            _session: ObjectPath<'_>,
+            shortcuts: Vec<(String, HashMap<String, OwnedValue>)>,
+            _parent_window: &str,
+            options: HashMap<String, OwnedValue>,
+        ) -> OwnedObjectPath {
+            let bound: Vec<(String, HashMap<&str, Value>)> = shortcuts
+                .into_iter()
+                .map(|(id, properties)| {
+                    let trigger = string_value(&properties, "preferred_trigger").unwrap();
+                    self.triggers.lock().push((id.clone(), trigger.clone()));
+                    (
+                        id,
+                        HashMap::from([("trigger_description", Value::from(trigger))]),
+                    )
+                })
+                .collect();
+            let results = HashMap::from([("shortcuts", Value::from(bound))]);
+            respond(conn, &header, &options, results).await
+        }
+
+        #[zbus(property, name = "version")]
+        fn version(&self) -> u32 {
+            1
+        }
+    }
+
+    #[test]
+    fn test_bound_shortcuts() {
+        let properties = HashMap::from([(
+            "trigger_description".to_string(),
+            OwnedValue::try_from(Value::from("Super+V")).unwrap(),
+        )]);
+        let list = vec![("toggle".to_string(), properties)];
+        let results = HashMap::from([(
+            "shortcuts".to_string(),
+            OwnedValue::try_from(Value::from(list)).unwrap(),
+        )]);
+        assert_eq!(
+            bound_shortcuts(&results),
+            vec![BoundShortcut {
+                id: "toggle".to_string(),
+                trigger: "Super+V".to_string(),
+            }]
+        );
+        assert!(bound_shortcuts(&HashMap::new()).is_empty());
+    }
+
+    #[test]
+    #[ignore = "needs dbus-daemon"]
+    fn test_session_with_mock_portal() {
+        let bus = PrivateBus::start().expect("failed to start dbus-daemon");
+
+        let portal = MockPortal::default();
+        let triggers = portal.triggers.clone();
+        let server = bus
+            .connect()
+            .name(PORTAL_BUS_NAME)
+            .unwrap()
+            .serve_at(PORTAL_PATH, portal)
+            .unwrap()
+            .build()
+            .unwrap();
+        let client = bus.connect().build().unwrap();
+        assert!(available(&client));
+
+        let session = GlobalShortcutsSession::create(&client).unwrap();
+        assert_eq!(session.path(), SESSION_PATH);
+        let mut activations = session.activations().unwrap();
+
+        let bound = session
+            .bind(&[PortalShortcut {
+                id: "win11-clipboard-history".to_string(),
+                description: "Clipboard History".to_string(),
+                trigger: "LOGO+v".to_string(),
+            }])
+            .unwrap();
+        assert_eq!(bound.len(), 1);
+        assert_eq!(bound[0].trigger, "LOGO+v");
+        assert_eq!(
+            *triggers.lock(),
+            vec![("win11-clipboard-history".to_string(), "LOGO+v".to_string())]
+        );
+
+        // Presses from other sessions are ignored
+        let options: HashMap<&str, Value> = HashMap::new();
+        for (session_path, id) in [
+            ("/org/freedesktop/portal/desktop/session/1/other", "other"),
+            (SESSION_PATH, "win11-clipboard-history"),
+        ] {
+            server
+                .emit_signal(
+                    None::<()>,
+                    PORTAL_PATH,
+                    GLOBAL_SHORTCUTS_INTERFACE,
+                    "Activated",
+                    &(
+                        ObjectPath::try_from(session_path).unwrap(),
+                        id,
+                        0u64,
+                        &options,
+                    ),
+                )
+                .unwrap();
+        }
+        assert_eq!(
+            activations.next().as_deref(),
+            Some("win11-clipboard-history")
+        );
+    }
+}
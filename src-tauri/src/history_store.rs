@@ -0,0 +1,899 @@
+//! History Store Module
+//! Optional on-disk persistence of clipboard history, with encryption at rest.
+//!
+//! Layout (inside `<data dir>/win11-clipboard-history/history/`, mode 0700):
+//! - `history.json` / `history.enc`: item index, image data stripped
+//! - `blobs/<id>.png` / `blobs/<id>.enc`: one file per image item
+//! - `key.json`: key derivation parameters and a sealed verifier (encrypted mode only)
+//!
+//! The generated key file defaults to `$XDG_RUNTIME_DIR/win11-clipboard-history/history.key`,
+//! never the data directory: whoever can read the history must not find its key next to it.
+//! The runtime directory is emptied at logout, so history meant to survive a reboot needs a
+//! key file chosen outside the data directory, a passphrase or the keyring.
+//!
+//! Encrypted files use XChaCha20-Poly1305 with the file's role as associated data, so
+//! blobs can't be swapped or replayed as the index. Every file is written 0600.
+
+use crate::clipboard_manager::{ClipboardContent, ClipboardItem};
+use crate::user_settings::UserSettings;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use zeroize::Zeroizing;
+
+const STORE_DIR: &str = "history";
+const BLOB_DIR: &str = "blobs";
+const INDEX_PLAIN: &str = "history.json";
+const INDEX_SEALED: &str = "history.enc";
+const KEY_PARAMS_FILE: &str = "key.json";
+const PLAIN_BLOB_EXT: &str = "png";
+const SEALED_BLOB_EXT: &str = "enc";
+
+/// Header of every encrypted file: magic + format version
+const SEALED_MAGIC: &[u8] = b"W11CHE\x01";
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+
+/// Known plaintext sealed into `key.json` to detect a wrong passphrase or key
+const VERIFIER: &[u8] = b"win11-clipboard-history";
+
+/// Default key file name under `$XDG_RUNTIME_DIR/win11-clipboard-history/`
+const KEY_FILE_NAME: &str = "history.key";
+
+/// Secret Service attributes identifying the key in the keyring
+const KEYRING_ATTRIBUTES: [&str; 4] = [
+    "application",
+    "win11-clipboard-history",
+    "kind",
+    "history-key",
+];
+
+type SecretKey = Zeroizing<[u8; KEY_LEN]>;
+
+// --- Data Structures ---
+
+/// Where the history encryption key comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryKeySource {
+    /// Derived from a passphrase typed by the user (Argon2id)
+    #[default]
+    Passphrase,
+    /// 32 random bytes read from a key file (raw or base64)
+    KeyFile,
+    /// Random key stored in the Secret Service keyring (via `secret-tool`)
+    Keyring,
+}
+
+/// Storage settings, taken from the user settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreConfig {
+    pub persist: bool,
+    pub encrypt: bool,
+    pub key_source: HistoryKeySource,
+    /// Key file chosen by the user; `None` uses `history.key` in the runtime directory
+    pub key_file: Option<PathBuf>,
+}
+
+impl StoreConfig {
+    pub fn from_settings(settings: &UserSettings) -> Self {
+        Self {
+            persist: settings.persist_history,
+            encrypt: settings.encrypt_history,
+            key_source: settings.history_key_source,
+            key_file: settings
+                .history_key_file
+                .as_deref()
+                .filter(|p| !p.trim().is_empty())
+                .map(PathBuf::from),
+        }
+    }
+}
+
+/// Argon2id cost parameters stored alongside the encrypted history
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Contents of `key.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyParams {
+    source: HistoryKeySource,
+    /// Base64 salt (passphrase only)
+    #[serde(default)]
+    salt: Option<String>,
+    #[serde(default)]
+    kdf: Option<KdfParams>,
+    /// Base64 sealed `VERIFIER`
+    verifier: String,
+}
+
+/// State reported to the UI
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryStoreStatus {
+    pub persist: bool,
+    pub encrypted: bool,
+    pub key_source: HistoryKeySource,
+    /// Encrypted history exists on disk but no key is loaded
+    pub locked: bool,
+    /// Passphrase mode without a passphrase set yet (unlocking sets it)
+    pub needs_new_passphrase: bool,
+    pub key_file: Option<String>,
+}
+
+/// Persists clipboard history, optionally encrypted
+pub struct HistoryStore {
+    /// App data directory holding `dir`
+    data_dir: PathBuf,
+    dir: PathBuf,
+    /// Generated key location, if there is a runtime directory
+    default_key_file: Option<PathBuf>,
+    config: StoreConfig,
+    key: Option<SecretKey>,
+    /// True once the stored history was merged into memory (or there was none).
+    /// Saving before that would overwrite history the user hasn't unlocked yet.
+    loaded: bool,
+    kdf: KdfParams,
+}
+
+// --- Helpers ---
+
+/// Creates `dir` (and parents) and restricts it to the current user
+fn create_private_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to secure {}: {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Atomically writes a file readable only by the current user
+fn write_private(path: &Path, data: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(&tmp)
+        .map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    file.write_all(data)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn remove_if_exists(path: &Path) {
+    if path.exists() {
+        if let Err(e) = fs::remove_file(path) {
+            eprintln!("[HistoryStore] Failed to remove {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn random_bytes<const N: usize>() -> Zeroizing<[u8; N]> {
+    let mut bytes = Zeroizing::new([0u8; N]);
+    OsRng.fill_bytes(bytes.as_mut());
+    bytes
+}
+
+fn seal(key: &SecretKey, role: &str, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: role.as_bytes(),
+            },
+        )
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let mut out = Vec::with_capacity(SEALED_MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(SEALED_MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn open(key: &SecretKey, role: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    let body = data
+        .strip_prefix(SEALED_MAGIC)
+        .filter(|b| b.len() > NONCE_LEN)
+        .ok_or_else(|| format!("{} is not an encrypted history file", role))?;
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+
+    XChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: role.as_bytes(),
+            },
+        )
+        .map_err(|_| format!("Failed to decrypt {} (wrong key or corrupted file)", role))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], kdf: KdfParams) -> Result<SecretKey, String> {
+    let params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(KEY_LEN))
+        .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Accepts 32 raw bytes or their base64 encoding
+fn parse_key(data: &[u8]) -> Option<SecretKey> {
+    let decoded;
+    let bytes = if data.len() == KEY_LEN {
+        data
+    } else {
+        decoded = Zeroizing::new(BASE64.decode(String::from_utf8_lossy(data).trim()).ok()?);
+        decoded.as_slice()
+    };
+
+    let key: [u8; KEY_LEN] = bytes.try_into().ok()?;
+    Some(Zeroizing::new(key))
+}
+
+fn keyring_lookup() -> Option<SecretKey> {
+    let output = Command::new("secret-tool")
+        .arg("lookup")
+        .args(KEYRING_ATTRIBUTES)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let secret = Zeroizing::new(output.stdout);
+    if !output.status.success() {
+        return None;
+    }
+    parse_key(&secret)
+}
+
+fn keyring_store(key: &SecretKey) -> Result<(), String> {
+    let mut child = Command::new("secret-tool")
+        .args(["store", "--label=Clipboard History encryption key"])
+        .args(KEYRING_ATTRIBUTES)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run secret-tool: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let encoded = Zeroizing::new(BASE64.encode(key.as_ref()));
+        stdin
+            .write_all(encoded.as_bytes())
+            .map_err(|e| format!("Failed to write to secret-tool: {}", e))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("secret-tool failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to store key in keyring: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+// --- Store ---
+
+impl HistoryStore {
+    /// `base_dir` is the app data directory; history lives in its `history/` subdirectory
+    pub fn new(base_dir: &Path, config: StoreConfig) -> Self {
+        Self::with_runtime_dir(base_dir, dirs::runtime_dir().as_deref(), config)
+    }
+
+    /// Like `new`, with the runtime directory holding the generated key file
+    pub fn with_runtime_dir(
+        base_dir: &Path,
+        runtime_dir: Option<&Path>,
+        config: StoreConfig,
+    ) -> Self {
+        Self {
+            data_dir: base_dir.to_path_buf(),
+            dir: base_dir.join(STORE_DIR),
+            default_key_file: runtime_dir
+                .map(|dir| dir.join("win11-clipboard-history").join(KEY_FILE_NAME)),
+            config,
+            key: None,
+            loaded: false,
+            kdf: KdfParams::default(),
+        }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    fn blob_path(&self, id: &str, sealed: bool) -> PathBuf {
+        let ext = if sealed {
+            SEALED_BLOB_EXT
+        } else {
+            PLAIN_BLOB_EXT
+        };
+        self.dir.join(BLOB_DIR).join(format!("{}.{}", id, ext))
+    }
+
+    fn read_key_params(&self) -> Option<KeyParams> {
+        let content = fs::read_to_string(self.path(KEY_PARAMS_FILE)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn has_sealed_data(&self) -> bool {
+        self.path(INDEX_SEALED).exists() || self.path(KEY_PARAMS_FILE).exists()
+    }
+
+    pub fn config(&self) -> &StoreConfig {
+        &self.config
+    }
+
+    fn key_file(&self) -> Option<&Path> {
+        self.config
+            .key_file
+            .as_deref()
+            .or(self.default_key_file.as_deref())
+    }
+
+    /// True once stored history has been merged into memory
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    /// True when encrypted history can't be read or written until a key is provided
+    pub fn is_locked(&self) -> bool {
+        self.config.persist && self.config.encrypt && self.key.is_none()
+    }
+
+    pub fn status(&self) -> HistoryStoreStatus {
+        HistoryStoreStatus {
+            persist: self.config.persist,
+            encrypted: self.config.encrypt,
+            key_source: self.config.key_source,
+            locked: self.is_locked(),
+            needs_new_passphrase: self.config.key_source == HistoryKeySource::Passphrase
+                && self.read_key_params().is_none(),
+            key_file: self.key_file().map(|p| p.to_string_lossy().to_string()),
+        }
+    }
+
+    /// Loads the key without user interaction (key file or keyring).
+    /// A new key is generated only when no encrypted history exists yet.
+    pub fn unlock_without_prompt(&mut self) -> Result<(), String> {
+        if !self.is_locked() {
+            return Ok(());
+        }
+
+        let existing = self.read_key_params();
+        let key = match self.config.key_source {
+            HistoryKeySource::Passphrase => {
+                return Err("A passphrase is required to unlock history".to_string())
+            }
+            HistoryKeySource::KeyFile => {
+                let path = self
+                    .key_file()
+                    .ok_or("No runtime directory for the key file; choose a key file location")?
+                    .to_path_buf();
+                if path.starts_with(&self.data_dir) {
+                    return Err(format!(
+                        "Key file {} must be outside the data directory {}",
+                        path.display(),
+                        self.data_dir.display()
+                    ));
+                }
+                match fs::read(&path) {
+                    Ok(data) => parse_key(&Zeroizing::new(data)).ok_or_else(|| {
+                        format!("{} does not contain a 32-byte key", path.display())
+                    })?,
+                    Err(_) if existing.is_none() => {
+                        let key: SecretKey = random_bytes();
+                        if let Some(parent) = path.parent() {
+                            create_private_dir(parent)?;
+                        }
+                        write_private(&path, key.as_ref())?;
+                        println!("[HistoryStore] Created key file {}", path.display());
+                        key
+                    }
+                    // The runtime directory is emptied at logout, taking the generated key along
+                    Err(_) if self.config.key_file.is_none() => {
+                        return Err(format!(
+                            "The generated key {} is gone, so stored history can't be decrypted. \
+                             For history that survives a logout, choose a key file outside the \
+                             data directory, or use a passphrase or the keyring.",
+                            path.display()
+                        ))
+                    }
+                    Err(e) => return Err(format!("Key file {}: {}", path.display(), e)),
+                }
+            }
+            HistoryKeySource::Keyring => match keyring_lookup() {
+                Some(key) => key,
+                None if existing.is_none() => {
+                    let key: SecretKey = random_bytes();
+                    keyring_store(&key)?;
+                    println!("[HistoryStore] Stored a new key in the keyring");
+                    key
+                }
+                None => return Err("History key not found in the keyring".to_string()),
+            },
+        };
+
+        self.accept_key(key, existing, None)
+    }
+
+    /// Unlocks with a passphrase. In passphrase mode without stored history, this sets it.
+    pub fn unlock_with_passphrase(&mut self, passphrase: &str) -> Result<(), String> {
+        if self.config.key_source != HistoryKeySource::Passphrase {
+            return self.unlock_without_prompt();
+        }
+        if passphrase.is_empty() {
+            return Err("Passphrase must not be empty".to_string());
+        }
+
+        match self.read_key_params() {
+            Some(params) => {
+                let salt = params
+                    .salt
+                    .as_deref()
+                    .and_then(|s| BASE64.decode(s).ok())
+                    .ok_or("Stored key parameters are missing the salt")?;
+                let key = derive_key(passphrase, &salt, params.kdf.unwrap_or(self.kdf))?;
+                self.accept_key(key, Some(params), None)
+            }
+            None => {
+                let salt = random_bytes::<SALT_LEN>();
+                let key = derive_key(passphrase, salt.as_ref(), self.kdf)?;
+                self.accept_key(key, None, Some(BASE64.encode(salt.as_ref())))
+            }
+        }
+    }
+
+    /// Checks `key` against the stored verifier, or records a new verifier
+    fn accept_key(
+        &mut self,
+        key: SecretKey,
+        existing: Option<KeyParams>,
+        new_salt: Option<String>,
+    ) -> Result<(), String> {
+        match existing {
+            Some(params) => {
+                if params.source != self.config.key_source {
+                    return Err(
+                        "Stored history was encrypted with a different key source".to_string()
+                    );
+                }
+                let verifier = BASE64
+                    .decode(&params.verifier)
+                    .map_err(|_| "Stored key parameters are corrupted".to_string())?;
+                match open(&key, KEY_PARAMS_FILE, &verifier) {
+                    Ok(plain) if plain == VERIFIER => {}
+                    _ => return Err("Wrong passphrase or key".to_string()),
+                }
+            }
+            None => {
+                create_private_dir(&self.dir)?;
+                let params = KeyParams {
+                    source: self.config.key_source,
+                    kdf: new_salt.as_ref().map(|_| self.kdf),
+                    salt: new_salt,
+                    verifier: BASE64.encode(seal(&key, KEY_PARAMS_FILE, VERIFIER)?),
+                };
+                let content = serde_json::to_string_pretty(&params)
+                    .map_err(|e| format!("Failed to serialize key parameters: {}", e))?;
+                write_private(&self.path(KEY_PARAMS_FILE), content.as_bytes())?;
+            }
+        }
+
+        self.key = Some(key);
+        Ok(())
+    }
+
+    /// Drops the key. Call `save` first; in-memory history should be cleared by the caller.
+    pub fn lock(&mut self) {
+        self.key = None;
+        if self.config.encrypt {
+            self.loaded = false;
+        }
+    }
+
+    /// Reads stored history (most recent first). Marks the store as loaded.
+    pub fn load(&mut self) -> Result<Vec<ClipboardItem>, String> {
+        if !self.config.persist {
+            self.loaded = true;
+            return Ok(Vec::new());
+        }
+        if self.is_locked() {
+            return Err("History is locked".to_string());
+        }
+
+        let sealed = self.config.encrypt;
+        let index_path = self.path(if sealed { INDEX_SEALED } else { INDEX_PLAIN });
+        if !index_path.exists() {
+            self.loaded = true;
+            return Ok(Vec::new());
+        }
+
+        let data = fs::read(&index_path)
+            .map_err(|e| format!("Failed to read {}: {}", index_path.display(), e))?;
+        let data = match &self.key {
+            Some(key) if sealed => Zeroizing::new(open(key, INDEX_SEALED, &data)?),
+            _ => Zeroizing::new(data),
+        };
+        let mut items: Vec<ClipboardItem> = serde_json::from_slice(&data)
+            .map_err(|e| format!("Failed to parse stored history: {}", e))?;
+
+        // Re-attach image data from blobs; drop items whose blob is gone
+        items.retain_mut(|item| {
+            let id = item.id.clone();
+            let ClipboardContent::Image { base64, .. } = &mut item.content else {
+                return true;
+            };
+            match self.read_blob(&id, sealed) {
+                Ok(bytes) => {
+                    *base64 = BASE64.encode(bytes);
+                    true
+                }
+                Err(e) => {
+                    eprintln!("[HistoryStore] Dropping image {}: {}", id, e);
+                    false
+                }
+            }
+        });
+
+        self.loaded = true;
+        println!("[HistoryStore] Loaded {} stored items", items.len());
+        Ok(items)
+    }
+
+    fn read_blob(&self, id: &str, sealed: bool) -> Result<Vec<u8>, String> {
+        let path = self.blob_path(id, sealed);
+        let data = fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        match &self.key {
+            Some(key) if sealed => open(key, &format!("blob:{}", id), &data),
+            _ => Ok(data),
+        }
+    }
+
+    /// Writes `items` to disk. Does nothing when persistence is off, the store is locked,
+    /// or the stored history hasn't been loaded yet.
+    pub fn save(&self, items: &[ClipboardItem]) -> Result<(), String> {
+        if !self.config.persist || self.is_locked() || !self.loaded {
+            return Ok(());
+        }
+
+        let sealed = self.config.encrypt;
+        create_private_dir(&self.dir)?;
+        create_private_dir(&self.dir.join(BLOB_DIR))?;
+
+        let mut index = Vec::with_capacity(items.len());
+        let mut blobs = HashSet::new();
+        // Expiring items (secrets, copies from flagged apps) never touch the disk
+        for item in items.iter().filter(|item| item.expires_at.is_none()) {
+            let mut stored = item.clone();
+            if let ClipboardContent::Image { base64, .. } = &mut stored.content {
+                // Item ids name blob files, so only accept plain uuids
+                if !item
+                    .id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-')
+                {
+                    continue;
+                }
+                let path = self.blob_path(&item.id, sealed);
+                if !path.exists() {
+                    let bytes = BASE64
+                        .decode(base64.as_bytes())
+                        .map_err(|e| format!("Invalid image data: {}", e))?;
+                    let bytes = match &self.key {
+                        Some(key) if sealed => seal(key, &format!("blob:{}", item.id), &bytes)?,
+                        _ => bytes,
+                    };
+                    write_private(&path, &bytes)?;
+                }
+                blobs.insert(path);
+                base64.clear();
+            }
+            index.push(stored);
+        }
+
+        let json = Zeroizing::new(
+            serde_json::to_vec(&index)
+                .map_err(|e| format!("Failed to serialize history: {}", e))?,
+        );
+        match &self.key {
+            Some(key) if sealed => {
+                write_private(&self.path(INDEX_SEALED), &seal(key, INDEX_SEALED, &json)?)?;
+                remove_if_exists(&self.path(INDEX_PLAIN));
+            }
+            _ => {
+                write_private(&self.path(INDEX_PLAIN), &json)?;
+                remove_if_exists(&self.path(INDEX_SEALED));
+                remove_if_exists(&self.path(KEY_PARAMS_FILE));
+            }
+        }
+
+        // Remove blobs of deleted items (and of the other storage format)
+        if let Ok(entries) = fs::read_dir(self.dir.join(BLOB_DIR)) {
+            for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+                if !blobs.contains(&path) {
+                    remove_if_exists(&path);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deletes everything stored on disk
+    pub fn wipe(&self) {
+        if self.dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&self.dir) {
+                eprintln!("[HistoryStore] Failed to remove stored history: {}", e);
+            }
+        }
+    }
+
+    /// Applies new storage settings. `current` is the in-memory history, which becomes the
+    /// authoritative copy whenever the on-disk format or key changes.
+    pub fn reconfigure(
+        &mut self,
+        config: StoreConfig,
+        current: &[ClipboardItem],
+    ) -> Result<(), String> {
+        if config == self.config {
+            return Ok(());
+        }
+
+        let format_changed = config.encrypt != self.config.encrypt
+            || (config.encrypt && config.key_source != self.config.key_source)
+            || (config.encrypt
+                && config.key_source == HistoryKeySource::KeyFile
+                && config.key_file != self.config.key_file);
+
+        if config.persist && format_changed && !self.loaded && self.has_sealed_data() {
+            return Err("Unlock history before changing how it is encrypted".to_string());
+        }
+
+        let was_persisting = self.config.persist;
+        self.config = config;
+
+        if !self.config.persist {
+            // Nothing may stay on disk once persistence is turned off
+            self.wipe();
+            self.key = None;
+            self.loaded = false;
+            return Ok(());
+        }
+
+        if !was_persisting {
+            // Stored history from an earlier session is merged by the caller via `load`
+            self.loaded = false;
+        } else if format_changed {
+            // Old files (plaintext or sealed with the previous key) must not linger
+            self.wipe();
+            self.key = None;
+            self.loaded = true;
+        }
+
+        if self.is_locked() && self.config.key_source != HistoryKeySource::Passphrase {
+            if let Err(e) = self.unlock_without_prompt() {
+                eprintln!("[HistoryStore] {}", e);
+            }
+        }
+
+        if self.loaded {
+            self.save(current)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str, encrypt: bool) -> HistoryStore {
+        let dir = std::env::temp_dir().join(format!(
+            "win11-clipboard-history-store-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        let mut store = HistoryStore::new(
+            &dir,
+            StoreConfig {
+                persist: true,
+                encrypt,
+                key_source: HistoryKeySource::Passphrase,
+                key_file: None,
+            },
+        );
+        // Keep tests fast
+        store.kdf = KdfParams {
+            m_cost: 64,
+            t_cost: 1,
+            p_cost: 1,
+        };
+        store
+    }
+
+    fn sample_items() -> Vec<ClipboardItem> {
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::new_rgba8(1, 1)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        vec![
+            ClipboardItem::new_text("secret token".to_string()),
+            ClipboardItem::from_image_bytes(png.get_ref()).unwrap(),
+        ]
+    }
+
+    fn contains(dir: &Path, needle: &[u8]) -> bool {
+        walk(dir).iter().any(|path| {
+            fs::read(path)
+                .map(|data| data.windows(needle.len()).any(|w| w == needle))
+                .unwrap_or(false)
+        })
+    }
+
+    fn walk(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .flat_map(|p| if p.is_dir() { walk(&p) } else { vec![p] })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_encrypted_roundtrip() {
+        let mut store = temp_store("roundtrip", true);
+        assert!(store.is_locked());
+        assert!(store.status().needs_new_passphrase);
+
+        store.unlock_with_passphrase("hunter2").unwrap();
+        assert!(store.load().unwrap().is_empty());
+        store.save(&sample_items()).unwrap();
+
+        // Nothing readable in plaintext, and files are private
+        assert!(!contains(&store.dir, b"secret token"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            for path in walk(&store.dir) {
+                let mode = fs::metadata(&path).unwrap().permissions().mode();
+                assert_eq!(mode & 0o077, 0, "{} is not private", path.display());
+            }
+        }
+
+        store.lock();
+        assert!(store.is_locked());
+        assert!(store.unlock_with_passphrase("wrong").is_err());
+        store.unlock_with_passphrase("hunter2").unwrap();
+
+        let items = store.load().unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(matches!(&items[0].content, ClipboardContent::Text(t) if t == "secret token"));
+        assert!(
+            matches!(&items[1].content, ClipboardContent::Image { base64, .. } if !base64.is_empty())
+        );
+
+        store.wipe();
+    }
+
+    #[test]
+    fn test_generated_key_file() {
+        let store = temp_store("keyfile", true);
+        let base_dir = store.dir.parent().unwrap().to_path_buf();
+        let runtime_dir = std::env::temp_dir().join(format!(
+            "win11-clipboard-history-runtime-{}",
+            std::process::id()
+        ));
+        let config = StoreConfig {
+            key_source: HistoryKeySource::KeyFile,
+            ..store.config().clone()
+        };
+        let mut store = HistoryStore::with_runtime_dir(&base_dir, Some(&runtime_dir), config);
+        store.unlock_without_prompt().unwrap();
+        store.load().unwrap();
+        store.save(&sample_items()).unwrap();
+
+        // The key stays out of the data directory
+        let key_file = runtime_dir
+            .join("win11-clipboard-history")
+            .join(KEY_FILE_NAME);
+        assert!(key_file.exists());
+        assert!(walk(&base_dir)
+            .iter()
+            .all(|path| !path.ends_with(KEY_FILE_NAME)));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&key_file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let config = store.config().clone();
+        drop(store);
+
+        // Same session: the key is still there
+        let mut store = HistoryStore::with_runtime_dir(&base_dir, Some(&runtime_dir), config);
+        store.unlock_without_prompt().unwrap();
+        assert_eq!(store.load().unwrap().len(), 2);
+        let config = store.config().clone();
+        drop(store);
+
+        // After a logout the runtime directory is empty: no new key replaces the lost one
+        fs::remove_dir_all(&runtime_dir).unwrap();
+        let mut store =
+            HistoryStore::with_runtime_dir(&base_dir, Some(&runtime_dir), config.clone());
+        assert!(store.unlock_without_prompt().is_err());
+        assert!(store.is_locked());
+
+        // A chosen key file inside the data directory is refused
+        let inside = StoreConfig {
+            key_file: Some(base_dir.join("my.key")),
+            ..config
+        };
+        let mut store = HistoryStore::with_runtime_dir(&base_dir, Some(&runtime_dir), inside);
+        assert!(store.unlock_without_prompt().is_err());
+
+        let _ = fs::remove_dir_all(&base_dir);
+    }
+
+    #[test]
+    fn test_reconfigure_removes_plaintext() {
+        let mut store = temp_store("reconfigure", false);
+        store.load().unwrap();
+        store.save(&sample_items()).unwrap();
+        assert!(contains(&store.dir, b"secret token"));
+
+        let mut config = store.config().clone();
+        config.encrypt = true;
+        store.reconfigure(config, &sample_items()).unwrap();
+
+        // Plaintext is gone; the in-memory history is saved once a key is provided
+        assert!(!contains(&store.dir, b"secret token"));
+        store.unlock_with_passphrase("pw").unwrap();
+        store.save(&sample_items()).unwrap();
+        assert!(store.path(INDEX_SEALED).exists());
+        assert!(!store.path(INDEX_PLAIN).exists());
+
+        store.wipe();
+    }
+}
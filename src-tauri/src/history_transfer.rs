@@ -0,0 +1,407 @@
+//! History Transfer Module
+//! Exports and imports clipboard history, pins and custom kaomoji snippets as a versioned
+//! JSON document, or as a zip bundle that stores images as separate PNG files.
+
+use crate::clipboard_manager::{ClipboardContent, ClipboardItem, ImportSummary};
+use crate::user_settings::CustomKaomoji;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Identifies files produced by this module
+const EXPORT_FORMAT: &str = "win11-clipboard-history";
+
+/// Current export schema version. Bump when the document layout changes.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Name of the JSON document inside a zip bundle
+const BUNDLE_MANIFEST: &str = "history.json";
+
+/// Directory holding images inside a zip bundle
+const BUNDLE_IMAGE_DIR: &str = "images";
+
+/// Zip local file header signature, used to detect bundles regardless of extension
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+// --- Data Structures ---
+
+/// Versioned export document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryExport {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: DateTime<Utc>,
+    pub items: Vec<ClipboardItem>,
+    /// User-defined kaomoji snippets
+    #[serde(default)]
+    pub custom_kaomojis: Vec<CustomKaomoji>,
+    /// Bundle only: item id → image path inside the archive
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub image_files: BTreeMap<String, String>,
+}
+
+/// Options for `export_history`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    /// Only export pinned items
+    pub pinned_only: bool,
+    /// Only export items copied at or after this instant
+    pub since: Option<DateTime<Utc>>,
+    /// Only export items copied at or before this instant
+    pub until: Option<DateTime<Utc>>,
+    /// Include image items (inline base64 in JSON, PNG files in a bundle)
+    pub include_images: bool,
+    /// Write a zip bundle instead of plain JSON
+    pub bundle: bool,
+}
+
+/// How imported items are combined with the current history
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Keep current history and add items whose content isn't present yet
+    #[default]
+    Merge,
+    /// Discard current history and use the imported items
+    Replace,
+}
+
+/// Summary returned by `export_history`
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub items: usize,
+    pub images: usize,
+    pub custom_kaomojis: usize,
+}
+
+/// Summary returned by `import_history`
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    #[serde(flatten)]
+    pub history: ImportSummary,
+    /// Custom kaomoji snippets added to the user settings
+    pub custom_kaomojis: usize,
+}
+
+// --- Export ---
+
+/// Same defaults as the command line: everything, images included, as plain JSON
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            pinned_only: false,
+            since: None,
+            until: None,
+            include_images: true,
+            bundle: false,
+        }
+    }
+}
+
+impl ExportOptions {
+    fn includes(&self, item: &ClipboardItem) -> bool {
+        if self.pinned_only && !item.pinned {
+            return false;
+        }
+        if !self.include_images && matches!(item.content, ClipboardContent::Image { .. }) {
+            return false;
+        }
+        if self.since.is_some_and(|since| item.timestamp < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| item.timestamp > until) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Builds the export document from the current history according to `options`.
+pub fn build_export(
+    history: &[ClipboardItem],
+    custom_kaomojis: &[CustomKaomoji],
+    options: &ExportOptions,
+) -> HistoryExport {
+    HistoryExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: Utc::now(),
+        items: history
+            .iter()
+            .filter(|item| options.includes(item))
+            .cloned()
+            .collect(),
+        custom_kaomojis: custom_kaomojis.to_vec(),
+        image_files: BTreeMap::new(),
+    }
+}
+
+/// Writes the export to `path`, as JSON or as a zip bundle.
+pub fn write_export(
+    mut export: HistoryExport,
+    path: &Path,
+    bundle: bool,
+) -> Result<ExportSummary, String> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let images = export
+        .items
+        .iter()
+        .filter(|i| matches!(i.content, ClipboardContent::Image { .. }))
+        .count();
+    let summary = ExportSummary {
+        path: path.display().to_string(),
+        items: export.items.len(),
+        images,
+        custom_kaomojis: export.custom_kaomojis.len(),
+    };
+
+    if !bundle {
+        let json = serde_json::to_string_pretty(&export)
+            .map_err(|e| format!("Failed to serialize export: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write export: {}", e))?;
+        return Ok(summary);
+    }
+
+    let file = fs::File::create(path).map_err(|e| format!("Failed to create bundle: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    // Move image payloads out of the JSON document into their own PNG files
+    for item in export.items.iter_mut() {
+        if let ClipboardContent::Image { base64, .. } = &mut item.content {
+            let bytes = BASE64
+                .decode(base64.as_bytes())
+                .map_err(|e| format!("Invalid image data in item {}: {}", item.id, e))?;
+            let name = format!("{}/{}.png", BUNDLE_IMAGE_DIR, item.id);
+            zip.start_file(name.as_str(), options)
+                .map_err(|e| format!("Failed to add image to bundle: {}", e))?;
+            zip.write_all(&bytes)
+                .map_err(|e| format!("Failed to add image to bundle: {}", e))?;
+            base64.clear();
+            export.image_files.insert(item.id.clone(), name);
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize export: {}", e))?;
+    zip.start_file(BUNDLE_MANIFEST, options)
+        .map_err(|e| format!("Failed to write bundle: {}", e))?;
+    zip.write_all(json.as_bytes())
+        .map_err(|e| format!("Failed to write bundle: {}", e))?;
+    zip.finish()
+        .map_err(|e| format!("Failed to finalize bundle: {}", e))?;
+
+    Ok(summary)
+}
+
+// --- Import ---
+
+/// Reads an export (JSON or zip bundle, detected by content) from `path`.
+pub fn read_export(path: &Path) -> Result<HistoryExport, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let export = if bytes.starts_with(ZIP_MAGIC) {
+        read_bundle(&bytes)?
+    } else {
+        parse_export(&bytes)?
+    };
+
+    Ok(export)
+}
+
+fn parse_export(bytes: &[u8]) -> Result<HistoryExport, String> {
+    let export: HistoryExport =
+        serde_json::from_slice(bytes).map_err(|e| format!("Invalid export file: {}", e))?;
+
+    if export.format != EXPORT_FORMAT {
+        return Err(format!("Unsupported export format '{}'", export.format));
+    }
+    if export.version > EXPORT_VERSION {
+        return Err(format!(
+            "Export version {} is newer than supported version {}. Please update the app.",
+            export.version, EXPORT_VERSION
+        ));
+    }
+
+    Ok(export)
+}
+
+fn read_bundle(bytes: &[u8]) -> Result<HistoryExport, String> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .map_err(|e| format!("Invalid bundle: {}", e))?;
+
+    let mut manifest = Vec::new();
+    archive
+        .by_name(BUNDLE_MANIFEST)
+        .map_err(|e| format!("Bundle is missing {}: {}", BUNDLE_MANIFEST, e))?
+        .read_to_end(&mut manifest)
+        .map_err(|e| format!("Failed to read {}: {}", BUNDLE_MANIFEST, e))?;
+
+    let mut export = parse_export(&manifest)?;
+    let image_files = std::mem::take(&mut export.image_files);
+
+    for item in export.items.iter_mut() {
+        let ClipboardContent::Image { base64, .. } = &mut item.content else {
+            continue;
+        };
+        let Some(name) = image_files.get(&item.id) else {
+            continue;
+        };
+
+        let mut data = Vec::new();
+        archive
+            .by_name(name)
+            .map_err(|e| format!("Bundle is missing {}: {}", name, e))?
+            .read_to_end(&mut data)
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        *base64 = BASE64.encode(data);
+    }
+
+    // Drop image items whose payload couldn't be restored
+    export.items.retain(|item| match &item.content {
+        ClipboardContent::Image { base64, .. } => !base64.is_empty(),
+        ClipboardContent::Text(_) => true,
+    });
+
+    Ok(export)
+}
+
+/// Merges imported kaomoji snippets into `existing`, skipping identical texts.
+/// Returns the number of snippets added.
+pub fn merge_custom_kaomojis(
+    existing: &mut Vec<CustomKaomoji>,
+    imported: Vec<CustomKaomoji>,
+    mode: ImportMode,
+) -> usize {
+    if mode == ImportMode::Replace {
+        existing.clear();
+    }
+
+    let mut added = 0;
+    for kaomoji in imported {
+        if !existing.iter().any(|k| k.text == kaomoji.text) {
+            existing.push(kaomoji);
+            added += 1;
+        }
+    }
+    added
+}
+
+// --- Helpers ---
+
+/// Parses a CLI/UI date: RFC 3339 timestamp or a plain `YYYY-MM-DD` date.
+/// Plain dates resolve to the start of the day, or its end when `end_of_day` is set.
+pub fn parse_date(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}'. Use YYYY-MM-DD or RFC 3339.", value))?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+
+    time.map(|t| t.and_utc())
+        .ok_or_else(|| format!("Invalid date '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+
+    fn sample_history() -> Vec<ClipboardItem> {
+        let mut pinned = ClipboardItem::new_text("pinned".to_string());
+        pinned.pinned = true;
+        // 1x1 transparent PNG
+        let png = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+        let image = ClipboardItem::new_image(png.to_string(), 1, 1, 42);
+        vec![pinned, ClipboardItem::new_text("plain".to_string()), image]
+    }
+
+    #[test]
+    fn test_export_filters() {
+        let history = sample_history();
+
+        let pinned = build_export(
+            &history,
+            &[],
+            &ExportOptions {
+                pinned_only: true,
+                include_images: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(pinned.items.len(), 1);
+
+        let no_images = build_export(
+            &history,
+            &[],
+            &ExportOptions {
+                include_images: false,
+                ..Default::default()
+            },
+        );
+        assert_eq!(no_images.items.len(), 2);
+
+        // Options missing from a UI request default to the command line's
+        let options: ExportOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(build_export(&history, &[], &options).items.len(), 3);
+
+        let future = build_export(
+            &history,
+            &[],
+            &ExportOptions {
+                since: Some(Utc::now() + chrono::Duration::days(1)),
+                include_images: true,
+                ..Default::default()
+            },
+        );
+        assert!(future.items.is_empty());
+    }
+
+    #[test]
+    fn test_json_and_bundle_roundtrip() {
+        let history = sample_history();
+        let options = ExportOptions {
+            include_images: true,
+            ..Default::default()
+        };
+
+        for bundle in [false, true] {
+            let path = temp_dir().join(format!("history_transfer_test_{}.out", bundle));
+            let export = build_export(&history, &[], &options);
+            write_export(export, &path, bundle).unwrap();
+
+            let imported = read_export(&path).unwrap();
+            assert_eq!(imported.items.len(), 3);
+            assert_eq!(imported.items[2].content, history[2].content);
+            assert!(imported.image_files.is_empty());
+            let _ = fs::remove_file(&path);
+        }
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let start = parse_date("2024-05-01", false).unwrap();
+        let end = parse_date("2024-05-01", true).unwrap();
+        assert!(start < end);
+        assert!(parse_date("2024-05-01T10:00:00+02:00", false).is_ok());
+        assert!(parse_date("yesterday", false).is_err());
+    }
+}
@@ -1,8 +1,18 @@
+#[cfg(target_os = "linux")]
+use crate::keymap::{self, Keymap};
+#[cfg(target_os = "linux")]
+use crate::remote_desktop;
 use crate::session;
+#[cfg(target_os = "linux")]
+use crate::virtual_keyboard::VirtualKeyboard;
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::Duration;
 
-type PasteStrategy = (&'static str, fn() -> Result<(), String>);
+type PasteStrategy = (&'static str, fn(PasteChord) -> Result<(), String>);
 
 /// Delay before starting the paste sequence to ensure window focus is stable
 const PRE_PASTE_DELAY_MS: u64 = 50;
@@ -13,15 +23,268 @@ const KEY_EVENT_DELAY_MS: u64 = 50;
 /// Delay after device creation for uinput to be recognized
 const UINPUT_DEVICE_SETTLE_MS: u64 = 100;
 
+/// Key codes declared by the virtual keyboard: every code a keymap can use, so both
+/// paste chords and type-out mode work with the same device (plus BTN_MIDDLE)
+const VIRTUAL_KEYBOARD_KEYS: std::ops::RangeInclusive<u16> = 1..=255;
+
 /// Delay after paste sequence completes
 const POST_PASTE_DELAY_MS: u64 = 30;
 
+// Linux input event codes (X11 keycodes are these + 8)
+const KEY_LEFTCTRL: u16 = 29;
+const KEY_LEFTSHIFT: u16 = 42;
+const KEY_V: u16 = 47;
+const KEY_INSERT: u16 = 110;
+const BTN_MIDDLE: u16 = 0x112;
+
+/// WM_CLASS values and Wayland app ids of terminals, which paste with Ctrl+Shift+V.
+/// Compared case-insensitively against the whole class name.
+const TERMINAL_CLASSES: &[&str] = &[
+    "gnome-terminal",
+    "gnome-terminal-server",
+    "org.gnome.Terminal",
+    "kgx",
+    "org.gnome.Console",
+    "ptyxis",
+    "org.gnome.Ptyxis",
+    "kitty",
+    "alacritty",
+    "konsole",
+    "org.kde.konsole",
+    "yakuake",
+    "org.kde.yakuake",
+    "xterm",
+    "uxterm",
+    "urxvt",
+    "rxvt",
+    "st-256color",
+    "terminator",
+    "tilix",
+    "com.gexperts.Tilix",
+    "guake",
+    "tilda",
+    "xfce4-terminal",
+    "mate-terminal",
+    "lxterminal",
+    "qterminal",
+    "deepin-terminal",
+    "terminology",
+    "wezterm",
+    "wezterm-gui",
+    "org.wezfurlong.wezterm",
+    "foot",
+    "footclient",
+    "ghostty",
+    "com.mitchellh.ghostty",
+    "blackbox",
+    "com.raggesilver.BlackBox",
+    "cool-retro-term",
+    "contour",
+    "org.contourterminal.Contour",
+    "rio",
+    "dev.warp.Warp",
+    "tabby",
+    "Hyper",
+];
+
+/// What is sent to the focused window to paste
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteChord {
+    #[default]
+    CtrlV,
+    CtrlShiftV,
+    ShiftInsert,
+    /// Pastes the primary selection at the mouse pointer
+    MiddleClick,
+    /// Only copy to the clipboard, the user pastes manually
+    None,
+}
+
+impl PasteChord {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::CtrlV => "Ctrl+V",
+            Self::CtrlShiftV => "Ctrl+Shift+V",
+            Self::ShiftInsert => "Shift+Insert",
+            Self::MiddleClick => "Middle click",
+            Self::None => "Copy only",
+        }
+    }
+
+    fn uses_v(self) -> bool {
+        matches!(self, Self::CtrlV | Self::CtrlShiftV)
+    }
+
+    /// Keys pressed in order (and released in reverse); empty for clicks.
+    /// `v_key` is wherever the active layout puts "v".
+    fn keys(self, v_key: u16) -> Vec<u16> {
+        match self {
+            Self::CtrlV => vec![KEY_LEFTCTRL, v_key],
+            Self::CtrlShiftV => vec![KEY_LEFTCTRL, KEY_LEFTSHIFT, v_key],
+            Self::ShiftInsert => vec![KEY_LEFTSHIFT, KEY_INSERT],
+            Self::MiddleClick | Self::None => Vec::new(),
+        }
+    }
+
+    /// Keysyms of the chord, for backends that let the compositor map keys
+    fn keysyms(self) -> &'static [u32] {
+        const XK_CONTROL_L: u32 = 0xffe3;
+        const XK_SHIFT_L: u32 = 0xffe1;
+        const XK_INSERT: u32 = 0xff63;
+        match self {
+            Self::CtrlV => &[XK_CONTROL_L, keymap::XK_V],
+            Self::CtrlShiftV => &[XK_CONTROL_L, XK_SHIFT_L, keymap::XK_V],
+            Self::ShiftInsert => &[XK_SHIFT_L, XK_INSERT],
+            Self::MiddleClick | Self::None => &[],
+        }
+    }
+
+    fn xdotool_args(self) -> &'static [&'static str] {
+        match self {
+            Self::CtrlV => &["key", "--clearmodifiers", "ctrl+v"],
+            Self::CtrlShiftV => &["key", "--clearmodifiers", "ctrl+shift+v"],
+            Self::ShiftInsert => &["key", "--clearmodifiers", "shift+Insert"],
+            Self::MiddleClick => &["click", "--clearmodifiers", "2"],
+            Self::None => &[],
+        }
+    }
+}
+
+/// Per-application paste chord, matched on the target window's WM_CLASS
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PasteOverride {
+    /// Instance or class name (e.g. "kitty", "org.wezfurlong.wezterm")
+    pub app: String,
+    pub chord: PasteChord,
+}
+
+/// Paste options, stored in the user settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PasteSettings {
+    /// Chord used when no override matches
+    pub chord: PasteChord,
+    /// Use Ctrl+Shift+V for known terminals
+    pub detect_terminals: bool,
+    /// Checked before terminal detection
+    pub app_overrides: Vec<PasteOverride>,
+    /// Delay between characters in type-out mode, in milliseconds
+    pub type_delay_ms: u64,
+    /// Shortcut that stops type-out mode (registered while typing)
+    pub type_abort_shortcut: String,
+}
+
+impl Default for PasteSettings {
+    fn default() -> Self {
+        Self {
+            chord: PasteChord::CtrlV,
+            detect_terminals: true,
+            app_overrides: Vec::new(),
+            type_delay_ms: 12,
+            type_abort_shortcut: "Escape".to_string(),
+        }
+    }
+}
+
+impl PasteSettings {
+    /// Picks the chord for a window with the given WM_CLASS names
+    pub fn chord_for(&self, classes: &[String]) -> PasteChord {
+        let matches = |name: &str| {
+            let name = name.trim();
+            !name.is_empty()
+                && classes.iter().any(|class| {
+                    class.eq_ignore_ascii_case(name)
+                        || short_class(class).eq_ignore_ascii_case(name)
+                })
+        };
+
+        if let Some(rule) = self.app_overrides.iter().find(|rule| matches(&rule.app)) {
+            return rule.chord;
+        }
+        let is_terminal = classes.iter().any(|class| {
+            TERMINAL_CLASSES
+                .iter()
+                .any(|term| class.eq_ignore_ascii_case(term))
+        });
+        if self.detect_terminals && is_terminal {
+            return PasteChord::CtrlShiftV;
+        }
+        self.chord
+    }
+}
+
+/// Last segment of reverse-DNS class names ("org.wezfurlong.wezterm" -> "wezterm")
+fn short_class(class: &str) -> &str {
+    class.rsplit('.').next().unwrap_or(class)
+}
+
+static PASTE_SETTINGS: Lazy<RwLock<PasteSettings>> =
+    Lazy::new(|| RwLock::new(PasteSettings::default()));
+
+/// Replaces the active paste settings (called on startup and when settings change)
+pub fn set_paste_settings(settings: &PasteSettings) {
+    *PASTE_SETTINGS.write() = settings.clone();
+}
+
+pub fn paste_settings() -> PasteSettings {
+    PASTE_SETTINGS.read().clone()
+}
+
+/// Chord for the window that had focus before the clipboard window opened
+#[cfg(target_os = "linux")]
+fn resolve_chord() -> PasteChord {
+    let settings = PASTE_SETTINGS.read().clone();
+    if settings.app_overrides.is_empty() && !settings.detect_terminals {
+        return settings.chord;
+    }
+
+    // Windows whose class is unknown get the default chord
+    match crate::focus_manager::saved_window_class() {
+        Some(classes) => {
+            let chord = settings.chord_for(&classes);
+            eprintln!(
+                "[SimulatePaste] Target window class {:?} -> {}",
+                classes,
+                chord.label()
+            );
+            chord
+        }
+        None => settings.chord,
+    }
+}
+
+/// Middle click pastes the primary selection, so mirror the clipboard text into it
+#[cfg(target_os = "linux")]
+fn copy_clipboard_to_primary() -> Result<(), String> {
+    use arboard::{Clipboard, LinuxClipboardKind, SetExtLinux};
+
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    let text = clipboard.get_text().map_err(|e| e.to_string())?;
+    clipboard
+        .set()
+        .clipboard(LinuxClipboardKind::Primary)
+        .text(text)
+        .map_err(|e| e.to_string())
+}
+
 #[cfg(target_os = "linux")]
 pub fn simulate_paste_keystroke() -> Result<(), String> {
+    let chord = resolve_chord();
+    if chord == PasteChord::None {
+        eprintln!("[SimulatePaste] Copy-only mode, not sending a paste keystroke");
+        return Ok(());
+    }
+    if chord == PasteChord::MiddleClick {
+        if let Err(e) = copy_clipboard_to_primary() {
+            eprintln!("[SimulatePaste] Failed to set primary selection: {}", e);
+        }
+    }
+
     // Give window manager time to settle focus before sending keystrokes
     thread::sleep(Duration::from_millis(PRE_PASTE_DELAY_MS));
 
-    eprintln!("[SimulatePaste] Sending Ctrl+V...");
+    eprintln!("[SimulatePaste] Sending {}...", chord.label());
 
     const X11_STRATEGIES: &[PasteStrategy] = &[
         ("xdotool", simulate_paste_xdotool),
@@ -29,7 +292,11 @@ pub fn simulate_paste_keystroke() -> Result<(), String> {
         ("uinput", simulate_paste_uinput),
     ];
 
-    const NON_X11_STRATEGIES: &[PasteStrategy] = &[("uinput", simulate_paste_uinput)];
+    const NON_X11_STRATEGIES: &[PasteStrategy] = &[
+        ("virtual keyboard", simulate_paste_virtual_keyboard),
+        ("uinput", simulate_paste_uinput),
+        ("RemoteDesktop portal", simulate_paste_portal),
+    ];
 
     let strategies = if session::is_x11() {
         X11_STRATEGIES
@@ -38,9 +305,9 @@ pub fn simulate_paste_keystroke() -> Result<(), String> {
     };
 
     for (name, func) in strategies {
-        match func() {
+        match func(chord) {
             Ok(()) => {
-                eprintln!("[SimulatePaste] Ctrl+V sent via {}", name);
+                eprintln!("[SimulatePaste] {} sent via {}", chord.label(), name);
                 // Small delay after paste to let the target app process it
                 thread::sleep(Duration::from_millis(POST_PASTE_DELAY_MS));
                 return Ok(());
@@ -69,15 +336,18 @@ fn fake_key<C: x11rb::connection::Connection + x11rb::protocol::xtest::Connectio
     Ok(())
 }
 
-/// Simulate Ctrl+V using X11 XTest extension
+/// Simulate the paste chord using X11 XTest extension
 #[cfg(target_os = "linux")]
-fn simulate_paste_xtest() -> Result<(), String> {
+fn simulate_paste_xtest(chord: PasteChord) -> Result<(), String> {
     use x11rb::connection::Connection;
     use x11rb::protocol::xtest::ConnectionExt as XtestConnectionExt;
     use x11rb::wrapper::ConnectionExt as WrapperConnectionExt; // Imported for sync()
 
-    const CTRL_L_KEYCODE: u8 = 37;
-    const V_KEYCODE: u8 = 55;
+    const KEY_PRESS: u8 = 2;
+    const KEY_RELEASE: u8 = 3;
+    const BUTTON_PRESS: u8 = 4;
+    const BUTTON_RELEASE: u8 = 5;
+    const MIDDLE_BUTTON: u8 = 2;
 
     let (conn, screen_num) =
         x11rb::connect(None).map_err(|e| format!("X11 connect failed: {}", e))?;
@@ -92,77 +362,336 @@ fn simulate_paste_xtest() -> Result<(), String> {
     conn.sync()
         .map_err(|e| format!("Sync setup failed: {}", e))?;
 
-    // Press Ctrl and wait for it to be registered
-    fake_key(
-        &conn,
-        2,
-        CTRL_L_KEYCODE,
-        root_window,
-        "Failed to press Ctrl",
-    )?;
-    conn.sync()
-        .map_err(|e| format!("Sync after Ctrl press failed: {}", e))?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
+    // Find "v" on the active layout. Without it (e.g. Cyrillic selected), bind "v" to a
+    // spare keycode rather than relying on the app to match shortcuts across layouts.
+    let mut bound_spare = None;
+    let mut v_key = KEY_V;
+    if chord.uses_v() {
+        let position = Keymap::load()
+            .map_err(|e| eprintln!("[SimulatePaste] Failed to read the keymap: {}", e))
+            .ok()
+            .and_then(|keymap| keymap.locate(keymap::XK_V));
+        match position {
+            Some(position) if position.on_active_layout => v_key = position.evdev,
+            _ => match SpareKeycode::find(&conn)? {
+                Some(spare) => {
+                    spare.bind(&conn, keymap::XK_V)?;
+                    v_key = u16::from(spare.keycode) - 8;
+                    bound_spare = Some(spare);
+                }
+                None => {
+                    if let Some(position) = position {
+                        v_key = position.evdev;
+                    }
+                }
+            },
+        }
+    }
 
-    // Press V
-    fake_key(&conn, 2, V_KEYCODE, root_window, "Failed to press V")?;
-    conn.sync()
-        .map_err(|e| format!("Sync after V press failed: {}", e))?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
+    // (event type, detail) in order; keys are released in reverse
+    let mut events: Vec<(u8, u8)> = Vec::new();
+    if chord == PasteChord::MiddleClick {
+        events.push((BUTTON_PRESS, MIDDLE_BUTTON));
+        events.push((BUTTON_RELEASE, MIDDLE_BUTTON));
+    } else {
+        let keycodes: Vec<u8> = chord
+            .keys(v_key)
+            .iter()
+            .map(|&key| (key + 8) as u8)
+            .collect();
+        events.extend(keycodes.iter().map(|&code| (KEY_PRESS, code)));
+        events.extend(keycodes.iter().rev().map(|&code| (KEY_RELEASE, code)));
+    }
 
-    // Release V
-    fake_key(&conn, 3, V_KEYCODE, root_window, "Failed to release V")?;
-    conn.sync()
-        .map_err(|e| format!("Sync after V release failed: {}", e))?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    // Release Ctrl
-    fake_key(
-        &conn,
-        3,
-        CTRL_L_KEYCODE,
-        root_window,
-        "Failed to release Ctrl",
-    )?;
+    for (i, (event_type, detail)) in events.iter().enumerate() {
+        // Wait for each event to be registered before sending the next one
+        if i > 0 {
+            thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
+        }
+        fake_key(
+            &conn,
+            *event_type,
+            *detail,
+            root_window,
+            &format!("Failed to send event {} ({})", event_type, detail),
+        )?;
+        conn.sync()
+            .map_err(|e| format!("Sync after event failed: {}", e))?;
+    }
+
+    if let Some(spare) = bound_spare {
+        // Let the target app read the key before it changes again
+        thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
+        spare.unbind(&conn);
+    }
 
-    conn.sync()
-        .map_err(|e| format!("Final sync failed: {}", e))?;
     Ok(())
 }
 
-/// Simulate Ctrl+V using xdotool
+/// An X11 keycode without keysyms, bound for a moment to a keysym missing from the layout
+#[cfg(target_os = "linux")]
+struct SpareKeycode {
+    keycode: u8,
+    keysyms_per_keycode: u8,
+}
+
+#[cfg(target_os = "linux")]
+impl SpareKeycode {
+    fn find<C: x11rb::connection::Connection>(conn: &C) -> Result<Option<Self>, String> {
+        use x11rb::protocol::xproto::ConnectionExt as XprotoConnectionExt;
+
+        let min_keycode = conn.setup().min_keycode;
+        let max_keycode = conn.setup().max_keycode;
+        let mapping = conn
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| format!("Keyboard mapping query failed: {}", e))?;
+        let per_keycode = mapping.keysyms_per_keycode;
+
+        Ok(mapping
+            .keysyms
+            .chunks(usize::from(per_keycode).max(1))
+            .enumerate()
+            .rev()
+            .find(|(_, syms)| syms.iter().all(|&sym| sym == 0))
+            .map(|(i, _)| Self {
+                keycode: min_keycode + i as u8,
+                keysyms_per_keycode: per_keycode,
+            }))
+    }
+
+    /// Puts `keysym` on every layout and level of the keycode
+    fn bind<C: x11rb::connection::Connection>(&self, conn: &C, keysym: u32) -> Result<(), String> {
+        self.set(conn, keysym)?;
+        // Clients need a moment to pick up the new mapping
+        thread::sleep(Duration::from_millis(20));
+        Ok(())
+    }
+
+    fn unbind<C: x11rb::connection::Connection>(&self, conn: &C) {
+        let _ = self.set(conn, 0);
+    }
+
+    fn set<C: x11rb::connection::Connection>(&self, conn: &C, keysym: u32) -> Result<(), String> {
+        use x11rb::protocol::xproto::ConnectionExt as XprotoConnectionExt;
+        use x11rb::wrapper::ConnectionExt as WrapperConnectionExt;
+
+        let syms = vec![keysym; usize::from(self.keysyms_per_keycode)];
+        conn.change_keyboard_mapping(1, self.keycode, self.keysyms_per_keycode, &syms)
+            .map_err(|e| format!("Failed to remap keycode {}: {}", self.keycode, e))?;
+        conn.sync().map_err(|e| e.to_string())
+    }
+}
+
+/// Simulate the paste chord using xdotool
 #[cfg(target_os = "linux")]
-fn simulate_paste_xdotool() -> Result<(), String> {
-    // Send Ctrl+V to the currently focused window without specifying a target
+fn simulate_paste_xdotool(chord: PasteChord) -> Result<(), String> {
+    let Some((command, options)) = chord.xdotool_args().split_first() else {
+        return Ok(());
+    };
+
+    // Send the chord to the currently focused window without specifying a target
     // Using --delay ensures proper timing between key events
     let output = std::process::Command::new("xdotool")
-        .args(["key", "--delay"])
+        .arg(command)
+        .arg("--delay")
         .arg(KEY_EVENT_DELAY_MS.to_string())
-        .arg("--clearmodifiers")
-        .arg("ctrl+v")
+        .args(options)
         .output()
-        .map_err(|e| format!("Failed to run xdotool key: {}", e))?;
+        .map_err(|e| format!("Failed to run xdotool {}: {}", command, e))?;
 
     if output.status.success() {
-        eprintln!("[SimulatePaste] xdotool sent ctrl+v to focused window");
+        eprintln!(
+            "[SimulatePaste] xdotool sent {} to focused window",
+            chord.label()
+        );
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("xdotool key failed: {}", stderr))
+        Err(format!("xdotool {} failed: {}", command, stderr))
+    }
+}
+
+/// Simulate the paste chord with the Wayland virtual keyboard protocol (wlroots compositors)
+#[cfg(target_os = "linux")]
+fn simulate_paste_virtual_keyboard(chord: PasteChord) -> Result<(), String> {
+    if chord == PasteChord::MiddleClick {
+        return Err("Virtual keyboards can't click".to_string());
+    }
+
+    let mut keyboard = VirtualKeyboard::connect()?;
+    // The keyboard has its own layout state, so select the layout that has "v"
+    let mut v_key = KEY_V;
+    if chord.uses_v() {
+        if let Some(position) = keyboard.keymap().locate(keymap::XK_V) {
+            v_key = position.evdev;
+            keyboard.set_layout(position.layout);
+        }
+    }
+
+    keyboard.tap(
+        &chord.keys(v_key),
+        Duration::from_millis(KEY_EVENT_DELAY_MS),
+    )
+}
+
+/// Simulate the paste chord through the RemoteDesktop portal (GNOME, KDE). Tried after
+/// uinput, so users who granted uinput access never see the portal's approval dialog.
+#[cfg(target_os = "linux")]
+fn simulate_paste_portal(chord: PasteChord) -> Result<(), String> {
+    if chord == PasteChord::MiddleClick {
+        return Err("Only keyboard access is requested from the portal".to_string());
+    }
+
+    let send = |session: &remote_desktop::RemoteDesktopSession| {
+        session.tap(chord.keysyms(), Duration::from_millis(KEY_EVENT_DELAY_MS))
+    };
+    remote_desktop::with_session(send)
+}
+
+#[cfg(target_os = "linux")]
+fn simulate_paste_uinput(chord: PasteChord) -> Result<(), String> {
+    // Buttons are sent like keys (the virtual keyboard also declares BTN_MIDDLE)
+    let is_click = chord == PasteChord::MiddleClick;
+    let codes = if is_click {
+        vec![BTN_MIDDLE]
+    } else if chord.uses_v() {
+        // Keys are interpreted with the active layout, so send whichever key types "v".
+        // Without a "v" anywhere, the US position is what apps fall back to.
+        let v_key = keymap::locate_keysym(keymap::XK_V).map_or(KEY_V, |p| p.evdev);
+        chord.keys(v_key)
+    } else {
+        chord.keys(KEY_V)
+    };
+
+    // Press in order, release in reverse
+    let send = |device: &mut UinputDevice| {
+        let presses = codes.iter().map(|&code| (code, true));
+        let releases = codes.iter().rev().map(|&code| (code, false));
+        for (code, pressed) in presses.chain(releases) {
+            device.send_key(code, pressed)?;
+            thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
+        }
+        Ok(())
+    };
+
+    with_virtual_keyboard(send).or_else(|e| {
+        eprintln!("[SimulatePaste] uinput write failed ({}), retrying", e);
+        with_virtual_keyboard(send)
+    })
+}
+
+/// Long-lived virtual keyboard, created on first use
+#[cfg(target_os = "linux")]
+static VIRTUAL_KEYBOARD: Lazy<Mutex<Option<UinputDevice>>> = Lazy::new(|| Mutex::new(None));
+
+/// Runs `f` with the process-wide virtual keyboard, creating it if needed.
+/// Keeping one device avoids the settle delay on every paste, and some compositors
+/// ignore freshly created devices. After a failure the device is dropped so the next
+/// call starts with a fresh one.
+#[cfg(target_os = "linux")]
+fn with_virtual_keyboard<T>(
+    f: impl FnOnce(&mut UinputDevice) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut slot = VIRTUAL_KEYBOARD.lock();
+    let device = match slot.as_mut() {
+        Some(device) => device,
+        None => {
+            let keys: Vec<u16> = VIRTUAL_KEYBOARD_KEYS.chain([BTN_MIDDLE]).collect();
+            eprintln!("[VirtualKeyboard] Creating uinput device");
+            slot.insert(UinputDevice::create(&keys, true)?)
+        }
+    };
+
+    let result = f(device);
+    if result.is_err() {
+        *slot = None;
     }
+    result
 }
 
+/// Virtual input device created through /dev/uinput, destroyed on drop
 #[cfg(target_os = "linux")]
-fn simulate_paste_uinput() -> Result<(), String> {
-    use std::fs::OpenOptions;
-    use std::io::Write;
-    use std::os::unix::io::AsRawFd;
+struct UinputDevice {
+    file: std::fs::File,
+}
 
+#[cfg(target_os = "linux")]
+impl UinputDevice {
     const EV_SYN: u16 = 0x00;
     const EV_KEY: u16 = 0x01;
+    const EV_REL: u16 = 0x02;
     const SYN_REPORT: u16 = 0x00;
-    const KEY_LEFTCTRL: u16 = 29;
-    const KEY_V: u16 = 47;
+    const REL_X: u16 = 0x00;
+    const REL_Y: u16 = 0x01;
+
+    /// Creates a device able to send `keys` (and pointer buttons if `pointer` is set)
+    fn create(keys: &[u16], pointer: bool) -> Result<Self, String> {
+        use std::fs::OpenOptions;
+        use std::os::unix::io::AsRawFd;
+
+        let file = OpenOptions::new()
+            .write(true)
+            .open("/dev/uinput")
+            .map_err(|e| format!("Failed to open /dev/uinput: {}", e))?;
+
+        const UI_SET_EVBIT: libc::c_ulong = 0x40045564;
+        const UI_SET_KEYBIT: libc::c_ulong = 0x40045565;
+        const UI_SET_RELBIT: libc::c_ulong = 0x40045566;
+        const UI_DEV_SETUP: libc::c_ulong = 0x405c5503;
+        const UI_DEV_CREATE: libc::c_ulong = 0x5501;
+
+        let fd = file.as_raw_fd();
+        unsafe {
+            if libc::ioctl(fd, UI_SET_EVBIT, Self::EV_KEY as libc::c_int) < 0 {
+                return Err("Failed to set EV_KEY".to_string());
+            }
+            for &code in keys {
+                if libc::ioctl(fd, UI_SET_KEYBIT, code as libc::c_int) < 0 {
+                    return Err(format!("Failed to set key bit {}", code));
+                }
+            }
+            if pointer {
+                if libc::ioctl(fd, UI_SET_EVBIT, Self::EV_REL as libc::c_int) < 0 {
+                    return Err("Failed to set EV_REL".to_string());
+                }
+                for axis in [Self::REL_X, Self::REL_Y] {
+                    if libc::ioctl(fd, UI_SET_RELBIT, axis as libc::c_int) < 0 {
+                        return Err("Failed to set relative axis".to_string());
+                    }
+                }
+            }
+
+            #[repr(C)]
+            struct UinputSetup {
+                id: [u16; 4],
+                name: [u8; 80],
+                ff_effects_max: u32,
+            }
+
+            let mut setup = UinputSetup {
+                id: [0x03, 0x1234, 0x5678, 0x0001],
+                name: [0; 80],
+                ff_effects_max: 0,
+            };
+            let name = b"emoji-paste-helper";
+            setup.name[..name.len()].copy_from_slice(name);
+
+            if libc::ioctl(fd, UI_DEV_SETUP, &setup) < 0 {
+                return Err("Failed to setup uinput device".to_string());
+            }
+            if libc::ioctl(fd, UI_DEV_CREATE) < 0 {
+                return Err("Failed to create uinput device".to_string());
+            }
+        }
+
+        // Wait longer for the virtual device to be recognized by the system
+        // This is critical for some desktop environments (Cinnamon, GNOME)
+        thread::sleep(Duration::from_millis(UINPUT_DEVICE_SETTLE_MS));
+
+        Ok(Self { file })
+    }
 
     fn make_event(type_: u16, code: u16, value: i32) -> [u8; 24] {
         let mut event = [0u8; 24];
@@ -172,108 +701,429 @@ fn simulate_paste_uinput() -> Result<(), String> {
         event
     }
 
-    let mut uinput = OpenOptions::new()
-        .write(true)
-        .open("/dev/uinput")
-        .map_err(|e| format!("Failed to open /dev/uinput: {}", e))?;
+    /// Sends a key (or button) press or release followed by a sync report
+    fn send_key(&mut self, code: u16, pressed: bool) -> Result<(), String> {
+        use std::io::Write;
 
-    const UI_SET_EVBIT: libc::c_ulong = 0x40045564;
-    const UI_SET_KEYBIT: libc::c_ulong = 0x40045565;
-    const UI_DEV_SETUP: libc::c_ulong = 0x405c5503;
-    const UI_DEV_CREATE: libc::c_ulong = 0x5501;
-    const UI_DEV_DESTROY: libc::c_ulong = 0x5502;
+        self.file
+            .write_all(&Self::make_event(Self::EV_KEY, code, pressed as i32))
+            .map_err(|e| e.to_string())?;
+        self.file
+            .write_all(&Self::make_event(Self::EV_SYN, Self::SYN_REPORT, 0))
+            .map_err(|e| e.to_string())?;
+        self.file.flush().map_err(|e| e.to_string())
+    }
 
-    unsafe {
-        if libc::ioctl(uinput.as_raw_fd(), UI_SET_EVBIT, EV_KEY as libc::c_int) < 0 {
-            return Err("Failed to set EV_KEY".to_string());
+    /// Presses the keys in order, then releases them in reverse
+    fn tap(&mut self, keys: &[u16], delay: Duration) -> Result<(), String> {
+        for &key in keys {
+            self.send_key(key, true)?;
         }
-        if libc::ioctl(
-            uinput.as_raw_fd(),
-            UI_SET_KEYBIT,
-            KEY_LEFTCTRL as libc::c_int,
-        ) < 0
-        {
-            return Err("Failed to set KEY_LEFTCTRL".to_string());
+        thread::sleep(delay);
+        for &key in keys.iter().rev() {
+            self.send_key(key, false)?;
         }
-        if libc::ioctl(uinput.as_raw_fd(), UI_SET_KEYBIT, KEY_V as libc::c_int) < 0 {
-            return Err("Failed to set KEY_V".to_string());
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for UinputDevice {
+    fn drop(&mut self) {
+        use std::os::unix::io::AsRawFd;
+        const UI_DEV_DESTROY: libc::c_ulong = 0x5502;
+
+        // Wait for events to be processed before destroying device
+        thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
+        unsafe {
+            libc::ioctl(self.file.as_raw_fd(), UI_DEV_DESTROY);
         }
+    }
+}
+
+// =============================================================================
+// Type-out Mode
+// =============================================================================
+
+/// Set by the abort hotkey; checked between characters
+static TYPING_ABORTED: AtomicBool = AtomicBool::new(false);
+
+/// Stops an ongoing `simulate_typing` after the current character
+pub fn abort_typing() {
+    eprintln!("[SimulateTyping] Abort requested");
+    TYPING_ABORTED.store(true, Ordering::SeqCst);
+}
+
+fn typing_aborted() -> bool {
+    TYPING_ABORTED.load(Ordering::SeqCst)
+}
 
-        #[repr(C)]
-        struct UinputSetup {
-            id: [u16; 4],
-            name: [u8; 80],
-            ff_effects_max: u32,
+/// Why a typing strategy stopped
+#[cfg(target_os = "linux")]
+enum TypingError {
+    /// Nothing was typed yet; the next strategy may be tried
+    Unavailable(String),
+    /// Typing started and stopped midway; retrying would type text twice
+    Interrupted(String),
+}
+
+#[cfg(target_os = "linux")]
+type TypingStrategy = (&'static str, fn(&str, Duration) -> Result<(), TypingError>);
+
+/// Types `text` into the focused window as key events, for fields that block pasting
+#[cfg(target_os = "linux")]
+pub fn simulate_typing(text: &str) -> Result<(), String> {
+    let delay = Duration::from_millis(PASTE_SETTINGS.read().type_delay_ms);
+    TYPING_ABORTED.store(false, Ordering::SeqCst);
+
+    // Give window manager time to settle focus before sending keystrokes
+    thread::sleep(Duration::from_millis(PRE_PASTE_DELAY_MS));
+
+    eprintln!(
+        "[SimulateTyping] Typing {} characters...",
+        text.chars().count()
+    );
+
+    const X11_STRATEGIES: &[TypingStrategy] = &[
+        ("xdotool", type_xdotool),
+        ("XTest", type_xtest),
+        ("uinput", type_uinput),
+    ];
+
+    const NON_X11_STRATEGIES: &[TypingStrategy] = &[("uinput", type_uinput)];
+
+    let strategies = if session::is_x11() {
+        X11_STRATEGIES
+    } else {
+        NON_X11_STRATEGIES
+    };
+
+    for (name, func) in strategies {
+        match func(text, delay) {
+            Ok(()) if typing_aborted() => {
+                eprintln!("[SimulateTyping] Aborted ({})", name);
+                return Ok(());
+            }
+            Ok(()) => {
+                eprintln!("[SimulateTyping] Text typed via {}", name);
+                return Ok(());
+            }
+            Err(TypingError::Unavailable(err)) => {
+                eprintln!("[SimulateTyping] {} failed: {}", name, err);
+            }
+            Err(TypingError::Interrupted(err)) => {
+                return Err(format!("Typing via {} stopped: {}", name, err));
+            }
         }
+    }
+
+    Err("All typing methods failed".to_string())
+}
+
+/// Types with `xdotool type`, which remaps keysyms for characters missing from the layout
+#[cfg(target_os = "linux")]
+fn type_xdotool(text: &str, delay: Duration) -> Result<(), TypingError> {
+    let mut child = std::process::Command::new("xdotool")
+        .args(["type", "--clearmodifiers", "--delay"])
+        .arg(delay.as_millis().to_string())
+        .arg("--")
+        .arg(text)
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| TypingError::Unavailable(format!("Failed to run xdotool type: {}", e)))?;
 
-        let mut setup = UinputSetup {
-            id: [0x03, 0x1234, 0x5678, 0x0001],
-            name: [0; 80],
-            ff_effects_max: 0,
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => {
+                return Err(TypingError::Interrupted(format!(
+                    "xdotool type exited with {}",
+                    status
+                )))
+            }
+            Ok(None) if typing_aborted() => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(());
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => return Err(TypingError::Interrupted(e.to_string())),
+        }
+    }
+}
+
+/// Types through XTest. Characters missing from the active layout are typed by
+/// temporarily binding their keysym to an unused keycode.
+#[cfg(target_os = "linux")]
+fn type_xtest(text: &str, delay: Duration) -> Result<(), TypingError> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xtest::ConnectionExt as XtestConnectionExt;
+    use x11rb::wrapper::ConnectionExt as WrapperConnectionExt;
+
+    const KEY_PRESS: u8 = 2;
+    const KEY_RELEASE: u8 = 3;
+    const SHIFT_L_KEYCODE: u8 = (KEY_LEFTSHIFT + 8) as u8;
+
+    let unavailable = |e: String| TypingError::Unavailable(e);
+    let (conn, screen_num) =
+        x11rb::connect(None).map_err(|e| unavailable(format!("X11 connect failed: {}", e)))?;
+    let root_window = conn.setup().roots[screen_num].root;
+
+    conn.xtest_get_version(2, 1)
+        .map_err(|e| unavailable(e.to_string()))?
+        .reply()
+        .map_err(|e| unavailable(format!("XTest version query failed: {}", e)))?;
+
+    let keymap = Keymap::load().map_err(unavailable)?;
+    let spare = SpareKeycode::find(&conn).map_err(unavailable)?;
+
+    let interrupted = |e: String| TypingError::Interrupted(e);
+    let mut bound_keysym = None;
+    for c in text.chars() {
+        if typing_aborted() {
+            break;
+        }
+        let keysym = keymap::keysym_for_char(c);
+
+        let position = keymap.locate(keysym).filter(|p| p.on_active_layout);
+        let (keycode, shift) = match (position, &spare) {
+            (Some(position), _) => ((position.evdev + 8) as u8, position.shift),
+            (None, Some(spare)) => {
+                if bound_keysym != Some(keysym) {
+                    spare.bind(&conn, keysym).map_err(interrupted)?;
+                    bound_keysym = Some(keysym);
+                }
+                (spare.keycode, false)
+            }
+            (None, None) => {
+                eprintln!("[SimulateTyping] Cannot type {:?}: no spare keycode", c);
+                continue;
+            }
         };
-        let name = b"emoji-paste-helper";
-        setup.name[..name.len()].copy_from_slice(name);
 
-        if libc::ioctl(uinput.as_raw_fd(), UI_DEV_SETUP, &setup) < 0 {
-            return Err("Failed to setup uinput device".to_string());
+        let mut events = Vec::with_capacity(4);
+        if shift {
+            events.push((KEY_PRESS, SHIFT_L_KEYCODE));
         }
-        if libc::ioctl(uinput.as_raw_fd(), UI_DEV_CREATE) < 0 {
-            return Err("Failed to create uinput device".to_string());
+        events.push((KEY_PRESS, keycode));
+        events.push((KEY_RELEASE, keycode));
+        if shift {
+            events.push((KEY_RELEASE, SHIFT_L_KEYCODE));
         }
+        for (event_type, code) in events {
+            fake_key(&conn, event_type, code, root_window, "Failed to send key")
+                .map_err(interrupted)?;
+        }
+        conn.sync().map_err(|e| interrupted(e.to_string()))?;
+        thread::sleep(delay);
     }
 
-    // Wait longer for the virtual device to be recognized by the system
-    // This is critical for some desktop environments (Cinnamon, GNOME)
-    thread::sleep(Duration::from_millis(UINPUT_DEVICE_SETTLE_MS));
-
-    // Press Ctrl
-    uinput
-        .write_all(&make_event(EV_KEY, KEY_LEFTCTRL, 1))
-        .map_err(|e| e.to_string())?;
-    uinput
-        .write_all(&make_event(EV_SYN, SYN_REPORT, 0))
-        .map_err(|e| e.to_string())?;
-    uinput.flush().map_err(|e| e.to_string())?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    // Press V
-    uinput
-        .write_all(&make_event(EV_KEY, KEY_V, 1))
-        .map_err(|e| e.to_string())?;
-    uinput
-        .write_all(&make_event(EV_SYN, SYN_REPORT, 0))
-        .map_err(|e| e.to_string())?;
-    uinput.flush().map_err(|e| e.to_string())?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    // Release V
-    uinput
-        .write_all(&make_event(EV_KEY, KEY_V, 0))
-        .map_err(|e| e.to_string())?;
-    uinput
-        .write_all(&make_event(EV_SYN, SYN_REPORT, 0))
-        .map_err(|e| e.to_string())?;
-    uinput.flush().map_err(|e| e.to_string())?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    // Release Ctrl
-    uinput
-        .write_all(&make_event(EV_KEY, KEY_LEFTCTRL, 0))
-        .map_err(|e| e.to_string())?;
-    uinput
-        .write_all(&make_event(EV_SYN, SYN_REPORT, 0))
-        .map_err(|e| e.to_string())?;
-    uinput.flush().map_err(|e| e.to_string())?;
-
-    // Wait for events to be processed before destroying device
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    unsafe {
-        libc::ioctl(uinput.as_raw_fd(), UI_DEV_DESTROY);
-    }
-
-    // Small delay after device destruction
-    thread::sleep(Duration::from_millis(POST_PASTE_DELAY_MS));
+    // Give the spare keycode back
+    if let (Some(_), Some(spare)) = (bound_keysym, &spare) {
+        spare.unbind(&conn);
+    }
 
     Ok(())
 }
+
+/// Evdev key and Shift state for a character on a US QWERTY layout
+#[cfg(target_os = "linux")]
+fn us_layout_key(c: char) -> Option<(u16, bool)> {
+    const LETTERS: [u16; 26] = [
+        30, 48, 46, 32, 18, 33, 34, 35, 23, 36, 37, 38, 50, 49, 24, 25, 16, 19, 31, 20, 22, 47, 17,
+        45, 21, 44,
+    ];
+    const DIGITS: [u16; 10] = [11, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+    const SHIFTED_DIGITS: &str = ")!@#$%^&*(";
+    const SYMBOLS: &[(char, char, u16)] = &[
+        ('-', '_', 12),
+        ('=', '+', 13),
+        ('[', '{', 26),
+        (']', '}', 27),
+        (';', ':', 39),
+        ('\'', '"', 40),
+        ('`', '~', 41),
+        ('\\', '|', 43),
+        (',', '<', 51),
+        ('.', '>', 52),
+        ('/', '?', 53),
+    ];
+
+    match c {
+        'a'..='z' => Some((LETTERS[(c as u8 - b'a') as usize], false)),
+        'A'..='Z' => Some((LETTERS[(c as u8 - b'A') as usize], true)),
+        '0'..='9' => Some((DIGITS[(c as u8 - b'0') as usize], false)),
+        ' ' => Some((57, false)),
+        '\n' | '\r' => Some((28, false)),
+        '\t' => Some((15, false)),
+        _ => {
+            if let Some(i) = SHIFTED_DIGITS.find(c) {
+                return Some((DIGITS[i], true));
+            }
+            SYMBOLS.iter().find_map(|&(plain, shifted, code)| {
+                if c == plain {
+                    Some((code, false))
+                } else if c == shifted {
+                    Some((code, true))
+                } else {
+                    None
+                }
+            })
+        }
+    }
+}
+
+/// Types through uinput, using the active layout (US QWERTY when the keymap can't be
+/// read). Other characters use the Ctrl+Shift+U Unicode input sequence of GTK and IBus.
+#[cfg(target_os = "linux")]
+fn type_uinput(text: &str, delay: Duration) -> Result<(), TypingError> {
+    const KEY_U: u16 = 22;
+    const KEY_SPACE: u16 = 57;
+
+    let keymap = Keymap::load()
+        .map_err(|e| eprintln!("[SimulateTyping] Failed to read the keymap: {}", e))
+        .ok();
+    let key_for = |c: char| match &keymap {
+        Some(keymap) => keymap
+            .locate(keymap::keysym_for_char(c))
+            .filter(|p| p.on_active_layout)
+            .map(|p| (p.evdev, p.shift)),
+        None => us_layout_key(c),
+    };
+
+    let tap = |device: &mut UinputDevice, (key, shift): (u16, bool)| {
+        if shift {
+            device.tap(&[KEY_LEFTSHIFT, key], delay)
+        } else {
+            device.tap(&[key], delay)
+        }
+    };
+
+    // Failing before the first key is like the device being unavailable
+    let mut started = false;
+    with_virtual_keyboard(|device| {
+        for c in text.chars() {
+            if typing_aborted() {
+                break;
+            }
+            match key_for(c) {
+                Some(key) => tap(device, key)?,
+                None => {
+                    let (u_key, _) = key_for('u').unwrap_or((KEY_U, false));
+                    device.tap(&[KEY_LEFTCTRL, KEY_LEFTSHIFT, u_key], delay)?;
+                    for digit in format!("{:x}", c as u32).chars() {
+                        let key = key_for(digit)
+                            .or_else(|| us_layout_key(digit))
+                            .expect("hex digits are on the US layout");
+                        tap(device, key)?;
+                    }
+                    tap(device, key_for(' ').unwrap_or((KEY_SPACE, false)))?;
+                }
+            }
+            started = true;
+            thread::sleep(delay);
+        }
+        Ok(())
+    })
+    .map_err(|e| {
+        if started {
+            TypingError::Interrupted(e)
+        } else {
+            TypingError::Unavailable(e)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classes(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_terminal_detection() {
+        let settings = PasteSettings::default();
+        for window in [
+            &["gnome-terminal-server", "Gnome-terminal"][..],
+            &["kitty", "kitty"],
+            &["Alacritty", "Alacritty"],
+            &["konsole", "konsole"],
+            &["org.wezfurlong.wezterm", "org.wezfurlong.wezterm"],
+            &["org.gnome.Console"],
+            &["st", "st-256color"],
+            &["dev.warp.Warp"],
+        ] {
+            assert_eq!(settings.chord_for(&classes(window)), PasteChord::CtrlShiftV);
+        }
+        for window in [
+            &["Navigator", "firefox"][..],
+            // Apps whose id merely ends like a terminal name
+            &["com.example.Console"],
+            &["io.github.example.Terminal"],
+            &["net.example.Warp"],
+            &["org.example.st"],
+        ] {
+            assert_eq!(settings.chord_for(&classes(window)), PasteChord::CtrlV);
+        }
+        assert_eq!(settings.chord_for(&[]), PasteChord::CtrlV);
+
+        let disabled = PasteSettings {
+            detect_terminals: false,
+            ..PasteSettings::default()
+        };
+        assert_eq!(
+            disabled.chord_for(&classes(&["kitty", "kitty"])),
+            PasteChord::CtrlV
+        );
+    }
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let settings = PasteSettings {
+            chord: PasteChord::ShiftInsert,
+            detect_terminals: true,
+            app_overrides: vec![
+                PasteOverride {
+                    app: "XTerm".to_string(),
+                    chord: PasteChord::MiddleClick,
+                },
+                PasteOverride {
+                    app: "code".to_string(),
+                    chord: PasteChord::None,
+                },
+            ],
+            ..PasteSettings::default()
+        };
+        assert_eq!(
+            settings.chord_for(&classes(&["xterm", "XTerm"])),
+            PasteChord::MiddleClick
+        );
+        assert_eq!(
+            settings.chord_for(&classes(&["code", "Code"])),
+            PasteChord::None
+        );
+        assert_eq!(
+            settings.chord_for(&classes(&["gimp", "Gimp"])),
+            PasteChord::ShiftInsert
+        );
+
+        let json = serde_json::to_string(&PasteChord::CtrlShiftV).unwrap();
+        assert_eq!(json, "\"ctrl_shift_v\"");
+    }
+
+    #[test]
+    fn test_typing_key_tables() {
+        assert_eq!(us_layout_key('a'), Some((30, false)));
+        assert_eq!(us_layout_key('Z'), Some((44, true)));
+        assert_eq!(us_layout_key('0'), Some((11, false)));
+        assert_eq!(us_layout_key('@'), Some((3, true)));
+        assert_eq!(us_layout_key('|'), Some((43, true)));
+        assert_eq!(us_layout_key('é'), None);
+
+        let settings: PasteSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.type_delay_ms, 12);
+        assert_eq!(settings.type_abort_shortcut, "Escape");
+    }
+}
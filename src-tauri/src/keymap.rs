@@ -0,0 +1,639 @@
+//! Keymap Module
+//! Finds the physical key that produces a keysym on the active keyboard layout, so injected
+//! shortcuts and typed text work on AZERTY, Dvorak, Colemak or Cyrillic layouts too.
+//!
+//! X11 sessions read the keymap through the XKB extension. Wayland sessions ask the
+//! compositor for the seat keymap and compile it with libxkbcommon (loaded at runtime).
+//! The selected layout comes from `wl_keyboard.modifiers` when the compositor sends it,
+//! otherwise from the compositor's own settings (sway, Hyprland, KDE, GNOME).
+
+use crate::session;
+
+/// X11 and xkbcommon keycodes are evdev codes shifted by 8
+const EVDEV_OFFSET: u32 = 8;
+
+/// Keysym of the lowercase "v" key
+pub const XK_V: u32 = 0x0076;
+
+/// X11 keysym for a character (Latin-1 maps directly, the rest uses Unicode keysyms)
+pub fn keysym_for_char(c: char) -> u32 {
+    match c {
+        '\n' | '\r' => 0xff0d, // Return
+        '\t' => 0xff09,        // Tab
+        '\u{8}' => 0xff08,     // BackSpace
+        c if (' '..='~').contains(&c) || ('\u{a0}'..='\u{ff}').contains(&c) => c as u32,
+        c => 0x0100_0000 + c as u32,
+    }
+}
+
+/// Where a keysym lives on the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPosition {
+    /// Linux evdev code (X11 keycode minus 8)
+    pub evdev: u16,
+    /// Whether Shift must be held (second shift level)
+    pub shift: bool,
+    /// Layout (XKB group) the keysym was found on
+    pub layout: usize,
+    /// False when the keysym is only on another layout of the keymap
+    pub on_active_layout: bool,
+}
+
+/// Keysyms of the first two shift levels of every key, per layout (XKB group)
+#[derive(Debug, Default)]
+pub struct Keymap {
+    /// (evdev code, [layout][level] keysyms), sorted by evdev code
+    keys: Vec<(u16, Vec<[u32; 2]>)>,
+    /// Layout currently selected (0 when the compositor doesn't tell)
+    active_layout: usize,
+}
+
+impl Keymap {
+    /// Reads the keymap currently used by the display server
+    pub fn load() -> Result<Self, String> {
+        if session::is_x11() {
+            return x11::load();
+        }
+
+        wayland::probe()
+            .and_then(|(text, group)| {
+                let layout = group.map(|g| g as usize).or_else(compositor_layout_index);
+                xkbcommon::from_text(&text).map(|keymap| keymap.with_active_layout(layout))
+            })
+            .or_else(|e| {
+                eprintln!(
+                    "[Keymap] Compositor keymap unavailable ({}), using XKB defaults",
+                    e
+                );
+                xkbcommon::from_names()
+            })
+    }
+
+    /// Compiles a keymap in the XKB text format (as sent by Wayland compositors)
+    pub fn from_xkb_text(text: &str) -> Result<Self, String> {
+        xkbcommon::from_text(text)
+    }
+
+    fn new(mut keys: Vec<(u16, Vec<[u32; 2]>)>, active_layout: usize) -> Self {
+        // Main block keys have lower codes than the keypad, so they win ties
+        keys.sort_by_key(|(evdev, _)| *evdev);
+        Self {
+            keys,
+            active_layout,
+        }
+    }
+
+    /// Selects the active layout, when known and present in the keymap
+    fn with_active_layout(mut self, layout: Option<usize>) -> Self {
+        let num_layouts = self.keys.iter().map(|(_, l)| l.len()).max().unwrap_or(0);
+        if let Some(layout) = layout.filter(|&l| l < num_layouts) {
+            self.active_layout = layout;
+        }
+        self
+    }
+
+    /// Finds the key producing `keysym`, preferring the active layout and unshifted keys.
+    /// Other layouts are searched too, since toolkits match shortcuts across layouts.
+    pub fn locate(&self, keysym: u32) -> Option<KeyPosition> {
+        let num_layouts = self.keys.iter().map(|(_, l)| l.len()).max().unwrap_or(0);
+        let others = (0..num_layouts).filter(|&l| l != self.active_layout);
+
+        std::iter::once(self.active_layout)
+            .chain(others)
+            .find_map(|layout| {
+                (0..2).find_map(|level| {
+                    self.keys.iter().find_map(|(evdev, layouts)| {
+                        // Keys with fewer layouts wrap back to their first one
+                        let levels = layouts.get(layout).or_else(|| layouts.first())?;
+                        (levels[level] == keysym).then_some(KeyPosition {
+                            evdev: *evdev,
+                            shift: level == 1,
+                            layout,
+                            on_active_layout: layout == self.active_layout,
+                        })
+                    })
+                })
+            })
+    }
+}
+
+/// Loads the keymap and looks up `keysym`, logging why it couldn't be found
+pub fn locate_keysym(keysym: u32) -> Option<KeyPosition> {
+    match Keymap::load() {
+        Ok(keymap) => {
+            let position = keymap.locate(keysym);
+            if position.is_none() {
+                eprintln!("[Keymap] Keysym {:#x} is not on any layout", keysym);
+            }
+            position
+        }
+        Err(e) => {
+            eprintln!("[Keymap] Failed to read the keymap: {}", e);
+            None
+        }
+    }
+}
+
+/// The XKB keymap the Wayland compositor gives keyboard clients
+pub fn compositor_keymap_text() -> Result<String, String> {
+    wayland::probe().map(|(text, _)| text)
+}
+
+/// Layout selected on the compositor, for sessions where `wl_keyboard.modifiers`
+/// isn't sent to us (it only follows keyboard focus)
+fn compositor_layout_index() -> Option<usize> {
+    use crate::linux_shortcut_manager::{detect_desktop, DesktopEnvironment};
+    use std::process::Command;
+
+    let output = |cmd: &str, args: &[&str]| {
+        Command::new(cmd)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+    };
+
+    match detect_desktop() {
+        DesktopEnvironment::Sway => {
+            sway_layout_index(&output("swaymsg", &["-t", "get_inputs", "--raw"])?)
+        }
+        DesktopEnvironment::Hyprland => {
+            hyprland_layout_index(&output("hyprctl", &["devices", "-j"])?)
+        }
+        DesktopEnvironment::Kde => {
+            let conn = zbus::blocking::Connection::session().ok()?;
+            let reply = conn
+                .call_method(
+                    Some("org.kde.keyboard"),
+                    "/Layouts",
+                    Some("org.kde.KeyboardLayouts"),
+                    "getLayout",
+                    &(),
+                )
+                .ok()?;
+            reply.body().deserialize::<u32>().ok().map(|i| i as usize)
+        }
+        DesktopEnvironment::Gnome | DesktopEnvironment::PopOs | DesktopEnvironment::Budgie => {
+            let get = |key| {
+                output(
+                    "gsettings",
+                    &["get", "org.gnome.desktop.input-sources", key],
+                )
+            };
+            gnome_layout_index(&get("sources")?, &get("mru-sources")?)
+        }
+        _ => None,
+    }
+}
+
+/// `xkb_active_layout_index` of the first keyboard in `swaymsg -t get_inputs`
+fn sway_layout_index(json: &str) -> Option<usize> {
+    let inputs: serde_json::Value = serde_json::from_str(json).ok()?;
+    inputs
+        .as_array()?
+        .iter()
+        .filter(|input| input["type"] == "keyboard")
+        .find_map(|input| input["xkb_active_layout_index"].as_u64())
+        .map(|i| i as usize)
+}
+
+/// `active_layout_index` of the main keyboard in `hyprctl devices -j`
+fn hyprland_layout_index(json: &str) -> Option<usize> {
+    let devices: serde_json::Value = serde_json::from_str(json).ok()?;
+    let keyboards = devices["keyboards"].as_array()?;
+    keyboards
+        .iter()
+        .find(|k| k["main"].as_bool() == Some(true))
+        .or_else(|| keyboards.first())?["active_layout_index"]
+        .as_u64()
+        .map(|i| i as usize)
+}
+
+/// GNOME compiles one group per XKB input source, in `sources` order; the current
+/// source is the first of `mru-sources`
+fn gnome_layout_index(sources: &str, mru_sources: &str) -> Option<usize> {
+    let pairs = |value: &str| -> Vec<(String, String)> {
+        value
+            .split(')')
+            .filter_map(|entry| {
+                let mut quoted = entry.split('\'').skip(1).step_by(2);
+                Some((quoted.next()?.to_string(), quoted.next()?.to_string()))
+            })
+            .collect()
+    };
+    let current = pairs(mru_sources).into_iter().next()?;
+    pairs(sources)
+        .into_iter()
+        .filter(|(kind, _)| kind == "xkb")
+        .position(|source| source == current)
+}
+
+/// The default XKB keymap (XKB_DEFAULT_* environment variables, else "us") as text
+pub fn default_keymap_text() -> Result<String, String> {
+    xkbcommon::default_text()
+}
+
+/// First two shift levels of a key's symbols in one group
+fn level_pair(syms: &[u32]) -> [u32; 2] {
+    [
+        syms.first().copied().unwrap_or(0),
+        syms.get(1).copied().unwrap_or(0),
+    ]
+}
+
+mod x11 {
+    use super::{level_pair, Keymap, EVDEV_OFFSET};
+    use x11rb::protocol::xkb::{self, ConnectionExt as XkbConnectionExt, MapPart, VMod};
+
+    pub fn load() -> Result<Keymap, String> {
+        let (conn, _) = x11rb::connect(None).map_err(|e| format!("X11 connect failed: {}", e))?;
+
+        let version = conn
+            .xkb_use_extension(1, 0)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| format!("XKB extension query failed: {}", e))?;
+        if !version.supported {
+            return Err("XKB extension not supported".to_string());
+        }
+
+        let device = xkb::ID::USE_CORE_KBD.into();
+        let state = conn
+            .xkb_get_state(device)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| format!("XKB state query failed: {}", e))?;
+
+        // Asking for the full key symbol table ignores the partial ranges below
+        let map = conn
+            .xkb_get_map(
+                device,
+                MapPart::KEY_SYMS,
+                MapPart::from(0u16),
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                VMod::from(0u16),
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            )
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| format!("XKB map query failed: {}", e))?;
+        let syms = map.map.syms_rtrn.ok_or("XKB map has no key symbols")?;
+
+        let keys = syms
+            .iter()
+            .enumerate()
+            .filter_map(|(i, key)| {
+                let keycode = u32::from(map.first_key_sym) + i as u32;
+                let evdev = u16::try_from(keycode.checked_sub(EVDEV_OFFSET)?).ok()?;
+                let width = usize::from(key.width).max(1);
+                let groups = usize::from(key.group_info & 0x0f);
+                let layouts = key
+                    .syms
+                    .chunks(width)
+                    .take(groups)
+                    .map(level_pair)
+                    .collect();
+                Some((evdev, layouts))
+            })
+            .collect();
+
+        Ok(Keymap::new(keys, usize::from(u8::from(state.group))))
+    }
+}
+
+mod wayland {
+    use std::os::unix::fs::FileExt;
+    use wayland_client::protocol::{wl_keyboard, wl_registry, wl_seat};
+    use wayland_client::{Connection, Dispatch, Proxy, QueueHandle, WEnum};
+
+    #[derive(Default)]
+    struct KeymapProbe {
+        keyboard: Option<wl_keyboard::WlKeyboard>,
+        keymap: Option<Result<String, String>>,
+        /// Effective layout (XKB group), if the compositor sent the modifier state
+        group: Option<u32>,
+    }
+
+    /// Fetches the XKB keymap the compositor sends to keyboard clients, and the
+    /// selected layout when the compositor reports it to unfocused clients
+    pub fn probe() -> Result<(String, Option<u32>), String> {
+        let conn =
+            Connection::connect_to_env().map_err(|e| format!("Wayland connect failed: {}", e))?;
+        let mut queue = conn.new_event_queue();
+        let qh = queue.handle();
+        conn.display().get_registry(&qh, ());
+
+        // Globals, then seat capabilities, then the keymap
+        let mut probe = KeymapProbe::default();
+        for _ in 0..3 {
+            queue.roundtrip(&mut probe).map_err(|e| e.to_string())?;
+            if probe.keymap.is_some() {
+                break;
+            }
+        }
+        // Modifiers follow the keymap when the compositor sends them at all
+        if probe.keymap.is_some() && probe.group.is_none() {
+            queue.roundtrip(&mut probe).map_err(|e| e.to_string())?;
+        }
+
+        if let Some(keyboard) = probe.keyboard.take() {
+            if keyboard.version() >= 3 {
+                keyboard.release();
+            }
+        }
+        let group = probe.group;
+        probe
+            .keymap
+            .unwrap_or_else(|| Err("Seat has no keyboard".to_string()))
+            .map(|text| (text, group))
+    }
+
+    impl Dispatch<wl_registry::WlRegistry, ()> for KeymapProbe {
+        fn event(
+            _state: &mut Self,
+            registry: &wl_registry::WlRegistry,
+            event: wl_registry::Event,
+            _data: &(),
+            _conn: &Connection,
+            qh: &QueueHandle<Self>,
+        ) {
+            if let wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } = event
+            {
+                if interface == "wl_seat" {
+                    registry.bind::<wl_seat::WlSeat, _, _>(name, version.min(5), qh, ());
+                }
+            }
+        }
+    }
+
+    impl Dispatch<wl_seat::WlSeat, ()> for KeymapProbe {
+        fn event(
+            state: &mut Self,
+            seat: &wl_seat::WlSeat,
+            event: wl_seat::Event,
+            _data: &(),
+            _conn: &Connection,
+            qh: &QueueHandle<Self>,
+        ) {
+            if let wl_seat::Event::Capabilities {
+                capabilities: WEnum::Value(capabilities),
+            } = event
+            {
+                if capabilities.contains(wl_seat::Capability::Keyboard) && state.keyboard.is_none()
+                {
+                    state.keyboard = Some(seat.get_keyboard(qh, ()));
+                }
+            }
+        }
+    }
+
+    impl Dispatch<wl_keyboard::WlKeyboard, ()> for KeymapProbe {
+        fn event(
+            state: &mut Self,
+            _keyboard: &wl_keyboard::WlKeyboard,
+            event: wl_keyboard::Event,
+            _data: &(),
+            _conn: &Connection,
+            _qh: &QueueHandle<Self>,
+        ) {
+            if let wl_keyboard::Event::Modifiers { group, .. } = event {
+                state.group = Some(group);
+                return;
+            }
+            if let wl_keyboard::Event::Keymap { format, fd, size } = event {
+                if format != WEnum::Value(wl_keyboard::KeymapFormat::XkbV1) {
+                    state.keymap = Some(Err("Compositor keymap is not in XKB format".to_string()));
+                    return;
+                }
+                // Read from offset 0: the file description may be shared with other clients
+                let file = std::fs::File::from(fd);
+                let mut buf = vec![0u8; size as usize];
+                state.keymap = Some(
+                    file.read_exact_at(&mut buf, 0)
+                        .map_err(|e| format!("Failed to read keymap: {}", e))
+                        .map(|()| {
+                            let text = String::from_utf8_lossy(&buf);
+                            text.trim_end_matches('\0').to_string()
+                        }),
+                );
+            }
+        }
+    }
+}
+
+mod xkbcommon {
+    use super::{Keymap, EVDEV_OFFSET};
+    use std::ffi::{CStr, CString};
+    use xkbcommon_dl::{
+        xkb_context, xkb_context_flags, xkb_keymap, xkb_keymap_compile_flags, xkb_keymap_format,
+        xkbcommon_option, XkbCommon,
+    };
+
+    /// Compiles a keymap in the XKB text format
+    pub fn from_text(text: &str) -> Result<Keymap, String> {
+        let text = CString::new(text).map_err(|e| format!("Invalid keymap text: {}", e))?;
+        with_keymap(
+            |xkb, context| unsafe {
+                (xkb.xkb_keymap_new_from_string)(
+                    context,
+                    text.as_ptr(),
+                    xkb_keymap_format::XKB_KEYMAP_FORMAT_TEXT_V1,
+                    xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+                )
+            },
+            collect_keys,
+        )
+    }
+
+    /// Compiles the default keymap (XKB_DEFAULT_* environment variables, else "us")
+    pub fn from_names() -> Result<Keymap, String> {
+        with_keymap(new_default_keymap, collect_keys)
+    }
+
+    /// The default keymap serialized in the XKB text format
+    pub fn default_text() -> Result<String, String> {
+        with_keymap(new_default_keymap, |xkb, keymap| unsafe {
+            let text = (xkb.xkb_keymap_get_as_string)(
+                keymap,
+                xkb_keymap_format::XKB_KEYMAP_FORMAT_TEXT_V1,
+            );
+            if text.is_null() {
+                return Err("Failed to serialize the keymap".to_string());
+            }
+            let owned = CStr::from_ptr(text).to_string_lossy().into_owned();
+            libc::free(text as *mut libc::c_void);
+            Ok(owned)
+        })?
+    }
+
+    fn new_default_keymap(xkb: &XkbCommon, context: *mut xkb_context) -> *mut xkb_keymap {
+        unsafe {
+            (xkb.xkb_keymap_new_from_names)(
+                context,
+                std::ptr::null(),
+                xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+            )
+        }
+    }
+
+    /// Compiles a keymap with `new_keymap` and hands it to `f`
+    fn with_keymap<T>(
+        new_keymap: impl FnOnce(&XkbCommon, *mut xkb_context) -> *mut xkb_keymap,
+        f: impl FnOnce(&XkbCommon, *mut xkb_keymap) -> T,
+    ) -> Result<T, String> {
+        let xkb = xkbcommon_option().ok_or("libxkbcommon is not available")?;
+
+        unsafe {
+            let context = (xkb.xkb_context_new)(xkb_context_flags::XKB_CONTEXT_NO_FLAGS);
+            if context.is_null() {
+                return Err("Failed to create an XKB context".to_string());
+            }
+            let keymap = new_keymap(xkb, context);
+            // The keymap keeps its own reference to the context
+            (xkb.xkb_context_unref)(context);
+            if keymap.is_null() {
+                return Err("Failed to compile the keymap".to_string());
+            }
+
+            let result = f(xkb, keymap);
+            (xkb.xkb_keymap_unref)(keymap);
+            Ok(result)
+        }
+    }
+
+    fn collect_keys(xkb: &XkbCommon, keymap: *mut xkb_keymap) -> Keymap {
+        unsafe {
+            let min = (xkb.xkb_keymap_min_keycode)(keymap).max(EVDEV_OFFSET);
+            let max = (xkb.xkb_keymap_max_keycode)(keymap);
+            let keys = (min..=max)
+                .filter_map(|keycode| {
+                    let evdev = u16::try_from(keycode - EVDEV_OFFSET).ok()?;
+                    let num_layouts = (xkb.xkb_keymap_num_layouts_for_key)(keymap, keycode);
+                    let layouts = (0..num_layouts)
+                        .map(|layout| {
+                            let sym = |level| {
+                                let mut syms: *const u32 = std::ptr::null();
+                                let count = (xkb.xkb_keymap_key_get_syms_by_level)(
+                                    keymap, keycode, layout, level, &mut syms,
+                                );
+                                if count > 0 {
+                                    *syms
+                                } else {
+                                    0
+                                }
+                            };
+                            [sym(0), sym(1)]
+                        })
+                        .collect();
+                    Some((evdev, layouts))
+                })
+                .collect();
+
+            Keymap::new(keys, 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// "v" on the AZERTY/QWERTY key and a Cyrillic second layout, plus a keypad digit
+    fn sample_keymap(active_layout: usize) -> Keymap {
+        Keymap::new(
+            vec![
+                (79, vec![[0xffb1, 0xff9c]]),                        // KP_1
+                (47, vec![[XK_V, 0x56], [0x6cc, 0x6ec]]),            // v V / Cyrillic em
+                (2, vec![[0x26, 0x31]]),                             // AZERTY & 1
+                (52, vec![[0x3b, 0x2e], [0x6d0, 0x6f0]]),            // ; . / Cyrillic yu
+                (17, vec![[0x7a, 0x5a], [0x6c3, 0x6e3], [XK_V, 0]]), // z Z / ... / v
+            ],
+            active_layout,
+        )
+    }
+
+    #[test]
+    fn test_locate_prefers_active_layout() {
+        let keymap = sample_keymap(0);
+        assert_eq!(
+            keymap.locate(XK_V),
+            Some(KeyPosition {
+                evdev: 47,
+                shift: false,
+                layout: 0,
+                on_active_layout: true
+            })
+        );
+        // Shifted digit on the AZERTY number row
+        assert_eq!(
+            keymap.locate(0x31).map(|p| (p.evdev, p.shift)),
+            Some((2, true))
+        );
+
+        // Third layout has "v" on another key; the active one wins
+        let keymap = sample_keymap(2);
+        assert_eq!(
+            keymap.locate(XK_V).map(|p| (p.evdev, p.layout)),
+            Some((17, 2))
+        );
+
+        // Cyrillic active: "v" is only on the Latin layout
+        let keymap = sample_keymap(1);
+        let position = keymap.locate(XK_V).unwrap();
+        assert_eq!((position.evdev, position.layout), (47, 0));
+        assert!(!position.on_active_layout);
+        // Keys with a single layout wrap back to it
+        assert_eq!(keymap.locate(0x26).map(|p| p.evdev), Some(2));
+
+        assert_eq!(keymap.locate(0x1234), None);
+    }
+
+    #[test]
+    fn test_active_layout_from_compositor() {
+        let keymap = sample_keymap(0).with_active_layout(Some(1));
+        assert_eq!(keymap.locate(0x6cc).map(|p| p.on_active_layout), Some(true));
+        // Out-of-range layouts are ignored
+        assert_eq!(
+            sample_keymap(0).with_active_layout(Some(7)).active_layout,
+            0
+        );
+
+        let sway = r#"[{"type":"pointer"},{"type":"keyboard","xkb_active_layout_index":1}]"#;
+        assert_eq!(sway_layout_index(sway), Some(1));
+        let hyprland = r#"{"keyboards":[{"main":false,"active_layout_index":0},
+            {"main":true,"active_layout_index":1}]}"#;
+        assert_eq!(hyprland_layout_index(hyprland), Some(1));
+
+        let sources = "[('xkb', 'us'), ('ibus', 'anthy'), ('xkb', 'ru')]";
+        assert_eq!(
+            gnome_layout_index(sources, "[('xkb', 'ru'), ('xkb', 'us')]"),
+            Some(1)
+        );
+        assert_eq!(gnome_layout_index(sources, "@a(ss) []"), None);
+    }
+
+    #[test]
+    fn test_keysym_for_char() {
+        assert_eq!(keysym_for_char('a'), 0x61);
+        assert_eq!(keysym_for_char('é'), 0xe9);
+        assert_eq!(keysym_for_char('€'), 0x0100_20ac);
+        assert_eq!(keysym_for_char('\n'), 0xff0d);
+    }
+}
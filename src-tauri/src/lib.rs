@@ -2,22 +2,48 @@
 //! This module re-exports the core functionality for use as a library
 
 pub mod autostart_manager;
+#[cfg(target_os = "linux")]
+pub mod caret_locator;
+pub mod cli;
+pub mod clipboard_importers;
 pub mod clipboard_manager;
+pub mod clipboard_rules;
+pub mod clipboard_snapshot;
+pub mod config_backup;
 pub mod config_manager;
 pub mod emoji_manager;
 pub mod focus_manager;
 pub mod gif_manager;
+#[cfg(target_os = "linux")]
+pub mod global_shortcuts;
+pub mod history_store;
+pub mod history_transfer;
 pub mod input_simulator;
+#[cfg(target_os = "linux")]
+pub mod keymap;
 pub mod permission_checker;
+#[cfg(target_os = "linux")]
+pub mod portal;
+pub mod privacy_policy;
+#[cfg(target_os = "linux")]
+pub mod remote_desktop;
 pub mod session;
+pub mod shortcut_chord;
 pub mod shortcut_conflict_detector;
 pub mod shortcut_setup;
+pub mod uninstall;
 pub mod user_settings;
+#[cfg(target_os = "linux")]
+pub mod virtual_keyboard;
+#[cfg(target_os = "linux")]
+pub mod wayland_focus;
+pub mod wm_bindings;
 
 #[cfg(target_os = "linux")]
 pub mod linux_shortcut_manager;
 
 pub use clipboard_manager::{ClipboardContent, ClipboardItem, ClipboardManager};
+pub use clipboard_rules::{ClipboardRule, RuleAction};
 pub use config_manager::ConfigManager;
 pub use emoji_manager::{EmojiManager, EmojiUsage};
 pub use focus_manager::{restore_focused_window, save_focused_window};
@@ -31,7 +57,8 @@ pub use permission_checker::{
 };
 pub use session::{get_session_type, is_wayland, is_x11, SessionType};
 pub use shortcut_conflict_detector::{
-    auto_resolve_conflicts, detect_shortcut_conflicts, ConflictDetectionResult, ShortcutConflict,
+    auto_resolve_conflicts, detect_conflicts_for, detect_shortcut_conflicts,
+    ConflictDetectionResult, ShortcutConflict,
 };
 pub use shortcut_setup::{
     check_shortcut_tools, detect_conflicts, get_desktop_environment, register_de_shortcut,
@@ -5,27 +5,33 @@ use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::config_backup::ConfigBackups;
+use crate::global_shortcuts::{BoundShortcut, GlobalShortcutsSession, PortalShortcut};
+use crate::shortcut_chord::{Chord, ShortcutAction, ShortcutBindings};
+use crate::user_settings::UserSettingsManager;
+use crate::wm_bindings::ConfigSyntax;
+
 // =============================================================================
 // Configuration
 // =============================================================================
 
 #[derive(Debug, Clone)]
 pub struct ShortcutConfig {
-    pub id: &'static str,
-    pub name: &'static str,
-    pub command: &'static str,
-    pub gnome_binding: &'static str,
-    pub kde_binding: &'static str,
-    pub xfce_binding: &'static str,
-    pub cosmic_mods: &'static str,
-    pub cosmic_key: &'static str,
-    // Tiling WM bindings
-    pub i3_binding: &'static str,
-    pub sway_binding: &'static str,
-    pub hyprland_binding: &'static str,
+    pub id: String,
+    pub name: String,
+    /// Command line run by the shortcut: the binary, plus a subcommand for actions
+    pub command: String,
+    /// Translated into each handler's syntax (`<Super>v`, `Meta+V`, `$mod+v`...)
+    pub chord: Chord,
+    /// Action run on the running instance; `None` toggles the history
+    pub action: Option<ShortcutAction>,
 }
 
 fn get_command_path() -> &'static str {
@@ -46,35 +52,59 @@ fn get_command_path() -> &'static str {
     "win11-clipboard-history"
 }
 
-const SHORTCUTS: &[ShortcutConfig] = &[
-    ShortcutConfig {
-        id: "win11-clipboard-history",
-        name: "Clipboard History",
-        command: "win11-clipboard-history", // Will be replaced at runtime
-        gnome_binding: "<Super>v",
-        kde_binding: "Meta+V",
-        xfce_binding: "<Super>v",
-        cosmic_mods: "Super",
-        cosmic_key: "v",
-        i3_binding: "$mod+v",
-        sway_binding: "$mod+v",
-        hyprland_binding: "SUPER, V",
-    },
-    ShortcutConfig {
-        id: "win11-clipboard-history-alt",
-        name: "Clipboard History (Alt)",
-        command: "win11-clipboard-history", // Will be replaced at runtime
-        gnome_binding: "<Ctrl><Alt>v",
-        kde_binding: "Ctrl+Alt+V",
-        xfce_binding: "<Primary><Alt>v",
-        cosmic_mods: "Ctrl, Alt",
-        cosmic_key: "v",
-        i3_binding: "Ctrl+Mod1+v",
-        sway_binding: "Ctrl+Mod1+v",
-        hyprland_binding: "CTRL ALT, V",
-    },
+/// Shortcut slots: id and display name. The first opens with `ShortcutBindings::open`,
+/// the second with `ShortcutBindings::alternate`.
+const SHORTCUT_SLOTS: [(&str, &str); 2] = [
+    ("win11-clipboard-history", "Clipboard History"),
+    ("win11-clipboard-history-alt", "Clipboard History (Alt)"),
 ];
 
+/// Builds the shortcut configs for the user's bindings; empty or invalid chords are skipped
+fn shortcut_configs(bindings: &ShortcutBindings) -> Vec<ShortcutConfig> {
+    let command = get_command_path();
+    let slots = SHORTCUT_SLOTS
+        .iter()
+        .zip([&bindings.open, &bindings.alternate])
+        .map(|(&(id, name), binding)| {
+            (
+                id.to_string(),
+                name.to_string(),
+                command.to_string(),
+                binding,
+                None,
+            )
+        });
+    let actions = bindings.actions.iter().map(|binding| {
+        (
+            format!("{}-{}", SHORTCUT_SLOTS[0].0, binding.action.slug()),
+            binding.action.display_name(),
+            format!("{} {}", command, binding.action.cli_args().join(" ")),
+            &binding.chord,
+            Some(binding.action.clone()),
+        )
+    });
+
+    slots
+        .chain(actions)
+        .filter(|(_, _, _, binding, _)| !binding.trim().is_empty())
+        .filter_map(
+            |(id, name, command, binding, action)| match Chord::parse(binding) {
+                Ok(chord) => Some(ShortcutConfig {
+                    id,
+                    name,
+                    command,
+                    chord,
+                    action,
+                }),
+                Err(e) => {
+                    eprintln!("[ShortcutManager] Skipping '{}': {}", name, e);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
 // =============================================================================
 // Error Handling
 // =============================================================================
@@ -112,43 +142,360 @@ impl std::error::Error for ShortcutError {}
 
 type Result<T> = std::result::Result<T, ShortcutError>;
 
+/// What registering a shortcut did
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "status", content = "detail", rename_all = "snake_case")]
+pub enum RegistrationOutcome {
+    /// Written and active
+    Registered,
+    /// The desktop already had the binding
+    AlreadyPresent,
+    /// Written, but only active once the desktop reloads it; holds how to do that
+    NeedsReload(String),
+    #[serde(serialize_with = "serialize_error")]
+    Failed(ShortcutError),
+}
+
+fn serialize_error<S: serde::Serializer>(
+    error: &ShortcutError,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(error)
+}
+
+impl std::fmt::Display for RegistrationOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Registered => write!(f, "registered"),
+            Self::AlreadyPresent => write!(f, "already present"),
+            Self::NeedsReload(hint) => write!(f, "needs reload ({})", hint),
+            Self::Failed(e) => write!(f, "failed: {}", e),
+        }
+    }
+}
+
+/// State of one shortcut, as reported to the Setup Wizard
+#[derive(Debug, serde::Serialize)]
+pub struct ShortcutStatus {
+    pub name: String,
+    pub chord: String,
+    /// What registering did in this run; `None` when only verifying
+    pub outcome: Option<RegistrationOutcome>,
+    /// Whether the binding was read back from the desktop's configuration;
+    /// `None` if the configuration couldn't be read
+    pub verified: Option<bool>,
+}
+
+fn shortcut_status(
+    handler: &dyn ShortcutHandler,
+    config: &ShortcutConfig,
+    outcome: Option<RegistrationOutcome>,
+) -> ShortcutStatus {
+    let verified = match handler.verify(config) {
+        Ok(verified) => Some(verified),
+        Err(e) => {
+            eprintln!(
+                "[ShortcutManager] Could not verify '{}': {}",
+                config.name, e
+            );
+            None
+        }
+    };
+    ShortcutStatus {
+        name: config.name.clone(),
+        chord: config.chord.to_string(),
+        outcome,
+        verified,
+    }
+}
+
+/// Outcome for configs the desktop reloads by itself once written
+fn written_outcome(modified: bool) -> RegistrationOutcome {
+    if modified {
+        RegistrationOutcome::Registered
+    } else {
+        RegistrationOutcome::AlreadyPresent
+    }
+}
+
+/// `Registered` if the desktop reloaded its configuration, else `NeedsReload` with `hint`
+fn reload_outcome(reload: Result<String>, hint: &str) -> RegistrationOutcome {
+    match reload {
+        Ok(_) => RegistrationOutcome::Registered,
+        Err(e) => {
+            eprintln!("[ShortcutManager] Reload failed: {}", e);
+            RegistrationOutcome::NeedsReload(hint.to_string())
+        }
+    }
+}
+
+// =============================================================================
+// GlobalShortcuts Portal
+// =============================================================================
+
+/// Runs the action of a shortcut pressed through the portal
+pub type ShortcutCallback = Arc<dyn Fn(Option<&ShortcutAction>) + Send + Sync>;
+
+/// Shortcuts bound through the GlobalShortcuts portal
+struct PortalBinding {
+    session: GlobalShortcutsSession,
+    configs: Vec<ShortcutConfig>,
+    bound: Vec<BoundShortcut>,
+    on_activated: ShortcutCallback,
+}
+
+/// Set while the portal holds the shortcuts; the desktop-specific handlers are skipped then
+static PORTAL: Lazy<Mutex<Option<PortalBinding>>> = Lazy::new(|| Mutex::new(None));
+
+/// Binds the shortcuts from the user settings through the GlobalShortcuts portal and runs
+/// `on_activated` in-process for each press. Returns false when the portal is missing or
+/// binding failed; the desktop-specific handlers should be used then.
+pub fn bind_portal_shortcuts(on_activated: ShortcutCallback) -> bool {
+    match start_portal_binding(on_activated) {
+        Ok(()) => true,
+        Err(e) => {
+            println!("[ShortcutManager] GlobalShortcuts portal not used: {}", e);
+            false
+        }
+    }
+}
+
+fn start_portal_binding(on_activated: ShortcutCallback) -> std::result::Result<(), String> {
+    let conn = zbus::blocking::Connection::session().map_err(|e| e.to_string())?;
+    if !crate::global_shortcuts::available(&conn) {
+        return Err("portal not available".to_string());
+    }
+
+    let configs = shortcut_configs(&UserSettingsManager::new().load().shortcuts);
+    let session = GlobalShortcutsSession::create(&conn)?;
+    let activations = session.activations()?;
+    let shortcuts: Vec<PortalShortcut> = configs
+        .iter()
+        .map(|config| PortalShortcut {
+            id: config.id.clone(),
+            description: config.name.clone(),
+            trigger: config.chord.portal(),
+        })
+        .collect();
+    let bound = session.bind(&shortcuts)?;
+    println!(
+        "[ShortcutManager] \u{2713} Bound {} shortcut(s) through the GlobalShortcuts portal",
+        bound.len()
+    );
+
+    // Bindings in the desktop config are kept: they start the app when it isn't running,
+    // and while it runs the press they forward is dropped as a repeat of the portal's
+    let path = session.path().to_string();
+    *PORTAL.lock() = Some(PortalBinding {
+        session,
+        configs,
+        bound,
+        on_activated,
+    });
+
+    std::thread::spawn(move || {
+        for id in activations {
+            // A rebind replaces the session; this listener then stops
+            let (on_activated, action) = {
+                let portal = PORTAL.lock();
+                let Some(binding) = portal.as_ref().filter(|b| b.session.path() == path) else {
+                    break;
+                };
+                let Some(config) = binding.configs.iter().find(|c| c.id == id) else {
+                    continue;
+                };
+                (binding.on_activated.clone(), config.action.clone())
+            };
+            on_activated(action.as_ref());
+        }
+    });
+    Ok(())
+}
+
+/// Statuses of the shortcuts, if the portal holds them. The chord is the trigger the
+/// user accepted in the portal dialog; registering reports them as already present.
+fn portal_statuses(registering: bool) -> Option<Vec<ShortcutStatus>> {
+    let portal = PORTAL.lock();
+    let binding = portal.as_ref()?;
+    Some(
+        binding
+            .configs
+            .iter()
+            .map(|config| {
+                let bound = binding.bound.iter().find(|b| b.id == config.id);
+                ShortcutStatus {
+                    name: config.name.clone(),
+                    chord: bound
+                        .map(|b| b.trigger.clone())
+                        .filter(|trigger| !trigger.is_empty())
+                        .unwrap_or_else(|| config.chord.to_string()),
+                    outcome: registering.then_some(RegistrationOutcome::AlreadyPresent),
+                    verified: Some(bound.is_some()),
+                }
+            })
+            .collect(),
+    )
+}
+
 // =============================================================================
 // Public API
 // =============================================================================
 
-pub fn register_global_shortcut() {
+/// Registers the shortcuts from the user settings with the desktop environment,
+/// then reads each one back
+pub fn register_global_shortcut() -> Vec<ShortcutStatus> {
+    if let Some(statuses) = portal_statuses(true) {
+        return statuses;
+    }
     let handler = detect_handler();
     println!("[ShortcutManager] Detected Environment: {}", handler.name());
+    println!(
+        "[ShortcutManager] Using command path: {}",
+        get_command_path()
+    );
+
+    let bindings = UserSettingsManager::new().load().shortcuts;
+    shortcut_configs(&bindings)
+        .iter()
+        .map(|config| {
+            let outcome = handler
+                .register(config)
+                .unwrap_or_else(RegistrationOutcome::Failed);
+            match outcome {
+                RegistrationOutcome::Failed(ref e) => {
+                    eprintln!("[ShortcutManager] \u{2717} Failed '{}': {}", config.name, e)
+                }
+                ref outcome => println!(
+                    "[ShortcutManager] \u{2713} '{}' ({}): {}",
+                    config.name, config.chord, outcome
+                ),
+            }
+            shortcut_status(handler.as_ref(), config, Some(outcome))
+        })
+        .collect()
+}
 
-    let command_path = get_command_path();
-    println!("[ShortcutManager] Using command path: {}", command_path);
+/// Reads the shortcuts from the user settings back from the desktop environment
+pub fn verify_global_shortcut() -> Vec<ShortcutStatus> {
+    if let Some(statuses) = portal_statuses(false) {
+        return statuses;
+    }
+    let handler = detect_handler();
+    let bindings = UserSettingsManager::new().load().shortcuts;
+    shortcut_configs(&bindings)
+        .iter()
+        .map(|config| shortcut_status(handler.as_ref(), config, None))
+        .collect()
+}
 
-    for shortcut in SHORTCUTS {
-        // Create a new config with the correct command path
-        let mut config = shortcut.clone();
-        config.command = command_path;
+pub fn unregister_global_shortcut() {
+    let handler = detect_handler();
+    println!("[ShortcutManager] Environment: {}", handler.name());
 
-        match handler.register(&config) {
-            Ok(_) => println!("[ShortcutManager] \u{2713} Registered '{}'", config.name),
+    let bindings = UserSettingsManager::new().load().shortcuts;
+    for config in shortcut_configs(&bindings) {
+        match handler.unregister(&config) {
+            Ok(_) => println!("[ShortcutManager] \u{2713} Unregistered '{}'", config.name),
             Err(e) => eprintln!("[ShortcutManager] \u{2717} Failed '{}': {}", config.name, e),
         }
     }
 }
 
-pub fn unregister_global_shortcut() {
+/// Removes the shortcuts from every handler still holding them, whichever desktop
+/// is running now. Default chords are checked too, in case they were changed since.
+/// Returns a line per removed shortcut, or an error per failed one.
+pub fn unregister_from_all_handlers() -> Vec<std::result::Result<String, String>> {
+    let mut configs = shortcut_configs(&UserSettingsManager::new().load().shortcuts);
+    for config in shortcut_configs(&ShortcutBindings::default()) {
+        if !configs
+            .iter()
+            .any(|c| c.id == config.id && c.chord == config.chord)
+        {
+            configs.push(config);
+        }
+    }
+
+    let mut report = Vec::new();
+    for handler in all_handlers() {
+        for config in &configs {
+            // Handlers whose tools or config files are missing can't hold the shortcut
+            if !matches!(handler.verify(config), Ok(true)) {
+                continue;
+            }
+            let what = format!(
+                "'{}' shortcut ({}) from {}",
+                config.name,
+                config.chord,
+                handler.name()
+            );
+            report.push(
+                handler
+                    .unregister(config)
+                    .map(|_| what.clone())
+                    .map_err(|e| format!("{}: {}", what, e)),
+            );
+        }
+    }
+    report
+}
+
+/// Moves the shortcuts to new key combos: each changed or removed shortcut is
+/// unregistered with its old chord, then changed or added ones are registered
+pub fn rebind_global_shortcut(old: &ShortcutBindings, new: &ShortcutBindings) {
+    // The portal binds the whole set again, in a new session. The old one is closed
+    // first, or both would hold the triggers.
+    let previous = PORTAL.lock().take();
+    let portal_bound = previous.is_some_and(|previous| {
+        let on_activated = previous.on_activated.clone();
+        drop(previous);
+        bind_portal_shortcuts(on_activated)
+    });
+
     let handler = detect_handler();
-    println!("[ShortcutManager] Environment: {}", handler.name());
+    let old_configs = shortcut_configs(old);
+    let new_configs = shortcut_configs(new);
 
-    let command_path = get_command_path();
+    let mut ids: Vec<&str> = old_configs.iter().map(|c| c.id.as_str()).collect();
+    for config in &new_configs {
+        if !ids.contains(&config.id.as_str()) {
+            ids.push(&config.id);
+        }
+    }
 
-    for shortcut in SHORTCUTS {
-        // Create a new config with the correct command path
-        let mut config = shortcut.clone();
-        config.command = command_path;
+    for id in ids {
+        let old_config = old_configs.iter().find(|c| c.id == id);
+        let new_config = new_configs.iter().find(|c| c.id == id);
+        let key = |c: &ShortcutConfig| (c.chord.clone(), c.command.clone());
+        if old_config.map(key) == new_config.map(key) {
+            continue;
+        }
+        let name = &old_config.or(new_config).map_or("", |c| c.name.as_str());
 
-        match handler.unregister(&config) {
-            Ok(_) => println!("[ShortcutManager] \u{2713} Unregistered '{}'", config.name),
-            Err(e) => eprintln!("[ShortcutManager] \u{2717} Failed '{}': {}", config.name, e),
+        // With the portal bound, only bindings kept in the desktop config are moved
+        if portal_bound && !old_config.is_some_and(|c| matches!(handler.verify(c), Ok(true))) {
+            continue;
+        }
+
+        if let Some(config) = old_config {
+            match handler.unregister(config) {
+                Ok(_) => println!(
+                    "[ShortcutManager] \u{2713} Unregistered '{}' ({})",
+                    name, config.chord
+                ),
+                Err(e) => eprintln!(
+                    "[ShortcutManager] \u{2717} Failed to unregister '{}': {}",
+                    name, e
+                ),
+            }
+        }
+        if let Some(config) = new_config {
+            match handler.register(config) {
+                Ok(outcome) => println!(
+                    "[ShortcutManager] \u{2713} '{}' ({}): {}",
+                    name, config.chord, outcome
+                ),
+                Err(e) => eprintln!("[ShortcutManager] \u{2717} Failed '{}': {}", name, e),
+            }
         }
     }
 }
@@ -159,78 +506,230 @@ pub fn unregister_global_shortcut() {
 
 trait ShortcutHandler {
     fn name(&self) -> &str;
-    fn register(&self, shortcut: &ShortcutConfig) -> Result<()>;
+    fn register(&self, shortcut: &ShortcutConfig) -> Result<RegistrationOutcome>;
     fn unregister(&self, shortcut: &ShortcutConfig) -> Result<()>;
+    /// Whether the desktop's configuration has the binding, read back from it
+    fn verify(&self, shortcut: &ShortcutConfig) -> Result<bool>;
 }
 
-fn detect_handler() -> Box<dyn ShortcutHandler> {
-    let xdg_current = env_var("XDG_CURRENT_DESKTOP").to_lowercase();
-    let xdg_session = env_var("XDG_SESSION_DESKTOP").to_lowercase();
-    let combined = format!("{} {}", xdg_current, xdg_session);
+/// Desktop environment or window manager the session runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DesktopEnvironment {
+    Gnome,
+    /// Pop!_OS's GNOME session, with Pop Shell's tiling bindings
+    PopOs,
+    Cinnamon,
+    Kde,
+    Xfce,
+    Mate,
+    Cosmic,
+    Lxqt,
+    Lxde,
+    Budgie,
+    Deepin,
+    I3,
+    Sway,
+    Hyprland,
+    Niri,
+    River,
+    Labwc,
+    Wayfire,
+    Openbox,
+    Bspwm,
+    Awesome,
+    Qtile,
+}
+
+impl DesktopEnvironment {
+    /// Name shown to the user and reported by the setup commands
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Gnome => "GNOME",
+            Self::PopOs => "Pop!_OS",
+            Self::Cinnamon => "Cinnamon",
+            Self::Kde => "KDE Plasma",
+            Self::Xfce => "XFCE",
+            Self::Mate => "MATE",
+            Self::Cosmic => "COSMIC",
+            Self::Lxqt => "LXQt",
+            Self::Lxde => "LXDE",
+            Self::Budgie => "Budgie",
+            Self::Deepin => "Deepin",
+            Self::I3 => "i3",
+            Self::Sway => "Sway",
+            Self::Hyprland => "Hyprland",
+            Self::Niri => "niri",
+            Self::River => "River",
+            Self::Labwc => "labwc",
+            Self::Wayfire => "Wayfire",
+            Self::Openbox => "Openbox",
+            Self::Bspwm => "bspwm",
+            Self::Awesome => "awesome",
+            Self::Qtile => "Qtile",
+        }
+    }
+}
+
+/// Window managers found by their XDG name or process name
+const STANDALONE_WMS: [(&str, DesktopEnvironment); 8] = [
+    ("niri", DesktopEnvironment::Niri),
+    ("river", DesktopEnvironment::River),
+    ("labwc", DesktopEnvironment::Labwc),
+    ("wayfire", DesktopEnvironment::Wayfire),
+    ("bspwm", DesktopEnvironment::Bspwm),
+    ("awesome", DesktopEnvironment::Awesome),
+    ("qtile", DesktopEnvironment::Qtile),
+    // Last: LXDE sessions run openbox too
+    ("openbox", DesktopEnvironment::Openbox),
+];
+
+/// Detects the desktop from the XDG variables, then from running processes and installed tools
+pub fn detect_desktop() -> DesktopEnvironment {
+    let xdg_current = env_var("XDG_CURRENT_DESKTOP");
+    let xdg_session = env_var("XDG_SESSION_DESKTOP");
+    if let Some(desktop) = desktop_from_xdg(&format!("{} {}", xdg_current, xdg_session)) {
+        return desktop;
+    }
 
+    // Heuristic Fallback - check running processes for tiling WMs
+    if is_process_running("i3") {
+        return DesktopEnvironment::I3;
+    }
+    if is_process_running("sway") {
+        return DesktopEnvironment::Sway;
+    }
+    if is_process_running("hyprland") || is_process_running("Hyprland") {
+        return DesktopEnvironment::Hyprland;
+    }
+    // Before openbox, which LXDE runs
+    if is_process_running("lxsession") {
+        return DesktopEnvironment::Lxde;
+    }
+    for (name, desktop) in STANDALONE_WMS {
+        if is_process_running(name) {
+            return desktop;
+        }
+    }
+
+    // Heuristic Fallback for traditional DEs
+    if Utils::command_exists("kwriteconfig5") || Utils::command_exists("kwriteconfig6") {
+        return DesktopEnvironment::Kde;
+    }
+    if Utils::command_exists("xfconf-query") {
+        return DesktopEnvironment::Xfce;
+    }
+
+    // Default fallback
+    DesktopEnvironment::Gnome
+}
+
+/// Desktop named in the XDG_CURRENT_DESKTOP / XDG_SESSION_DESKTOP values
+fn desktop_from_xdg(xdg: &str) -> Option<DesktopEnvironment> {
+    let combined = xdg.to_lowercase();
+
+    // GNOME-based sessions report e.g. "pop:GNOME" and "Budgie:GNOME", so they go first
+    if combined.contains("pop") {
+        return Some(DesktopEnvironment::PopOs);
+    }
+    if combined.contains("budgie") {
+        return Some(DesktopEnvironment::Budgie);
+    }
     if combined.contains("gnome") || combined.contains("unity") || combined.contains("pantheon") {
-        return Box::new(GnomeHandler);
+        return Some(DesktopEnvironment::Gnome);
     }
     if combined.contains("cinnamon") {
-        return Box::new(CinnamonHandler);
+        return Some(DesktopEnvironment::Cinnamon);
     }
     // KDE Plasma 5 or 6
     if combined.contains("kde") || combined.contains("plasma") {
-        return Box::new(KdeHandler);
+        return Some(DesktopEnvironment::Kde);
     }
     if combined.contains("xfce") {
-        return Box::new(XfceHandler);
+        return Some(DesktopEnvironment::Xfce);
     }
     if combined.contains("mate") {
-        return Box::new(MateHandler);
+        return Some(DesktopEnvironment::Mate);
     }
     if combined.contains("cosmic") {
-        return Box::new(CosmicHandler);
+        return Some(DesktopEnvironment::Cosmic);
     }
     if combined.contains("lxqt") {
-        return Box::new(LxqtHandler);
+        return Some(DesktopEnvironment::Lxqt);
     }
     if combined.contains("lxde") {
-        return Box::new(LxdeHandler);
+        return Some(DesktopEnvironment::Lxde);
     }
-    if combined.contains("budgie") {
-        return Box::new(GnomeHandler); // Budgie uses gsettings like GNOME
-    }
-    if combined.contains("deepin") {
-        return Box::new(GnomeHandler); // Deepin uses gsettings like GNOME
+    if combined.contains("deepin") || combined.contains("dde") {
+        return Some(DesktopEnvironment::Deepin);
     }
     // Tiling Window Managers
     if combined.contains("i3") {
-        return Box::new(I3Handler);
+        return Some(DesktopEnvironment::I3);
     }
     if combined.contains("sway") {
-        return Box::new(SwayHandler);
+        return Some(DesktopEnvironment::Sway);
     }
     if combined.contains("hyprland") {
-        return Box::new(HyprlandHandler);
-    }
-
-    // Heuristic Fallback - check running processes for tiling WMs
-    if is_process_running("i3") {
-        return Box::new(I3Handler);
-    }
-    if is_process_running("sway") {
-        return Box::new(SwayHandler);
+        return Some(DesktopEnvironment::Hyprland);
     }
-    if is_process_running("hyprland") || is_process_running("Hyprland") {
-        return Box::new(HyprlandHandler);
+    // Standalone window managers and compositors
+    for (name, desktop) in STANDALONE_WMS {
+        if combined.contains(name) {
+            return Some(desktop);
+        }
     }
+    None
+}
 
-    // Heuristic Fallback for traditional DEs
-    if Utils::command_exists("kwriteconfig5") || Utils::command_exists("kwriteconfig6") {
-        return Box::new(KdeHandler);
-    }
-    if Utils::command_exists("xfconf-query") {
-        return Box::new(XfceHandler);
+fn detect_handler() -> Box<dyn ShortcutHandler> {
+    match detect_desktop() {
+        DesktopEnvironment::Gnome | DesktopEnvironment::PopOs => Box::new(GnomeHandler),
+        DesktopEnvironment::Cinnamon => Box::new(CinnamonHandler),
+        DesktopEnvironment::Kde => Box::new(KdeHandler),
+        DesktopEnvironment::Xfce => Box::new(XfceHandler),
+        DesktopEnvironment::Mate => Box::new(MateHandler),
+        DesktopEnvironment::Cosmic => Box::new(CosmicHandler),
+        DesktopEnvironment::Lxqt => Box::new(LxqtHandler),
+        DesktopEnvironment::Lxde => Box::new(OpenboxHandler::LXDE),
+        // Budgie and Deepin use gsettings like GNOME
+        DesktopEnvironment::Budgie | DesktopEnvironment::Deepin => Box::new(GnomeHandler),
+        DesktopEnvironment::I3 => Box::new(I3Handler),
+        DesktopEnvironment::Sway => Box::new(SwayHandler),
+        DesktopEnvironment::Hyprland => Box::new(HyprlandHandler),
+        DesktopEnvironment::Niri => Box::new(NiriHandler),
+        DesktopEnvironment::River => Box::new(RiverHandler),
+        DesktopEnvironment::Labwc => Box::new(OpenboxHandler::LABWC),
+        DesktopEnvironment::Wayfire => Box::new(WayfireHandler),
+        DesktopEnvironment::Openbox => Box::new(OpenboxHandler::OPENBOX),
+        DesktopEnvironment::Bspwm => Box::new(BspwmHandler),
+        DesktopEnvironment::Awesome => Box::new(AwesomeHandler),
+        DesktopEnvironment::Qtile => Box::new(QtileHandler),
     }
+}
 
-    // Default fallback
-    Box::new(GnomeHandler)
+/// Every handler a shortcut can be registered with
+fn all_handlers() -> Vec<Box<dyn ShortcutHandler>> {
+    vec![
+        Box::new(GnomeHandler),
+        Box::new(CinnamonHandler),
+        Box::new(KdeHandler),
+        Box::new(XfceHandler),
+        Box::new(MateHandler),
+        Box::new(CosmicHandler),
+        Box::new(LxqtHandler),
+        Box::new(OpenboxHandler::LXDE),
+        Box::new(I3Handler),
+        Box::new(SwayHandler),
+        Box::new(HyprlandHandler),
+        Box::new(NiriHandler),
+        Box::new(RiverHandler),
+        Box::new(OpenboxHandler::LABWC),
+        Box::new(WayfireHandler),
+        Box::new(OpenboxHandler::OPENBOX),
+        Box::new(BspwmHandler),
+        Box::new(AwesomeHandler),
+        Box::new(QtileHandler),
+    ]
 }
 
 fn is_process_running(name: &str) -> bool {
@@ -246,30 +745,258 @@ fn env_var(key: &str) -> String {
     env::var(key).unwrap_or_default()
 }
 
-/// Check if a line contains a $mod+v or mod4+v binding with proper word boundaries.
-/// This ensures we match "bindsym $mod+v" even at end of line or followed by comments.
-fn has_mod_v_binding(trimmed_line: &str) -> bool {
-    for pattern in &["$mod+v", "mod4+v"] {
-        if let Some(idx) = trimmed_line.find(pattern) {
-            // Check what follows the pattern
-            let after = trimmed_line[idx + pattern.len()..].chars().next();
-            // Valid word boundaries: end of string, space, tab, comment, semicolon
-            if matches!(after, None | Some(' ') | Some('\t') | Some('#') | Some(';')) {
-                return true;
+/// How bindings are added to a line-based window manager config
+struct ConfigFormat {
+    syntax: ConfigSyntax,
+    /// Line comment prefix
+    comment: &'static str,
+    /// Opening and closing lines of the block bindings go in (niri's `binds {`, Wayfire's
+    /// `[command]`); `None` appends them to the end of the file
+    block: Option<(&'static str, &'static str)>,
+    /// Whether other bindings of the chord are commented out. Off for configs written in a
+    /// programming language, where a binding can span several lines.
+    comment_conflicts: bool,
+}
+
+impl ConfigFormat {
+    const fn appended(syntax: ConfigSyntax, comment: &'static str) -> Self {
+        Self {
+            syntax,
+            comment,
+            block: None,
+            comment_conflicts: true,
+        }
+    }
+
+    fn binds(&self, line: &str, chord: &Chord) -> bool {
+        self.syntax.parse_line(line).as_ref() == Some(chord)
+    }
+
+    /// Whether `line` belongs to the binding above it (sxhkd's indented commands)
+    fn continues(&self, line: &str) -> bool {
+        self.syntax == ConfigSyntax::Sxhkd
+            && line.starts_with(char::is_whitespace)
+            && !line.trim().is_empty()
+    }
+
+    /// Marker placed above the binding lines we add
+    fn marker(&self, s: &ShortcutConfig) -> String {
+        format!(
+            "{} {} (added by win11-clipboard-history)",
+            self.comment, s.name
+        )
+    }
+
+    /// Suffix of conflicting lines we commented out, so they can be restored
+    fn commented_suffix(&self) -> String {
+        format!(" {} Commented by win11-clipboard-history", self.comment)
+    }
+}
+
+const I3_FORMAT: ConfigFormat = ConfigFormat::appended(ConfigSyntax::I3, "#");
+const HYPRLAND_FORMAT: ConfigFormat = ConfigFormat::appended(ConfigSyntax::Hyprland, "#");
+
+/// Appends `binding` (one or more lines) under our marker and comments out other
+/// bindings of the same chord. Returns `None` if the binding is already there.
+fn add_config_binding(
+    content: &str,
+    s: &ShortcutConfig,
+    binding: &str,
+    format: &ConfigFormat,
+    tag: &str,
+) -> Option<String> {
+    if has_config_binding(content, binding) {
+        return None;
+    }
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+
+    let mut had_existing = false;
+    if format.comment_conflicts {
+        let suffix = format.commented_suffix();
+        let mut in_conflict = false;
+        for line in lines.iter_mut() {
+            if line.trim().starts_with(format.comment) {
+                in_conflict = false;
+                continue;
+            }
+            if format.binds(line, &s.chord) {
+                in_conflict = true;
+                had_existing = true;
+            } else if !(in_conflict && format.continues(line)) {
+                in_conflict = false;
+                continue;
+            }
+            *line = format!("{} {}{}", format.comment, line, suffix);
+        }
+    }
+
+    // The marker gets the binding's indentation
+    let indent: String = binding.chars().take_while(|c| c.is_whitespace()).collect();
+    let mut added = vec![format!("{}{}", indent, format.marker(s))];
+    added.extend(binding.lines().map(String::from));
+
+    match format.block {
+        Some((open, close)) => match lines.iter().position(|l| l.trim() == open) {
+            Some(pos) => {
+                lines.splice(pos + 1..pos + 1, added);
+            }
+            None => {
+                lines.push(format!("\n{}", open));
+                lines.extend(added);
+                if !close.is_empty() {
+                    lines.push(close.to_string());
+                }
+            }
+        },
+        None => {
+            added[0] = format!("\n{}", added[0]);
+            lines.extend(added);
+        }
+    }
+
+    if had_existing {
+        println!("[{}] Commented out existing {} binding(s)", tag, s.chord);
+    }
+    Some(lines.join("\n"))
+}
+
+/// Whether all lines of `binding` appear in order, uncommented
+fn has_config_binding(content: &str, binding: &str) -> bool {
+    let entry: Vec<&str> = binding.lines().map(str::trim).collect();
+    let lines: Vec<&str> = content.lines().map(str::trim).collect();
+    lines.windows(entry.len()).any(|window| window == entry)
+}
+
+/// Removes our marker and `binding` below it, and restores the lines commented out for
+/// its chord. Returns `None` if the shortcut isn't there.
+fn remove_config_binding(
+    content: &str,
+    s: &ShortcutConfig,
+    binding: &str,
+    format: &ConfigFormat,
+) -> Option<String> {
+    let marker = format.marker(s);
+    let lines: Vec<&str> = content.lines().collect();
+    if !lines.iter().any(|line| line.trim() == marker) {
+        return None;
+    }
+
+    let entry: Vec<&str> = binding.lines().map(str::trim).collect();
+    let prefix = format!("{} ", format.comment);
+    let suffix = format.commented_suffix();
+    let mut new_lines: Vec<&str> = Vec::new();
+    let mut restoring = false;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+
+        // Skip our marker and the binding lines below it
+        if line.trim() == marker {
+            for expected in &entry {
+                if lines.get(i).is_some_and(|l| l.trim() == *expected) {
+                    i += 1;
+                }
+            }
+            continue;
+        }
+
+        let original = line
+            .strip_prefix(&prefix)
+            .and_then(|l| l.strip_suffix(&suffix));
+        match original {
+            Some(original) if format.binds(original, &s.chord) => {
+                restoring = true;
+                new_lines.push(original);
+            }
+            Some(original) if restoring && format.continues(original) => {
+                new_lines.push(original);
             }
+            _ => {
+                restoring = false;
+                new_lines.push(line);
+            }
+        }
+    }
+    Some(new_lines.join("\n"))
+}
+
+/// Adds a binding to a line-based config file; returns whether the file changed
+fn register_in_config(
+    path: &Path,
+    s: &ShortcutConfig,
+    binding: &str,
+    format: &ConfigFormat,
+    tag: &str,
+) -> Result<bool> {
+    let reason = Utils::change_reason("Register", s);
+    Utils::modify_file_atomic(path, &reason, |content| {
+        Ok(add_config_binding(&content, s, binding, format, tag))
+    })
+}
+
+/// Removes a binding added by `register_in_config`; returns whether the file changed
+fn unregister_from_config(
+    path: &Path,
+    s: &ShortcutConfig,
+    binding: &str,
+    format: &ConfigFormat,
+) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let reason = Utils::change_reason("Unregister", s);
+    Utils::modify_file_atomic(path, &reason, |content| {
+        Ok(remove_config_binding(&content, s, binding, format))
+    })
+}
+
+/// Whether the config file at `path` has `binding`
+fn config_has_binding(path: &Path, binding: &str) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    Ok(has_config_binding(&fs::read_to_string(path)?, binding))
+}
+
+/// `$XDG_CONFIG_HOME`, or `~/.config`
+fn config_home() -> Result<PathBuf> {
+    if let Ok(dir) = env::var("XDG_CONFIG_HOME") {
+        if !dir.is_empty() {
+            return Ok(PathBuf::from(dir));
         }
     }
-    false
+    let home = env::var("HOME")
+        .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
+    Ok(PathBuf::from(home).join(".config"))
+}
+
+/// Fails with a hint when a config the window manager ships defaults for is missing:
+/// creating it would replace all of the defaults.
+fn require_config(path: PathBuf, wm: &str) -> Result<PathBuf> {
+    if path.exists() {
+        Ok(path)
+    } else {
+        Err(ShortcutError::Io(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} config not found at {}", wm, path.display()),
+        )))
+    }
+}
+
+/// Quotes a command for a double-quoted Lua or Python string
+fn quoted_command(command: &str) -> String {
+    format!("\"{}\"", command.replace('\\', "\\\\").replace('"', "\\\""))
 }
 
 // =============================================================================
 // Utilities
 // =============================================================================
 
-struct Utils;
+pub(crate) struct Utils;
 
 impl Utils {
-    fn command_exists(cmd: &str) -> bool {
+    pub(crate) fn command_exists(cmd: &str) -> bool {
         Command::new("which")
             .arg(cmd)
             .output()
@@ -290,10 +1017,50 @@ impl Utils {
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     }
 
-    /// Reads a file, creates a .bak copy, modifies content via callback,
-    /// then writes back atomically using a temp file rename strategy.
+    /// Describes a change made to the desktop config, as shown in the change log
+    fn change_reason(verb: &str, s: &ShortcutConfig) -> String {
+        format!("{} '{}' shortcut ({})", verb, s.name, s.chord)
+    }
+
+    /// Sets a gsettings key after recording its previous value in the config backups
+    fn gsettings_set(schema: &str, key: &str, value: &str, reason: &str) -> Result<()> {
+        Self::gsettings_change(schema, key, &["set", schema, key, value], reason)
+    }
+
+    /// Resets a gsettings key to its default, recorded in the config backups
+    fn gsettings_reset(schema: &str, key: &str, reason: &str) -> Result<()> {
+        Self::gsettings_change(schema, key, &["reset", schema, key], reason)
+    }
+
+    fn gsettings_change(schema: &str, key: &str, args: &[&str], reason: &str) -> Result<()> {
+        let backups = ConfigBackups::new();
+        backups
+            .backup_gsettings(schema, key, reason)
+            .map_err(|e| ShortcutError::Io(io::Error::other(e)))?;
+        Self::run("gsettings", args)?;
+        backups
+            .gsettings_written(schema, key)
+            .map_err(|e| ShortcutError::Io(io::Error::other(e)))
+    }
+
+    /// Runs `xfconf-query` on a property of the channel, recorded in the config backups
+    fn xfconf_change(channel: &str, property: &str, args: &[&str], reason: &str) -> Result<()> {
+        let backups = ConfigBackups::new();
+        backups
+            .backup_xfconf(channel, property, reason)
+            .map_err(|e| ShortcutError::Io(io::Error::other(e)))?;
+        let mut full = vec!["-c", channel, "-p", property];
+        full.extend_from_slice(args);
+        Self::run("xfconf-query", &full)?;
+        backups
+            .xfconf_written(channel, property)
+            .map_err(|e| ShortcutError::Io(io::Error::other(e)))
+    }
+
+    /// Reads a file, modifies content via callback, records the previous state
+    /// in the config backups, then writes back atomically using a temp file rename strategy.
     /// Returns Ok(true) if file was modified, Ok(false) if no changes were needed.
-    fn modify_file_atomic<F>(path: &Path, modifier: F) -> Result<bool>
+    fn modify_file_atomic<F>(path: &Path, reason: &str, modifier: F) -> Result<bool>
     where
         F: FnOnce(String) -> Result<Option<String>>,
     {
@@ -305,54 +1072,6 @@ impl Utils {
         }
 
         let content = if path.exists() {
-            // Calculate timestamp for backup filename (with safe fallback)
-            let timestamp = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_else(|_| std::time::Duration::from_secs(0))
-                .as_secs();
-            let bak_extension = format!("bak.{}", timestamp);
-            let bak_path = path.with_extension(&bak_extension);
-            // Create timestamped backup to preserve history
-            fs::copy(path, &bak_path)?;
-            println!("[Utils] Created backup: {:?}", bak_path);
-
-            // Cleanup old backups - keep only the last 3
-            if let Some(parent) = path.parent() {
-                if let Some(file_stem) = path.file_stem() {
-                    let prefix = format!("{}.", file_stem.to_string_lossy());
-                    let mut backups: Vec<_> = fs::read_dir(parent)
-                        .ok()
-                        .into_iter()
-                        .flatten()
-                        .filter_map(|e| e.ok())
-                        .filter(|e| {
-                            let name = e.file_name().to_string_lossy().to_string();
-                            name.starts_with(&prefix) && name.contains(".bak.")
-                        })
-                        .collect();
-
-                    // Sort by timestamp extracted from filename (oldest first)
-                    // Filename format: name.bak.TIMESTAMP, so we parse the number after last '.'
-                    backups.sort_by_key(|e| {
-                        e.file_name()
-                            .to_string_lossy()
-                            .rsplit('.')
-                            .next()
-                            .and_then(|s| s.parse::<u64>().ok())
-                            .unwrap_or(0)
-                    });
-
-                    // Remove oldest backups, keep only 3
-                    while backups.len() > 3 {
-                        if let Some(oldest) = backups.first() {
-                            let _ = fs::remove_file(oldest.path());
-                            println!("[Utils] Removed old backup: {:?}", oldest.path());
-                        }
-                        backups.remove(0);
-                    }
-                }
-            }
-
             fs::read_to_string(path)?
         } else {
             String::new()
@@ -365,6 +1084,11 @@ impl Utils {
             Err(e) => return Err(e),
         };
 
+        let backups = ConfigBackups::new();
+        backups
+            .backup_file(path, reason)
+            .map_err(|e| ShortcutError::Io(io::Error::other(e)))?;
+
         // Atomic Write Strategy: Write to .tmp, then rename
         let tmp_path = path.with_extension(format!(
             "tmp.{}",
@@ -380,6 +1104,9 @@ impl Utils {
 
         // Atomic rename
         fs::rename(&tmp_path, path)?;
+        backups
+            .file_written(path)
+            .map_err(|e| ShortcutError::Io(io::Error::other(e)))?;
 
         Ok(true) // File was modified
     }
@@ -436,7 +1163,7 @@ impl GSettings {
             .collect())
     }
 
-    fn set_list(&self, items: &[String]) -> Result<()> {
+    fn set_list(&self, items: &[String], reason: &str) -> Result<()> {
         let formatted_list = if items.is_empty() {
             "[]".to_string()
         } else {
@@ -448,47 +1175,66 @@ impl GSettings {
                 .join(", ");
             format!("[{}]", inner)
         };
-        Utils::run(
-            "gsettings",
-            &["set", self.schema, self.list_key, &formatted_list],
-        )
-        .map(|_| ())
+        Utils::gsettings_set(self.schema, self.list_key, &formatted_list, reason)
     }
 
-    fn register(&self, shortcut: &ShortcutConfig, use_array_for_binding: bool) -> Result<()> {
+    /// Relocatable schema path of the shortcut, and its entry in the custom list
+    fn locate(&self, shortcut: &ShortcutConfig) -> (String, String) {
+        let path = format!("{}/{}/", self.path_prefix, shortcut.id);
+        let schema_path = format!("{}:{}", self.binding_schema, path);
+        let entry = if self.path_prefix.contains("cinnamon") {
+            shortcut.id.clone()
+        } else {
+            path
+        };
+        (schema_path, entry)
+    }
+
+    fn register(
+        &self,
+        shortcut: &ShortcutConfig,
+        use_array_for_binding: bool,
+    ) -> Result<RegistrationOutcome> {
         if !Utils::command_exists("gsettings") {
             return Err(ShortcutError::DependencyMissing("gsettings".into()));
         }
+        if self.verify(shortcut)? {
+            return Ok(RegistrationOutcome::AlreadyPresent);
+        }
 
-        let path = format!("{}/{}/", self.path_prefix, shortcut.id);
-        let schema_path = format!("{}:{}", self.binding_schema, path);
+        let (schema_path, entry) = self.locate(shortcut);
+        let reason = Utils::change_reason("Register", shortcut);
 
         // Idempotent setting
-        Utils::run("gsettings", &["set", &schema_path, "name", shortcut.name])?;
-        Utils::run(
-            "gsettings",
-            &["set", &schema_path, "command", shortcut.command],
-        )?;
+        Utils::gsettings_set(&schema_path, "name", &shortcut.name, &reason)?;
+        Utils::gsettings_set(&schema_path, "command", &shortcut.command, &reason)?;
 
         let binding_val = if use_array_for_binding {
-            format!("['{}']", shortcut.gnome_binding)
+            format!("['{}']", shortcut.chord.gnome())
         } else {
-            format!("'{}'", shortcut.gnome_binding)
+            format!("'{}'", shortcut.chord.gnome())
         };
-        Utils::run("gsettings", &["set", &schema_path, "binding", &binding_val])?;
+        Utils::gsettings_set(&schema_path, "binding", &binding_val, &reason)?;
 
         let mut list = self.get_list()?;
-        let entry_check = if self.path_prefix.contains("cinnamon") {
-            shortcut.id
-        } else {
-            &path
-        };
+        // Exact match: action ids share the main shortcut's id as a prefix
+        if !list.contains(&entry) {
+            list.push(entry);
+            self.set_list(&list, &reason)?;
+        }
+        // The settings daemon picks up the change right away
+        Ok(RegistrationOutcome::Registered)
+    }
 
-        if !list.iter().any(|x| x.contains(entry_check)) {
-            list.push(entry_check.to_string());
-            self.set_list(&list)?;
+    fn verify(&self, shortcut: &ShortcutConfig) -> Result<bool> {
+        let (schema_path, entry) = self.locate(shortcut);
+        if !self.get_list()?.contains(&entry) {
+            return Ok(false);
         }
-        Ok(())
+        let binding = Utils::run("gsettings", &["get", &schema_path, "binding"])?;
+        let command = Utils::run("gsettings", &["get", &schema_path, "command"])?;
+        Ok(binding.contains(&format!("'{}'", shortcut.chord.gnome()))
+            && command.trim_matches(['\'', '"']) == shortcut.command)
     }
 
     fn unregister(&self, shortcut: &ShortcutConfig) -> Result<()> {
@@ -496,25 +1242,19 @@ impl GSettings {
             return Ok(());
         }
 
-        let path = format!("{}/{}/", self.path_prefix, shortcut.id);
-        let schema_path = format!("{}:{}", self.binding_schema, path);
+        let (schema_path, entry) = self.locate(shortcut);
+        let reason = Utils::change_reason("Unregister", shortcut);
 
-        let _ = Utils::run("gsettings", &["reset", &schema_path, "name"]);
-        let _ = Utils::run("gsettings", &["reset", &schema_path, "command"]);
-        let _ = Utils::run("gsettings", &["reset", &schema_path, "binding"]);
+        for key in ["name", "command", "binding"] {
+            let _ = Utils::gsettings_reset(&schema_path, key, &reason);
+        }
 
         let mut list = self.get_list()?;
         let initial_len = list.len();
-        let entry_check = if self.path_prefix.contains("cinnamon") {
-            shortcut.id
-        } else {
-            &path
-        };
-
-        list.retain(|x| !x.contains(entry_check));
+        list.retain(|x| *x != entry);
 
         if list.len() != initial_len {
-            self.set_list(&list)?;
+            self.set_list(&list, &reason)?;
         }
         Ok(())
     }
@@ -526,12 +1266,15 @@ impl ShortcutHandler for GnomeHandler {
     fn name(&self) -> &str {
         "GNOME/Unity"
     }
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
         GSettings::new_gnome().register(s, false)
     }
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
         GSettings::new_gnome().unregister(s)
     }
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        GSettings::new_gnome().verify(s)
+    }
 }
 
 struct CinnamonHandler;
@@ -539,12 +1282,15 @@ impl ShortcutHandler for CinnamonHandler {
     fn name(&self) -> &str {
         "Cinnamon"
     }
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
         GSettings::new_cinnamon().register(s, true)
     }
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
         GSettings::new_cinnamon().unregister(s)
     }
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        GSettings::new_cinnamon().verify(s)
+    }
 }
 
 // --- KDE Plasma Logic ---
@@ -557,16 +1303,20 @@ impl KdeHandler {
         Ok(PathBuf::from(home).join(".config/khotkeysrc"))
     }
 
-    fn reload_kde() {
+    fn section_name(s: &ShortcutConfig) -> String {
+        format!("Data_{}", s.id.replace('-', "_"))
+    }
+
+    fn reload_kde() -> Result<String> {
         // Try both Plasma 5 and modern methods
-        let _ = Utils::run(
+        Utils::run(
             "qdbus",
             &[
                 "org.kde.kglobalaccel",
                 "/kglobalaccel",
                 "org.kde.KGlobalAccel.reloadConfig",
             ],
-        );
+        )
     }
 }
 
@@ -575,11 +1325,12 @@ impl ShortcutHandler for KdeHandler {
         "KDE Plasma"
     }
 
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
         let path = Self::get_config_path()?;
-        let section_name = format!("Data_{}", s.id.replace('-', "_"));
+        let section_name = Self::section_name(s);
 
-        Utils::modify_file_atomic(&path, |content| {
+        let reason = Utils::change_reason("Register", s);
+        let modified = Utils::modify_file_atomic(&path, &reason, |content| {
             if content.contains(&format!("[{}]", section_name)) {
                 return Ok(None); // Already exists
             }
@@ -622,22 +1373,32 @@ impl ShortcutHandler for KdeHandler {
 
             let entry = format!(
                 "\n[{0}]\nComment={1}\nEnabled=true\nName={1}\nType=SIMPLE_ACTION_DATA\n\n[{0}/Actions]\nActionsCount=1\n\n[{0}/Actions/Action0]\nCommandURL={2}\nType=COMMAND_URL\n\n[{0}/Conditions]\nComment=\nConditionsCount=0\n\n[{0}/Triggers]\nTriggersCount=1\n\n[{0}/Triggers/Trigger0]\nKey={3}\nType=SHORTCUT\nUuid={{{4}}}\n",
-                section_name, s.name, s.command, s.kde_binding, uuid
+                section_name,
+                s.name,
+                s.command,
+                s.chord.kde(),
+                uuid
             );
 
             lines.push(entry);
             Ok(Some(lines.join("\n")))
         })?;
 
-        Self::reload_kde();
-        Ok(())
+        if !modified {
+            return Ok(RegistrationOutcome::AlreadyPresent);
+        }
+        Ok(reload_outcome(
+            Self::reload_kde(),
+            "Log out and back in to activate the shortcut",
+        ))
     }
 
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
         let path = Self::get_config_path()?;
-        let section_name = format!("Data_{}", s.id.replace('-', "_"));
+        let section_name = Self::section_name(s);
 
-        Utils::modify_file_atomic(&path, |content| {
+        let reason = Utils::change_reason("Unregister", s);
+        Utils::modify_file_atomic(&path, &reason, |content| {
             if !content.contains(&section_name) {
                 return Ok(None);
             }
@@ -663,111 +1424,149 @@ impl ShortcutHandler for KdeHandler {
             Ok(Some(new_lines.join("\n")))
         })?;
 
-        Self::reload_kde();
+        let _ = Self::reload_kde();
         Ok(())
     }
+
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        let path = Self::get_config_path()?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(content.contains(&format!("[{}]", Self::section_name(s)))
+            && content.contains(&format!("CommandURL={}", s.command)))
+    }
 }
 
 // --- XFCE ---
 
 struct XfceHandler;
-impl ShortcutHandler for XfceHandler {
-    fn name(&self) -> &str {
+impl XfceHandler {
+    const CHANNEL: &'static str = "xfce4-keyboard-shortcuts";
+
+    /// Command bound to a custom shortcut property, `None` if the property isn't set
+    fn command(property: &str) -> Result<Option<String>> {
+        match Utils::run("xfconf-query", &["-c", Self::CHANNEL, "-p", property]) {
+            Ok(command) => Ok(Some(command)),
+            Err(ShortcutError::CommandFailed { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl ShortcutHandler for XfceHandler {
+    fn name(&self) -> &str {
         "XFCE"
     }
 
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
         if !Utils::command_exists("xfconf-query") {
             return Err(ShortcutError::DependencyMissing("xfconf-query".into()));
         }
-        let property = format!("/commands/custom/{}", s.xfce_binding);
+        let property = format!("/commands/custom/{}", s.chord.xfce());
 
         // Check if exists to avoid error spam
-        let exists = Command::new("xfconf-query")
-            .args(["-c", "xfce4-keyboard-shortcuts", "-p", &property])
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false);
-
-        if !exists {
-            Utils::run(
-                "xfconf-query",
-                &[
-                    "-c",
-                    "xfce4-keyboard-shortcuts",
-                    "-p",
+        match Self::command(&property)? {
+            Some(command) if command == s.command => Ok(RegistrationOutcome::AlreadyPresent),
+            Some(command) => Err(ShortcutError::Io(io::Error::other(format!(
+                "{} is already bound to '{}'",
+                s.chord, command
+            )))),
+            None => {
+                Utils::xfconf_change(
+                    Self::CHANNEL,
                     &property,
-                    "-n",
-                    "-t",
-                    "string",
-                    "-s",
-                    s.command,
-                ],
-            )?;
+                    &["-n", "-t", "string", "-s", &s.command],
+                    &Utils::change_reason("Register", s),
+                )?;
+                Ok(RegistrationOutcome::Registered)
+            }
         }
-        Ok(())
     }
 
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
         if !Utils::command_exists("xfconf-query") {
             return Ok(());
         }
-        let property = format!("/commands/custom/{}", s.xfce_binding);
-        // Ignore error on unregister if it doesn't exist
-        let _ = Utils::run(
-            "xfconf-query",
-            &["-c", "xfce4-keyboard-shortcuts", "-p", &property, "-r"],
-        );
+        let property = format!("/commands/custom/{}", s.chord.xfce());
+        // Only our own binding; register leaves other commands on the chord alone
+        if Self::command(&property)?.as_deref() == Some(s.command.as_str()) {
+            Utils::xfconf_change(
+                Self::CHANNEL,
+                &property,
+                &["-r"],
+                &Utils::change_reason("Unregister", s),
+            )?;
+        }
         Ok(())
     }
+
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        if !Utils::command_exists("xfconf-query") {
+            return Err(ShortcutError::DependencyMissing("xfconf-query".into()));
+        }
+        let property = format!("/commands/custom/{}", s.chord.xfce());
+        Ok(Self::command(&property)?.as_deref() == Some(s.command.as_str()))
+    }
 }
 
 // --- MATE ---
 
 struct MateHandler;
+impl MateHandler {
+    const COMMANDS_SCHEMA: &'static str = "org.mate.Marco.keybinding-commands";
+    const BINDINGS_SCHEMA: &'static str = "org.mate.Marco.global-keybindings";
+
+    /// Command and binding of Marco's numbered run-command slot
+    fn slot(i: u32) -> Result<(String, String)> {
+        let command = Utils::run(
+            "gsettings",
+            &["get", Self::COMMANDS_SCHEMA, &format!("command-{}", i)],
+        )?;
+        let binding = Utils::run(
+            "gsettings",
+            &["get", Self::BINDINGS_SCHEMA, &format!("run-command-{}", i)],
+        )?;
+        Ok((
+            command.trim_matches('\'').to_string(),
+            binding.trim_matches('\'').to_string(),
+        ))
+    }
+}
+
 impl ShortcutHandler for MateHandler {
     fn name(&self) -> &str {
         "MATE"
     }
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
         if !Utils::command_exists("gsettings") {
             return Err(ShortcutError::DependencyMissing("gsettings".into()));
         }
+        if self.verify(s)? {
+            return Ok(RegistrationOutcome::AlreadyPresent);
+        }
 
+        let binding = s.chord.gnome();
         // Logic similar to original but with Utils::run for better errors
         for i in 1..=12 {
-            let cmd_key = format!("command-{}", i);
-            let current = Utils::run(
-                "gsettings",
-                &["get", "org.mate.Marco.keybinding-commands", &cmd_key],
-            )?;
-            let current = current.trim_matches('\'');
-
-            if current == s.command {
-                return Ok(());
-            } // Already done
-
-            if current.is_empty() {
-                let binding_key = format!("run-command-{}", i);
-                Utils::run(
-                    "gsettings",
-                    &[
-                        "set",
-                        "org.mate.Marco.keybinding-commands",
-                        &cmd_key,
-                        s.command,
-                    ],
+            let (command, _) = Self::slot(i)?;
+
+            if command.is_empty() {
+                let reason = Utils::change_reason("Register", s);
+                Utils::gsettings_set(
+                    Self::COMMANDS_SCHEMA,
+                    &format!("command-{}", i),
+                    &s.command,
+                    &reason,
                 )?;
-                Utils::run(
-                    "gsettings",
-                    &[
-                        "set",
-                        "org.mate.Marco.global-keybindings",
-                        &binding_key,
-                        s.gnome_binding,
-                    ],
+                Utils::gsettings_set(
+                    Self::BINDINGS_SCHEMA,
+                    &format!("run-command-{}", i),
+                    &binding,
+                    &reason,
                 )?;
-                return Ok(());
+                return Ok(RegistrationOutcome::Registered);
             }
         }
         Err(ShortcutError::Io(io::Error::other(
@@ -779,53 +1578,70 @@ impl ShortcutHandler for MateHandler {
         if !Utils::command_exists("gsettings") {
             return Ok(());
         }
+        let binding = s.chord.gnome();
         for i in 1..=12 {
-            let cmd_key = format!("command-{}", i);
-            let current = Utils::run(
-                "gsettings",
-                &["get", "org.mate.Marco.keybinding-commands", &cmd_key],
-            )?;
-
-            if current.contains(s.command) {
-                Utils::run(
-                    "gsettings",
-                    &["reset", "org.mate.Marco.keybinding-commands", &cmd_key],
-                )?;
-                Utils::run(
-                    "gsettings",
-                    &[
-                        "reset",
-                        "org.mate.Marco.global-keybindings",
-                        &format!("run-command-{}", i),
-                    ],
+            let (command, current_binding) = Self::slot(i)?;
+
+            // Both shortcuts run the same command; the binding tells them apart
+            if command == s.command && current_binding == binding {
+                let reason = Utils::change_reason("Unregister", s);
+                Utils::gsettings_reset(Self::COMMANDS_SCHEMA, &format!("command-{}", i), &reason)?;
+                Utils::gsettings_reset(
+                    Self::BINDINGS_SCHEMA,
+                    &format!("run-command-{}", i),
+                    &reason,
                 )?;
             }
         }
         Ok(())
     }
+
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        let binding = s.chord.gnome();
+        for i in 1..=12 {
+            let (command, current_binding) = Self::slot(i)?;
+            if command == s.command && current_binding == binding {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
 }
 
 // --- COSMIC ---
 
 struct CosmicHandler;
+impl CosmicHandler {
+    fn get_config_path() -> Result<PathBuf> {
+        let home = env::var("HOME")
+            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
+        Ok(PathBuf::from(home)
+            .join(".config/cosmic/com.system76.CosmicSettings.Shortcuts/v1/custom"))
+    }
+
+    fn entry(s: &ShortcutConfig) -> String {
+        format!(
+            "(modifiers: [{}], key: \"{}\"): Spawn(\"{}\"),",
+            s.chord.cosmic_mods(),
+            s.chord.cosmic_key(),
+            s.command
+        )
+    }
+}
+
 impl ShortcutHandler for CosmicHandler {
     fn name(&self) -> &str {
         "COSMIC"
     }
 
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
-        let path = PathBuf::from(home)
-            .join(".config/cosmic/com.system76.CosmicSettings.Shortcuts/v1/custom");
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
+        let path = Self::get_config_path()?;
 
         // Naive but safer append
-        let entry = format!(
-            "(modifiers: [{}], key: \"{}\"): Spawn(\"{}\"),",
-            s.cosmic_mods, s.cosmic_key, s.command
-        );
+        let entry = Self::entry(s);
 
-        Utils::modify_file_atomic(&path, |content| {
+        let reason = Utils::change_reason("Register", s);
+        let modified = Utils::modify_file_atomic(&path, &reason, |content| {
             if content.contains(&entry) {
                 return Ok(None);
             }
@@ -848,36 +1664,81 @@ impl ShortcutHandler for CosmicHandler {
             }
             Ok(Some(new_content))
         })?;
-        Ok(())
+        // cosmic-settings-daemon watches the file
+        Ok(if modified {
+            RegistrationOutcome::Registered
+        } else {
+            RegistrationOutcome::AlreadyPresent
+        })
     }
 
-    fn unregister(&self, _s: &ShortcutConfig) -> Result<()> {
-        // Requires real RON parser
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = Self::get_config_path()?;
+        if !path.exists() {
+            return Ok(());
+        }
+
+        // Removes the exact line written by register; a real RON parser would be
+        // needed to remove entries edited by hand
+        let entry = Self::entry(s);
+        let reason = Utils::change_reason("Unregister", s);
+        Utils::modify_file_atomic(&path, &reason, |content| {
+            if !content.lines().any(|line| line.trim() == entry) {
+                return Ok(None);
+            }
+            let lines: Vec<&str> = content
+                .lines()
+                .filter(|line| line.trim() != entry)
+                .collect();
+            Ok(Some(lines.join("\n")))
+        })?;
         Ok(())
     }
+
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        let path = Self::get_config_path()?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        let entry = Self::entry(s);
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .any(|line| line.trim() == entry))
+    }
 }
 
 // --- LXQt ---
 
 struct LxqtHandler;
+impl LxqtHandler {
+    fn get_config_path() -> Result<PathBuf> {
+        let home = env::var("HOME")
+            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
+        Ok(PathBuf::from(home).join(".config/lxqt/globalkeyshortcuts.conf"))
+    }
+
+    fn section(s: &ShortcutConfig) -> String {
+        format!("{}%2F{}", s.chord.kde(), s.id)
+    }
+}
+
 impl ShortcutHandler for LxqtHandler {
     fn name(&self) -> &str {
         "LXQt"
     }
 
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
-        let path = PathBuf::from(home).join(".config/lxqt/globalkeyshortcuts.conf");
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
+        let path = Self::get_config_path()?;
 
         // LXQt uses INI format for shortcuts
-        let section = format!("Meta+V%2F{}", s.id);
+        let section = Self::section(s);
         let entry = format!(
             "\n[{}]\nComment={}\nEnabled=true\nExec={}",
             section, s.name, s.command
         );
 
-        Utils::modify_file_atomic(&path, |content| {
+        let reason = Utils::change_reason("Register", s);
+        let modified = Utils::modify_file_atomic(&path, &reason, |content| {
             if content.contains(&format!("[{}]", section)) {
                 return Ok(None); // Already exists
             }
@@ -886,21 +1747,25 @@ impl ShortcutHandler for LxqtHandler {
             new_content.push_str(&entry);
             Ok(Some(new_content))
         })?;
-        Ok(())
+        // lxqt-globalkeysd only reads its config at startup
+        Ok(if modified {
+            RegistrationOutcome::NeedsReload("Log out and back in to activate the shortcut".into())
+        } else {
+            RegistrationOutcome::AlreadyPresent
+        })
     }
 
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
-        let path = PathBuf::from(home).join(".config/lxqt/globalkeyshortcuts.conf");
+        let path = Self::get_config_path()?;
 
         if !path.exists() {
             return Ok(());
         }
 
-        let section = format!("Meta+V%2F{}", s.id);
+        let section = Self::section(s);
 
-        Utils::modify_file_atomic(&path, |content| {
+        let reason = Utils::change_reason("Unregister", s);
+        Utils::modify_file_atomic(&path, &reason, |content| {
             if !content.contains(&format!("[{}]", section)) {
                 return Ok(None);
             }
@@ -925,114 +1790,190 @@ impl ShortcutHandler for LxqtHandler {
         })?;
         Ok(())
     }
-}
-
-// --- LXDE (Openbox) ---
 
-struct LxdeHandler;
-impl ShortcutHandler for LxdeHandler {
-    fn name(&self) -> &str {
-        "LXDE/Openbox"
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        let path = Self::get_config_path()?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        Ok(fs::read_to_string(path)?.contains(&format!("[{}]", Self::section(s))))
     }
+}
 
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
-
-        // LXDE uses Openbox for window management
-        let path = PathBuf::from(&home).join(".config/openbox/lxde-rc.xml");
+// --- Openbox / LXDE / labwc ---
+
+/// Openbox and labwc share the rc.xml keybind format
+struct OpenboxHandler {
+    name: &'static str,
+    /// Candidate configs under the config home, in order of preference
+    configs: &'static [&'static str],
+    /// Binary accepting `--reconfigure`
+    binary: &'static str,
+    /// labwc: the config may be created, and a new `<keyboard>` keeps the default keybinds
+    labwc: bool,
+}
 
-        // Fallback to default openbox config if LXDE-specific doesn't exist
-        let path = if path.exists() {
-            path
+impl OpenboxHandler {
+    const LXDE: Self = Self {
+        name: "LXDE/Openbox",
+        // LXDE uses Openbox for window management, with its own config
+        configs: &["openbox/lxde-rc.xml", "openbox/rc.xml"],
+        binary: "openbox",
+        labwc: false,
+    };
+    const OPENBOX: Self = Self {
+        name: "Openbox",
+        configs: &["openbox/rc.xml"],
+        binary: "openbox",
+        labwc: false,
+    };
+    const LABWC: Self = Self {
+        name: "labwc",
+        configs: &["labwc/rc.xml"],
+        binary: "labwc",
+        labwc: true,
+    };
+
+    fn get_config_path(&self) -> Result<PathBuf> {
+        let dir = config_home()?;
+        let path = self
+            .configs
+            .iter()
+            .map(|config| dir.join(config))
+            .find(|path| path.exists())
+            .unwrap_or_else(|| dir.join(self.configs[self.configs.len() - 1]));
+        if self.labwc {
+            Ok(path)
         } else {
-            PathBuf::from(&home).join(".config/openbox/rc.xml")
-        };
-
-        if !path.exists() {
-            return Err(ShortcutError::Io(io::Error::new(
-                io::ErrorKind::NotFound,
-                "Openbox config not found",
-            )));
+            // Without a user rc.xml Openbox uses the system one; creating ours would drop it
+            require_config(path, self.name)
         }
+    }
 
-        // The keybind XML to add
-        let keybind = format!(
-            r#"    <keybind key="W-v">
+    fn keybind(s: &ShortcutConfig) -> String {
+        format!(
+            r#"    <keybind key="{}">
       <action name="Execute">
         <command>{}</command>
       </action>
     </keybind>"#,
+            s.chord.openbox(),
             s.command
-        );
-
-        Utils::modify_file_atomic(&path, |content| {
-            if content.contains(&format!("<command>{}</command>", s.command)) {
-                return Ok(None); // Already exists
-            }
+        )
+    }
 
-            // Find the </keyboard> closing tag and insert before it
-            if let Some(pos) = content.find("</keyboard>") {
-                let mut new_content = content.clone();
-                new_content.insert_str(pos, &format!("{}\n  ", keybind));
+    fn add_keybind(&self, content: &str, keybind: &str) -> Result<Option<String>> {
+        if content.contains(keybind) {
+            return Ok(None); // Already exists
+        }
 
-                // Trigger openbox reconfigure
-                let _ = Utils::run("openbox", &["--reconfigure"]);
+        // Insert before the closing </keyboard> tag
+        if let Some(pos) = content.find("</keyboard>") {
+            let mut new_content = content.to_string();
+            new_content.insert_str(pos, &format!("{}\n  ", keybind));
+            return Ok(Some(new_content));
+        }
 
+        if self.labwc {
+            // <default /> keeps labwc's built-in keybinds next to ours
+            let keyboard = format!(
+                "  <keyboard>\n    <default />\n{}\n  </keyboard>\n",
+                keybind
+            );
+            if let Some(pos) = content.find("</labwc_config>") {
+                let mut new_content = content.to_string();
+                new_content.insert_str(pos, &keyboard);
                 return Ok(Some(new_content));
             }
+            if content.trim().is_empty() {
+                return Ok(Some(format!(
+                    "<?xml version=\"1.0\"?>\n<labwc_config>\n{}</labwc_config>\n",
+                    keyboard
+                )));
+            }
+        }
 
-            Err(ShortcutError::ParseError(
-                "Could not find </keyboard> in Openbox config".into(),
-            ))
+        Err(ShortcutError::ParseError(format!(
+            "Could not find </keyboard> in {} config",
+            self.name
+        )))
+    }
+
+    fn reconfigure(&self) -> Result<String> {
+        Utils::run(self.binary, &["--reconfigure"])
+    }
+}
+
+impl ShortcutHandler for OpenboxHandler {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
+        let path = self.get_config_path()?;
+        let keybind = Self::keybind(s);
+
+        let reason = Utils::change_reason("Register", s);
+        let modified = Utils::modify_file_atomic(&path, &reason, |content| {
+            self.add_keybind(&content, &keybind)
         })?;
-        Ok(())
+
+        // Reconfigure only after the file was written
+        if !modified {
+            return Ok(RegistrationOutcome::AlreadyPresent);
+        }
+        Ok(reload_outcome(
+            self.reconfigure(),
+            &format!("Run `{} --reconfigure`", self.binary),
+        ))
     }
 
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
-
-        let path = PathBuf::from(&home).join(".config/openbox/lxde-rc.xml");
-        let path = if path.exists() {
-            path
-        } else {
-            PathBuf::from(&home).join(".config/openbox/rc.xml")
+        let Ok(path) = self.get_config_path() else {
+            return Ok(());
         };
-
         if !path.exists() {
             return Ok(());
         }
 
-        Utils::modify_file_atomic(&path, |content| {
-            if !content.contains(&format!("<command>{}</command>", s.command)) {
+        // Remove the keybind block - this is a simplified approach
+        // A proper XML parser would be better but adds dependency
+        let pattern = Self::keybind(s);
+
+        let reason = Utils::change_reason("Unregister", s);
+        let modified = Utils::modify_file_atomic(&path, &reason, |content| {
+            if !content.contains(&pattern) {
                 return Ok(None);
             }
-
-            // Remove the keybind block - this is a simplified approach
-            // A proper XML parser would be better but adds dependency
-            let pattern = format!(
-                r#"    <keybind key="W-v">
-      <action name="Execute">
-        <command>{}</command>
-      </action>
-    </keybind>"#,
-                s.command
-            );
-
-            let new_content = content.replace(&pattern, "");
-
-            // Trigger openbox reconfigure
-            let _ = Utils::run("openbox", &["--reconfigure"]);
-
-            Ok(Some(new_content))
+            Ok(Some(
+                content
+                    .replace(&format!("{}\n  ", pattern), "")
+                    .replace(&pattern, ""),
+            ))
         })?;
+
+        if modified {
+            let _ = self.reconfigure();
+        }
         Ok(())
     }
+
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        let path = self.get_config_path()?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        Ok(fs::read_to_string(path)?.contains(&Self::keybind(s)))
+    }
 }
 
 // --- i3 Window Manager ---
 
+/// i3 and sway: `bindsym $mod+v exec command`
+fn i3_binding(s: &ShortcutConfig) -> String {
+    format!("bindsym {} exec {}", s.chord.i3(), s.command)
+}
+
 struct I3Handler;
 impl I3Handler {
     fn get_config_path() -> Result<PathBuf> {
@@ -1055,9 +1996,9 @@ impl I3Handler {
         Ok(PathBuf::from(&home).join(".config/i3/config"))
     }
 
-    fn reload_i3() {
+    fn reload_i3() -> Result<String> {
         // Send reload command to i3
-        let _ = Utils::run("i3-msg", &["reload"]);
+        Utils::run("i3-msg", &["reload"])
     }
 }
 
@@ -1066,102 +2007,37 @@ impl ShortcutHandler for I3Handler {
         "i3"
     }
 
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
         let path = Self::get_config_path()?;
 
         // i3 binding format: bindsym $mod+v exec command
-        let binding_line = format!("bindsym {} exec {}", s.i3_binding, s.command);
-
-        let modified = Utils::modify_file_atomic(&path, |content| {
-            // Check if already registered
-            if content.contains(s.command) {
-                return Ok(None);
-            }
-
-            // Check for existing $mod+v binding and comment it out
-            let mut lines: Vec<String> = content.lines().map(String::from).collect();
-            let mut had_existing = false;
-
-            for line in lines.iter_mut() {
-                let trimmed = line.trim().to_lowercase();
-                // Skip if already a comment
-                if trimmed.starts_with('#') {
-                    continue;
-                }
-                // Check for existing mod+v bindings (word boundary check)
-                if trimmed.starts_with("bindsym") && has_mod_v_binding(&trimmed) {
-                    *line = format!("# {} # Commented by win11-clipboard-history", line);
-                    had_existing = true;
-                }
-            }
-
-            // Add our binding at the end
-            lines.push("\n# Clipboard History (added by win11-clipboard-history)".to_string());
-            lines.push(binding_line.clone());
-
-            if had_existing {
-                println!("[i3Handler] Commented out existing $mod+v binding(s)");
-            }
-
-            Ok(Some(lines.join("\n")))
-        })?;
+        let binding_line = i3_binding(s);
+        let modified = register_in_config(&path, s, &binding_line, &I3_FORMAT, "i3Handler")?;
 
         // Reload i3 only after file was successfully written
-        if modified {
-            Self::reload_i3();
+        if !modified {
+            return Ok(RegistrationOutcome::AlreadyPresent);
         }
-        Ok(())
+        Ok(reload_outcome(
+            Self::reload_i3(),
+            "Reload i3 with $mod+Shift+r",
+        ))
     }
 
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
         let path = Self::get_config_path()?;
-
-        if !path.exists() {
-            return Ok(());
-        }
-
-        let modified = Utils::modify_file_atomic(&path, |content| {
-            if !content.contains(s.command) {
-                return Ok(None);
-            }
-
-            let lines: Vec<&str> = content.lines().collect();
-            let mut new_lines: Vec<String> = Vec::new();
-            let mut skip_comment = false;
-
-            for line in lines {
-                // Skip our comment line
-                if line.contains("# Clipboard History (added by win11-clipboard-history)") {
-                    skip_comment = true;
-                    continue;
-                }
-                // Skip our binding line
-                if skip_comment && line.contains(s.command) {
-                    skip_comment = false;
-                    continue;
-                }
-                skip_comment = false;
-
-                // Restore commented out bindings
-                if line.contains("# Commented by win11-clipboard-history") {
-                    let restored = line
-                        .replace("# ", "")
-                        .replace(" # Commented by win11-clipboard-history", "");
-                    new_lines.push(restored);
-                } else {
-                    new_lines.push(line.to_string());
-                }
-            }
-
-            Ok(Some(new_lines.join("\n")))
-        })?;
+        let modified = unregister_from_config(&path, s, &i3_binding(s), &I3_FORMAT)?;
 
         // Reload i3 only after file was successfully written
         if modified {
-            Self::reload_i3();
+            let _ = Self::reload_i3();
         }
         Ok(())
     }
+
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        config_has_binding(&Self::get_config_path()?, &i3_binding(s))
+    }
 }
 
 // --- Sway ---
@@ -1186,8 +2062,8 @@ impl SwayHandler {
         Ok(PathBuf::from(&home).join(".config/sway/config"))
     }
 
-    fn reload_sway() {
-        let _ = Utils::run("swaymsg", &["reload"]);
+    fn reload_sway() -> Result<String> {
+        Utils::run("swaymsg", &["reload"])
     }
 }
 
@@ -1196,94 +2072,36 @@ impl ShortcutHandler for SwayHandler {
         "Sway"
     }
 
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
         let path = Self::get_config_path()?;
 
-        let binding_line = format!("bindsym {} exec {}", s.sway_binding, s.command);
-
-        let modified = Utils::modify_file_atomic(&path, |content| {
-            if content.contains(s.command) {
-                return Ok(None);
-            }
-
-            let mut lines: Vec<String> = content.lines().map(String::from).collect();
-            let mut had_existing = false;
-
-            for line in lines.iter_mut() {
-                let trimmed = line.trim().to_lowercase();
-                if trimmed.starts_with('#') {
-                    continue;
-                }
-                // Check for existing mod+v bindings (word boundary check)
-                if trimmed.starts_with("bindsym") && has_mod_v_binding(&trimmed) {
-                    *line = format!("# {} # Commented by win11-clipboard-history", line);
-                    had_existing = true;
-                }
-            }
-
-            lines.push("\n# Clipboard History (added by win11-clipboard-history)".to_string());
-            lines.push(binding_line.clone());
-
-            if had_existing {
-                println!("[SwayHandler] Commented out existing $mod+v binding(s)");
-            }
-
-            Ok(Some(lines.join("\n")))
-        })?;
+        let binding_line = i3_binding(s);
+        let modified = register_in_config(&path, s, &binding_line, &I3_FORMAT, "SwayHandler")?;
 
         // Reload Sway only after file was successfully written
-        if modified {
-            Self::reload_sway();
+        if !modified {
+            return Ok(RegistrationOutcome::AlreadyPresent);
         }
-        Ok(())
+        Ok(reload_outcome(
+            Self::reload_sway(),
+            "Reload Sway with $mod+Shift+c",
+        ))
     }
 
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
         let path = Self::get_config_path()?;
-
-        if !path.exists() {
-            return Ok(());
-        }
-
-        let modified = Utils::modify_file_atomic(&path, |content| {
-            if !content.contains(s.command) {
-                return Ok(None);
-            }
-
-            let lines: Vec<&str> = content.lines().collect();
-            let mut new_lines: Vec<String> = Vec::new();
-            let mut skip_comment = false;
-
-            for line in lines {
-                if line.contains("# Clipboard History (added by win11-clipboard-history)") {
-                    skip_comment = true;
-                    continue;
-                }
-                if skip_comment && line.contains(s.command) {
-                    skip_comment = false;
-                    continue;
-                }
-                skip_comment = false;
-
-                if line.contains("# Commented by win11-clipboard-history") {
-                    let restored = line
-                        .replace("# ", "")
-                        .replace(" # Commented by win11-clipboard-history", "");
-                    new_lines.push(restored);
-                } else {
-                    new_lines.push(line.to_string());
-                }
-            }
-
-            Ok(Some(new_lines.join("\n")))
-        })?;
+        let modified = unregister_from_config(&path, s, &i3_binding(s), &I3_FORMAT)?;
 
         // Reload Sway only after file was successfully written
         if modified {
-            Self::reload_sway();
+            let _ = Self::reload_sway();
         }
         Ok(())
     }
+
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        config_has_binding(&Self::get_config_path()?, &i3_binding(s))
+    }
 }
 
 // --- Hyprland ---
@@ -1300,6 +2118,11 @@ impl HyprlandHandler {
         let path = PathBuf::from(&xdg_config).join("hypr/hyprland.conf");
         Ok(path)
     }
+
+    /// `bind = SUPER, V, exec, command`
+    fn binding(s: &ShortcutConfig) -> String {
+        format!("bind = {}, exec, {}", s.chord.hyprland(), s.command)
+    }
 }
 
 impl ShortcutHandler for HyprlandHandler {
@@ -1307,87 +2130,519 @@ impl ShortcutHandler for HyprlandHandler {
         "Hyprland"
     }
 
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
         let path = Self::get_config_path()?;
 
-        // Hyprland format: bind = SUPER, V, exec, command
-        let binding_line = format!("bind = {}, exec, {}", s.hyprland_binding, s.command);
+        // Hyprland auto-reloads config, no explicit reload needed
+        let modified = register_in_config(
+            &path,
+            s,
+            &Self::binding(s),
+            &HYPRLAND_FORMAT,
+            "HyprlandHandler",
+        )?;
+        Ok(written_outcome(modified))
+    }
 
-        Utils::modify_file_atomic(&path, |content| {
-            if content.contains(s.command) {
-                return Ok(None);
-            }
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = Self::get_config_path()?;
+        unregister_from_config(&path, s, &Self::binding(s), &HYPRLAND_FORMAT)?;
+        Ok(())
+    }
 
-            let mut lines: Vec<String> = content.lines().map(String::from).collect();
-            let mut modified = false;
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        config_has_binding(&Self::get_config_path()?, &Self::binding(s))
+    }
+}
 
-            for line in lines.iter_mut() {
-                let trimmed = line.trim().to_lowercase();
-                if trimmed.starts_with('#') {
-                    continue;
-                }
-                // Check for existing SUPER, V bindings
-                if trimmed.starts_with("bind")
-                    && trimmed.contains("super")
-                    && (trimmed.contains(", v,") || trimmed.contains(",v,"))
-                {
-                    *line = format!("# {} # Commented by win11-clipboard-history", line);
-                    modified = true;
-                }
-            }
+// --- niri ---
+
+const NIRI_FORMAT: ConfigFormat = ConfigFormat {
+    syntax: ConfigSyntax::Niri,
+    comment: "//",
+    block: Some(("binds {", "}")),
+    comment_conflicts: true,
+};
+
+struct NiriHandler;
+impl NiriHandler {
+    /// `Mod+V { spawn "command" "--arg"; }` inside the `binds` block
+    fn binding(s: &ShortcutConfig) -> String {
+        let args: Vec<String> = s
+            .command
+            .split_whitespace()
+            .map(|arg| format!("\"{}\"", arg))
+            .collect();
+        format!("    {} {{ spawn {}; }}", s.chord.niri(), args.join(" "))
+    }
+}
 
-            lines.push("\n# Clipboard History (added by win11-clipboard-history)".to_string());
-            lines.push(binding_line.clone());
+impl ShortcutHandler for NiriHandler {
+    fn name(&self) -> &str {
+        "niri"
+    }
 
-            if modified {
-                println!("[HyprlandHandler] Commented out existing SUPER+V binding(s)");
-            }
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
+        // niri writes its default config on first start and reloads it on change
+        let path = require_config(config_home()?.join("niri/config.kdl"), "niri")?;
+        let modified =
+            register_in_config(&path, s, &Self::binding(s), &NIRI_FORMAT, "NiriHandler")?;
+        Ok(written_outcome(modified))
+    }
 
-            // Hyprland auto-reloads config, no explicit reload needed
-            Ok(Some(lines.join("\n")))
-        })?;
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = config_home()?.join("niri/config.kdl");
+        unregister_from_config(&path, s, &Self::binding(s), &NIRI_FORMAT)?;
         Ok(())
     }
 
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        config_has_binding(&config_home()?.join("niri/config.kdl"), &Self::binding(s))
+    }
+}
+
+// --- river ---
+
+const RIVER_FORMAT: ConfigFormat = ConfigFormat::appended(ConfigSyntax::River, "#");
+
+struct RiverHandler;
+impl RiverHandler {
+    /// `riverctl map normal Super V spawn 'command'`
+    fn binding(s: &ShortcutConfig) -> String {
+        format!(
+            "riverctl map normal {} {} spawn '{}'",
+            s.chord.river_mods(),
+            s.chord.keysym(),
+            s.command
+        )
+    }
+}
+
+impl ShortcutHandler for RiverHandler {
+    fn name(&self) -> &str {
+        "river"
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
+        // The init script only runs at startup: map the key on the running river as well
+        let path = require_config(config_home()?.join("river/init"), "river")?;
+        if !register_in_config(&path, s, &Self::binding(s), &RIVER_FORMAT, "RiverHandler")? {
+            return Ok(RegistrationOutcome::AlreadyPresent);
+        }
+        let hint = "Restart river to load the new mapping";
+        if !Utils::command_exists("riverctl") {
+            return Ok(RegistrationOutcome::NeedsReload(hint.into()));
+        }
+        let mods = s.chord.river_mods();
+        let key = s.chord.keysym();
+        Ok(reload_outcome(
+            Utils::run(
+                "riverctl",
+                &["map", "normal", &mods, &key, "spawn", &s.command],
+            ),
+            hint,
+        ))
+    }
+
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = config_home()?.join("river/init");
+        unregister_from_config(&path, s, &Self::binding(s), &RIVER_FORMAT)?;
+        if Utils::command_exists("riverctl") {
+            let mods = s.chord.river_mods();
+            let key = s.chord.keysym();
+            let _ = Utils::run("riverctl", &["unmap", "normal", &mods, &key]);
+        }
+        Ok(())
+    }
+
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        config_has_binding(&config_home()?.join("river/init"), &Self::binding(s))
+    }
+}
+
+// --- Wayfire ---
+
+const WAYFIRE_FORMAT: ConfigFormat = ConfigFormat {
+    syntax: ConfigSyntax::Wayfire,
+    comment: "#",
+    block: Some(("[command]", "")),
+    comment_conflicts: true,
+};
+
+struct WayfireHandler;
+impl WayfireHandler {
+    fn get_config_path() -> Result<PathBuf> {
+        match env::var("WAYFIRE_CONFIG_FILE") {
+            Ok(path) if !path.is_empty() => Ok(PathBuf::from(path)),
+            _ => Ok(config_home()?.join("wayfire.ini")),
+        }
+    }
+
+    /// `binding_<id>` and `command_<id>` options of the command plugin
+    fn binding(s: &ShortcutConfig) -> String {
+        let key = s.id.replace('-', "_");
+        format!(
+            "binding_{0} = {1}\ncommand_{0} = {2}",
+            key,
+            s.chord.wayfire(),
+            s.command
+        )
+    }
+}
+
+impl ShortcutHandler for WayfireHandler {
+    fn name(&self) -> &str {
+        "Wayfire"
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
+        // Wayfire reloads its config on change; missing options keep their defaults
         let path = Self::get_config_path()?;
+        let modified = register_in_config(
+            &path,
+            s,
+            &Self::binding(s),
+            &WAYFIRE_FORMAT,
+            "WayfireHandler",
+        )?;
+        Ok(written_outcome(modified))
+    }
 
-        if !path.exists() {
-            return Ok(());
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = Self::get_config_path()?;
+        unregister_from_config(&path, s, &Self::binding(s), &WAYFIRE_FORMAT)?;
+        Ok(())
+    }
+
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        config_has_binding(&Self::get_config_path()?, &Self::binding(s))
+    }
+}
+
+// --- bspwm (sxhkd) ---
+
+const SXHKD_FORMAT: ConfigFormat = ConfigFormat::appended(ConfigSyntax::Sxhkd, "#");
+
+struct BspwmHandler;
+impl BspwmHandler {
+    /// The chord line, then the command indented below it
+    fn binding(s: &ShortcutConfig) -> String {
+        format!("{}\n\t{}", s.chord.sxhkd(), s.command)
+    }
+
+    fn reload_sxhkd() -> Result<String> {
+        Utils::run("pkill", &["-USR1", "-x", "sxhkd"])
+    }
+}
+
+impl ShortcutHandler for BspwmHandler {
+    fn name(&self) -> &str {
+        "bspwm (sxhkd)"
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
+        let path = config_home()?.join("sxhkd/sxhkdrc");
+        if !register_in_config(&path, s, &Self::binding(s), &SXHKD_FORMAT, "BspwmHandler")? {
+            return Ok(RegistrationOutcome::AlreadyPresent);
         }
+        Ok(reload_outcome(
+            Self::reload_sxhkd(),
+            "Reload sxhkd with `pkill -USR1 -x sxhkd`",
+        ))
+    }
 
-        Utils::modify_file_atomic(&path, |content| {
-            if !content.contains(s.command) {
-                return Ok(None);
-            }
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = config_home()?.join("sxhkd/sxhkdrc");
+        if unregister_from_config(&path, s, &Self::binding(s), &SXHKD_FORMAT)? {
+            let _ = Self::reload_sxhkd();
+        }
+        Ok(())
+    }
 
-            let lines: Vec<&str> = content.lines().collect();
-            let mut new_lines: Vec<String> = Vec::new();
-            let mut skip_comment = false;
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        config_has_binding(&config_home()?.join("sxhkd/sxhkdrc"), &Self::binding(s))
+    }
+}
 
-            for line in lines {
-                if line.contains("# Clipboard History (added by win11-clipboard-history)") {
-                    skip_comment = true;
-                    continue;
-                }
-                if skip_comment && line.contains(s.command) {
-                    skip_comment = false;
-                    continue;
-                }
-                skip_comment = false;
-
-                if line.contains("# Commented by win11-clipboard-history") {
-                    let restored = line
-                        .replace("# ", "")
-                        .replace(" # Commented by win11-clipboard-history", "");
-                    new_lines.push(restored);
-                } else {
-                    new_lines.push(line.to_string());
-                }
-            }
+// --- awesome ---
+
+/// Both bindings of a chord fire in awesome, so conflicts are only reported
+const AWESOME_FORMAT: ConfigFormat = ConfigFormat {
+    syntax: ConfigSyntax::Awesome,
+    comment: "--",
+    block: None,
+    comment_conflicts: false,
+};
+
+struct AwesomeHandler;
+impl AwesomeHandler {
+    /// A self-contained line adding the key to the global keys, so it doesn't depend on
+    /// the locals of the user's rc.lua
+    fn binding(s: &ShortcutConfig) -> String {
+        format!(
+            r#"root.keys(require("gears").table.join(root.keys(), require("awful").key({}, "{}", function() require("awful").spawn({}) end)))"#,
+            s.chord.awesome_mods(),
+            s.chord.keysym(),
+            quoted_command(&s.command)
+        )
+    }
+}
 
-            Ok(Some(new_lines.join("\n")))
-        })?;
+impl ShortcutHandler for AwesomeHandler {
+    fn name(&self) -> &str {
+        "awesome"
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
+        let path = require_config(config_home()?.join("awesome/rc.lua"), "awesome")?;
+        let binding = Self::binding(s);
+        if !register_in_config(&path, s, &binding, &AWESOME_FORMAT, "AwesomeHandler")? {
+            return Ok(RegistrationOutcome::AlreadyPresent);
+        }
+        // Run the same line in the running session, so no restart is needed
+        let hint = "Restart awesome with Mod4+Ctrl+r";
+        if !Utils::command_exists("awesome-client") {
+            return Ok(RegistrationOutcome::NeedsReload(hint.into()));
+        }
+        Ok(reload_outcome(
+            Utils::run("awesome-client", &[&binding]),
+            hint,
+        ))
+    }
+
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = config_home()?.join("awesome/rc.lua");
+        if unregister_from_config(&path, s, &Self::binding(s), &AWESOME_FORMAT)? {
+            println!("[AwesomeHandler] Restart awesome to drop '{}'", s.name);
+        }
         Ok(())
     }
+
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        config_has_binding(&config_home()?.join("awesome/rc.lua"), &Self::binding(s))
+    }
+}
+
+// --- Qtile ---
+
+/// Keys are list entries that often span several lines, so conflicts are only reported.
+/// The key appended last wins.
+const QTILE_FORMAT: ConfigFormat = ConfigFormat {
+    syntax: ConfigSyntax::Qtile,
+    comment: "#",
+    block: None,
+    comment_conflicts: false,
+};
+
+struct QtileHandler;
+impl QtileHandler {
+    /// Appended after the `keys` list; `Key` and `lazy` are imported by every default config
+    fn binding(s: &ShortcutConfig) -> String {
+        format!(
+            r#"keys.append(Key({}, "{}", lazy.spawn({})))"#,
+            s.chord.qtile_mods(),
+            s.chord.keysym(),
+            quoted_command(&s.command)
+        )
+    }
+
+    fn reload_qtile() -> Result<String> {
+        Utils::run("qtile", &["cmd-obj", "-o", "cmd", "-f", "reload_config"])
+    }
+}
+
+impl ShortcutHandler for QtileHandler {
+    fn name(&self) -> &str {
+        "Qtile"
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<RegistrationOutcome> {
+        let path = require_config(config_home()?.join("qtile/config.py"), "Qtile")?;
+        if !register_in_config(&path, s, &Self::binding(s), &QTILE_FORMAT, "QtileHandler")? {
+            return Ok(RegistrationOutcome::AlreadyPresent);
+        }
+        Ok(reload_outcome(
+            Self::reload_qtile(),
+            "Reload Qtile's config with Mod4+Ctrl+r",
+        ))
+    }
+
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = config_home()?.join("qtile/config.py");
+        if unregister_from_config(&path, s, &Self::binding(s), &QTILE_FORMAT)? {
+            let _ = Self::reload_qtile();
+        }
+        Ok(())
+    }
+
+    fn verify(&self, s: &ShortcutConfig) -> Result<bool> {
+        config_has_binding(&config_home()?.join("qtile/config.py"), &Self::binding(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(name: &'static str, chord: &str) -> ShortcutConfig {
+        ShortcutConfig {
+            id: "win11-clipboard-history".to_string(),
+            name: name.to_string(),
+            command: "win11-clipboard-history".to_string(),
+            chord: Chord::parse(chord).unwrap(),
+            action: None,
+        }
+    }
+
+    #[test]
+    fn test_desktop_from_xdg() {
+        let cases = [
+            ("pop:GNOME pop", Some(DesktopEnvironment::PopOs)),
+            (
+                "Budgie:GNOME budgie-desktop",
+                Some(DesktopEnvironment::Budgie),
+            ),
+            ("ubuntu:GNOME ubuntu", Some(DesktopEnvironment::Gnome)),
+            ("Deepin dde", Some(DesktopEnvironment::Deepin)),
+            ("LXDE LXDE", Some(DesktopEnvironment::Lxde)),
+            ("wayfire ", Some(DesktopEnvironment::Wayfire)),
+            (" ", None),
+        ];
+        for (xdg, expected) in cases {
+            assert_eq!(desktop_from_xdg(xdg), expected, "{}", xdg);
+        }
+        assert_eq!(DesktopEnvironment::PopOs.display_name(), "Pop!_OS");
+    }
+
+    #[test]
+    fn test_config_binding_roundtrip() {
+        let original =
+            "set $mod Mod4\nbindsym $mod+v split vertical\nbindsym $mod+Return exec foot";
+        let shortcut = config("Clipboard History", "Super+V");
+        let binding = i3_binding(&shortcut);
+
+        let added = add_config_binding(original, &shortcut, &binding, &I3_FORMAT, "test").unwrap();
+        assert!(added
+            .contains("# bindsym $mod+v split vertical # Commented by win11-clipboard-history"));
+        assert!(added.ends_with("# Clipboard History (added by win11-clipboard-history)\nbindsym $mod+v exec win11-clipboard-history"));
+        // Registering again is a no-op
+        assert!(add_config_binding(&added, &shortcut, &binding, &I3_FORMAT, "test").is_none());
+
+        // The alternate slot leaves the main binding alone
+        let alternate = config("Clipboard History (Alt)", "Ctrl+Alt+V");
+        let alternate_binding = i3_binding(&alternate);
+        assert!(
+            remove_config_binding(&added, &alternate, &alternate_binding, &I3_FORMAT).is_none()
+        );
+
+        let removed = remove_config_binding(&added, &shortcut, &binding, &I3_FORMAT).unwrap();
+        assert_eq!(removed.trim_end(), original);
+    }
+
+    #[test]
+    fn test_registration_outcome() {
+        let shortcut = config("Clipboard History", "Super+V");
+        let binding = BspwmHandler::binding(&shortcut);
+        let added = add_config_binding("", &shortcut, &binding, &SXHKD_FORMAT, "test").unwrap();
+        assert!(has_config_binding(&added, &binding));
+        // A commented-out copy doesn't count
+        assert!(!has_config_binding(
+            "# super + v\n#\twin11-clipboard-history",
+            &binding
+        ));
+
+        let json = |outcome| serde_json::to_value(outcome).unwrap();
+        assert_eq!(
+            json(RegistrationOutcome::AlreadyPresent),
+            serde_json::json!({ "status": "already_present" })
+        );
+        assert_eq!(
+            json(RegistrationOutcome::NeedsReload("Reload sxhkd".into())),
+            serde_json::json!({ "status": "needs_reload", "detail": "Reload sxhkd" })
+        );
+        assert_eq!(
+            json(RegistrationOutcome::Failed(
+                ShortcutError::DependencyMissing("gsettings".into())
+            )),
+            serde_json::json!({ "status": "failed", "detail": "Missing dependency: gsettings" })
+        );
+    }
+
+    #[test]
+    fn test_block_and_multiline_bindings() {
+        let shortcut = config("Clipboard History", "Super+V");
+
+        // niri: inserted at the top of the binds block, conflicts commented with //
+        let niri = "input {\n}\n\nbinds {\n    Mod+V { toggle-window-floating; }\n}";
+        let binding = NiriHandler::binding(&shortcut);
+        assert_eq!(binding, r#"    Mod+V { spawn "win11-clipboard-history"; }"#);
+        let added = add_config_binding(niri, &shortcut, &binding, &NIRI_FORMAT, "test").unwrap();
+        assert!(added.contains(&format!(
+            "binds {{\n    // Clipboard History (added by win11-clipboard-history)\n{}\n",
+            binding
+        )));
+        assert!(added.contains("//     Mod+V { toggle-window-floating; } // Commented by"));
+        let removed = remove_config_binding(&added, &shortcut, &binding, &NIRI_FORMAT).unwrap();
+        assert_eq!(removed, niri);
+
+        // sxhkd: the command under a conflicting chord is commented and restored with it
+        let sxhkd = "super + v\n\tbspc node -t floating\n\nsuper + Return\n\talacritty";
+        let binding = BspwmHandler::binding(&shortcut);
+        let added = add_config_binding(sxhkd, &shortcut, &binding, &SXHKD_FORMAT, "test").unwrap();
+        assert!(added.starts_with("# super + v # Commented by win11-clipboard-history\n# \tbspc"));
+        assert!(!added.contains("# \talacritty"));
+        assert!(added.ends_with("\nsuper + v\n\twin11-clipboard-history"));
+        let removed = remove_config_binding(&added, &shortcut, &binding, &SXHKD_FORMAT).unwrap();
+        assert_eq!(removed.trim_end(), sxhkd);
+
+        // Wayfire: the [command] section is created when missing
+        let binding = WayfireHandler::binding(&shortcut);
+        let added = add_config_binding(
+            "[core]\nplugins = command",
+            &shortcut,
+            &binding,
+            &WAYFIRE_FORMAT,
+            "test",
+        )
+        .unwrap();
+        assert!(added.ends_with(
+            "\n[command]\n# Clipboard History (added by win11-clipboard-history)\n\
+             binding_win11_clipboard_history = <super> KEY_V\n\
+             command_win11_clipboard_history = win11-clipboard-history"
+        ));
+        assert!(HYPRLAND_FORMAT.binds("bindl=SUPER,v,exec,foo", &shortcut.chord));
+        assert!(!HYPRLAND_FORMAT.binds("bind = SUPER SHIFT, V, exec, foo", &shortcut.chord));
+    }
+
+    #[test]
+    fn test_action_configs() {
+        use crate::shortcut_chord::{ActionBinding, ShortcutAction};
+
+        let bindings = ShortcutBindings {
+            alternate: String::new(),
+            actions: vec![
+                ActionBinding {
+                    action: ShortcutAction::PasteItem { index: 2 },
+                    chord: "Super+2".to_string(),
+                },
+                ActionBinding {
+                    action: ShortcutAction::TogglePause,
+                    chord: "Super+Bogus".to_string(),
+                },
+            ],
+            ..ShortcutBindings::default()
+        };
+        let configs = shortcut_configs(&bindings);
+        // The empty alternate and the unparsable chord are skipped
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[1].id, "win11-clipboard-history-paste-2");
+        assert!(configs[1].command.ends_with(" --paste-item 2"));
+        assert_eq!(configs[1].chord.i3(), "$mod+2");
+        // The conflict detector suggests bindings under the same ids
+        let ids: Vec<String> = bindings.chords().into_iter().map(|c| c.id).collect();
+        assert_eq!(
+            ids,
+            configs.iter().map(|c| c.id.clone()).collect::<Vec<_>>()
+        );
+    }
 }
@@ -9,21 +9,42 @@ use tauri::{
     image::Image,
     menu::{Menu, MenuItem},
     tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Emitter, Manager, Monitor, PhysicalPosition, PhysicalSize, State, WebviewWindow,
-    WindowEvent,
+    AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, State, WebviewWindow, WindowEvent,
 };
 use win11_clipboard_history_lib::autostart_manager;
-use win11_clipboard_history_lib::clipboard_manager::{ClipboardItem, ClipboardManager};
-use win11_clipboard_history_lib::config_manager::{resolve_window_position, ConfigManager};
+#[cfg(target_os = "linux")]
+use win11_clipboard_history_lib::caret_locator::CaretLookup;
+use win11_clipboard_history_lib::cli::{self, CliCommand};
+use win11_clipboard_history_lib::clipboard_importers::{self, ImportSource};
+use win11_clipboard_history_lib::clipboard_manager::{
+    ClipboardContent, ClipboardItem, ClipboardManager, ImportSummary,
+};
+use win11_clipboard_history_lib::clipboard_rules;
+use win11_clipboard_history_lib::clipboard_snapshot::{ClipboardSnapshot, RESTORE_DELAY};
+use win11_clipboard_history_lib::config_backup;
+use win11_clipboard_history_lib::config_manager::{
+    layout_fingerprint, resolve_placement, ConfigManager, MonitorArea, PlacementSources,
+    ScreenRect, WindowPlacement,
+};
 use win11_clipboard_history_lib::emoji_manager::{EmojiManager, EmojiUsage};
 #[cfg(target_os = "linux")]
 use win11_clipboard_history_lib::focus_manager::x11_robust_activate;
 use win11_clipboard_history_lib::focus_manager::{restore_focused_window, save_focused_window};
-use win11_clipboard_history_lib::input_simulator::simulate_paste_keystroke;
+use win11_clipboard_history_lib::history_store::{HistoryStore, HistoryStoreStatus, StoreConfig};
+use win11_clipboard_history_lib::history_transfer::{
+    self, ExportOptions, ExportSummary, ImportMode, ImportReport,
+};
+use win11_clipboard_history_lib::input_simulator::{self, simulate_paste_keystroke};
 use win11_clipboard_history_lib::permission_checker;
+use win11_clipboard_history_lib::privacy_policy;
 use win11_clipboard_history_lib::session::is_wayland;
+#[cfg(target_os = "linux")]
+use win11_clipboard_history_lib::shortcut_chord::ShortcutAction;
 use win11_clipboard_history_lib::shortcut_setup;
-use win11_clipboard_history_lib::user_settings::{UserSettings, UserSettingsManager};
+use win11_clipboard_history_lib::uninstall;
+use win11_clipboard_history_lib::user_settings::{
+    CustomKaomoji, UserSettings, UserSettingsManager,
+};
 
 /// Global flag to track if we started in background mode
 /// This is used to block the initial window show
@@ -34,14 +55,48 @@ static STARTED_IN_BACKGROUND: AtomicBool = AtomicBool::new(false);
 /// After the first user toggle, this is set to true to allow normal show/hide behavior
 static INITIAL_SHOW_ALLOWED: AtomicBool = AtomicBool::new(false);
 
+/// Wait before a quick paste, so the shortcut's keys are released first
+const QUICK_PASTE_DELAY: Duration = Duration::from_millis(250);
+
+/// How long after a shortcut press the same press arriving the other way is ignored
+const SHORTCUT_ECHO_WINDOW: Duration = Duration::from_millis(1500);
+
+/// Last shortcut press: when, its command line arguments, and whether the portal sent it
+static LAST_SHORTCUT_PRESS: Mutex<Option<(std::time::Instant, Vec<String>, bool)>> =
+    Mutex::new(None);
+
+/// With the portal bound, the desktop config binding is kept so the shortcut still starts
+/// the app once it has quit. While it runs, one press can then arrive twice: from the
+/// portal and from the launched instance. Returns true for the second one.
+fn is_shortcut_echo(args: &[String], from_portal: bool) -> bool {
+    let now = std::time::Instant::now();
+    let mut last = LAST_SHORTCUT_PRESS.lock();
+    let echo = last
+        .as_ref()
+        .is_some_and(|(at, last_args, last_from_portal)| {
+            *last_from_portal != from_portal
+                && last_args == args
+                && now.duration_since(*at) < SHORTCUT_ECHO_WINDOW
+        });
+    *last = (!echo).then(|| (now, args.to_vec(), from_portal));
+    echo
+}
+
 /// Application state shared across all handlers
 pub struct AppState {
     clipboard_manager: Arc<Mutex<ClipboardManager>>,
     emoji_manager: Arc<Mutex<EmojiManager>>,
     config_manager: Arc<Mutex<ConfigManager>>,
+    history_store: Arc<Mutex<HistoryStore>>,
     is_mouse_inside: Arc<AtomicBool>,
+    /// Restore the previous clipboard contents after pasting
+    restore_clipboard: Arc<AtomicBool>,
 }
 
+/// Keeps the D-Bus connection serving CLI subcommands open
+#[cfg(target_os = "linux")]
+struct CliChannel(#[allow(dead_code)] zbus::blocking::Connection);
+
 // --- Commands ---
 
 #[tauri::command]
@@ -68,6 +123,418 @@ fn toggle_pin(state: State<AppState>, id: String) -> Option<ClipboardItem> {
     result
 }
 
+// --- History Export / Import Commands ---
+
+#[tauri::command]
+fn export_history(
+    state: State<AppState>,
+    path: String,
+    options: ExportOptions,
+) -> Result<ExportSummary, String> {
+    export_history_to(&state, std::path::Path::new(&path), &options)
+}
+
+#[tauri::command]
+fn import_history(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+    mode: ImportMode,
+) -> Result<ImportReport, String> {
+    import_history_from(&app, &state, std::path::Path::new(&path), mode)
+}
+
+/// Imports history from another clipboard manager (CopyQ, GPaste, Klipper, ...)
+#[tauri::command]
+fn import_from_manager(
+    app: AppHandle,
+    state: State<AppState>,
+    source: ImportSource,
+    path: Option<String>,
+    mode: ImportMode,
+) -> Result<ImportSummary, String> {
+    let path = path
+        .filter(|p| !p.trim().is_empty())
+        .map(std::path::PathBuf::from);
+    import_from_manager_at(&app, &state, source, path.as_deref(), mode)
+}
+
+fn export_history_to(
+    state: &AppState,
+    path: &std::path::Path,
+    options: &ExportOptions,
+) -> Result<ExportSummary, String> {
+    let history = state.clipboard_manager.lock().get_history();
+    let settings = UserSettingsManager::new().load();
+
+    let export = history_transfer::build_export(&history, &settings.custom_kaomojis, options);
+    history_transfer::write_export(export, path, options.bundle)
+}
+
+fn import_history_from(
+    app: &AppHandle,
+    state: &AppState,
+    path: &std::path::Path,
+    mode: ImportMode,
+) -> Result<ImportReport, String> {
+    let export = history_transfer::read_export(path)?;
+    let summary = import_items_into(app, state, export.items, mode);
+
+    let (custom_kaomojis, settings) = import_custom_kaomojis(export.custom_kaomojis, mode)?;
+    if let Some(settings) = settings {
+        let _ = app.emit("app-settings-changed", &settings);
+    }
+
+    Ok(ImportReport {
+        history: summary,
+        custom_kaomojis,
+    })
+}
+
+/// Merges imported kaomojis into the user settings (where custom kaomojis live).
+/// Returns how many were added, and the saved settings if they changed.
+fn import_custom_kaomojis(
+    kaomojis: Vec<CustomKaomoji>,
+    mode: ImportMode,
+) -> Result<(usize, Option<UserSettings>), String> {
+    let settings_manager = UserSettingsManager::new();
+    let mut settings = settings_manager.load();
+    let had_kaomojis = !settings.custom_kaomojis.is_empty();
+    let added =
+        history_transfer::merge_custom_kaomojis(&mut settings.custom_kaomojis, kaomojis, mode);
+    if added == 0 && !(mode == ImportMode::Replace && had_kaomojis) {
+        return Ok((0, None));
+    }
+    settings_manager.save(&settings)?;
+    Ok((added, Some(settings)))
+}
+
+fn import_from_manager_at(
+    app: &AppHandle,
+    state: &AppState,
+    source: ImportSource,
+    path: Option<&std::path::Path>,
+    mode: ImportMode,
+) -> Result<ImportSummary, String> {
+    let items = clipboard_importers::read_source(source, path)?;
+    Ok(import_items_into(app, state, items, mode))
+}
+
+/// Adds imported items to history and notifies the frontend
+fn import_items_into(
+    app: &AppHandle,
+    state: &AppState,
+    items: Vec<ClipboardItem>,
+    mode: ImportMode,
+) -> ImportSummary {
+    let (summary, history) = {
+        let mut manager = state.clipboard_manager.lock();
+        let summary = manager.import_items(items, mode == ImportMode::Replace);
+        (summary, manager.get_history())
+    };
+
+    let _ = app.emit("history-sync", &history);
+    summary
+}
+
+// --- History Storage Commands ---
+
+#[tauri::command]
+fn get_history_store_status(state: State<AppState>) -> HistoryStoreStatus {
+    state.history_store.lock().status()
+}
+
+/// Unlocks encrypted history (passphrase, or key file / keyring when omitted)
+/// and merges it into the current history
+#[tauri::command]
+fn unlock_history(
+    app: AppHandle,
+    state: State<AppState>,
+    passphrase: Option<String>,
+) -> Result<HistoryStoreStatus, String> {
+    {
+        let mut store = state.history_store.lock();
+        match passphrase.as_deref() {
+            Some(passphrase) => store.unlock_with_passphrase(passphrase)?,
+            None => store.unlock_without_prompt()?,
+        }
+    }
+    load_stored_history(&app, &state)?;
+
+    let status = state.history_store.lock().status();
+    let _ = app.emit("history-store-changed", &status);
+    Ok(status)
+}
+
+/// Saves encrypted history, forgets the key and clears history from memory
+#[tauri::command]
+fn lock_history(app: AppHandle, state: State<AppState>) -> Result<HistoryStoreStatus, String> {
+    let history = state.clipboard_manager.lock().get_history();
+    let status = {
+        let mut store = state.history_store.lock();
+        if !store.config().persist || !store.config().encrypt {
+            return Err("History encryption is not enabled".to_string());
+        }
+        store.save(&history)?;
+        store.lock();
+        store.status()
+    };
+
+    state.clipboard_manager.lock().clear_all();
+    let _ = app.emit("history-sync", Vec::<ClipboardItem>::new());
+    let _ = app.emit("history-store-changed", &status);
+    Ok(status)
+}
+
+/// Merges stored history into memory, once, as soon as the store can be read
+fn load_stored_history(app: &AppHandle, state: &AppState) -> Result<(), String> {
+    let items = {
+        let mut store = state.history_store.lock();
+        if store.is_loaded() || store.is_locked() {
+            return Ok(());
+        }
+        store.load()?
+    };
+
+    import_items_into(app, state, items, ImportMode::Merge);
+    Ok(())
+}
+
+/// Writes history to disk immediately (used before quitting)
+fn save_history_now(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let history = state.clipboard_manager.lock().get_history();
+    let result = state.history_store.lock().save(&history);
+    if let Err(e) = result {
+        eprintln!("[HistoryStore] Failed to save history: {}", e);
+    }
+}
+
+/// Applies clear-on-quit and writes history to disk; runs on every exit path
+fn on_exit(app: &AppHandle) {
+    let clear_on_quit = app
+        .state::<AppState>()
+        .clipboard_manager
+        .lock()
+        .privacy()
+        .clear_on_quit;
+    if clear_on_quit {
+        clear_unpinned_history(app, "quit");
+    }
+    save_history_now(app);
+}
+
+/// Clears unpinned history because of a privacy policy (lock, idle, quit)
+fn clear_unpinned_history(app: &AppHandle, reason: &str) {
+    println!("[PrivacyPolicy] Clearing history ({})", reason);
+    app.state::<AppState>().clipboard_manager.lock().clear();
+    let _ = app.emit("history-cleared", ());
+}
+
+fn export_message(summary: &ExportSummary) -> String {
+    format!(
+        "Exported {} item(s) ({} image(s)) to {}",
+        summary.items, summary.images, summary.path
+    )
+}
+
+fn import_history_message(path: &std::path::Path, report: &ImportReport) -> String {
+    format!(
+        "Imported {} item(s) from {} ({} duplicate(s) skipped, {} kaomoji(s))",
+        report.history.added,
+        path.display(),
+        report.history.duplicates,
+        report.custom_kaomojis
+    )
+}
+
+fn import_from_message(source: ImportSource, summary: &ImportSummary) -> String {
+    format!(
+        "Imported {} item(s) from {} ({} duplicate(s) skipped)",
+        summary.added,
+        source.display_name(),
+        summary.duplicates
+    )
+}
+
+/// Executes a CLI subcommand inside the running instance.
+/// Returns the message for the user who ran it (empty for actions without a report).
+fn run_cli_command(app: &AppHandle, command: CliCommand) -> Result<String, String> {
+    let state = app.state::<AppState>();
+
+    match command {
+        CliCommand::ExportHistory { path, options } => {
+            export_history_to(&state, &path, &options).map(|summary| export_message(&summary))
+        }
+        CliCommand::ImportHistory { path, mode } => import_history_from(app, &state, &path, mode)
+            .map(|report| import_history_message(&path, &report)),
+        CliCommand::ImportFrom { source, path, mode } => {
+            import_from_manager_at(app, &state, source, path.as_deref(), mode)
+                .map(|summary| import_from_message(source, &summary))
+                .map_err(|e| format!("Import from {} failed: {}", source.display_name(), e))
+        }
+        CliCommand::AbortTyping => {
+            input_simulator::abort_typing();
+            Ok(String::new())
+        }
+        CliCommand::PasteItem { index } => {
+            let item = state.clipboard_manager.lock().item_at(index).cloned();
+            let item = item.ok_or_else(|| format!("History has no item {}", index))?;
+            quick_paste(app, item);
+            Ok(String::new())
+        }
+        CliCommand::PastePlain => {
+            // History text is stored without formatting, so it is pasted as text/plain only
+            let item = state.clipboard_manager.lock().latest_text_item().cloned();
+            quick_paste(app, item.ok_or("History has no text item")?);
+            Ok(String::new())
+        }
+        CliCommand::OpenTab { tab } => {
+            let _ = app.emit("open-tab", &tab);
+            WindowController::show(app);
+            Ok(String::new())
+        }
+        CliCommand::TogglePause => {
+            let paused = !state.clipboard_manager.lock().is_paused();
+            set_recording_paused_inner(app, &state, paused);
+            Ok(String::new())
+        }
+    }
+}
+
+/// Runs a CLI subcommand and logs its outcome (commands without a waiting caller)
+fn run_cli_command_logged(app: &AppHandle, command: CliCommand) {
+    match run_cli_command(app, command) {
+        Ok(message) if !message.is_empty() => println!("[CLI] {}", message),
+        Ok(_) => {}
+        Err(e) => eprintln!("[CLI] {}", e),
+    }
+}
+
+/// Runs an export/import subcommand when no instance is running, on the history stored
+/// on disk. The app isn't started: its history would be empty.
+fn run_history_command_offline(
+    base_dir: &std::path::Path,
+    command: CliCommand,
+) -> Result<String, String> {
+    let settings = UserSettingsManager::new().load();
+    let mut store = HistoryStore::new(base_dir, StoreConfig::from_settings(&settings));
+    if !store.config().persist {
+        return Err(
+            "Clipboard History is not running, and history is only kept while it runs".to_string(),
+        );
+    }
+    if store.is_locked() {
+        store
+            .unlock_without_prompt()
+            .map_err(|e| format!("Stored history is locked: {}", e))?;
+    }
+
+    let mut manager = ClipboardManager::new();
+    manager.import_items(store.load()?, true);
+
+    let message = match command {
+        CliCommand::ExportHistory { path, options } => {
+            let export = history_transfer::build_export(
+                &manager.get_history(),
+                &settings.custom_kaomojis,
+                &options,
+            );
+            return history_transfer::write_export(export, &path, options.bundle)
+                .map(|summary| export_message(&summary));
+        }
+        CliCommand::ImportHistory { path, mode } => {
+            let export = history_transfer::read_export(&path)?;
+            let history = manager.import_items(export.items, mode == ImportMode::Replace);
+            let (custom_kaomojis, _) = import_custom_kaomojis(export.custom_kaomojis, mode)?;
+            import_history_message(
+                &path,
+                &ImportReport {
+                    history,
+                    custom_kaomojis,
+                },
+            )
+        }
+        CliCommand::ImportFrom { source, path, mode } => {
+            let items = clipboard_importers::read_source(source, path.as_deref())
+                .map_err(|e| format!("Import from {} failed: {}", source.display_name(), e))?;
+            let summary = manager.import_items(items, mode == ImportMode::Replace);
+            import_from_message(source, &summary)
+        }
+        _ => return Err("This command needs a running instance".to_string()),
+    };
+
+    store.save(&manager.get_history())?;
+    Ok(message)
+}
+
+/// Runs a shortcut pressed through the GlobalShortcuts portal, as its command line would
+#[cfg(target_os = "linux")]
+fn run_shortcut_action(app: &AppHandle, action: Option<&ShortcutAction>) {
+    if is_shortcut_echo(&action.map(|a| a.cli_args()).unwrap_or_default(), true) {
+        return;
+    }
+    let Some(action) = action else {
+        WindowController::toggle(app);
+        return;
+    };
+    let args: Vec<String> = std::iter::once(String::new())
+        .chain(action.cli_args())
+        .collect();
+    match cli::parse_cli_command(&args, std::path::Path::new("/")) {
+        Some(Ok(command)) => run_cli_command_logged(app, command),
+        Some(Err(e)) => eprintln!("[Shortcut] Invalid action: {}", e),
+        None => {}
+    }
+}
+
+/// Pastes a history item into the focused window without opening the history
+/// (quick-paste shortcuts). The previous clipboard is restored afterwards.
+fn quick_paste(app: &AppHandle, item: ClipboardItem) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let snapshot = PasteHelper::snapshot_clipboard(&state).await;
+
+        let window_visible = app
+            .get_webview_window("main")
+            .is_some_and(|window| window.is_visible().unwrap_or(false));
+        if window_visible {
+            WindowController::hide(&app);
+            let _ = PasteHelper::prepare_target_window().await;
+        } else {
+            // Give the user time to release the shortcut's modifiers,
+            // which would otherwise combine with the paste chord
+            tokio::time::sleep(QUICK_PASTE_DELAY).await;
+        }
+
+        let result = state.clipboard_manager.lock().paste_item(&item);
+        match result {
+            Ok(()) => PasteHelper::restore_clipboard(&state, snapshot).await,
+            Err(e) => eprintln!("[CLI] Quick paste failed: {}", e),
+        }
+    });
+}
+
+fn set_recording_paused_inner(app: &AppHandle, state: &AppState, paused: bool) {
+    state.clipboard_manager.lock().set_paused(paused);
+    println!(
+        "[Clipboard] Recording {}",
+        if paused { "paused" } else { "resumed" }
+    );
+    let _ = app.emit("recording-paused-changed", paused);
+}
+
+#[tauri::command]
+fn get_recording_paused(state: State<AppState>) -> bool {
+    state.clipboard_manager.lock().is_paused()
+}
+
+#[tauri::command]
+fn set_recording_paused(app: AppHandle, state: State<AppState>, paused: bool) {
+    set_recording_paused_inner(&app, &state, paused);
+}
+
 #[tauri::command]
 fn get_recent_emojis(state: State<AppState>) -> Vec<EmojiUsage> {
     state.emoji_manager.lock().get_recent()
@@ -87,10 +554,55 @@ fn get_user_settings() -> Result<UserSettings, String> {
 }
 
 #[tauri::command]
-fn set_user_settings(app: AppHandle, new_settings: UserSettings) -> Result<(), String> {
+fn set_user_settings(
+    app: AppHandle,
+    state: State<AppState>,
+    new_settings: UserSettings,
+) -> Result<(), String> {
+    new_settings.shortcuts.validate()?;
+
+    // Storage changes go first: they are refused while encrypted history is still locked
+    let history = state.clipboard_manager.lock().get_history();
+    let store_status = {
+        let mut store = state.history_store.lock();
+        store.reconfigure(StoreConfig::from_settings(&new_settings), &history)?;
+        store.status()
+    };
+    // Persistence may have just been enabled: merge history from an earlier session
+    if let Err(e) = load_stored_history(&app, &state) {
+        eprintln!("[HistoryStore] {}", e);
+    }
+    let _ = app.emit("history-store-changed", &store_status);
+
     let manager = UserSettingsManager::new();
+    #[cfg(target_os = "linux")]
+    let old_shortcuts = manager.load().shortcuts;
     manager.save(&new_settings)?;
 
+    // Move the desktop environment shortcuts to the new key combos
+    #[cfg(target_os = "linux")]
+    if old_shortcuts != new_settings.shortcuts {
+        let new_shortcuts = new_settings.shortcuts.clone();
+        std::thread::spawn(move || {
+            win11_clipboard_history_lib::linux_shortcut_manager::rebind_global_shortcut(
+                &old_shortcuts,
+                &new_shortcuts,
+            );
+        });
+    }
+
+    // Apply new clipboard rules and privacy policy immediately
+    {
+        let mut manager = state.clipboard_manager.lock();
+        manager.set_rules(&new_settings.clipboard_rules);
+        manager.set_privacy(&new_settings.privacy);
+    }
+    state.restore_clipboard.store(
+        new_settings.restore_clipboard_after_paste,
+        Ordering::Relaxed,
+    );
+    input_simulator::set_paste_settings(&new_settings.paste);
+
     // Emit event to notify all windows that settings have changed
     app.emit("app-settings-changed", &new_settings)
         .map_err(|e| format!("Failed to emit settings changed event: {}", e))?;
@@ -115,13 +627,21 @@ async fn paste_item(app: AppHandle, state: State<'_, AppState>, id: String) -> R
 
     match item {
         Some(item) => {
+            let snapshot = PasteHelper::snapshot_clipboard(&state).await;
+
             // 2. Prepare Environment (Hide Window -> Restore Focus)
             WindowController::hide(&app);
             PasteHelper::prepare_target_window().await?;
 
             // 3. Perform Paste
-            let mut manager = state.clipboard_manager.lock();
-            manager.paste_item(&item).map_err(|e| e.to_string())?;
+            state
+                .clipboard_manager
+                .lock()
+                .paste_item(&item)
+                .map_err(|e| e.to_string())?;
+
+            // 4. Give the previous clipboard contents back
+            PasteHelper::restore_clipboard(&state, snapshot).await;
         }
         None => {
             eprintln!(
@@ -144,6 +664,7 @@ async fn paste_emoji(
     char: String,
 ) -> Result<(), String> {
     state.emoji_manager.lock().record_usage(&char);
+    let snapshot = PasteHelper::snapshot_clipboard(&state).await;
 
     // 1. Prepare Environment
     WindowController::hide(&app);
@@ -164,6 +685,9 @@ async fn paste_emoji(
     // 3. Simulate Paste (Manual trigger required for emoji)
     simulate_paste_keystroke().map_err(|e| e.to_string())?;
 
+    // 4. Give the previous clipboard contents back
+    PasteHelper::restore_clipboard(&state, snapshot).await;
+
     Ok(())
 }
 
@@ -173,6 +697,9 @@ async fn paste_gif_from_url(
     state: State<'_, AppState>,
     url: String,
 ) -> Result<(), String> {
+    // Taken before the download overwrites the clipboard
+    let snapshot = PasteHelper::snapshot_clipboard(&state).await;
+
     // 1. Download (Blocking) - Window stays open to show loading if UI supports it
     let url_clone = url.clone();
     let file_uri = tokio::task::spawn_blocking(move || {
@@ -198,9 +725,58 @@ async fn paste_gif_from_url(
     // The clipboard is already set by paste_gif_to_clipboard_with_uri, we just need to paste
     simulate_paste_keystroke().map_err(|e| e.to_string())?;
 
+    // 4. Give the previous clipboard contents back
+    PasteHelper::restore_clipboard(&state, snapshot).await;
+
     Ok(())
 }
 
+/// Types a text item into the previous window key by key, for fields that block pasting.
+/// The abort shortcut is grabbed only while typing.
+#[tauri::command]
+async fn type_item(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<(), String> {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+    let text = {
+        let manager = state.clipboard_manager.lock();
+        match manager.get_item(&id).map(|item| &item.content) {
+            Some(ClipboardContent::Text(text)) => text.clone(),
+            Some(_) => return Err("Only text items can be typed".to_string()),
+            None => return Err(format!("Item '{}' not found", id)),
+        }
+    };
+
+    WindowController::hide(&app);
+    PasteHelper::prepare_target_window().await?;
+
+    let shortcut = input_simulator::paste_settings().type_abort_shortcut;
+    let shortcuts = app.global_shortcut();
+    let abort_registered = !shortcut.is_empty()
+        && shortcuts
+            .on_shortcut(shortcut.as_str(), |_app, _shortcut, event| {
+                if event.state == ShortcutState::Pressed {
+                    input_simulator::abort_typing();
+                }
+            })
+            .map_err(|e| {
+                eprintln!(
+                    "[type_item] Abort shortcut '{}' unavailable: {}",
+                    shortcut, e
+                )
+            })
+            .is_ok();
+
+    let result = tokio::task::spawn_blocking(move || input_simulator::simulate_typing(&text))
+        .await
+        .map_err(|e| e.to_string());
+
+    if abort_registered {
+        let _ = shortcuts.unregister(shortcut.as_str());
+    }
+
+    result?
+}
+
 #[tauri::command]
 async fn finish_paste(app: AppHandle) -> Result<(), String> {
     WindowController::hide(&app);
@@ -223,10 +799,66 @@ impl PasteHelper {
         tokio::time::sleep(Duration::from_millis(100)).await;
         Ok(())
     }
+
+    /// Captures the clipboard before a paste, if the user wants it restored afterwards
+    async fn snapshot_clipboard(state: &AppState) -> Option<ClipboardSnapshot> {
+        if !state.restore_clipboard.load(Ordering::Relaxed) {
+            return None;
+        }
+        match tokio::task::spawn_blocking(ClipboardSnapshot::capture).await {
+            Ok(Ok(snapshot)) => Some(snapshot),
+            Ok(Err(e)) => {
+                eprintln!("[PasteHelper] Clipboard snapshot failed: {}", e);
+                None
+            }
+            Err(e) => {
+                eprintln!("[PasteHelper] Clipboard snapshot task failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Puts the snapshot back once the target app has had time to read the pasted content.
+    /// The manager stays locked while restoring so the watcher can't record it as a new copy.
+    async fn restore_clipboard(state: &AppState, snapshot: Option<ClipboardSnapshot>) {
+        let Some(snapshot) = snapshot else {
+            return;
+        };
+        tokio::time::sleep(RESTORE_DELAY).await;
+
+        let clipboard_manager = state.clipboard_manager.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut manager = clipboard_manager.lock();
+            manager.mark_snapshot_restored(&snapshot);
+            snapshot.restore()
+        })
+        .await;
+
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("[PasteHelper] Clipboard restore failed: {}", e),
+            Err(e) => eprintln!("[PasteHelper] Clipboard restore task failed: {}", e),
+        }
+    }
 }
 
 // --- Window Controller (Visibility & Positioning) ---
 
+/// The caret is only looked up through AT-SPI on Linux
+#[cfg(not(target_os = "linux"))]
+struct CaretLookup;
+
+#[cfg(not(target_os = "linux"))]
+impl CaretLookup {
+    fn start() -> Self {
+        Self
+    }
+
+    fn wait(self) -> Option<ScreenRect> {
+        None
+    }
+}
+
 struct WindowController;
 
 impl WindowController {
@@ -241,8 +873,24 @@ impl WindowController {
             if window.is_visible().unwrap_or(false) {
                 let _ = window.hide();
             } else {
+                let caret = Self::start_caret_lookup();
+                save_focused_window();
+                Self::position_and_show(&window, app, caret);
+            }
+        }
+    }
+
+    /// Shows the window if hidden; unlike `toggle`, never hides it
+    pub fn show(app: &AppHandle) {
+        if STARTED_IN_BACKGROUND.load(Ordering::SeqCst) {
+            INITIAL_SHOW_ALLOWED.store(true, Ordering::SeqCst);
+        }
+
+        if let Some(window) = app.get_webview_window("main") {
+            if !window.is_visible().unwrap_or(false) {
+                let caret = Self::start_caret_lookup();
                 save_focused_window();
-                Self::position_and_show(&window, app);
+                Self::position_and_show(&window, app, caret);
             }
         }
     }
@@ -251,22 +899,15 @@ impl WindowController {
         if let Some(window) = app.get_webview_window("main") {
             // FLUSH CONFIG TO DISK ON HIDE
             if let Some(state) = app.try_state::<AppState>() {
-                if is_wayland() {
-                    state.config_manager.lock().sync_to_disk();
-                }
+                state.config_manager.lock().sync_to_disk();
             }
             let _ = window.hide();
         }
     }
 
-    fn position_and_show(window: &WebviewWindow, app: &AppHandle) {
+    fn position_and_show(window: &WebviewWindow, app: &AppHandle, caret: Option<CaretLookup>) {
         let state = app.state::<AppState>();
-
-        if is_wayland() {
-            Self::position_for_wayland(window, &state);
-        } else {
-            Self::position_for_non_wayland(window);
-        }
+        Self::position(window, &state, caret);
 
         #[cfg(target_os = "linux")]
         let is_wayland_session = is_wayland();
@@ -333,67 +974,68 @@ impl WindowController {
         }
     }
 
-    fn position_for_wayland(window: &WebviewWindow, state: &State<AppState>) {
-        let config = state.config_manager.lock();
-
-        if let Ok(monitors) = window.available_monitors() {
-            if !monitors.is_empty() {
-                let win_size = window.outer_size().unwrap_or(PhysicalSize::new(360, 480));
+    /// Placement setting for the current session
+    fn placement_mode() -> WindowPlacement {
+        UserSettingsManager::new()
+            .load()
+            .window_placement
+            .for_session(is_wayland())
+    }
 
-                let window_state = config.get_state();
-                let pos = resolve_window_position(&window_state, &monitors, win_size);
+    /// Starts looking for the caret when the window opens there, so the lookup runs
+    /// while the focused window is saved. Wayland has no global coordinates for it.
+    fn start_caret_lookup() -> Option<CaretLookup> {
+        (!is_wayland() && Self::placement_mode() == WindowPlacement::Caret).then(CaretLookup::start)
+    }
 
-                let _ = window.set_position(pos);
-            }
+    /// Places the window for the placement setting, at the size remembered for the monitor
+    /// it opens on. Wayland has no global coordinates, so caret and mouse placement fall back
+    /// to the active monitor there, which is the focused output where the compositor
+    /// reports it.
+    fn position(window: &WebviewWindow, state: &State<AppState>, caret: Option<CaretLookup>) {
+        let areas = monitor_areas(window);
+        if areas.is_empty() {
+            return;
         }
-    }
 
-    fn position_for_non_wayland(window: &WebviewWindow) {
-        let (cursor_x, cursor_y) = match Self::get_cursor_position(window) {
-            Some(pos) => pos,
-            None => {
-                // Fallback: center the window if we can't get cursor position
-                let _ = window.center();
-                return;
+        let wayland = is_wayland();
+        let mode = Self::placement_mode();
+        let sources = if wayland {
+            PlacementSources {
+                focused_output: Self::get_focused_output(),
+                ..PlacementSources::default()
+            }
+        } else {
+            PlacementSources {
+                caret: caret.and_then(CaretLookup::wait),
+                cursor: Self::get_cursor_position(window).map(|(x, y)| PhysicalPosition::new(x, y)),
+                focused_output: None,
             }
         };
 
-        let target_monitor = Self::find_monitor_containing(window, cursor_x, cursor_y)
-            .or_else(|| window.current_monitor().ok().flatten())
-            .or_else(|| window.primary_monitor().ok().flatten());
-
-        if let Some(monitor) = target_monitor {
-            let pos = Self::clamp_window_to_monitor(window, &monitor, cursor_x, cursor_y);
-            let _ = window.set_position(pos);
+        let window_state = state.config_manager.lock().get_state();
+        let layout = layout_fingerprint(&areas);
+        let current_size = window.outer_size().unwrap_or(PhysicalSize::new(360, 480));
+        let placement = resolve_placement(mode, &window_state, &sources, &areas, |monitor| {
+            window_state
+                .saved_size(monitor, &layout)
+                .unwrap_or(current_size)
+        });
+        if let Some(size) = window_state.saved_size(placement.monitor, &layout) {
+            let _ = window.set_size(size);
         }
+        let _ = window.set_position(placement.position);
     }
 
-    fn find_monitor_containing(window: &WebviewWindow, x: i32, y: i32) -> Option<Monitor> {
-        window.available_monitors().ok()?.into_iter().find(|m| {
-            let p = m.position();
-            let s = m.size();
-            x >= p.x && x < (p.x + s.width as i32) && y >= p.y && y < (p.y + s.height as i32)
-        })
+    /// Output recorded by `save_focused_window`, which runs just before the window opens
+    #[cfg(target_os = "linux")]
+    fn get_focused_output() -> Option<ScreenRect> {
+        win11_clipboard_history_lib::focus_manager::saved_focused_output()
     }
 
-    fn clamp_window_to_monitor(
-        window: &WebviewWindow,
-        monitor: &Monitor,
-        x: i32,
-        y: i32,
-    ) -> PhysicalPosition<i32> {
-        let win_size = window.outer_size().unwrap_or(PhysicalSize::new(360, 480));
-        let m_pos = monitor.position();
-        let m_size = monitor.size();
-
-        let max_x = m_pos.x + m_size.width as i32 - win_size.width as i32;
-        let max_y = m_pos.y + m_size.height as i32 - win_size.height as i32;
-
-        // Clamp with 10px padding
-        let safe_x = x.clamp(m_pos.x + 10, max_x - 10);
-        let safe_y = y.clamp(m_pos.y + 10, max_y - 10);
-
-        PhysicalPosition::new(safe_x, safe_y)
+    #[cfg(not(target_os = "linux"))]
+    fn get_focused_output() -> Option<ScreenRect> {
+        None
     }
 
     fn get_cursor_position(window: &WebviewWindow) -> Option<(i32, i32)> {
@@ -498,28 +1140,126 @@ impl SettingsController {
 
 // --- Window Event Helper ---
 
-fn handle_window_moved_for_wayland(
-    window: &WebviewWindow,
-    state: &State<AppState>,
-    pos: &PhysicalPosition<i32>,
-) {
-    if !is_wayland() || !window.is_visible().unwrap_or(false) {
+/// Connected monitors, primary first: it is the active monitor when nothing points at
+/// another one
+fn monitor_areas(window: &WebviewWindow) -> Vec<MonitorArea> {
+    let primary = window
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .map(|m| MonitorArea::from(&m));
+    let mut areas: Vec<MonitorArea> = window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(MonitorArea::from)
+        .collect();
+    // Compared whole: identical monitors share a name
+    areas.sort_by_key(|area| Some(area) != primary.as_ref());
+    areas
+}
+
+/// Remembers the window's position and size for the monitor it is on, after the user moves
+/// or resizes it
+fn handle_window_geometry_changed(window: &WebviewWindow, state: &State<AppState>) {
+    if !window.is_visible().unwrap_or(false) {
         return;
     }
 
-    let monitor_name = window
-        .current_monitor()
-        .ok()
-        .flatten()
-        .and_then(|m| m.name().map(|n| n.to_string()));
+    let (Ok(pos), Ok(size)) = (window.outer_position(), window.outer_size()) else {
+        return;
+    };
+    let Some(monitor) = window.current_monitor().ok().flatten() else {
+        return;
+    };
+    let layout = layout_fingerprint(&monitor_areas(window));
 
     let mut config = state.config_manager.lock();
     // UPDATE MEMORY ONLY (No Disk I/O here)
-    config.update_state(monitor_name, pos.x, pos.y);
+    config.update_state(&MonitorArea::from(&monitor), &layout, pos, size);
 }
 
 // --- Background Listeners ---
 
+/// Saves history shortly after it changes (a no-op unless persistence is enabled)
+fn start_history_saver(
+    clipboard_manager: Arc<Mutex<ClipboardManager>>,
+    history_store: Arc<Mutex<HistoryStore>>,
+) {
+    std::thread::spawn(move || {
+        let mut saved_revision = None;
+
+        loop {
+            std::thread::sleep(Duration::from_secs(2));
+
+            let revision = clipboard_manager.lock().revision();
+            if saved_revision == Some(revision) {
+                continue;
+            }
+
+            let history = clipboard_manager.lock().get_history();
+            match history_store.lock().save(&history) {
+                Ok(()) => saved_revision = Some(revision),
+                Err(e) => eprintln!("[HistoryStore] Failed to save history: {}", e),
+            }
+        }
+    });
+}
+
+/// Removes expired items and applies the clear-on-lock / clear-on-idle policies
+fn start_privacy_monitor(app: AppHandle, clipboard_manager: Arc<Mutex<ClipboardManager>>) {
+    let lock_app = app.clone();
+    let lock_manager = clipboard_manager.clone();
+    privacy_policy::watch_screen_lock(move || {
+        if lock_manager.lock().privacy().clear_on_lock {
+            clear_unpinned_history(&lock_app, "screen locked");
+        }
+    });
+
+    std::thread::spawn(move || {
+        let mut ticks: u32 = 0;
+        let mut cleared_while_idle = false;
+
+        loop {
+            std::thread::sleep(Duration::from_secs(1));
+            ticks = ticks.wrapping_add(1);
+
+            // Expiring items: drop them, and wipe the clipboard if one is still on it
+            let (expired, idle_timeout) = {
+                let mut manager = clipboard_manager.lock();
+                let expired = manager.remove_expired(chrono::Utc::now());
+                if !expired.is_empty() && manager.wipe_system_clipboard_if_holding(&expired) {
+                    println!("[PrivacyPolicy] Wiped expired item from the system clipboard");
+                }
+                (expired, manager.privacy().idle_timeout())
+            };
+            if !expired.is_empty() {
+                println!("[PrivacyPolicy] {} item(s) expired", expired.len());
+                let history = clipboard_manager.lock().get_history();
+                let _ = app.emit("history-sync", &history);
+            }
+
+            // Idle time is only sampled every 30 seconds
+            if ticks % 30 != 0 {
+                continue;
+            }
+            let Some(timeout) = idle_timeout else {
+                cleared_while_idle = false;
+                continue;
+            };
+            match privacy_policy::idle_time() {
+                Some(idle) if idle >= timeout => {
+                    if !cleared_while_idle {
+                        cleared_while_idle = true;
+                        clear_unpinned_history(&app, "idle");
+                    }
+                }
+                _ => cleared_while_idle = false,
+            }
+        }
+    });
+}
+
 fn start_clipboard_watcher(app: AppHandle, clipboard_manager: Arc<Mutex<ClipboardManager>>) {
     std::thread::spawn(move || {
         let mut last_text_hash: Option<u64> = None;
@@ -530,6 +1270,7 @@ fn start_clipboard_watcher(app: AppHandle, clipboard_manager: Arc<Mutex<Clipboar
             let mut manager = clipboard_manager.lock();
 
             // Text
+            let mut new_text = None;
             if let Ok(text) = manager.get_current_text() {
                 if !text.is_empty() {
                     let mut hasher = std::collections::hash_map::DefaultHasher::new();
@@ -539,21 +1280,40 @@ fn start_clipboard_watcher(app: AppHandle, clipboard_manager: Arc<Mutex<Clipboar
                     if Some(text_hash) != last_text_hash {
                         last_text_hash = Some(text_hash);
                         last_image_hash = None;
-                        if let Some(item) = manager.add_text(text) {
-                            let _ = app.emit("clipboard-changed", &item);
-                        }
+                        new_text = Some(text);
                     }
                 }
             }
 
             // Image
+            let mut new_image = None;
             if let Ok(Some((image_data, hash))) = manager.get_current_image() {
                 if Some(hash) != last_image_hash {
                     last_image_hash = Some(hash);
                     last_text_hash = None;
-                    if let Some(item) = manager.add_image(image_data, hash) {
-                        let _ = app.emit("clipboard-changed", &item);
-                    }
+                    new_image = Some((image_data, hash));
+                }
+            }
+
+            if new_text.is_none() && new_image.is_none() {
+                continue;
+            }
+
+            // The source app lookup can wait on the compositor (a KWin script on KDE),
+            // so it runs without holding the manager lock
+            let privacy = manager.privacy().clone();
+            drop(manager);
+            let source_classes = privacy.source_classes();
+            let mut manager = clipboard_manager.lock();
+
+            if let Some(text) = new_text {
+                if let Some(item) = manager.add_text(text, &source_classes) {
+                    let _ = app.emit("clipboard-changed", &item);
+                }
+            }
+            if let Some((image_data, hash)) = new_image {
+                if let Some(item) = manager.add_image(image_data, hash, &source_classes) {
+                    let _ = app.emit("clipboard-changed", &item);
                 }
             }
         }
@@ -585,6 +1345,12 @@ fn main() {
         println!("    -v, --version    Show version information");
         println!("        --background Start minimized to system tray (for autostart)");
         println!("        --settings   Open settings window on startup");
+        println!("        --uninstall-user");
+        println!("                     Remove this user's shortcuts, autostart entry and");
+        println!("                     changes to desktop settings, then exit");
+        println!("            --purge  Also delete history, settings and caches");
+        println!();
+        println!("{}", cli::SUBCOMMAND_HELP);
         println!();
         println!("SHORTCUTS:");
         println!("    Super+V          Open clipboard history");
@@ -592,6 +1358,14 @@ fn main() {
         return;
     }
 
+    // Handle --uninstall-user: runs on its own, without starting the app
+    if args.iter().any(|arg| arg == "--uninstall-user") {
+        let purge = args.iter().any(|arg| arg == "--purge");
+        let report = uninstall::uninstall_user(purge);
+        print!("{}", report);
+        std::process::exit(if report.failed.is_empty() { 0 } else { 1 });
+    }
+
     // Check if --background flag is present (start minimized to tray)
     let start_in_background = args.iter().any(|arg| arg == "--background");
     if start_in_background {
@@ -602,20 +1376,77 @@ fn main() {
     // Check if --settings flag is present (for first instance startup)
     let open_settings_on_start = args.iter().any(|arg| arg == "--settings");
 
+    // Subcommands given to the first instance run once setup has finished
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let startup_command = match cli::parse_cli_command(&args, &cwd) {
+        Some(Ok(command)) => Some(command),
+        Some(Err(e)) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+        None => None,
+    };
+
+    let base_dir = dirs::data_local_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("win11-clipboard-history");
+
+    // Export/import report back to this process: the running instance answers over
+    // D-Bus, otherwise the stored history is used without starting the app
+    if let Some(command) = startup_command.clone().filter(CliCommand::reports_result) {
+        #[cfg(target_os = "linux")]
+        let result = cli::forward(&args, &cwd)
+            .unwrap_or_else(|| run_history_command_offline(&base_dir, command));
+        #[cfg(not(target_os = "linux"))]
+        let result = run_history_command_offline(&base_dir, command);
+
+        match result {
+            Ok(message) => println!("{}", message),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     // Clone for use in setup closure
     let start_in_background_clone = start_in_background;
 
     win11_clipboard_history_lib::session::init();
 
     let is_mouse_inside = Arc::new(AtomicBool::new(false));
+    let restore_clipboard = Arc::new(AtomicBool::new(false));
     let clipboard_manager = Arc::new(Mutex::new(ClipboardManager::new()));
 
-    let base_dir = dirs::data_local_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("win11-clipboard-history");
+    let user_settings = UserSettingsManager::new().load();
+    {
+        let mut manager = clipboard_manager.lock();
+        manager.set_rules(&user_settings.clipboard_rules);
+        manager.set_privacy(&user_settings.privacy);
+    }
+    restore_clipboard.store(
+        user_settings.restore_clipboard_after_paste,
+        Ordering::Relaxed,
+    );
+    input_simulator::set_paste_settings(&user_settings.paste);
 
     let emoji_manager = Arc::new(Mutex::new(EmojiManager::new(base_dir.clone())));
 
+    let history_store = Arc::new(Mutex::new(HistoryStore::new(
+        &base_dir,
+        StoreConfig::from_settings(&user_settings),
+    )));
+    {
+        // Key files and keyring keys unlock without a prompt; passphrases wait for the UI
+        let mut store = history_store.lock();
+        if store.is_locked() {
+            if let Err(e) = store.unlock_without_prompt() {
+                println!("[HistoryStore] History is locked: {}", e);
+            }
+        }
+    }
+
     let config_manager = Arc::new(Mutex::new(ConfigManager::new(base_dir)));
 
     tauri::Builder::default()
@@ -624,7 +1455,20 @@ fn main() {
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         // Single Instance Plugin: When user triggers shortcut and app is already running,
         // the OS launches a new instance which signals the existing one to toggle
-        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            if is_shortcut_echo(argv.get(1..).unwrap_or_default(), false) {
+                println!("[SingleInstance] Press already handled through the portal");
+                return;
+            }
+            // Subcommands (export/import...) are executed here, without toggling the window
+            if let Some(command) = cli::parse_cli_command(&argv, std::path::Path::new(&cwd)) {
+                match command {
+                    Ok(command) => run_cli_command_logged(app, command),
+                    Err(e) => eprintln!("[SingleInstance] Invalid subcommand: {}", e),
+                }
+                return;
+            }
+
             // Check if --settings flag is present
             if argv.iter().any(|arg| arg == "--settings") {
                 println!(
@@ -640,7 +1484,9 @@ fn main() {
             clipboard_manager: clipboard_manager.clone(),
             emoji_manager: emoji_manager.clone(),
             config_manager: config_manager.clone(),
+            history_store: history_store.clone(),
             is_mouse_inside: is_mouse_inside.clone(),
+            restore_clipboard,
         })
         .setup(move |app| {
             let app_handle = app.handle().clone();
@@ -679,6 +1525,7 @@ fn main() {
                 .temp_dir_path(temp_dir)
                 .menu(&menu)
                 .on_menu_event(move |app, event| match event.id.as_ref() {
+                    // History is cleared and saved by the exit event handler
                     "quit" => app.exit(0),
                     "show" => WindowController::toggle(app),
                     "settings" => SettingsController::show(app),
@@ -737,36 +1584,69 @@ fn main() {
                         }
                     }
 
-                    if is_wayland() {
-                        state.config_manager.lock().sync_to_disk();
-                    }
+                    state.config_manager.lock().sync_to_disk();
 
                     let _ = w_clone.hide();
                 }
 
-                WindowEvent::Moved(pos) => {
+                WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
                     let state = w_clone.state::<AppState>();
-                    handle_window_moved_for_wayland(&w_clone, &state, pos);
+                    handle_window_geometry_changed(&w_clone, &state);
                 }
                 _ => {}
             });
 
+            // Restore history from disk (if enabled and not locked) before watching
+            if let Err(e) = load_stored_history(&app_handle, &app.state::<AppState>()) {
+                eprintln!("[HistoryStore] Failed to load stored history: {}", e);
+            }
+            start_history_saver(clipboard_manager.clone(), history_store);
+            start_privacy_monitor(app_handle.clone(), clipboard_manager.clone());
             start_clipboard_watcher(app_handle.clone(), clipboard_manager);
 
+            // Answer export/import from later launches, which print the result
+            #[cfg(target_os = "linux")]
+            {
+                let app = app_handle.clone();
+                let handler: cli::CliHandler = Arc::new(move |command| run_cli_command(&app, command));
+                match cli::serve(handler) {
+                    Ok(connection) => {
+                        app_handle.manage(CliChannel(connection));
+                    }
+                    Err(e) => eprintln!("[CLI] {}", e),
+                }
+            }
+
             // Register global shortcut (Super+V) with the desktop environment
             // This runs in a background thread to avoid blocking startup
             #[cfg(target_os = "linux")]
-            std::thread::spawn(|| {
-                // Give the desktop environment a moment to settle
-                std::thread::sleep(std::time::Duration::from_secs(2));
-                win11_clipboard_history_lib::linux_shortcut_manager::register_global_shortcut();
-            });
+            {
+                use win11_clipboard_history_lib::linux_shortcut_manager;
+
+                let app = app_handle.clone();
+                std::thread::spawn(move || {
+                    // Give the desktop environment a moment to settle
+                    std::thread::sleep(std::time::Duration::from_secs(2));
+                    // The portal delivers presses here; without it, each press launches
+                    // a new instance that forwards to this one
+                    let on_activated = Arc::new(move |action: Option<&ShortcutAction>| {
+                        run_shortcut_action(&app, action)
+                    });
+                    if !linux_shortcut_manager::bind_portal_shortcuts(on_activated) {
+                        linux_shortcut_manager::register_global_shortcut();
+                    }
+                });
+            }
 
             // If --settings flag was passed on first startup, open the settings window
             if open_settings_on_start {
                 SettingsController::show(&app_handle);
             }
 
+            if let Some(command) = startup_command {
+                run_cli_command_logged(&app_handle, command);
+            }
+
             // If --background flag was passed, ensure the main window stays hidden
             // This is the primary mechanism for starting minimized to tray
             // Background mode: spawn enforcer thread as fallback
@@ -808,15 +1688,26 @@ fn main() {
             clear_history,
             delete_item,
             toggle_pin,
+            export_history,
+            import_history,
+            import_from_manager,
+            get_history_store_status,
+            unlock_history,
+            lock_history,
+            clipboard_importers::list_import_sources,
             paste_item,
             get_recent_emojis,
             paste_emoji,
             paste_gif_from_url,
+            type_item,
             finish_paste,
             set_mouse_state,
+            get_recording_paused,
+            set_recording_paused,
             get_user_settings,
             set_user_settings,
             is_settings_window_visible,
+            clipboard_rules::preview_clipboard_rules,
             permission_checker::check_permissions,
             permission_checker::fix_permissions_now,
             permission_checker::is_first_run,
@@ -827,11 +1718,18 @@ fn main() {
             shortcut_setup::check_shortcut_tools,
             shortcut_setup::detect_conflicts,
             shortcut_setup::resolve_conflicts,
+            config_backup::get_config_changes,
+            config_backup::rollback_config_changes,
             autostart_manager::autostart_enable,
             autostart_manager::autostart_disable,
             autostart_manager::autostart_is_enabled,
             autostart_manager::autostart_migrate,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                on_exit(app);
+            }
+        });
 }
@@ -10,6 +10,12 @@ pub struct PermissionStatus {
     pub uinput_accessible: bool,
     pub uinput_path: String,
     pub user_in_input_group: bool,
+    /// The compositor offers zwp_virtual_keyboard_v1, so pasting works without uinput
+    pub virtual_keyboard_available: bool,
+    /// The RemoteDesktop portal can inject keys (GNOME, KDE), so pasting works without uinput
+    pub remote_desktop_available: bool,
+    /// The user already approved the RemoteDesktop portal session
+    pub remote_desktop_approved: bool,
     pub suggestion: String,
 }
 
@@ -41,8 +47,30 @@ pub fn check_permissions() -> PermissionStatus {
         .map(|o| String::from_utf8_lossy(&o.stdout).contains("input"))
         .unwrap_or(false);
 
+    #[cfg(target_os = "linux")]
+    let virtual_keyboard_available =
+        crate::session::is_wayland() && crate::virtual_keyboard::is_available();
+    #[cfg(not(target_os = "linux"))]
+    let virtual_keyboard_available = false;
+
+    #[cfg(target_os = "linux")]
+    let (remote_desktop_available, remote_desktop_approved) = if crate::session::is_wayland() {
+        let portal = crate::remote_desktop::status();
+        (portal.available, portal.approved)
+    } else {
+        (false, false)
+    };
+    #[cfg(not(target_os = "linux"))]
+    let (remote_desktop_available, remote_desktop_approved) = (false, false);
+
     let suggestion = if uinput_accessible {
         "Permissions OK! Paste simulation will work.".to_string()
+    } else if virtual_keyboard_available {
+        "Your compositor supports virtual keyboards, no extra permissions are needed.".to_string()
+    } else if remote_desktop_approved {
+        "Pasting through the Remote Desktop portal is approved.".to_string()
+    } else if remote_desktop_available {
+        "Pasting will use the Remote Desktop portal. Approve the request on your first paste, or click 'Fix Permissions' to use uinput instead.".to_string()
     } else if user_in_input_group {
         "You're in the 'input' group but /dev/uinput is not accessible. Try logging out and back in.".to_string()
     } else {
@@ -53,6 +81,9 @@ pub fn check_permissions() -> PermissionStatus {
         uinput_accessible,
         uinput_path: uinput_path.to_string(),
         user_in_input_group,
+        virtual_keyboard_available,
+        remote_desktop_available,
+        remote_desktop_approved,
         suggestion,
     }
 }
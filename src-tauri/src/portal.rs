@@ -0,0 +1,228 @@
+//! XDG Desktop Portal Module
+//! Request/Response plumbing shared by the portal clients: methods return a Request
+//! object and deliver their results later in its Response signal.
+
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::time::Duration;
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::message::Type;
+use zbus::zvariant::{ObjectPath, OwnedValue};
+use zbus::{MatchRule, Message};
+
+pub(crate) const PORTAL_BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+pub(crate) const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+pub(crate) const REQUEST_INTERFACE: &str = "org.freedesktop.portal.Request";
+pub(crate) const SESSION_INTERFACE: &str = "org.freedesktop.portal.Session";
+
+pub(crate) type PortalResults = HashMap<String, OwnedValue>;
+
+/// How long a call waits for its Response. A dialog left open is closed after that,
+/// instead of blocking the calling thread (e.g. a paste) forever.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Object path the portal uses for the Request answering a call made with `token`
+pub(crate) fn request_path(unique_name: &str, token: &str) -> String {
+    let sender = unique_name.trim_start_matches(':').replace('.', "_");
+    format!("{}/request/{}/{}", PORTAL_PATH, sender, token)
+}
+
+pub(crate) fn new_token() -> String {
+    format!("clipboard_{}", uuid::Uuid::new_v4().simple())
+}
+
+/// String entry of a portal results or options map
+pub(crate) fn string_value(map: &PortalResults, key: &str) -> Option<String> {
+    map.get(key)
+        .and_then(|v| String::try_from(v.try_clone().ok()?).ok())
+}
+
+/// Makes a portal call and waits for its Request::Response signal.
+/// The match rule is added first, so a fast response can't be missed.
+pub(crate) fn call_request(
+    conn: &Connection,
+    method: &str,
+    call: impl FnOnce(&str) -> zbus::Result<()>,
+) -> Result<PortalResults, String> {
+    let token = new_token();
+    let unique_name = conn
+        .unique_name()
+        .ok_or("D-Bus connection has no unique name")?
+        .to_string();
+    let path = request_path(&unique_name, &token);
+
+    let rule = MatchRule::builder()
+        .msg_type(Type::Signal)
+        .interface(REQUEST_INTERFACE)
+        .and_then(|b| b.member("Response"))
+        .and_then(|b| b.path(ObjectPath::try_from(path.as_str())?))
+        .map_err(|e| e.to_string())?
+        .build();
+    let responses = MessageIterator::for_match_rule(rule, conn, Some(1))
+        .map_err(|e| format!("Failed to watch {} response: {}", method, e))?;
+
+    call(&token).map_err(|e| format!("{} failed: {}", method, e))?;
+
+    let message = match next_response(responses, RESPONSE_TIMEOUT) {
+        Some(message) => message.map_err(|e| e.to_string())?,
+        None => {
+            // Dismisses the dialog, if the portal still shows one
+            let _ = conn.call_method(
+                Some(PORTAL_BUS_NAME),
+                path.as_str(),
+                Some(REQUEST_INTERFACE),
+                "Close",
+                &(),
+            );
+            return Err(format!(
+                "{} got no response within {} seconds",
+                method,
+                RESPONSE_TIMEOUT.as_secs()
+            ));
+        }
+    };
+    let (code, results): (u32, PortalResults) = message
+        .body()
+        .deserialize()
+        .map_err(|e| format!("Invalid {} response: {}", method, e))?;
+
+    match code {
+        0 => Ok(results),
+        1 => Err(format!("{} was cancelled by the user", method)),
+        _ => Err(format!("{} failed (response {})", method, code)),
+    }
+}
+
+/// Waits for the next message of `responses`, or `None` after `timeout`
+fn next_response(responses: MessageIterator, timeout: Duration) -> Option<zbus::Result<Message>> {
+    let mut stream = responses.into_inner();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .ok()?;
+    runtime
+        .block_on(async { tokio::time::timeout(timeout, stream.next()).await })
+        .ok()
+        .flatten()
+}
+
+/// Private bus and mock portal helpers for the portal client tests
+#[cfg(test)]
+pub(crate) mod test_bus {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::path::PathBuf;
+    use std::process::{Child, Command, Stdio};
+    use zbus::message::Header;
+    use zbus::zvariant::{OwnedObjectPath, Value};
+
+    /// Answers a mock portal call: emits the Response signal on the caller's Request
+    /// path and returns that path
+    pub(crate) async fn respond(
+        conn: &zbus::Connection,
+        header: &Header<'_>,
+        options: &PortalResults,
+        results: HashMap<&str, Value<'_>>,
+    ) -> OwnedObjectPath {
+        let token = string_value(options, "handle_token").unwrap();
+        let sender = header.sender().unwrap().to_string();
+        let path = OwnedObjectPath::try_from(request_path(&sender, &token)).unwrap();
+        conn.emit_signal(
+            None::<()>,
+            &path,
+            REQUEST_INTERFACE,
+            "Response",
+            &(0u32, results),
+        )
+        .await
+        .unwrap();
+        path
+    }
+
+    /// A dbus-daemon on a private socket, killed on drop
+    pub(crate) struct PrivateBus {
+        daemon: Child,
+        address: String,
+        pub(crate) dir: PathBuf,
+    }
+
+    impl PrivateBus {
+        pub(crate) fn start() -> Option<Self> {
+            let dir = std::env::temp_dir().join(format!("portal-test-{}", new_token()));
+            std::fs::create_dir_all(&dir).ok()?;
+            let config = dir.join("bus.conf");
+            std::fs::write(
+                &config,
+                format!(
+                    r#"<busconfig>
+  <type>session</type>
+  <listen>unix:dir={}</listen>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>"#,
+                    dir.display()
+                ),
+            )
+            .ok()?;
+
+            let mut daemon = Command::new("dbus-daemon")
+                .arg(format!("--config-file={}", config.display()))
+                .args(["--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take()?)
+                .read_line(&mut address)
+                .ok()?;
+
+            Some(Self {
+                daemon,
+                address: address.trim().to_string(),
+                dir,
+            })
+        }
+
+        pub(crate) fn connect(&self) -> zbus::blocking::connection::Builder<'static> {
+            zbus::blocking::connection::Builder::address(self.address.as_str()).unwrap()
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[ignore = "needs dbus-daemon"]
+    fn test_response_timeout() {
+        let bus = test_bus::PrivateBus::start().expect("failed to start dbus-daemon");
+        let conn = bus.connect().build().unwrap();
+        let rule = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .interface(REQUEST_INTERFACE)
+            .unwrap()
+            .build();
+        let responses = MessageIterator::for_match_rule(rule, &conn, Some(1)).unwrap();
+        assert!(next_response(responses, Duration::from_millis(50)).is_none());
+    }
+
+    #[test]
+    fn test_request_path() {
+        assert_eq!(
+            request_path(":1.42", "clipboard_abc"),
+            "/org/freedesktop/portal/desktop/request/1_42/clipboard_abc"
+        );
+    }
+}
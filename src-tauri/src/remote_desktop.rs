@@ -0,0 +1,368 @@
+//! Remote Desktop Portal Module
+//! Injects keys through `org.freedesktop.portal.RemoteDesktop`, implemented by GNOME and KDE
+//! on Wayland. The user approves the session once: the restore token handed out by the
+//! portal is saved, so later sessions start without asking again.
+
+use crate::portal::{
+    call_request, new_token, string_value, PORTAL_BUS_NAME, PORTAL_PATH, SESSION_INTERFACE,
+};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedObjectPath, Value};
+
+const REMOTE_DESKTOP_INTERFACE: &str = "org.freedesktop.portal.RemoteDesktop";
+
+/// Device type bit for keyboards
+const DEVICE_KEYBOARD: u32 = 1;
+/// Keep the permission until the user revokes it
+const PERSIST_UNTIL_REVOKED: u32 = 2;
+
+/// File holding the last restore token
+const RESTORE_TOKEN_FILE: &str = "remote-desktop-token";
+
+/// Portal state, reported in the permission diagnostics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PortalStatus {
+    /// The portal offers keyboard injection
+    pub available: bool,
+    /// The user already approved a session (a restore token is saved)
+    pub approved: bool,
+}
+
+fn restore_token_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("win11-clipboard-history")
+        .join(RESTORE_TOKEN_FILE)
+}
+
+/// Writes the restore token readable by the current user only: it grants keyboard access
+fn write_private(path: &Path, token: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // Files saved by earlier versions were world-readable
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    file.write_all(token.as_bytes())
+}
+
+/// Checks whether the session bus has a RemoteDesktop portal that can drive a keyboard
+pub fn status() -> PortalStatus {
+    let available = Connection::session()
+        .map(|conn| keyboard_available(&conn))
+        .unwrap_or(false);
+    PortalStatus {
+        available,
+        approved: restore_token_path().exists(),
+    }
+}
+
+fn keyboard_available(conn: &Connection) -> bool {
+    Proxy::new(conn, PORTAL_BUS_NAME, PORTAL_PATH, REMOTE_DESKTOP_INTERFACE)
+        .and_then(|proxy| proxy.get_property::<u32>("AvailableDeviceTypes"))
+        .map(|types| types & DEVICE_KEYBOARD != 0)
+        .unwrap_or(false)
+}
+
+/// A started RemoteDesktop session with keyboard access, closed on drop
+pub struct RemoteDesktopSession {
+    conn: Connection,
+    session: OwnedObjectPath,
+}
+
+impl RemoteDesktopSession {
+    /// Creates and starts a session, asking the user unless the token in `token_file`
+    /// is still valid. The new restore token is written back to `token_file`.
+    pub fn start(conn: &Connection, token_file: &Path) -> Result<Self, String> {
+        let results = call_request(conn, "CreateSession", |token| {
+            let session_token = new_token();
+            let options = HashMap::from([
+                ("handle_token", Value::from(token)),
+                ("session_handle_token", Value::from(session_token.as_str())),
+            ]);
+            conn.call_method(
+                Some(PORTAL_BUS_NAME),
+                PORTAL_PATH,
+                Some(REMOTE_DESKTOP_INTERFACE),
+                "CreateSession",
+                &(options,),
+            )
+            .map(|_| ())
+        })?;
+        let session = string_value(&results, "session_handle")
+            .and_then(|path| OwnedObjectPath::try_from(path).ok())
+            .ok_or("Portal returned no session handle")?;
+
+        // Close the session if anything below fails
+        let session = Self {
+            conn: conn.clone(),
+            session,
+        };
+        let session_path = &session.session;
+
+        let restore_token = std::fs::read_to_string(token_file)
+            .ok()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty());
+        call_request(conn, "SelectDevices", |token| {
+            let mut options = HashMap::from([
+                ("handle_token", Value::from(token)),
+                ("types", Value::from(DEVICE_KEYBOARD)),
+                ("persist_mode", Value::from(PERSIST_UNTIL_REVOKED)),
+            ]);
+            if let Some(restore_token) = &restore_token {
+                options.insert("restore_token", Value::from(restore_token.as_str()));
+            }
+            conn.call_method(
+                Some(PORTAL_BUS_NAME),
+                PORTAL_PATH,
+                Some(REMOTE_DESKTOP_INTERFACE),
+                "SelectDevices",
+                &(session_path, options),
+            )
+            .map(|_| ())
+        })?;
+
+        // Shows the approval dialog unless the restore token was accepted
+        let results = call_request(conn, "Start", |token| {
+            let options = HashMap::from([("handle_token", Value::from(token))]);
+            conn.call_method(
+                Some(PORTAL_BUS_NAME),
+                PORTAL_PATH,
+                Some(REMOTE_DESKTOP_INTERFACE),
+                "Start",
+                &(session_path, "", options),
+            )
+            .map(|_| ())
+        })?;
+
+        let devices = results
+            .get("devices")
+            .and_then(|v| u32::try_from(v).ok())
+            .unwrap_or(0);
+        if devices & DEVICE_KEYBOARD == 0 {
+            return Err("Keyboard access was not granted".to_string());
+        }
+
+        // Tokens are single-use: every session hands out the next one
+        if let Some(token) = string_value(&results, "restore_token") {
+            if let Some(dir) = token_file.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            if let Err(e) = write_private(token_file, &token) {
+                eprintln!("[RemoteDesktop] Failed to save restore token: {}", e);
+            }
+        }
+
+        Ok(session)
+    }
+
+    fn notify_keysym(&self, keysym: u32, pressed: bool) -> Result<(), String> {
+        let options: HashMap<&str, Value> = HashMap::new();
+        self.conn
+            .call_method(
+                Some(PORTAL_BUS_NAME),
+                PORTAL_PATH,
+                Some(REMOTE_DESKTOP_INTERFACE),
+                "NotifyKeyboardKeysym",
+                &(
+                    self.session.as_ref(),
+                    options,
+                    keysym as i32,
+                    u32::from(pressed),
+                ),
+            )
+            .map(|_| ())
+            .map_err(|e| format!("NotifyKeyboardKeysym failed: {}", e))
+    }
+
+    /// Presses the keysyms in order, then releases them in reverse.
+    /// Keysyms are mapped by the compositor, so no layout lookup is needed.
+    pub fn tap(&self, keysyms: &[u32], delay: Duration) -> Result<(), String> {
+        for &keysym in keysyms {
+            self.notify_keysym(keysym, true)?;
+            thread::sleep(delay);
+        }
+        for &keysym in keysyms.iter().rev() {
+            self.notify_keysym(keysym, false)?;
+            thread::sleep(delay);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RemoteDesktopSession {
+    fn drop(&mut self) {
+        let _ = self.conn.call_method(
+            Some(PORTAL_BUS_NAME),
+            self.session.as_ref(),
+            Some(SESSION_INTERFACE),
+            "Close",
+            &(),
+        );
+    }
+}
+
+/// Session reused across pastes; recreated after an error
+static SESSION: Lazy<Mutex<Option<RemoteDesktopSession>>> = Lazy::new(|| Mutex::new(None));
+
+/// Runs `f` with the shared portal session, starting one if needed
+pub fn with_session<T>(
+    f: impl FnOnce(&RemoteDesktopSession) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut slot = SESSION.lock();
+    let session = match slot.as_ref() {
+        Some(session) => session,
+        None => {
+            let conn = Connection::session().map_err(|e| e.to_string())?;
+            if !keyboard_available(&conn) {
+                return Err("RemoteDesktop portal has no keyboard support".to_string());
+            }
+            eprintln!("[RemoteDesktop] Starting portal session");
+            slot.insert(RemoteDesktopSession::start(&conn, &restore_token_path())?)
+        }
+    };
+
+    let result = f(session);
+    if result.is_err() {
+        *slot = None;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portal::test_bus::{respond, PrivateBus};
+    use std::sync::Arc;
+    use zbus::message::Header;
+    use zbus::zvariant::{ObjectPath, OwnedValue};
+
+    /// Mock portal recording the keys it receives
+    #[derive(Default)]
+    struct MockPortal {
+        keys: Arc<Mutex<Vec<(u32, u32)>>>,
+        restore_tokens: Arc<Mutex<Vec<Option<String>>>>,
+        sessions: Mutex<u32>,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.portal.RemoteDesktop")]
+    impl MockPortal {
+        async fn create_session(
+            &self,
+            #[zbus(header)] header: Header<'_>,
+            #[zbus(connection)] conn: &zbus::Connection,
+            options: HashMap<String, OwnedValue>,
+        ) -> OwnedObjectPath {
+            let results = HashMap::from([(
+                "session_handle",
+                Value::from("/org/freedesktop/portal/desktop/session/1/test"),
+            )]);
+            respond(conn, &header, &options, results).await
+        }
+
+        async fn select_devices(
+            &self,
+            #[zbus(header)] header: Header<'_>,
+            #[zbus(connection)] conn: &zbus::Connection,
+            _session: ObjectPath<'_>,
+            options: HashMap<String, OwnedValue>,
+        ) -> OwnedObjectPath {
+            let restore_token = string_value(&options, "restore_token");
+            self.restore_tokens.lock().push(restore_token);
+            respond(conn, &header, &options, HashMap::new()).await
+        }
+
+        async fn start(
+            &self,
+            #[zbus(header)] header: Header<'_>,
+            #[zbus(connection)] conn: &zbus::Connection,
+            _session: ObjectPath<'_>,
+            _parent_window: &str,
+            options: HashMap<String, OwnedValue>,
+        ) -> OwnedObjectPath {
+            let session = {
+                let mut sessions = self.sessions.lock();
+                *sessions += 1;
+                *sessions
+            };
+            let token = format!("token-{}", session);
+            let results = HashMap::from([
+                ("devices", Value::from(DEVICE_KEYBOARD)),
+                ("restore_token", Value::from(token)),
+            ]);
+            respond(conn, &header, &options, results).await
+        }
+
+        fn notify_keyboard_keysym(
+            &self,
+            _session: ObjectPath<'_>,
+            _options: HashMap<String, OwnedValue>,
+            keysym: i32,
+            state: u32,
+        ) {
+            self.keys.lock().push((keysym as u32, state));
+        }
+
+        #[zbus(property)]
+        fn available_device_types(&self) -> u32 {
+            DEVICE_KEYBOARD
+        }
+    }
+
+    #[test]
+    #[ignore = "needs dbus-daemon"]
+    fn test_session_with_mock_portal() {
+        let bus = PrivateBus::start().expect("failed to start dbus-daemon");
+
+        let portal = MockPortal::default();
+        let keys = portal.keys.clone();
+        let restore_tokens = portal.restore_tokens.clone();
+        let _server = bus
+            .connect()
+            .name(PORTAL_BUS_NAME)
+            .unwrap()
+            .serve_at(PORTAL_PATH, portal)
+            .unwrap()
+            .build()
+            .unwrap();
+        let client = bus.connect().build().unwrap();
+        assert!(keyboard_available(&client));
+
+        // First session asks the user and saves the token
+        let token_file = bus.dir.join(RESTORE_TOKEN_FILE);
+        let session = RemoteDesktopSession::start(&client, &token_file).unwrap();
+        assert_eq!(std::fs::read_to_string(&token_file).unwrap(), "token-1");
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&token_file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        session.tap(&[0xffe3, 0x76], Duration::ZERO).unwrap();
+        assert_eq!(
+            *keys.lock(),
+            vec![(0xffe3, 1), (0x76, 1), (0x76, 0), (0xffe3, 0)]
+        );
+        drop(session);
+
+        // The next session restores the approval and rotates the token
+        RemoteDesktopSession::start(&client, &token_file).unwrap();
+        assert_eq!(
+            *restore_tokens.lock(),
+            vec![None, Some("token-1".to_string())]
+        );
+        assert_eq!(std::fs::read_to_string(&token_file).unwrap(), "token-2");
+    }
+}
@@ -0,0 +1,564 @@
+//! Shortcut Chord Module
+//! Parses the canonical chord format used in settings ("Super+V", "Ctrl+Alt+V") and
+//! translates it into the binding syntax of each desktop environment.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Id of the main shortcut's desktop entry; the other entries add a suffix to it
+pub const SHORTCUT_ID: &str = "win11-clipboard-history";
+
+/// Tabs of the history window that a shortcut can open directly
+pub const TABS: &[&str] = &["clipboard", "emoji", "gifs", "kaomoji", "symbols"];
+
+/// Global shortcuts, in canonical form
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShortcutBindings {
+    /// Main shortcut, opens the clipboard history
+    pub open: String,
+    /// Second shortcut for the same action; empty disables it
+    #[serde(default)]
+    pub alternate: String,
+    /// Extra shortcuts for direct actions (quick paste, tabs, pause)
+    #[serde(default)]
+    pub actions: Vec<ActionBinding>,
+}
+
+impl Default for ShortcutBindings {
+    fn default() -> Self {
+        Self {
+            open: "Super+V".to_string(),
+            alternate: "Ctrl+Alt+V".to_string(),
+            actions: Vec::new(),
+        }
+    }
+}
+
+impl ShortcutBindings {
+    /// Checks that every chord parses (the alternate may be empty)
+    /// and that no chord is bound twice
+    pub fn validate(&self) -> Result<(), String> {
+        let mut seen = vec![Chord::parse(&self.open)?];
+        if !self.alternate.trim().is_empty() {
+            seen.push(Chord::parse(&self.alternate)?);
+        }
+        for binding in &self.actions {
+            binding.action.validate()?;
+            let chord = Chord::parse(&binding.chord)?;
+            if seen.contains(&chord) {
+                return Err(format!("Shortcut '{}' is bound more than once", chord));
+            }
+            seen.push(chord);
+        }
+        Ok(())
+    }
+
+    /// Every configured chord that parses, with what it runs; an empty alternate is skipped
+    pub fn chords(&self) -> Vec<BoundChord> {
+        let slots = [
+            (SHORTCUT_ID.to_string(), &self.open, None),
+            (format!("{}-alt", SHORTCUT_ID), &self.alternate, None),
+        ];
+        let actions = self.actions.iter().map(|binding| {
+            (
+                format!("{}-{}", SHORTCUT_ID, binding.action.slug()),
+                &binding.chord,
+                Some(binding.action.clone()),
+            )
+        });
+        slots
+            .into_iter()
+            .chain(actions)
+            .filter(|(_, chord, _)| !chord.trim().is_empty())
+            .filter_map(|(id, chord, action)| {
+                Some(BoundChord {
+                    id,
+                    chord: Chord::parse(chord).ok()?,
+                    action,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A configured chord and what it runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundChord {
+    /// Id of its desktop entry
+    pub id: String,
+    pub chord: Chord,
+    /// `None` opens the history
+    pub action: Option<ShortcutAction>,
+}
+
+impl BoundChord {
+    /// The command the binding runs, with the action's arguments
+    pub fn command(&self) -> Vec<String> {
+        let args = self.action.iter().flat_map(ShortcutAction::cli_args);
+        std::iter::once(SHORTCUT_ID.to_string())
+            .chain(args)
+            .collect()
+    }
+}
+
+/// A shortcut bound to a direct action
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ActionBinding {
+    pub action: ShortcutAction,
+    pub chord: String,
+}
+
+/// What an extra shortcut does. Each action runs a CLI subcommand on the running instance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ShortcutAction {
+    /// Paste the Nth history item (1-based) without opening the window
+    PasteItem { index: u8 },
+    /// Open the window on a tab
+    OpenTab { tab: String },
+    /// Paste the most recent text item as plain text
+    PastePlain,
+    /// Pause or resume recording the clipboard
+    TogglePause,
+}
+
+impl ShortcutAction {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::PasteItem { index } if !(1..=9).contains(index) => {
+                Err(format!("Quick-paste slot {} is out of range (1-9)", index))
+            }
+            Self::OpenTab { tab } if !TABS.contains(&tab.as_str()) => {
+                Err(format!("Unknown tab '{}'", tab))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Arguments of the CLI subcommand performing the action
+    pub fn cli_args(&self) -> Vec<String> {
+        match self {
+            Self::PasteItem { index } => vec!["--paste-item".into(), index.to_string()],
+            Self::OpenTab { tab } => vec!["--open-tab".into(), tab.clone()],
+            Self::PastePlain => vec!["--paste-plain".into()],
+            Self::TogglePause => vec!["--toggle-pause".into()],
+        }
+    }
+
+    /// Stable identifier, used to name the desktop environment entry
+    pub fn slug(&self) -> String {
+        match self {
+            Self::PasteItem { index } => format!("paste-{}", index),
+            Self::OpenTab { tab } => format!("tab-{}", tab),
+            Self::PastePlain => "paste-plain".to_string(),
+            Self::TogglePause => "toggle-pause".to_string(),
+        }
+    }
+
+    /// Name shown in the desktop environment's shortcut settings
+    pub fn display_name(&self) -> String {
+        match self {
+            Self::PasteItem { index } => format!("Clipboard History: Paste Item {}", index),
+            Self::OpenTab { tab } => format!("Clipboard History: Open {} Tab", capitalize(tab)),
+            Self::PastePlain => "Clipboard History: Paste as Plain Text".to_string(),
+            Self::TogglePause => "Clipboard History: Pause Recording".to_string(),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Named keys: canonical name, X keysym name, Qt name, evdev name
+const NAMED_KEYS: &[(&str, &str, &str, &str)] = &[
+    ("Space", "space", "Space", "KEY_SPACE"),
+    ("Enter", "Return", "Return", "KEY_ENTER"),
+    ("Tab", "Tab", "Tab", "KEY_TAB"),
+    ("Escape", "Escape", "Esc", "KEY_ESC"),
+    ("Backspace", "BackSpace", "Backspace", "KEY_BACKSPACE"),
+    ("Insert", "Insert", "Ins", "KEY_INSERT"),
+    ("Delete", "Delete", "Del", "KEY_DELETE"),
+    ("Home", "Home", "Home", "KEY_HOME"),
+    ("End", "End", "End", "KEY_END"),
+    ("PageUp", "Page_Up", "PgUp", "KEY_PAGEUP"),
+    ("PageDown", "Page_Down", "PgDown", "KEY_PAGEDOWN"),
+    ("Up", "Up", "Up", "KEY_UP"),
+    ("Down", "Down", "Down", "KEY_DOWN"),
+    ("Left", "Left", "Left", "KEY_LEFT"),
+    ("Right", "Right", "Right", "KEY_RIGHT"),
+    ("Period", "period", ".", "KEY_DOT"),
+    ("Comma", "comma", ",", "KEY_COMMA"),
+    ("Semicolon", "semicolon", ";", "KEY_SEMICOLON"),
+    ("Slash", "slash", "/", "KEY_SLASH"),
+    ("Minus", "minus", "-", "KEY_MINUS"),
+    ("Equal", "equal", "=", "KEY_EQUAL"),
+    ("Grave", "grave", "`", "KEY_GRAVE"),
+];
+
+/// A key with modifiers, e.g. Super+V
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub super_key: bool,
+    /// Canonical key name: "V", "5", "F1" or an entry of `NAMED_KEYS`
+    pub key: String,
+}
+
+impl Chord {
+    /// Parses "Super+V" or "ctrl+alt+v". Also accepts the aliases Meta/Win/Mod4/$mod
+    /// for Super, Control/Primary for Ctrl and Mod1 for Alt.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let (key, modifiers) = parts
+            .split_last()
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or_else(|| format!("Shortcut '{}' has no key", text))?;
+
+        let mut chord = Chord {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            super_key: false,
+            key: canonical_key(key).ok_or_else(|| format!("Unknown key '{}'", key))?,
+        };
+        for modifier in modifiers {
+            let flag = match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" | "primary" => &mut chord.ctrl,
+                "alt" | "mod1" => &mut chord.alt,
+                "shift" => &mut chord.shift,
+                "super" | "meta" | "win" | "logo" | "mod4" | "$mod" => &mut chord.super_key,
+                _ => return Err(format!("Unknown modifier '{}'", modifier)),
+            };
+            if *flag {
+                return Err(format!("Modifier '{}' is repeated", modifier));
+            }
+            *flag = true;
+        }
+        Ok(chord)
+    }
+
+    /// X keysym name of the key ("v", "F1", "Page_Up")
+    pub fn keysym(&self) -> String {
+        named_key(&self.key)
+            .map(|(_, keysym, _, _)| keysym.to_string())
+            .unwrap_or_else(|| {
+                if self.key.len() == 1 {
+                    self.key.to_lowercase()
+                } else {
+                    self.key.clone()
+                }
+            })
+    }
+
+    /// Qt key name ("V", "F1", "PgUp")
+    fn qt_key(&self) -> String {
+        named_key(&self.key)
+            .map(|(_, _, qt, _)| qt.to_string())
+            .unwrap_or_else(|| self.key.clone())
+    }
+
+    /// Linux input event name of the key ("KEY_V", "KEY_DOT")
+    fn evdev_key(&self) -> String {
+        named_key(&self.key)
+            .map(|(_, _, _, evdev)| evdev.to_string())
+            .unwrap_or_else(|| format!("KEY_{}", self.key))
+    }
+
+    /// Keysym with letters upper-cased, as niri and Hyprland write them ("V", "Page_Up")
+    fn display_keysym(&self) -> String {
+        if self.key.len() == 1 {
+            self.key.clone()
+        } else {
+            self.keysym()
+        }
+    }
+
+    /// Names of the held modifiers, given as `[ctrl, alt, shift, super]`.
+    /// Super comes last unless `super_first` (the tiling WM convention).
+    fn modifiers<'a>(&self, names: [&'a str; 4], super_first: bool) -> Vec<&'a str> {
+        let [ctrl, alt, shift, super_key] = names;
+        let mut held = vec![(self.ctrl, ctrl), (self.alt, alt), (self.shift, shift)];
+        if super_first {
+            held.insert(0, (self.super_key, super_key));
+        } else {
+            held.push((self.super_key, super_key));
+        }
+        held.into_iter()
+            .filter_map(|(held, name)| held.then_some(name))
+            .collect()
+    }
+
+    /// GNOME, Cinnamon and MATE: `<Super>v`, `<Ctrl><Alt>v`
+    pub fn gnome(&self) -> String {
+        let mods: String = self
+            .modifiers(["<Ctrl>", "<Alt>", "<Shift>", "<Super>"], false)
+            .concat();
+        format!("{}{}", mods, self.keysym())
+    }
+
+    /// XFCE: `<Super>v`, `<Primary><Alt>v`
+    pub fn xfce(&self) -> String {
+        let mods: String = self
+            .modifiers(["<Primary>", "<Alt>", "<Shift>", "<Super>"], false)
+            .concat();
+        format!("{}{}", mods, self.keysym())
+    }
+
+    /// KDE and LXQt (Qt key sequences): `Meta+V`, `Ctrl+Alt+V`
+    pub fn kde(&self) -> String {
+        let mut parts = self.modifiers(["Ctrl", "Alt", "Shift", "Meta"], false);
+        let key = self.qt_key();
+        parts.push(&key);
+        parts.join("+")
+    }
+
+    /// COSMIC modifier list: `Super`, `Ctrl, Alt`
+    pub fn cosmic_mods(&self) -> String {
+        self.modifiers(["Ctrl", "Alt", "Shift", "Super"], false)
+            .join(", ")
+    }
+
+    /// COSMIC key: `v`
+    pub fn cosmic_key(&self) -> String {
+        self.keysym()
+    }
+
+    /// i3 and sway: `$mod+v`, `Ctrl+Mod1+v`
+    pub fn i3(&self) -> String {
+        let mut parts = self.modifiers(["Ctrl", "Mod1", "Shift", "$mod"], true);
+        let key = self.keysym();
+        parts.push(&key);
+        parts.join("+")
+    }
+
+    /// Hyprland: `SUPER, V`, `CTRL ALT, V`
+    pub fn hyprland(&self) -> String {
+        let mods = self.modifiers(["CTRL", "ALT", "SHIFT", "SUPER"], true);
+        format!("{}, {}", mods.join(" "), self.display_keysym())
+    }
+
+    /// niri: `Mod+V`, `Ctrl+Alt+V`
+    pub fn niri(&self) -> String {
+        let mut parts = self.modifiers(["Ctrl", "Alt", "Shift", "Mod"], true);
+        let key = self.display_keysym();
+        parts.push(&key);
+        parts.join("+")
+    }
+
+    /// river modifier list: `Super`, `Control+Alt`, or `None`
+    pub fn river_mods(&self) -> String {
+        let mods = self.modifiers(["Control", "Alt", "Shift", "Super"], true);
+        if mods.is_empty() {
+            "None".to_string()
+        } else {
+            mods.join("+")
+        }
+    }
+
+    /// Wayfire: `<super> KEY_V`, `<ctrl> <alt> KEY_V`
+    pub fn wayfire(&self) -> String {
+        let mut parts = self.modifiers(["<ctrl>", "<alt>", "<shift>", "<super>"], true);
+        let key = self.evdev_key();
+        parts.push(&key);
+        parts.join(" ")
+    }
+
+    /// sxhkd (bspwm): `super + v`, `ctrl + alt + v`
+    pub fn sxhkd(&self) -> String {
+        let mut parts = self.modifiers(["ctrl", "alt", "shift", "super"], true);
+        let key = self.keysym();
+        parts.push(&key);
+        parts.join(" + ")
+    }
+
+    /// awesome modifier table: `{ "Mod4" }`, `{ "Control", "Mod1" }`
+    pub fn awesome_mods(&self) -> String {
+        let mods: Vec<String> = self
+            .modifiers(["Control", "Mod1", "Shift", "Mod4"], true)
+            .iter()
+            .map(|m| format!("\"{}\"", m))
+            .collect();
+        format!("{{ {} }}", mods.join(", "))
+    }
+
+    /// Qtile modifier list: `["mod4"]`, `["control", "mod1"]`
+    pub fn qtile_mods(&self) -> String {
+        let mods: Vec<String> = self
+            .modifiers(["control", "mod1", "shift", "mod4"], true)
+            .iter()
+            .map(|m| format!("\"{}\"", m))
+            .collect();
+        format!("[{}]", mods.join(", "))
+    }
+
+    /// XDG GlobalShortcuts portal trigger: `LOGO+v`, `CTRL+ALT+v`
+    pub fn portal(&self) -> String {
+        let mut parts = self.modifiers(["CTRL", "ALT", "SHIFT", "LOGO"], false);
+        let key = self.keysym();
+        parts.push(&key);
+        parts.join("+")
+    }
+
+    /// Openbox (LXDE): `W-v`, `C-A-v`
+    pub fn openbox(&self) -> String {
+        let mut parts = self.modifiers(["C", "A", "S", "W"], false);
+        let key = self.keysym();
+        parts.push(&key);
+        parts.join("-")
+    }
+}
+
+impl fmt::Display for Chord {
+    /// Canonical form: `Ctrl+Alt+Shift+Super+Key`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = self.modifiers(["Ctrl", "Alt", "Shift", "Super"], false);
+        parts.push(&self.key);
+        write!(f, "{}", parts.join("+"))
+    }
+}
+
+type NamedKey = (&'static str, &'static str, &'static str, &'static str);
+
+fn named_key(key: &str) -> Option<&'static NamedKey> {
+    NAMED_KEYS.iter().find(|(canonical, keysym, _, _)| {
+        canonical.eq_ignore_ascii_case(key) || keysym.eq_ignore_ascii_case(key)
+    })
+}
+
+/// Canonical key name for a Linux input event name ("KEY_V" -> "V", "KEY_DOT" -> "Period")
+pub(crate) fn key_from_evdev(name: &str) -> Option<String> {
+    if let Some((canonical, _, _, _)) = NAMED_KEYS.iter().find(|(_, _, _, evdev)| *evdev == name) {
+        return Some(canonical.to_string());
+    }
+    name.strip_prefix("KEY_").and_then(canonical_key)
+}
+
+fn canonical_key(key: &str) -> Option<String> {
+    if key.len() == 1 && key.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Some(key.to_ascii_uppercase());
+    }
+    if let Some(n) = key
+        .strip_prefix(['F', 'f'])
+        .and_then(|n| n.parse::<u8>().ok())
+    {
+        if (1..=24).contains(&n) {
+            return Some(format!("F{}", n));
+        }
+    }
+    named_key(key).map(|(canonical, _, _, _)| canonical.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let chord = Chord::parse("super+v").unwrap();
+        assert!(chord.super_key && !chord.ctrl);
+        assert_eq!(chord.to_string(), "Super+V");
+
+        // Aliases and order are normalized
+        let chord = Chord::parse("Mod1 + Control + page_up").unwrap();
+        assert_eq!(chord.to_string(), "Ctrl+Alt+PageUp");
+        assert_eq!(Chord::parse("Meta+F12").unwrap().to_string(), "Super+F12");
+
+        assert!(Chord::parse("Super+").is_err());
+        assert!(Chord::parse("Hyper+V").is_err());
+        assert!(Chord::parse("Ctrl+Ctrl+V").is_err());
+        assert!(Chord::parse("Super+Foo").is_err());
+    }
+
+    #[test]
+    fn test_translations_match_previous_bindings() {
+        let super_v = Chord::parse("Super+V").unwrap();
+        assert_eq!(super_v.gnome(), "<Super>v");
+        assert_eq!(super_v.kde(), "Meta+V");
+        assert_eq!(super_v.xfce(), "<Super>v");
+        assert_eq!(super_v.cosmic_mods(), "Super");
+        assert_eq!(super_v.cosmic_key(), "v");
+        assert_eq!(super_v.i3(), "$mod+v");
+        assert_eq!(super_v.hyprland(), "SUPER, V");
+        assert_eq!(super_v.openbox(), "W-v");
+        assert_eq!(super_v.portal(), "LOGO+v");
+
+        let ctrl_alt_v = Chord::parse("Ctrl+Alt+V").unwrap();
+        assert_eq!(ctrl_alt_v.gnome(), "<Ctrl><Alt>v");
+        assert_eq!(ctrl_alt_v.kde(), "Ctrl+Alt+V");
+        assert_eq!(ctrl_alt_v.xfce(), "<Primary><Alt>v");
+        assert_eq!(ctrl_alt_v.cosmic_mods(), "Ctrl, Alt");
+        assert_eq!(ctrl_alt_v.i3(), "Ctrl+Mod1+v");
+        assert_eq!(ctrl_alt_v.hyprland(), "CTRL ALT, V");
+        assert_eq!(ctrl_alt_v.openbox(), "C-A-v");
+        assert_eq!(ctrl_alt_v.portal(), "CTRL+ALT+v");
+    }
+
+    #[test]
+    fn test_named_keys() {
+        let chord = Chord::parse("Super+Shift+Insert").unwrap();
+        assert_eq!(chord.gnome(), "<Shift><Super>Insert");
+        assert_eq!(chord.kde(), "Shift+Meta+Ins");
+        assert_eq!(chord.i3(), "$mod+Shift+Insert");
+        assert_eq!(chord.hyprland(), "SUPER SHIFT, Insert");
+
+        let chord = Chord::parse("Ctrl+F5").unwrap();
+        assert_eq!(chord.gnome(), "<Ctrl>F5");
+        assert_eq!(chord.hyprland(), "CTRL, F5");
+
+        let chord = Chord::parse("Super+Shift+Period").unwrap();
+        assert_eq!(chord.niri(), "Mod+Shift+period");
+        assert_eq!(chord.river_mods(), "Super+Shift");
+        assert_eq!(chord.wayfire(), "<super> <shift> KEY_DOT");
+        assert_eq!(chord.sxhkd(), "super + shift + period");
+        assert_eq!(chord.awesome_mods(), r#"{ "Mod4", "Shift" }"#);
+        assert_eq!(chord.qtile_mods(), r#"["mod4", "shift"]"#);
+        assert_eq!(key_from_evdev("KEY_DOT").as_deref(), Some("Period"));
+        assert_eq!(key_from_evdev("KEY_F3").as_deref(), Some("F3"));
+
+        let bindings = ShortcutBindings {
+            open: "Super+V".to_string(),
+            alternate: String::new(),
+            actions: Vec::new(),
+        };
+        assert!(bindings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_action_bindings() {
+        let json = r#"{"open":"Super+V","actions":[
+            {"action":{"kind":"paste_item","index":3},"chord":"Super+3"},
+            {"action":{"kind":"open_tab","tab":"emoji"},"chord":"Super+Period"},
+            {"action":{"kind":"toggle_pause"},"chord":"Super+Shift+P"}]}"#;
+        let mut bindings: ShortcutBindings = serde_json::from_str(json).unwrap();
+        assert!(bindings.validate().is_ok());
+        // No alternate in the JSON: only the open and action chords are listed
+        let chords = bindings.chords();
+        assert_eq!(chords.len(), 4);
+        assert_eq!(chords[1].id, "win11-clipboard-history-paste-3");
+        assert_eq!(
+            chords[1].command(),
+            ["win11-clipboard-history", "--paste-item", "3"]
+        );
+        assert_eq!(chords[0].command(), ["win11-clipboard-history"]);
+        assert_eq!(bindings.actions[0].action.cli_args(), ["--paste-item", "3"]);
+        assert_eq!(bindings.actions[1].action.slug(), "tab-emoji");
+        assert_eq!(
+            bindings.actions[1].action.display_name(),
+            "Clipboard History: Open Emoji Tab"
+        );
+
+        // Chords must be unique and actions in range
+        bindings.actions[2].chord = "Meta+V".to_string();
+        assert!(bindings.validate().is_err());
+        bindings.actions[2].chord = "Super+P".to_string();
+        bindings.actions[0].action = ShortcutAction::PasteItem { index: 10 };
+        assert!(bindings.validate().is_err());
+    }
+}
@@ -1,15 +1,21 @@
 //! Shortcut Conflict Detection for Various Desktop Environments
-//! Detects existing shortcuts that conflict with Super+V across different DEs
-
+//! Detects existing shortcuts that conflict with the configured chords across different DEs,
+//! and other clipboard managers competing for the same keys or for clipboard ownership
+
+use crate::config_backup::ConfigBackups;
+use crate::shortcut_chord::{BoundChord, Chord};
+use crate::user_settings::UserSettingsManager;
+use crate::wm_bindings::ConfigSyntax;
+use std::collections::HashSet;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Represents a detected shortcut conflict
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ShortcutConflict {
-    /// The shortcut binding that conflicts (e.g., "Super+V")
+    /// The shortcut binding that conflicts (e.g., "Super+V"); empty for clipboard managers
     pub binding: String,
     /// What the shortcut currently does
     pub current_action: String,
@@ -34,30 +40,29 @@ pub struct ConflictDetectionResult {
     pub message: String,
 }
 
-/// Main entry point for conflict detection
+/// Main entry point for conflict detection: checks every configured shortcut
 pub fn detect_shortcut_conflicts() -> ConflictDetectionResult {
-    let de = get_desktop_environment();
-    let conflicts = match de.as_str() {
-        "GNOME" => detect_gnome_conflicts(),
-        "Pop" | "Pop!_OS" => detect_pop_shell_conflicts(),
-        "COSMIC" => detect_cosmic_conflicts(),
-        "KDE Plasma" => detect_kde_conflicts(),
-        "i3" | "i3wm" => detect_i3_conflicts(),
-        "Sway" => detect_sway_conflicts(),
-        "Hyprland" => detect_hyprland_conflicts(),
-        "Cinnamon" => detect_cinnamon_conflicts(),
-        "XFCE" => detect_xfce_conflicts(),
-        _ => Vec::new(),
-    };
+    let chords = UserSettingsManager::new().load().shortcuts.chords();
+    detect_conflicts_for(&chords)
+}
 
-    // Only true if there are actual conflicts AND all of them can be auto-resolved
+/// Conflicts with the given chords, plus clipboard managers competing with this app
+pub fn detect_conflicts_for(chords: &[BoundChord]) -> ConflictDetectionResult {
+    let de = crate::shortcut_setup::get_desktop_environment();
+    let mut conflicts: Vec<ShortcutConflict> = chords
+        .iter()
+        .flat_map(|bound| detect_chord_conflicts(&de, bound))
+        .collect();
+    // Only true if there are key conflicts AND all of them can be auto-resolved.
+    // Clipboard managers are other apps: they are reported, never stopped for the user.
     let can_auto_resolve =
         !conflicts.is_empty() && conflicts.iter().all(|c| c.resolution_command.is_some());
+    conflicts.extend(detect_clipboard_managers());
     let message = if conflicts.is_empty() {
         "No shortcut conflicts detected.".to_string()
     } else {
         format!(
-            "{} shortcut conflict(s) detected that may prevent Super+V from working.",
+            "{} conflict(s) detected that may prevent your shortcuts from working.",
             conflicts.len()
         )
     };
@@ -70,79 +75,69 @@ pub fn detect_shortcut_conflicts() -> ConflictDetectionResult {
     }
 }
 
+fn detect_chord_conflicts(de: &str, bound: &BoundChord) -> Vec<ShortcutConflict> {
+    let chord = &bound.chord;
+    match de {
+        "GNOME" => detect_gnome_conflicts(chord),
+        "Pop!_OS" => detect_pop_shell_conflicts(chord),
+        "COSMIC" => detect_cosmic_conflicts(chord),
+        "KDE Plasma" => detect_kde_conflicts(chord),
+        "i3" => detect_i3_conflicts(bound),
+        "Sway" => detect_sway_conflicts(bound),
+        "Hyprland" => detect_hyprland_conflicts(bound),
+        "Cinnamon" => detect_cinnamon_conflicts(chord),
+        "XFCE" => detect_xfce_conflicts(chord),
+        "MATE" => detect_mate_conflicts(chord),
+        "LXQt" => detect_lxqt_conflicts(chord),
+        "Budgie" => detect_budgie_conflicts(chord),
+        "Deepin" => detect_deepin_conflicts(chord),
+        other => WM_CONFIGS
+            .iter()
+            .find(|wm| wm.owner == other)
+            .map(|wm| detect_wm_config_conflicts(wm, bound))
+            .unwrap_or_default(),
+    }
+}
+
 /// Resolve all detected conflicts automatically where possible
 pub fn auto_resolve_conflicts() -> Result<Vec<String>, String> {
-    let result = detect_shortcut_conflicts();
     let mut resolved = Vec::new();
-
-    for conflict in result.conflicts {
-        if let Some(cmd) = conflict.resolution_command {
-            match run_resolution_command(&cmd) {
-                Ok(_) => resolved.push(format!(
-                    "Resolved: {} ({})",
-                    conflict.owner, conflict.binding
-                )),
-                Err(e) => return Err(format!("Failed to resolve {}: {}", conflict.owner, e)),
-            }
+    let mut attempted = HashSet::new();
+
+    // Detect again after every fix: a key bound to two of our chords changes with the first
+    loop {
+        let next = detect_shortcut_conflicts()
+            .conflicts
+            .into_iter()
+            .find_map(|c| {
+                let cmd = c.resolution_command.clone()?;
+                (!attempted.contains(&cmd)).then_some((c, cmd))
+            });
+        let Some((conflict, cmd)) = next else {
+            break;
+        };
+        attempted.insert(cmd.clone());
+
+        let reason = format!("Disable {} shortcut ({})", conflict.owner, conflict.binding);
+        let target = gsettings_target(&cmd);
+        if let Some((schema, key)) = target {
+            ConfigBackups::new().backup_gsettings(schema, key, &reason)?;
+        }
+        match run_resolution_command(&cmd) {
+            Ok(_) => resolved.push(format!(
+                "Resolved: {} ({})",
+                conflict.owner, conflict.binding
+            )),
+            Err(e) => return Err(format!("Failed to resolve {}: {}", conflict.owner, e)),
+        }
+        if let Some((schema, key)) = target {
+            ConfigBackups::new().gsettings_written(schema, key)?;
         }
     }
 
     Ok(resolved)
 }
 
-fn get_desktop_environment() -> String {
-    let xdg_current = env::var("XDG_CURRENT_DESKTOP")
-        .unwrap_or_default()
-        .to_lowercase();
-    let xdg_session = env::var("XDG_SESSION_DESKTOP")
-        .unwrap_or_default()
-        .to_lowercase();
-    let combined = format!("{} {}", xdg_current, xdg_session);
-
-    // Check for Pop!_OS specifically (uses pop:GNOME)
-    if combined.contains("pop") {
-        return "Pop".to_string();
-    }
-    if combined.contains("gnome") || combined.contains("unity") || combined.contains("pantheon") {
-        return "GNOME".to_string();
-    }
-    if combined.contains("cosmic") {
-        return "COSMIC".to_string();
-    }
-    if combined.contains("kde") || combined.contains("plasma") {
-        return "KDE Plasma".to_string();
-    }
-    if combined.contains("cinnamon") {
-        return "Cinnamon".to_string();
-    }
-    if combined.contains("xfce") {
-        return "XFCE".to_string();
-    }
-    // Tiling window managers
-    if combined.contains("i3") {
-        return "i3".to_string();
-    }
-    if combined.contains("sway") {
-        return "Sway".to_string();
-    }
-    if combined.contains("hyprland") {
-        return "Hyprland".to_string();
-    }
-
-    // Check running processes for tiling WMs (they often don't set XDG vars properly)
-    if is_process_running("i3") {
-        return "i3".to_string();
-    }
-    if is_process_running("sway") {
-        return "Sway".to_string();
-    }
-    if is_process_running("hyprland") || is_process_running("Hyprland") {
-        return "Hyprland".to_string();
-    }
-
-    xdg_current.to_uppercase()
-}
-
 fn is_process_running(name: &str) -> bool {
     Command::new("pgrep")
         .arg("-x")
@@ -152,6 +147,15 @@ fn is_process_running(name: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Schema and key a resolution command changes, recorded in the backups so it can be
+/// rolled back
+fn gsettings_target(cmd: &str) -> Option<(&str, &str)> {
+    match cmd.split_whitespace().collect::<Vec<_>>()[..] {
+        ["gsettings", "set", schema, key, ..] => Some((schema, key)),
+        _ => None,
+    }
+}
+
 fn run_resolution_command(cmd: &str) -> Result<(), String> {
     let output = Command::new("sh")
         .arg("-c")
@@ -174,107 +178,61 @@ fn command_exists(cmd: &str) -> bool {
         .unwrap_or(false)
 }
 
-fn gsettings_get(schema: &str, key: &str) -> Option<String> {
+/// `gsettings list-recursively` output for the schemas that are installed
+fn gsettings_list(schemas: &[&str]) -> String {
     if !command_exists("gsettings") {
-        return None;
+        return String::new();
     }
-    Command::new("gsettings")
-        .args(["get", schema, key])
-        .output()
-        .ok()
-        .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    schemas
+        .iter()
+        .filter_map(|schema| {
+            Command::new("gsettings")
+                .args(["list-recursively", schema])
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+        })
+        .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+        .collect()
 }
 
 // =============================================================================
 // GNOME Conflict Detection
 // =============================================================================
 
-fn detect_gnome_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
-
-    // Check for notification center shortcut (toggle-message-tray)
-    if let Some(binding) = gsettings_get("org.gnome.shell.keybindings", "toggle-message-tray") {
-        let binding_lower = binding.to_lowercase();
-        if binding_lower.contains("super") && binding_lower.contains("v") {
-            conflicts.push(ShortcutConflict {
-                binding: "<Super>v".to_string(),
-                current_action: "Open Notification Center / Message Tray".to_string(),
-                owner: "GNOME Shell".to_string(),
-                resolution_command: Some(
-                    "gsettings set org.gnome.shell.keybindings toggle-message-tray \"['<Super><Shift>v']\"".to_string()
-                ),
-                resolution_steps: r#"**To resolve manually:**
-1. Open Settings → Keyboard → Keyboard Shortcuts
-2. Search for "Notification" or "Message Tray"
-3. Change Super+V to Super+Shift+V (or disable it)
-
-**Or run this command:**
-```
-gsettings set org.gnome.shell.keybindings toggle-message-tray "['<Super><Shift>v']"
-```"#.to_string(),
-            });
-        }
-    }
-
-    // Check for Clipboard shortcut in GNOME 45+ (if applicable)
-    if let Some(binding) = gsettings_get("org.gnome.shell.keybindings", "toggle-quick-settings") {
-        let binding_lower = binding.to_lowercase();
-        if binding_lower.contains("super") && binding_lower.contains("v") {
-            conflicts.push(ShortcutConflict {
-                binding: "<Super>v".to_string(),
-                current_action: "Toggle Quick Settings".to_string(),
-                owner: "GNOME Shell".to_string(),
-                resolution_command: Some(
-                    "gsettings set org.gnome.shell.keybindings toggle-quick-settings \"[]\""
-                        .to_string(),
-                ),
-                resolution_steps:
-                    "Disable the Quick Settings shortcut in GNOME Settings → Keyboard → Shortcuts"
-                        .to_string(),
-            });
-        }
-    }
-
-    conflicts
+const GNOME_KEYBINDING_SCHEMAS: &[&str] = &[
+    "org.gnome.shell.keybindings",
+    "org.gnome.desktop.wm.keybindings",
+    "org.gnome.mutter.keybindings",
+    "org.gnome.mutter.wayland.keybindings",
+    "org.gnome.settings-daemon.plugins.media-keys",
+];
+
+fn detect_gnome_conflicts(chord: &Chord) -> Vec<ShortcutConflict> {
+    let listing = gsettings_list(GNOME_KEYBINDING_SCHEMAS);
+    gsettings_listing_conflicts(
+        "GNOME Shell",
+        "Settings → Keyboard → Keyboard Shortcuts",
+        chord,
+        listed_bindings(&listing, chord),
+    )
 }
 
 // =============================================================================
 // Pop!_OS / Pop Shell Conflict Detection
 // =============================================================================
 
-fn detect_pop_shell_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
-
-    // Pop Shell uses org.gnome.shell.extensions.pop-shell for some shortcuts
-    // Also inherits GNOME's notification tray shortcut
-
-    // Check GNOME's notification center first
-    conflicts.extend(detect_gnome_conflicts());
+fn detect_pop_shell_conflicts(chord: &Chord) -> Vec<ShortcutConflict> {
+    // Pop Shell inherits GNOME's shortcuts and adds its own tiling ones
+    let mut conflicts = detect_gnome_conflicts(chord);
 
-    // Check Pop Shell specific shortcuts
-    if let Some(binding) = gsettings_get("org.gnome.shell.extensions.pop-shell", "tile-enter") {
-        let binding_lower = binding.to_lowercase();
-        if binding_lower.contains("super") && binding_lower.contains("v") {
-            conflicts.push(ShortcutConflict {
-                binding: "<Super>v".to_string(),
-                current_action: "Enter Tiling Mode".to_string(),
-                owner: "Pop Shell".to_string(),
-                resolution_command: Some(
-                    "gsettings set org.gnome.shell.extensions.pop-shell tile-enter \"['<Super><Shift>v']\"".to_string()
-                ),
-                resolution_steps: r#"**To resolve manually:**
-1. Open Pop!_OS Settings → Keyboard → Customize Shortcuts
-2. Find "Pop Shell: Enter Tile Mode"
-3. Change it to a different binding
-
-**Or run:**
-```
-gsettings set org.gnome.shell.extensions.pop-shell tile-enter "['<Super><Shift>v']"
-```"#.to_string(),
-            });
-        }
-    }
+    let listing = gsettings_list(&["org.gnome.shell.extensions.pop-shell"]);
+    conflicts.extend(gsettings_listing_conflicts(
+        "Pop Shell",
+        "Pop!_OS Settings → Keyboard → Customize Shortcuts",
+        chord,
+        listed_bindings(&listing, chord),
+    ));
 
     conflicts
 }
@@ -283,7 +241,7 @@ gsettings set org.gnome.shell.extensions.pop-shell tile-enter "['<Super><Shift>v
 // COSMIC Desktop Conflict Detection
 // =============================================================================
 
-fn detect_cosmic_conflicts() -> Vec<ShortcutConflict> {
+fn detect_cosmic_conflicts(chord: &Chord) -> Vec<ShortcutConflict> {
     let mut conflicts = Vec::new();
 
     let home = match env::var("HOME") {
@@ -292,45 +250,30 @@ fn detect_cosmic_conflicts() -> Vec<ShortcutConflict> {
     };
 
     // COSMIC stores shortcuts in ~/.config/cosmic/com.system76.CosmicSettings.Shortcuts/
-    let shortcuts_path =
-        PathBuf::from(&home).join(".config/cosmic/com.system76.CosmicSettings.Shortcuts/v1/custom");
-
-    if let Ok(content) = fs::read_to_string(&shortcuts_path) {
-        // Check for Super+V bindings
-        if content.to_lowercase().contains("super")
-            && content.to_lowercase().contains("\"v\"")
-            && !content.contains("win11-clipboard-history")
+    let shortcuts_dir =
+        PathBuf::from(&home).join(".config/cosmic/com.system76.CosmicSettings.Shortcuts/v1");
+
+    for (file, settings_page) in [("custom", "Custom"), ("system_actions", "System")] {
+        let Ok(content) = fs::read_to_string(shortcuts_dir.join(file)) else {
+            continue;
+        };
+        for (_, action) in cosmic_bindings(&content)
+            .into_iter()
+            .filter(|(bound, _)| bound == chord)
+            .filter(|(_, action)| !action.contains("win11-clipboard-history"))
         {
             conflicts.push(ShortcutConflict {
-                binding: "Super+V".to_string(),
-                current_action: "Unknown COSMIC shortcut".to_string(),
-                owner: "COSMIC Desktop".to_string(),
-                resolution_command: None,
-                resolution_steps: r#"**To resolve manually:**
-1. Open COSMIC Settings → Keyboard → Shortcuts
-2. Find any shortcut using Super+V
-3. Change it to a different binding or remove it"#
-                    .to_string(),
-            });
-        }
-    }
-
-    // Also check system-level COSMIC shortcuts
-    let system_shortcuts = PathBuf::from(&home)
-        .join(".config/cosmic/com.system76.CosmicSettings.Shortcuts/v1/system_actions");
-
-    if let Ok(content) = fs::read_to_string(&system_shortcuts) {
-        if content.to_lowercase().contains("super") && content.to_lowercase().contains("\"v\"") {
-            conflicts.push(ShortcutConflict {
-                binding: "Super+V".to_string(),
-                current_action: "COSMIC System Action".to_string(),
+                binding: chord.to_string(),
+                current_action: action,
                 owner: "COSMIC Desktop".to_string(),
                 resolution_command: None,
-                resolution_steps: r#"**COSMIC System Shortcut Conflict:**
-1. Open COSMIC Settings → Keyboard → Shortcuts → System
-2. Find the Super+V binding
-3. Change or disable it"#
-                    .to_string(),
+                resolution_steps: format!(
+                    r#"**To resolve manually:**
+1. Open COSMIC Settings → Keyboard → Shortcuts → {}
+2. Find the {} binding
+3. Change it to a different binding or remove it"#,
+                    settings_page, chord
+                ),
             });
         }
     }
@@ -338,24 +281,56 @@ fn detect_cosmic_conflicts() -> Vec<ShortcutConflict> {
     conflicts
 }
 
+/// Bindings in a COSMIC shortcuts file, written on one line or spread over several:
+/// `(modifiers: [Super], key: "v"): Spawn("foo"),`
+fn cosmic_bindings(content: &str) -> Vec<(Chord, String)> {
+    content
+        .split("modifiers:")
+        .skip(1)
+        .filter_map(|entry| {
+            let (mods, rest) = entry.split_once(']')?;
+            let mods = mods.trim_start().strip_prefix('[')?;
+            let key = rest.split_once("key:")?.1.trim_start().strip_prefix('"')?;
+            let (key, rest) = key.split_once('"')?;
+            let action = rest
+                .split_once("):")?
+                .1
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())?
+                .trim_end_matches(',');
+
+            let chord = mods
+                .split(',')
+                .map(str::trim)
+                .filter(|m| !m.is_empty())
+                .chain([key])
+                .collect::<Vec<_>>()
+                .join("+");
+            Some((Chord::parse(&chord).ok()?, action.to_string()))
+        })
+        .collect()
+}
+
 // =============================================================================
 // KDE Plasma Conflict Detection
 // =============================================================================
 
-fn detect_kde_conflicts() -> Vec<ShortcutConflict> {
+fn detect_kde_conflicts(chord: &Chord) -> Vec<ShortcutConflict> {
     let mut conflicts = Vec::new();
 
     let home = match env::var("HOME") {
         Ok(h) => h,
         Err(_) => return conflicts,
     };
+    let binding = chord.kde();
 
-    // Check kglobalshortcutsrc for Meta+V bindings
+    // Check kglobalshortcutsrc for bindings of the chord
     let shortcuts_path = PathBuf::from(&home).join(".config/kglobalshortcutsrc");
 
     if let Ok(content) = fs::read_to_string(&shortcuts_path) {
         for line in content.lines() {
-            if line.contains("Meta+V") || line.contains("Meta+v") {
+            if kde_line_binds(line, chord) {
                 // Try to extract the action name
                 if let Some(action) = extract_kde_action(&content, line) {
                     // Skip if it's our own shortcut
@@ -364,7 +339,7 @@ fn detect_kde_conflicts() -> Vec<ShortcutConflict> {
                     }
 
                     conflicts.push(ShortcutConflict {
-                        binding: "Meta+V".to_string(),
+                        binding: binding.clone(),
                         current_action: action.clone(),
                         owner: "KDE Plasma".to_string(),
                         resolution_command: None,
@@ -372,10 +347,10 @@ fn detect_kde_conflicts() -> Vec<ShortcutConflict> {
                             r#"**To resolve manually:**
 1. Open System Settings → Shortcuts → Global Shortcuts
 2. Find "{}"
-3. Change or clear the Meta+V binding
+3. Change or clear the {} binding
 
-**Alternative:** Use the search function to find "Meta+V" bindings"#,
-                            action
+**Alternative:** Use the search function to find "{}" bindings"#,
+                            action, binding, binding
                         ),
                     });
                 }
@@ -387,21 +362,23 @@ fn detect_kde_conflicts() -> Vec<ShortcutConflict> {
     let klipper_path = PathBuf::from(&home).join(".config/klipperrc");
     if klipper_path.exists() {
         if let Ok(content) = fs::read_to_string(&klipper_path) {
-            if content.contains("Meta+V") {
+            if content.lines().any(|line| kde_line_binds(line, chord)) {
                 conflicts.push(ShortcutConflict {
-                    binding: "Meta+V".to_string(),
+                    binding: binding.clone(),
                     current_action: "Klipper Clipboard History".to_string(),
                     owner: "Klipper".to_string(),
                     resolution_command: None,
-                    resolution_steps: r#"**Klipper Conflict:**
-KDE's built-in clipboard manager (Klipper) may use Meta+V.
+                    resolution_steps: format!(
+                        r#"**Klipper Conflict:**
+KDE's built-in clipboard manager (Klipper) may use {}.
 
 1. Right-click the Klipper icon in the system tray
 2. Click "Configure Klipper"
 3. Go to "Shortcuts" and change or disable the shortcut
 
-**Alternatively:** Disable Klipper entirely if you prefer this app."#
-                        .to_string(),
+**Alternatively:** Disable Klipper entirely if you prefer this app."#,
+                        binding
+                    ),
                 });
             }
         }
@@ -410,6 +387,18 @@ KDE's built-in clipboard manager (Klipper) may use Meta+V.
     conflicts
 }
 
+/// Whether a `name=active,default,description` line binds the chord. Several active
+/// shortcuts are separated by tabs.
+fn kde_line_binds(line: &str, chord: &Chord) -> bool {
+    line.split_once('=')
+        .and_then(|(_, value)| value.split(',').next())
+        .is_some_and(|active| {
+            active
+                .split('\t')
+                .any(|shortcut| Chord::parse(shortcut).as_ref() == Ok(chord))
+        })
+}
+
 fn extract_kde_action(content: &str, target_line: &str) -> Option<String> {
     // KDE shortcut format: action=shortcut,default,description
     // We need to find the section header [Component] above the line
@@ -433,72 +422,47 @@ fn extract_kde_action(content: &str, target_line: &str) -> Option<String> {
 }
 
 // =============================================================================
-// i3 Window Manager Conflict Detection
+// i3 / Sway / Hyprland Conflict Detection
 // =============================================================================
 
-fn detect_i3_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
-
-    let config_paths = get_i3_config_paths();
-
-    for path in config_paths {
-        if let Ok(content) = fs::read_to_string(&path) {
-            // Look for bindsym $mod+v or bindsym Mod4+v
-            for line in content.lines() {
-                let line_lower = line.to_lowercase().trim().to_string();
-
-                // Skip comments
-                if line_lower.starts_with('#') {
-                    continue;
-                }
-
-                // Check for Super+V bindings (Mod4 is typically Super)
-                if (line_lower.contains("bindsym") || line_lower.contains("bindcode"))
-                    && (line_lower.contains("mod4+v") || line_lower.contains("$mod+v"))
-                    && !line_lower.contains("clipboard-history")
-                    && !line_lower.contains("win11")
-                {
-                    // Extract the action
-                    let action = line
-                        .split_whitespace()
-                        .skip(2)
-                        .collect::<Vec<_>>()
-                        .join(" ");
-
-                    conflicts.push(ShortcutConflict {
-                        binding: "$mod+v / Mod4+v".to_string(),
-                        current_action: if action.is_empty() {
-                            "Unknown action".to_string()
-                        } else {
-                            action
-                        },
-                        owner: "i3 config".to_string(),
-                        resolution_command: None,
-                        resolution_steps: format!(
-                            r#"**i3 Config Conflict:**
-Found in: {}
-
-**To resolve:**
-1. Edit your i3 config: `{}`
-2. Find the line with `bindsym $mod+v` or `bindsym Mod4+v`
-3. Change it to a different binding or comment it out
-
-**Then add:**
-```
-bindsym $mod+v exec win11-clipboard-history
-```
+/// A tiling WM config with its own fallback locations
+struct TilingWm {
+    owner: &'static str,
+    syntax: ConfigSyntax,
+    /// Binding to add once the conflict is gone
+    snippet: fn(&BoundChord) -> String,
+    reload: &'static str,
+}
 
-4. Reload i3: Press $mod+Shift+r"#,
-                            path.display(),
-                            path.display()
-                        ),
-                    });
-                }
-            }
-        }
-    }
+const I3: TilingWm = TilingWm {
+    owner: "i3",
+    syntax: ConfigSyntax::I3,
+    snippet: |b| format!("bindsym {} exec {}", b.chord.i3(), b.command().join(" ")),
+    reload: "Reload i3: Press $mod+Shift+r",
+};
+
+const SWAY: TilingWm = TilingWm {
+    owner: "Sway",
+    syntax: ConfigSyntax::I3,
+    snippet: |b| format!("bindsym {} exec {}", b.chord.i3(), b.command().join(" ")),
+    reload: "Reload Sway: Press $mod+Shift+c",
+};
+
+const HYPRLAND: TilingWm = TilingWm {
+    owner: "Hyprland",
+    syntax: ConfigSyntax::Hyprland,
+    snippet: |b| {
+        format!(
+            "bind = {}, exec, {}",
+            b.chord.hyprland(),
+            b.command().join(" ")
+        )
+    },
+    reload: "The config auto-reloads, or reload manually",
+};
 
-    conflicts
+fn detect_i3_conflicts(bound: &BoundChord) -> Vec<ShortcutConflict> {
+    tiling_wm_conflicts(&I3, &get_i3_config_paths(), bound)
 }
 
 fn get_i3_config_paths() -> Vec<PathBuf> {
@@ -510,224 +474,702 @@ fn get_i3_config_paths() -> Vec<PathBuf> {
     ]
 }
 
+fn detect_sway_conflicts(bound: &BoundChord) -> Vec<ShortcutConflict> {
+    tiling_wm_conflicts(&SWAY, &get_sway_config_paths(), bound)
+}
+
+fn get_sway_config_paths() -> Vec<PathBuf> {
+    let home = env::var("HOME").unwrap_or_default();
+    vec![
+        PathBuf::from(&home).join(".config/sway/config"),
+        PathBuf::from(&home).join(".sway/config"),
+        PathBuf::from("/etc/sway/config"),
+    ]
+}
+
+fn detect_hyprland_conflicts(bound: &BoundChord) -> Vec<ShortcutConflict> {
+    tiling_wm_conflicts(&HYPRLAND, &get_hyprland_config_paths(), bound)
+}
+
+fn get_hyprland_config_paths() -> Vec<PathBuf> {
+    let home = env::var("HOME").unwrap_or_default();
+    let xdg_config = env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| format!("{}/.config", home));
+
+    vec![
+        PathBuf::from(&xdg_config).join("hypr/hyprland.conf"),
+        PathBuf::from(&home).join(".config/hypr/hyprland.conf"),
+    ]
+}
+
+fn tiling_wm_conflicts(
+    wm: &TilingWm,
+    paths: &[PathBuf],
+    bound: &BoundChord,
+) -> Vec<ShortcutConflict> {
+    // The first existing file is the one the WM actually loads
+    paths
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok().map(|c| (path, c)))
+        .map(|(path, content)| {
+            let snippet = (wm.snippet)(bound);
+            config_conflicts(
+                wm.owner,
+                wm.syntax,
+                path,
+                &content,
+                &bound.chord,
+                &snippet,
+                wm.reload,
+            )
+        })
+        .unwrap_or_default()
+}
+
 // =============================================================================
-// Sway Window Manager Conflict Detection
+// Config-file Window Managers (niri, river, labwc, Wayfire, Openbox, ...)
 // =============================================================================
 
-fn detect_sway_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
+/// A window manager whose key bindings live in a config file we can read
+struct WmConfig {
+    /// Display name of the desktop the config belongs to, also the conflict owner
+    owner: &'static str,
+    syntax: ConfigSyntax,
+    /// Config files relative to $XDG_CONFIG_HOME, then absolute system-wide fallbacks
+    paths: &'static [&'static str],
+    /// Binding to add once the conflict is gone
+    snippet: fn(&BoundChord) -> String,
+    reload: &'static str,
+}
 
-    let config_paths = get_sway_config_paths();
+/// Looked up by the `owner`, which matches the desktop's display name
+const WM_CONFIGS: &[WmConfig] = &[
+    WmConfig {
+        owner: "niri",
+        syntax: ConfigSyntax::Niri,
+        paths: &["niri/config.kdl"],
+        snippet: |b| {
+            let args: Vec<String> = b.command().iter().map(|a| format!("\"{}\"", a)).collect();
+            format!("{} {{ spawn {}; }}", b.chord.niri(), args.join(" "))
+        },
+        reload: "niri reloads its config automatically",
+    },
+    WmConfig {
+        owner: "River",
+        syntax: ConfigSyntax::River,
+        paths: &["river/init"],
+        snippet: |b| {
+            format!(
+                "riverctl map normal {} {} spawn '{}'",
+                b.chord.river_mods(),
+                b.chord.keysym(),
+                b.command().join(" ")
+            )
+        },
+        reload: "Run the same `riverctl map` line to apply it without restarting",
+    },
+    WmConfig {
+        owner: "labwc",
+        syntax: ConfigSyntax::Openbox,
+        paths: &["labwc/rc.xml", "/etc/xdg/labwc/rc.xml"],
+        snippet: openbox_snippet,
+        reload: "Run `labwc --reconfigure`",
+    },
+    WmConfig {
+        owner: "Wayfire",
+        syntax: ConfigSyntax::Wayfire,
+        paths: &["wayfire.ini"],
+        snippet: |b| {
+            format!(
+                "binding_{0} = {1}\ncommand_{0} = {2}",
+                b.id.replace('-', "_"),
+                b.chord.wayfire(),
+                b.command().join(" ")
+            )
+        },
+        reload: "Wayfire reloads its config automatically",
+    },
+    WmConfig {
+        owner: "bspwm",
+        syntax: ConfigSyntax::Sxhkd,
+        paths: &["sxhkd/sxhkdrc"],
+        snippet: |b| format!("{}\n    {}", b.chord.sxhkd(), b.command().join(" ")),
+        reload: "Run `pkill -USR1 -x sxhkd`",
+    },
+    WmConfig {
+        owner: "awesome",
+        syntax: ConfigSyntax::Awesome,
+        paths: &["awesome/rc.lua", "/etc/xdg/awesome/rc.lua"],
+        snippet: |b| {
+            format!(
+                r#"awful.key({}, "{}", function() awful.spawn("{}") end)"#,
+                b.chord.awesome_mods(),
+                b.chord.keysym(),
+                b.command().join(" ")
+            )
+        },
+        reload: "Restart awesome (Mod4+Ctrl+R)",
+    },
+    WmConfig {
+        owner: "Qtile",
+        syntax: ConfigSyntax::Qtile,
+        paths: &["qtile/config.py"],
+        snippet: |b| {
+            format!(
+                r#"Key({}, "{}", lazy.spawn("{}"))"#,
+                b.chord.qtile_mods(),
+                b.chord.keysym(),
+                b.command().join(" ")
+            )
+        },
+        reload: "Reload the config (Mod4+Ctrl+R by default)",
+    },
+    WmConfig {
+        owner: "LXDE",
+        syntax: ConfigSyntax::Openbox,
+        paths: &[
+            "openbox/lxde-rc.xml",
+            "openbox/lubuntu-rc.xml",
+            "openbox/rc.xml",
+            "/etc/xdg/openbox/LXDE/rc.xml",
+        ],
+        snippet: openbox_snippet,
+        reload: "Run `openbox --reconfigure`",
+    },
+    WmConfig {
+        owner: "Openbox",
+        syntax: ConfigSyntax::Openbox,
+        paths: &["openbox/rc.xml", "/etc/xdg/openbox/rc.xml"],
+        snippet: openbox_snippet,
+        reload: "Run `openbox --reconfigure`",
+    },
+];
+
+fn openbox_snippet(bound: &BoundChord) -> String {
+    format!(
+        r#"<keybind key="{}"><action name="Execute" command="{}" /></keybind>"#,
+        bound.chord.openbox(),
+        bound.command().join(" ")
+    )
+}
 
-    for path in config_paths {
-        if let Ok(content) = fs::read_to_string(&path) {
-            for line in content.lines() {
-                let line_lower = line.to_lowercase().trim().to_string();
+fn detect_wm_config_conflicts(wm: &WmConfig, bound: &BoundChord) -> Vec<ShortcutConflict> {
+    let home = env::var("HOME").unwrap_or_default();
+    let xdg_config = env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| format!("{}/.config", home));
 
-                if line_lower.starts_with('#') {
-                    continue;
-                }
+    // The first existing file is the one the WM actually loads
+    wm.paths
+        .iter()
+        .map(|p| PathBuf::from(&xdg_config).join(p))
+        .find_map(|path| fs::read_to_string(&path).ok().map(|c| (path, c)))
+        .map(|(path, content)| wm_config_conflicts(wm, &path, &content, bound))
+        .unwrap_or_default()
+}
 
-                if (line_lower.contains("bindsym") || line_lower.contains("bindcode"))
-                    && (line_lower.contains("mod4+v") || line_lower.contains("$mod+v"))
-                    && !line_lower.contains("clipboard-history")
-                    && !line_lower.contains("win11")
-                {
-                    let action = line
-                        .split_whitespace()
-                        .skip(2)
-                        .collect::<Vec<_>>()
-                        .join(" ");
+fn wm_config_conflicts(
+    wm: &WmConfig,
+    path: &Path,
+    content: &str,
+    bound: &BoundChord,
+) -> Vec<ShortcutConflict> {
+    let snippet = (wm.snippet)(bound);
+    config_conflicts(
+        wm.owner,
+        wm.syntax,
+        path,
+        content,
+        &bound.chord,
+        &snippet,
+        wm.reload,
+    )
+}
 
-                    conflicts.push(ShortcutConflict {
-                        binding: "$mod+v / Mod4+v".to_string(),
-                        current_action: if action.is_empty() {
-                            "Unknown action".to_string()
-                        } else {
-                            action
-                        },
-                        owner: "Sway config".to_string(),
-                        resolution_command: None,
-                        resolution_steps: format!(
-                            r#"**Sway Config Conflict:**
+/// Conflicts with the chord in a WM config file
+fn config_conflicts(
+    owner: &str,
+    syntax: ConfigSyntax,
+    path: &Path,
+    content: &str,
+    chord: &Chord,
+    snippet: &str,
+    reload: &str,
+) -> Vec<ShortcutConflict> {
+    let lines: Vec<&str> = content.lines().collect();
+
+    syntax
+        .bindings(content)
+        .into_iter()
+        .filter(|b| &b.chord == chord)
+        .filter(|b| {
+            let action = b.action.to_lowercase();
+            !action.contains("clipboard-history") && !action.contains("win11")
+        })
+        .map(|b| {
+            let line = lines[b.line].trim();
+            ShortcutConflict {
+                binding: chord.to_string(),
+                current_action: if b.action.is_empty() {
+                    line.to_string()
+                } else {
+                    b.action
+                },
+                owner: format!("{} config", owner),
+                resolution_command: None,
+                resolution_steps: format!(
+                    r#"**{} Config Conflict:**
 Found in: {}
 
 **To resolve:**
-1. Edit your Sway config: `{}`
-2. Find the line with `bindsym $mod+v`
+1. Edit your {} config: `{}`
+2. Find the line `{}`
 3. Change it to a different binding or comment it out
 
 **Then add:**
 ```
-bindsym $mod+v exec win11-clipboard-history
+{}
 ```
 
-4. Reload Sway: Press $mod+Shift+c"#,
-                            path.display(),
-                            path.display()
-                        ),
-                    });
-                }
+4. {}"#,
+                    owner,
+                    path.display(),
+                    owner,
+                    path.display(),
+                    line,
+                    snippet,
+                    reload
+                ),
             }
-        }
-    }
-
-    conflicts
-}
-
-fn get_sway_config_paths() -> Vec<PathBuf> {
-    let home = env::var("HOME").unwrap_or_default();
-    vec![
-        PathBuf::from(&home).join(".config/sway/config"),
-        PathBuf::from(&home).join(".sway/config"),
-        PathBuf::from("/etc/sway/config"),
-    ]
+        })
+        .collect()
 }
 
 // =============================================================================
-// Hyprland Conflict Detection
+// gsettings Keybinding Schemas (GNOME, Cinnamon, MATE, Budgie, Deepin)
 // =============================================================================
 
-fn detect_hyprland_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
-
-    let config_paths = get_hyprland_config_paths();
+/// Parses a GTK accelerator: `<Super>v`, `<Mod4>v`, `<Primary><Alt>v`
+fn parse_gtk_accel(accel: &str) -> Option<Chord> {
+    Chord::parse(&accel.replace('<', "").replace('>', "+")).ok()
+}
 
-    for path in config_paths {
-        if let Ok(content) = fs::read_to_string(&path) {
-            for line in content.lines() {
-                let line_lower = line.to_lowercase().trim().to_string();
+/// Accelerators of a gsettings value: `'<Super>v'` or `['<Super>v', '<Super>m']`
+fn gsettings_accels(value: &str) -> Vec<&str> {
+    value
+        .trim_start_matches("@as ")
+        .trim_matches(['[', ']'])
+        .split(',')
+        .map(|accel| accel.trim().trim_matches(['\'', '"']))
+        .filter(|accel| !accel.is_empty())
+        .collect()
+}
 
-                if line_lower.starts_with('#') {
-                    continue;
-                }
+/// Schema, key and value of each binding of the chord in a `gsettings list-recursively`
+/// listing. Values are a single accelerator (MATE) or a list of them (GNOME-based desktops).
+fn listed_bindings(listing: &str, chord: &Chord) -> Vec<(String, String, String)> {
+    listing
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, ' ');
+            let (schema, key, value) = (parts.next()?, parts.next()?, parts.next()?);
+            gsettings_accels(value)
+                .iter()
+                .any(|accel| parse_gtk_accel(accel).as_ref() == Some(chord))
+                .then(|| (schema.to_string(), key.to_string(), value.to_string()))
+        })
+        .collect()
+}
 
-                // Hyprland uses bind = SUPER, V, exec, command
-                if line_lower.starts_with("bind")
-                    && line_lower.contains("super")
-                    && (line_lower.contains(", v,") || line_lower.contains(",v,"))
-                    && !line_lower.contains("clipboard-history")
-                    && !line_lower.contains("win11")
-                {
-                    // Extract action from bind line
-                    let parts: Vec<&str> = line.split(',').collect();
-                    let action = if parts.len() >= 4 {
-                        parts[3..].join(",").trim().to_string()
-                    } else {
-                        "Unknown action".to_string()
-                    };
+/// The value with the chord turned off: lists keep their other accelerators,
+/// single accelerators become 'disabled'
+fn gsettings_value_without(value: &str, chord: &Chord) -> String {
+    if !value.trim_start_matches("@as ").starts_with('[') {
+        return "'disabled'".to_string();
+    }
+    let kept: Vec<String> = gsettings_accels(value)
+        .into_iter()
+        .filter(|accel| parse_gtk_accel(accel).as_ref() != Some(chord))
+        .map(|accel| format!("'{}'", accel))
+        .collect();
+    format!("[{}]", kept.join(", "))
+}
 
-                    conflicts.push(ShortcutConflict {
-                        binding: "SUPER, V".to_string(),
-                        current_action: action,
-                        owner: "Hyprland config".to_string(),
-                        resolution_command: None,
-                        resolution_steps: format!(
-                            r#"**Hyprland Config Conflict:**
-Found in: {}
+/// Readable names for the keys users most often run into
+fn gsettings_action_name(key: &str) -> String {
+    match key {
+        "toggle-message-tray" => "Open Notification Center / Message Tray",
+        "toggle-quick-settings" => "Toggle Quick Settings",
+        "show-desklets" => "Show Desklets",
+        "tile-enter" => "Enter Tiling Mode",
+        other => other,
+    }
+    .to_string()
+}
 
-**To resolve:**
-1. Edit your Hyprland config: `{}`
-2. Find the line with `bind = SUPER, V, ...`
-3. Change it to a different binding or comment it out
+/// Conflicts for the listed bindings of the chord, each resolved by turning it off
+fn gsettings_listing_conflicts(
+    owner: &str,
+    settings_path: &str,
+    chord: &Chord,
+    bindings: Vec<(String, String, String)>,
+) -> Vec<ShortcutConflict> {
+    bindings
+        .into_iter()
+        .map(|(schema, key, value)| {
+            let command = format!(
+                "gsettings set {} {} \"{}\"",
+                schema,
+                key,
+                gsettings_value_without(&value, chord)
+            );
+            let action = gsettings_action_name(&key);
+            ShortcutConflict {
+                binding: chord.gnome(),
+                current_action: action.clone(),
+                owner: owner.to_string(),
+                resolution_command: Some(command.clone()),
+                resolution_steps: format!(
+                    r#"**To resolve manually:**
+1. Open {}
+2. Find the "{}" shortcut
+3. Change {} to a different binding or disable it
 
-**Then add:**
-```
-bind = SUPER, V, exec, win11-clipboard-history
+**Or run:**
 ```
-
-4. The config auto-reloads, or reload manually"#,
-                            path.display(),
-                            path.display()
-                        ),
-                    });
-                }
+{}
+```"#,
+                    settings_path, action, chord, command
+                ),
             }
-        }
-    }
+        })
+        .collect()
+}
 
-    conflicts
+fn detect_mate_conflicts(chord: &Chord) -> Vec<ShortcutConflict> {
+    let bindings = gsettings_list(&[
+        "org.mate.Marco.global-keybindings",
+        "org.mate.Marco.window-keybindings",
+    ]);
+    let commands = gsettings_list(&["org.mate.Marco.keybinding-commands"]);
+    mate_conflicts(&bindings, &commands, chord)
 }
 
-fn get_hyprland_config_paths() -> Vec<PathBuf> {
-    let home = env::var("HOME").unwrap_or_default();
-    let xdg_config = env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| format!("{}/.config", home));
+/// MATE conflicts, leaving out the run-command slot we registered ourselves
+fn mate_conflicts(bindings: &str, commands: &str, chord: &Chord) -> Vec<ShortcutConflict> {
+    let ours = |key: &str| {
+        let Some(slot) = key.strip_prefix("run-command-") else {
+            return false;
+        };
+        let command_key = format!(" command-{} ", slot);
+        commands
+            .lines()
+            .any(|line| line.contains(&command_key) && line.contains("clipboard-history"))
+    };
+    let bindings = listed_bindings(bindings, chord)
+        .into_iter()
+        .filter(|(_, key, _)| !ours(key))
+        .collect();
+    gsettings_listing_conflicts(
+        "MATE (Marco)",
+        "Control Center → Keyboard Shortcuts",
+        chord,
+        bindings,
+    )
+}
 
-    vec![
-        PathBuf::from(&xdg_config).join("hypr/hyprland.conf"),
-        PathBuf::from(&home).join(".config/hypr/hyprland.conf"),
-    ]
+fn detect_budgie_conflicts(chord: &Chord) -> Vec<ShortcutConflict> {
+    let listing = gsettings_list(&[
+        "com.solus-project.budgie-wm",
+        "org.gnome.desktop.wm.keybindings",
+    ]);
+    gsettings_listing_conflicts(
+        "Budgie",
+        "Budgie Desktop Settings → Keyboard (or GNOME Settings → Keyboard)",
+        chord,
+        listed_bindings(&listing, chord),
+    )
+}
+
+fn detect_deepin_conflicts(chord: &Chord) -> Vec<ShortcutConflict> {
+    let listing = gsettings_list(&[
+        "com.deepin.dde.keybinding.system",
+        "com.deepin.dde.keybinding.mediakey",
+        "com.deepin.wrap.gnome.desktop.wm.keybindings",
+    ]);
+    gsettings_listing_conflicts(
+        "Deepin",
+        "Control Center → Keyboard and Language → Shortcuts",
+        chord,
+        listed_bindings(&listing, chord),
+    )
 }
 
 // =============================================================================
-// Cinnamon Conflict Detection
+// LXQt Conflict Detection
 // =============================================================================
 
-fn detect_cinnamon_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
+fn detect_lxqt_conflicts(chord: &Chord) -> Vec<ShortcutConflict> {
+    let home = env::var("HOME").unwrap_or_default();
+    let xdg_config = env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| format!("{}/.config", home));
+    let path = PathBuf::from(xdg_config).join("lxqt/globalkeyshortcuts.conf");
+    fs::read_to_string(&path)
+        .map(|content| lxqt_conflicts(&path, &content, chord))
+        .unwrap_or_default()
+}
 
-    // Check for notification center / calendar shortcut
-    if let Some(binding) = gsettings_get("org.cinnamon.desktop.keybindings", "show-desklets") {
-        let binding_lower = binding.to_lowercase();
-        if binding_lower.contains("super") && binding_lower.contains("v") {
-            conflicts.push(ShortcutConflict {
-                binding: "<Super>v".to_string(),
-                current_action: "Show Desklets".to_string(),
-                owner: "Cinnamon".to_string(),
-                resolution_command: Some(
-                    "gsettings set org.cinnamon.desktop.keybindings show-desklets \"['<Super><Shift>v']\"".to_string()
-                ),
-                resolution_steps: r#"**To resolve manually:**
-1. Open System Settings → Keyboard → Shortcuts
-2. Find "Show Desklets"
-3. Change Super+V to Super+Shift+V"#.to_string(),
-            });
+/// Chord of a globalkeyshortcuts.conf section: `[Meta%2BV.12]` as lxqt-globalkeys writes
+/// it, or `[Meta+V%2Fid]` as we do
+fn lxqt_section_chord(section: &str) -> Option<Chord> {
+    let decoded = section.replace("%2B", "+").replace("%2F", "/");
+    let shortcut = decoded.split('/').next()?;
+    let shortcut = match shortcut.rsplit_once('.') {
+        Some((chord, n)) if n.chars().all(|c| c.is_ascii_digit()) => chord,
+        _ => shortcut,
+    };
+    Chord::parse(shortcut).ok()
+}
+
+fn lxqt_conflicts(path: &Path, content: &str, chord: &Chord) -> Vec<ShortcutConflict> {
+    // (section, chord, Comment/Exec/path values, enabled)
+    let mut sections: Vec<(String, Option<Chord>, Vec<String>, bool)> = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push((name.to_string(), lxqt_section_chord(name), Vec::new(), true));
+        } else if let (Some(section), Some((key, value))) =
+            (sections.last_mut(), line.split_once('='))
+        {
+            match key.trim() {
+                "Comment" | "Exec" | "path" => section.2.push(value.trim().to_string()),
+                "Enabled" => section.3 = value.trim() != "false",
+                _ => {}
+            }
         }
     }
 
-    conflicts
+    let binding = chord.kde();
+    sections
+        .into_iter()
+        .filter(|(_, bound, _, enabled)| *enabled && bound.as_ref() == Some(chord))
+        .filter(|(_, _, values, _)| {
+            !values
+                .iter()
+                .any(|v| v.contains("clipboard-history") || v.contains("win11"))
+        })
+        .map(|(section, _, values, _)| {
+            let action = values.first().cloned().unwrap_or(section);
+            ShortcutConflict {
+                binding: binding.clone(),
+                current_action: action.clone(),
+                owner: "LXQt".to_string(),
+                resolution_command: None,
+                resolution_steps: format!(
+                    r#"**To resolve manually:**
+1. Open LXQt Configuration Center → Shortcut Keys
+2. Find "{}"
+3. Change or remove the {} binding
+
+Shortcuts are stored in `{}`"#,
+                    action,
+                    binding,
+                    path.display()
+                ),
+            }
+        })
+        .collect()
 }
 
 // =============================================================================
-// XFCE Conflict Detection
+// Cinnamon Conflict Detection
 // =============================================================================
 
-fn detect_xfce_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
+fn detect_cinnamon_conflicts(chord: &Chord) -> Vec<ShortcutConflict> {
+    let listing = gsettings_list(&[
+        "org.cinnamon.desktop.keybindings",
+        "org.cinnamon.desktop.keybindings.wm",
+        "org.cinnamon.desktop.keybindings.media-keys",
+    ]);
+    gsettings_listing_conflicts(
+        "Cinnamon",
+        "System Settings → Keyboard → Shortcuts",
+        chord,
+        listed_bindings(&listing, chord),
+    )
+}
+
+// =============================================================================
+// XFCE Conflict Detection
+// =============================================================================
 
+fn detect_xfce_conflicts(chord: &Chord) -> Vec<ShortcutConflict> {
     if !command_exists("xfconf-query") {
-        return conflicts;
+        return Vec::new();
     }
 
-    // Check for Super+V in XFCE keyboard shortcuts
-    let output = Command::new("xfconf-query")
+    Command::new("xfconf-query")
         .args(["-c", "xfce4-keyboard-shortcuts", "-l", "-v"])
-        .output();
+        .output()
+        .map(|output| xfce_conflicts(&String::from_utf8_lossy(&output.stdout), chord))
+        .unwrap_or_default()
+}
 
-    if let Ok(output) = output {
-        let content = String::from_utf8_lossy(&output.stdout);
-        for line in content.lines() {
+/// Conflicts in `xfconf-query -l -v` output: `/commands/custom/<Super>v  command`
+fn xfce_conflicts(listing: &str, chord: &Chord) -> Vec<ShortcutConflict> {
+    listing
+        .lines()
+        .filter(|line| {
             let line_lower = line.to_lowercase();
-            if line_lower.contains("<super>v")
-                && !line_lower.contains("clipboard-history")
-                && !line_lower.contains("win11")
-            {
-                conflicts.push(ShortcutConflict {
-                    binding: "<Super>v".to_string(),
-                    current_action: line.to_string(),
-                    owner: "XFCE".to_string(),
-                    resolution_command: None,
-                    resolution_steps: r#"**To resolve manually:**
-1. Open Settings → Keyboard → Application Shortcuts
-2. Find the Super+V binding
-3. Change or remove it"#
-                        .to_string(),
-                });
-            }
-        }
-    }
+            !line_lower.contains("clipboard-history") && !line_lower.contains("win11")
+        })
+        .filter_map(|line| {
+            let (property, action) = line.split_once(char::is_whitespace)?;
+            let accel = property.rsplit('/').next()?;
+            (parse_gtk_accel(accel).as_ref() == Some(chord))
+                .then(|| (property.to_string(), action.trim().to_string()))
+        })
+        .filter(|(_, action)| action != "true" && action != "false")
+        .map(|(property, action)| ShortcutConflict {
+            binding: chord.xfce(),
+            current_action: action,
+            owner: "XFCE".to_string(),
+            resolution_command: None,
+            resolution_steps: format!(
+                r#"**To resolve manually:**
+1. Open Settings → Keyboard → Application Shortcuts (or Window Manager → Keyboard)
+2. Find the {} binding
+3. Change or remove it
+
+The shortcut is stored in the `{}` property"#,
+                chord, property
+            ),
+        })
+        .collect()
+}
 
-    conflicts
+// =============================================================================
+// Competing Clipboard Managers
+// =============================================================================
+
+/// A clipboard manager that fights this app over clipboard ownership and often grabs
+/// a clipboard-history shortcut of its own
+struct ClipboardManager {
+    name: &'static str,
+    /// Process names checked with pgrep
+    processes: &'static [&'static str],
+    /// Autostart desktop files that launch it at login
+    autostart: &'static [&'static str],
+    /// Stops it for the current session
+    stop_command: Option<&'static str>,
+    /// How to turn it off for good
+    disable_steps: &'static str,
+}
+
+const CLIPBOARD_MANAGERS: &[ClipboardManager] = &[
+    ClipboardManager {
+        name: "GPaste",
+        processes: &["gpaste-daemon"],
+        autostart: &["org.gnome.GPaste.desktop"],
+        stop_command: Some("gsettings set org.gnome.GPaste track-changes false"),
+        disable_steps: "Turn off the GPaste extension in Extensions, or untick \"Track clipboard changes\" in the GPaste preferences.",
+    },
+    ClipboardManager {
+        name: "CopyQ",
+        processes: &["copyq"],
+        autostart: &["com.github.hluk.copyq.desktop", "copyq.desktop"],
+        stop_command: Some("copyq exit"),
+        disable_steps: "Untick \"Autostart\" in CopyQ → Preferences → General, and clear its global shortcuts under Preferences → Shortcuts.",
+    },
+    ClipboardManager {
+        name: "Klipper",
+        processes: &["klipper"],
+        autostart: &["org.kde.klipper.desktop", "klipper.desktop"],
+        stop_command: None,
+        disable_steps: "Remove the Clipboard widget from the system tray (System Tray Settings → Entries → Clipboard → Disabled), and clear its shortcuts in System Settings → Shortcuts → Klipper.",
+    },
+    ClipboardManager {
+        name: "Diodon",
+        processes: &["diodon"],
+        autostart: &["diodon-autostart.desktop", "diodon.desktop"],
+        stop_command: Some("pkill -x diodon"),
+        disable_steps: "Untick \"Start at login\" in the Diodon preferences.",
+    },
+    ClipboardManager {
+        name: "Clipman",
+        processes: &["xfce4-clipman", "clipman"],
+        autostart: &["xfce4-clipman-plugin-autostart.desktop"],
+        stop_command: Some("pkill -x xfce4-clipman || pkill -x clipman"),
+        disable_steps: "Remove the Clipman panel plugin (XFCE), or the `wl-paste --watch clipman store` line from your compositor config (Wayland).",
+    },
+];
+
+fn detect_clipboard_managers() -> Vec<ShortcutConflict> {
+    let home = env::var("HOME").unwrap_or_default();
+    let xdg_config = env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| format!("{}/.config", home));
+    let user_dir = PathBuf::from(xdg_config).join("autostart");
+    let system_dir = PathBuf::from("/etc/xdg/autostart");
+
+    CLIPBOARD_MANAGERS
+        .iter()
+        .filter_map(|manager| {
+            let running = manager.processes.iter().any(|p| is_process_running(p));
+            let autostart = manager.autostart.iter().find(|file| {
+                autostart_enabled(
+                    fs::read_to_string(user_dir.join(file)).ok().as_deref(),
+                    fs::read_to_string(system_dir.join(file)).ok().as_deref(),
+                )
+            });
+            (running || autostart.is_some())
+                .then(|| clipboard_manager_conflict(manager, running, autostart.copied()))
+        })
+        .collect()
+}
+
+/// Whether an autostart entry launches at login. A user file overrides the system one
+/// of the same name.
+fn autostart_enabled(user: Option<&str>, system: Option<&str>) -> bool {
+    user.or(system).is_some_and(|entry| {
+        !entry.lines().map(str::trim).any(|line| {
+            line.eq_ignore_ascii_case("Hidden=true")
+                || line.eq_ignore_ascii_case("X-GNOME-Autostart-enabled=false")
+        })
+    })
+}
+
+fn clipboard_manager_conflict(
+    manager: &ClipboardManager,
+    running: bool,
+    autostart: Option<&str>,
+) -> ShortcutConflict {
+    let current_action = match (running, autostart) {
+        (true, Some(_)) => "Running and started at login",
+        (true, None) => "Running",
+        _ => "Started at login",
+    };
+    let autostart_step = autostart
+        .map(|file| {
+            format!(
+                "\n\nIt is started at login by `{}`: turn it off in your desktop's Startup Applications, or copy the file to `~/.config/autostart/` and add `Hidden=true`.",
+                file
+            )
+        })
+        .unwrap_or_default();
+    let stop_step = manager
+        .stop_command
+        .map(|cmd| format!("\n\n**To stop it now, run:**\n```\n{}\n```", cmd))
+        .unwrap_or_default();
+
+    ShortcutConflict {
+        binding: String::new(),
+        current_action: current_action.to_string(),
+        owner: manager.name.to_string(),
+        // Stopping another app or changing its settings is left to the user
+        resolution_command: None,
+        resolution_steps: format!(
+            r#"**Competing clipboard manager:**
+{} also records the clipboard and may take over the clipboard or your shortcuts.
+
+**To disable it:**
+{}{}{}"#,
+            manager.name, manager.disable_steps, autostart_step, stop_step
+        ),
+    }
 }
 
 #[cfg(test)]
@@ -739,4 +1181,202 @@ mod tests {
         // Just verify it doesn't panic when running
         let _result = detect_shortcut_conflicts();
     }
+
+    #[test]
+    fn test_wm_config_conflicts() {
+        let bound = |chord: &str, action| BoundChord {
+            id: "win11-clipboard-history".to_string(),
+            chord: Chord::parse(chord).unwrap(),
+            action,
+        };
+        let super_v = bound("Super+V", None);
+        let niri = WM_CONFIGS.iter().find(|wm| wm.owner == "niri").unwrap();
+        let config = r#"binds {
+    // Mod+V { toggle-window-floating; }
+    Mod+V { toggle-window-floating; }
+    Mod+Shift+V { spawn "win11-clipboard-history"; }
+    Ctrl+Alt+V { spawn "foot"; }
+}"#;
+        let path = Path::new("config.kdl");
+        let conflicts = wm_config_conflicts(niri, path, config, &super_v);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].owner, "niri config");
+        assert!(conflicts[0]
+            .current_action
+            .contains("toggle-window-floating"));
+
+        // Any chord is checked, and the suggested binding uses it
+        let ctrl_alt_v = bound("Ctrl+Alt+V", None);
+        let conflicts = wm_config_conflicts(niri, path, config, &ctrl_alt_v);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].binding, "Ctrl+Alt+V");
+        assert!(conflicts[0]
+            .resolution_steps
+            .contains(r#"Ctrl+Alt+V { spawn "win11-clipboard-history"; }"#));
+
+        // Action chords suggest their own command
+        let paste = bound(
+            "Ctrl+Alt+V",
+            Some(crate::shortcut_chord::ShortcutAction::PasteItem { index: 1 }),
+        );
+        let conflicts = wm_config_conflicts(niri, path, config, &paste);
+        assert!(conflicts[0]
+            .resolution_steps
+            .contains(r#"Ctrl+Alt+V { spawn "win11-clipboard-history" "--paste-item" "1"; }"#));
+
+        let openbox = WM_CONFIGS.iter().find(|wm| wm.owner == "Openbox").unwrap();
+        let rc = r#"<keyboard>
+  <keybind key="W-v">
+    <action name="Execute" command="win11-clipboard-history" />
+  </keybind>
+</keyboard>"#;
+        assert!(wm_config_conflicts(openbox, Path::new("rc.xml"), rc, &super_v).is_empty());
+
+        let lxde = WM_CONFIGS.iter().find(|wm| wm.owner == "LXDE").unwrap();
+        let rc = r#"<keyboard>
+  <keybind key="W-v">
+    <action name="ToggleShowDesktop" />
+  </keybind>
+</keyboard>"#;
+        let conflicts = wm_config_conflicts(lxde, Path::new("lxde-rc.xml"), rc, &super_v);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].owner, "LXDE config");
+    }
+
+    #[test]
+    fn test_gsettings_listing_conflicts() {
+        let super_v = Chord::parse("Super+V").unwrap();
+        let bindings = "org.mate.Marco.global-keybindings run-command-1 '<Mod4>v'
+org.mate.Marco.global-keybindings run-command-2 '<Super>v'
+org.mate.Marco.global-keybindings run-command-3 'disabled'
+org.mate.Marco.window-keybindings toggle-maximized '<Mod4>Up'";
+        let commands = "org.mate.Marco.keybinding-commands command-1 'win11-clipboard-history'
+org.mate.Marco.keybinding-commands command-2 'mate-screenshot'";
+        let conflicts = mate_conflicts(bindings, commands, &super_v);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].current_action, "run-command-2");
+        assert_eq!(
+            conflicts[0].resolution_command.as_deref(),
+            Some("gsettings set org.mate.Marco.global-keybindings run-command-2 \"'disabled'\"")
+        );
+
+        // Resolving keeps the other accelerators of a list
+        let budgie = "com.solus-project.budgie-wm toggle-raven ['<Super>a']
+com.solus-project.budgie-wm toggle-notifications ['<Super>n', '<Super>v']
+org.gnome.desktop.wm.keybindings close @as []";
+        let bindings = listed_bindings(budgie, &super_v);
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].1, "toggle-notifications");
+        let conflicts = gsettings_listing_conflicts("Budgie", "Settings", &super_v, bindings);
+        assert!(conflicts[0]
+            .resolution_command
+            .as_deref()
+            .unwrap()
+            .ends_with("toggle-notifications \"['<Super>n']\""));
+
+        let super_a = Chord::parse("Super+A").unwrap();
+        let bindings = listed_bindings(budgie, &super_a);
+        assert_eq!(bindings[0].1, "toggle-raven");
+        assert_eq!(gsettings_value_without(&bindings[0].2, &super_a), "[]");
+    }
+
+    #[test]
+    fn test_desktop_shortcut_files() {
+        let super_v = Chord::parse("Super+V").unwrap();
+
+        let cosmic = r#"{
+    (modifiers: [Super], key: "v"): System(Launcher),
+    (
+        modifiers: [
+            Ctrl,
+            Alt,
+        ],
+        key: "t",
+    ): Spawn("cosmic-term"),
+}"#;
+        let bindings = cosmic_bindings(cosmic);
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(
+            bindings[0],
+            (super_v.clone(), "System(Launcher)".to_string())
+        );
+        assert_eq!(bindings[1].0, Chord::parse("Ctrl+Alt+T").unwrap());
+
+        assert!(kde_line_binds(
+            "_launch=Ctrl+Alt+V\tMeta+V,Ctrl+Alt+V,Klipper",
+            &super_v
+        ));
+        assert!(!kde_line_binds(
+            "_launch=Ctrl+Alt+V,Meta+V,Klipper",
+            &super_v
+        ));
+
+        let xfconf = "/commands/custom/<Super>v          xfce4-popup-whiskermenu
+/commands/custom/<Primary><Alt>v   win11-clipboard-history
+/commands/custom/override          true";
+        let conflicts = xfce_conflicts(xfconf, &super_v);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].current_action, "xfce4-popup-whiskermenu");
+        let ctrl_alt_v = Chord::parse("Ctrl+Alt+V").unwrap();
+        assert!(xfce_conflicts(xfconf, &ctrl_alt_v).is_empty());
+    }
+
+    #[test]
+    fn test_lxqt_conflicts() {
+        let config = "[Meta%2BV.12]
+Comment=Show desktop
+Enabled=true
+path=/desktop/show
+
+[Meta%2BV.13]
+Comment=Disabled binding
+Enabled=false
+Exec=foo
+
+[Meta+V%2Fwin11-clipboard-history]
+Comment=Clipboard History
+Enabled=true
+Exec=win11-clipboard-history
+
+[Control%2BAlt%2BT.1]
+Exec=qterminal
+";
+        let ctrl_alt_t = Chord::parse("Ctrl+Alt+T").unwrap();
+        assert_eq!(
+            lxqt_section_chord("Control%2BAlt%2BT.1"),
+            Some(ctrl_alt_t.clone())
+        );
+        let path = Path::new("globalkeyshortcuts.conf");
+        let conflicts = lxqt_conflicts(path, config, &Chord::parse("Super+V").unwrap());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].current_action, "Show desktop");
+        assert_eq!(
+            lxqt_conflicts(path, config, &ctrl_alt_t)[0].current_action,
+            "qterminal"
+        );
+    }
+
+    #[test]
+    fn test_clipboard_manager_autostart() {
+        let entry = "[Desktop Entry]\nType=Application\nExec=copyq\n";
+        let hidden = "[Desktop Entry]\nHidden=true\n";
+        assert!(autostart_enabled(None, Some(entry)));
+        assert!(!autostart_enabled(Some(hidden), Some(entry)));
+        assert!(!autostart_enabled(
+            None,
+            Some("[Desktop Entry]\nX-GNOME-Autostart-enabled=false\n")
+        ));
+        assert!(!autostart_enabled(None, None));
+
+        let copyq = CLIPBOARD_MANAGERS
+            .iter()
+            .find(|m| m.name == "CopyQ")
+            .unwrap();
+        let conflict = clipboard_manager_conflict(copyq, false, Some("copyq.desktop"));
+        assert_eq!(conflict.current_action, "Started at login");
+        // Other apps are never stopped automatically
+        assert_eq!(conflict.resolution_command, None);
+        assert!(conflict.resolution_steps.contains("copyq.desktop"));
+        assert!(conflict.resolution_steps.contains("copyq exit"));
+    }
 }
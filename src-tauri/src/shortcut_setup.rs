@@ -1,8 +1,8 @@
 //! Shortcut setup commands for the frontend
 //! Provides Tauri commands to register/unregister shortcuts from the Setup Wizard
 
-use std::env;
-
+#[cfg(target_os = "linux")]
+use crate::linux_shortcut_manager::ShortcutStatus;
 use crate::shortcut_conflict_detector::{
     auto_resolve_conflicts, detect_shortcut_conflicts, ConflictDetectionResult,
 };
@@ -10,74 +10,20 @@ use crate::shortcut_conflict_detector::{
 /// Get the current desktop environment name
 #[tauri::command]
 pub fn get_desktop_environment() -> String {
-    let xdg_current = env::var("XDG_CURRENT_DESKTOP")
-        .unwrap_or_default()
-        .to_lowercase();
-    let xdg_session = env::var("XDG_SESSION_DESKTOP")
-        .unwrap_or_default()
-        .to_lowercase();
-    let combined = format!("{} {}", xdg_current, xdg_session);
-
-    // Check for Pop!_OS specifically (uses pop:GNOME)
-    if combined.contains("pop") {
-        return "Pop!_OS".to_string();
-    }
-    if combined.contains("gnome") || combined.contains("unity") || combined.contains("pantheon") {
-        "GNOME".to_string()
-    } else if combined.contains("cinnamon") {
-        "Cinnamon".to_string()
-    } else if combined.contains("kde") || combined.contains("plasma") {
-        "KDE Plasma".to_string()
-    } else if combined.contains("xfce") {
-        "XFCE".to_string()
-    } else if combined.contains("mate") {
-        "MATE".to_string()
-    } else if combined.contains("lxde") {
-        "LXDE".to_string()
-    } else if combined.contains("lxqt") {
-        "LXQt".to_string()
-    } else if combined.contains("cosmic") {
-        "COSMIC".to_string()
-    } else if combined.contains("budgie") {
-        "Budgie".to_string()
-    } else if combined.contains("deepin") {
-        "Deepin".to_string()
-    } else if combined.contains("i3") {
-        "i3".to_string()
-    } else if combined.contains("sway") {
-        "Sway".to_string()
-    } else if combined.contains("hyprland") {
-        "Hyprland".to_string()
-    } else {
-        // Check for running tiling WMs
-        if is_process_running("i3") {
-            "i3".to_string()
-        } else if is_process_running("sway") {
-            "Sway".to_string()
-        } else if is_process_running("hyprland") || is_process_running("Hyprland") {
-            "Hyprland".to_string()
-        } else {
-            xdg_current.to_uppercase()
-        }
+    #[cfg(target_os = "linux")]
+    {
+        crate::linux_shortcut_manager::detect_desktop()
+            .display_name()
+            .to_string()
     }
-}
-
-#[cfg(target_os = "linux")]
-fn is_process_running(name: &str) -> bool {
-    std::process::Command::new("pgrep")
-        .arg("-x")
-        .arg(name)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
-}
 
-#[cfg(not(target_os = "linux"))]
-fn is_process_running(_name: &str) -> bool {
-    false
+    #[cfg(not(target_os = "linux"))]
+    {
+        "Unknown".to_string()
+    }
 }
 
-/// Detect shortcut conflicts for Super+V
+/// Detect conflicts with the configured shortcuts and competing clipboard managers
 #[tauri::command]
 pub fn detect_conflicts() -> ConflictDetectionResult {
     detect_shortcut_conflicts()
@@ -98,14 +44,11 @@ pub fn register_de_shortcut() -> Result<String, String> {
         // Run in a separate thread but wait for completion to avoid race conditions
         let (tx, rx) = std::sync::mpsc::channel();
         std::thread::spawn(move || {
-            crate::linux_shortcut_manager::register_global_shortcut();
-            let _ = tx.send(());
+            let _ = tx.send(crate::linux_shortcut_manager::register_global_shortcut());
         });
 
         match rx.recv() {
-            Ok(()) => {
-                Ok("Shortcut registration completed. Check the app logs for details.".to_string())
-            }
+            Ok(statuses) => registration_summary(&statuses),
             Err(_) => Err("Shortcut registration thread failed unexpectedly.".to_string()),
         }
     }
@@ -116,6 +59,44 @@ pub fn register_de_shortcut() -> Result<String, String> {
     }
 }
 
+/// Error listing the shortcuts that failed or couldn't be read back, else a message
+/// with the reloads still needed
+#[cfg(target_os = "linux")]
+fn registration_summary(statuses: &[ShortcutStatus]) -> Result<String, String> {
+    use crate::linux_shortcut_manager::RegistrationOutcome;
+
+    if statuses.is_empty() {
+        return Err("No shortcuts are configured.".to_string());
+    }
+
+    let mut problems = Vec::new();
+    let mut hints = Vec::new();
+    for status in statuses {
+        match &status.outcome {
+            Some(RegistrationOutcome::Failed(e)) => {
+                problems.push(format!("{} ({}): {}", status.name, status.chord, e))
+            }
+            Some(RegistrationOutcome::NeedsReload(hint)) if !hints.contains(hint) => {
+                hints.push(hint.clone())
+            }
+            _ if status.verified == Some(false) => problems.push(format!(
+                "{} ({}): not found in the desktop configuration after registering",
+                status.name, status.chord
+            )),
+            _ => {}
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(problems.join("\n"));
+    }
+    let mut message = format!("Registered {} shortcut(s).", statuses.len());
+    for hint in hints {
+        message.push_str(&format!(" {}.", hint));
+    }
+    Ok(message)
+}
+
 /// Check if the DE shortcut manager has the tools needed
 #[tauri::command]
 pub fn check_shortcut_tools() -> ShortcutToolsStatus {
@@ -139,11 +120,15 @@ pub fn check_shortcut_tools() -> ShortcutToolsStatus {
             "i3" => true,       // Uses config files
             "Sway" => true,     // Uses config files
             "Hyprland" => true, // Uses config files
+            "niri" | "River" | "labwc" | "Wayfire" | "bspwm" | "awesome" | "Qtile" | "Openbox" => {
+                true
+            } // Uses config files
             _ => gsettings,     // Fallback to gsettings
         };
 
         // Check for conflicts
         let conflicts = detect_shortcut_conflicts();
+        let shortcuts = crate::linux_shortcut_manager::verify_global_shortcut();
 
         ShortcutToolsStatus {
             desktop_environment: de.clone(),
@@ -155,6 +140,7 @@ pub fn check_shortcut_tools() -> ShortcutToolsStatus {
             has_conflicts: !conflicts.conflicts.is_empty(),
             conflict_count: conflicts.conflicts.len(),
             can_auto_resolve_conflicts: conflicts.can_auto_resolve,
+            shortcuts,
         }
     }
 
@@ -185,6 +171,9 @@ pub struct ShortcutToolsStatus {
     pub has_conflicts: bool,
     pub conflict_count: usize,
     pub can_auto_resolve_conflicts: bool,
+    /// Each configured shortcut, read back from the desktop environment
+    #[cfg(target_os = "linux")]
+    pub shortcuts: Vec<ShortcutStatus>,
 }
 
 #[cfg(target_os = "linux")]
@@ -337,6 +326,78 @@ bind = CTRL ALT, V, exec, win11-clipboard-history
 ```"#
             .to_string(),
 
+        "niri" => r#"**niri Configuration:**
+1. Edit your niri config: `~/.config/niri/config.kdl`
+2. Comment out or remove any existing `Mod+V { ... }` line in the `binds` block
+3. Add this line inside `binds { }`:
+```
+Mod+V { spawn "win11-clipboard-history"; }
+```
+4. niri reloads its config automatically"#
+            .to_string(),
+
+        "River" => r#"**River Configuration:**
+1. Edit your River init script: `~/.config/river/init`
+2. Comment out or remove any existing `riverctl map normal Super V` line
+3. Add this line:
+```
+riverctl map normal Super V spawn win11-clipboard-history
+```
+4. Run the same line in a terminal to apply it without restarting"#
+            .to_string(),
+
+        "labwc" | "Openbox" => r#"**Openbox / labwc Configuration:**
+1. Edit `~/.config/openbox/rc.xml` (labwc: `~/.config/labwc/rc.xml`)
+2. Remove any existing `<keybind key="W-v">` inside `<keyboard>`
+3. Add this inside `<keyboard>`:
+```
+<keybind key="W-v">
+  <action name="Execute" command="win11-clipboard-history" />
+</keybind>
+```
+4. Run `openbox --reconfigure` (or `labwc --reconfigure`)"#
+            .to_string(),
+
+        "Wayfire" => r#"**Wayfire Configuration:**
+1. Edit your Wayfire config: `~/.config/wayfire.ini`
+2. Remove any other binding using `<super> KEY_V`
+3. Add these lines to the `[command]` section:
+```
+binding_win11_clipboard_history = <super> KEY_V
+command_win11_clipboard_history = win11-clipboard-history
+```
+4. Wayfire reloads its config automatically"#
+            .to_string(),
+
+        "bspwm" => r#"**bspwm (sxhkd) Configuration:**
+1. Edit your sxhkd config: `~/.config/sxhkd/sxhkdrc`
+2. Comment out or remove any existing `super + v` binding
+3. Add these lines:
+```
+super + v
+    win11-clipboard-history
+```
+4. Reload sxhkd: `pkill -USR1 -x sxhkd`"#
+            .to_string(),
+
+        "awesome" => r#"**awesome Configuration:**
+1. Edit your config: `~/.config/awesome/rc.lua`
+2. Add this key to your `globalkeys` table:
+```
+awful.key({ "Mod4" }, "v", function() awful.spawn("win11-clipboard-history") end)
+```
+3. Restart awesome: Press Mod4+Ctrl+r"#
+            .to_string(),
+
+        "Qtile" => r#"**Qtile Configuration:**
+1. Edit your config: `~/.config/qtile/config.py`
+2. Add this entry to your `keys` list:
+```
+Key(["mod4"], "v", lazy.spawn("win11-clipboard-history"))
+```
+3. Reload the config: Press Mod4+Ctrl+r"#
+            .to_string(),
+
         _ => r#"**Generic Instructions:**
 1. Open your desktop environment's keyboard shortcuts settings
 2. Add a new custom shortcut
@@ -0,0 +1,181 @@
+//! Per-user Uninstall
+//! Undoes what the app set up for the current user: shortcuts in every desktop handler,
+//! the autostart entry and conflicting shortcuts we disabled, and optionally the history,
+//! settings and caches. Package removal runs as root and can't reach any of these.
+
+use crate::autostart_manager;
+use crate::config_backup::ConfigBackups;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const APP_DIR: &str = "win11-clipboard-history";
+
+/// What `uninstall_user` removed or failed to remove
+#[derive(Debug, Default)]
+pub struct UninstallReport {
+    pub removed: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+impl UninstallReport {
+    fn record(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(line) => self.removed.push(line),
+            Err(line) => self.failed.push(line),
+        }
+    }
+}
+
+impl fmt::Display for UninstallReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.removed.is_empty() && self.failed.is_empty() {
+            return writeln!(f, "Nothing to remove");
+        }
+        for line in &self.removed {
+            writeln!(f, "  \u{2713} {}", line)?;
+        }
+        for line in &self.failed {
+            writeln!(f, "  \u{2717} {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Directories holding history, settings, caches and the history key
+fn app_dirs() -> Vec<PathBuf> {
+    [
+        dirs::data_local_dir(),
+        dirs::config_dir(),
+        dirs::cache_dir(),
+        dirs::runtime_dir(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|dir| dir.join(APP_DIR))
+    .collect()
+}
+
+/// Removes the shortcuts, the autostart entry and our changes to desktop settings.
+/// With `purge`, history, settings and caches are deleted as well.
+pub fn uninstall_user(purge: bool) -> UninstallReport {
+    let mut report = UninstallReport::default();
+
+    #[cfg(target_os = "linux")]
+    {
+        // Removing our own bindings isn't a change to roll back later
+        let _paused = ConfigBackups::pause();
+        for result in crate::linux_shortcut_manager::unregister_from_all_handlers() {
+            report.record(
+                result
+                    .map(|what| format!("Removed {}", what))
+                    .map_err(|e| format!("Failed to remove {}", e)),
+            );
+        }
+    }
+
+    // Our bindings are gone from the config files by now; only settings such as
+    // the conflicting shortcuts we disabled still need their original values
+    let backups = ConfigBackups::new();
+    let mut keep = None;
+    match backups.rollback_settings() {
+        Ok(restored) => report.removed.extend(restored),
+        Err(e) => {
+            report.failed.extend(
+                e.split("\n\n")
+                    .map(|line| format!("Failed to restore {}", line)),
+            );
+            // Their backups are the only way back to the settings that weren't restored
+            keep = Some(backups.dir());
+        }
+    }
+
+    if autostart_manager::get_autostart_file().is_some_and(|path| path.exists()) {
+        report.record(
+            autostart_manager::autostart_disable().map(|_| "Removed autostart entry".to_string()),
+        );
+    }
+
+    if purge {
+        for dir in app_dirs().into_iter().filter(|dir| dir.exists()) {
+            report.record(remove_app_dir(&dir, keep));
+        }
+    }
+
+    report
+}
+
+/// Deletes one of the app's directories, apart from `keep` if it lies inside
+fn remove_app_dir(dir: &Path, keep: Option<&Path>) -> Result<String, String> {
+    let failed = |e: std::io::Error| format!("Failed to delete {}: {}", dir.display(), e);
+    let Some(keep) = keep.filter(|keep| keep.starts_with(dir)) else {
+        return fs::remove_dir_all(dir)
+            .map(|_| format!("Deleted {}", dir.display()))
+            .map_err(failed);
+    };
+
+    for entry in fs::read_dir(dir).map_err(failed)?.flatten() {
+        let path = entry.path();
+        if keep.starts_with(&path) {
+            continue;
+        }
+        let removed = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        removed.map_err(failed)?;
+    }
+    Ok(format!(
+        "Deleted {}, keeping {} for the settings that weren't restored",
+        dir.display(),
+        keep.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_display() {
+        assert_eq!(
+            UninstallReport::default().to_string(),
+            "Nothing to remove\n"
+        );
+
+        let mut report = UninstallReport::default();
+        report.record(Ok("Removed autostart entry".to_string()));
+        report.record(Err("Failed to delete /x: denied".to_string()));
+        assert_eq!(
+            report.to_string(),
+            "  \u{2713} Removed autostart entry\n  \u{2717} Failed to delete /x: denied\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_app_dir_keeps_backups() {
+        let dir = std::env::temp_dir().join(format!("uninstall-test-{}", uuid::Uuid::new_v4()));
+        let backups = dir.join("config-backups");
+        fs::create_dir_all(&backups).unwrap();
+        fs::create_dir_all(dir.join("images")).unwrap();
+        fs::write(dir.join("history.json"), "[]").unwrap();
+        fs::write(backups.join("manifest.json"), "[]").unwrap();
+
+        remove_app_dir(&dir, Some(&backups)).unwrap();
+        assert!(backups.join("manifest.json").exists());
+        assert!(!dir.join("images").exists());
+        assert!(!dir.join("history.json").exists());
+
+        // Other directories are deleted whole
+        remove_app_dir(&dir, Some(Path::new("/elsewhere"))).unwrap();
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_app_dirs() {
+        let dirs = app_dirs();
+        assert!(!dirs.is_empty());
+        assert!(dirs.iter().all(|dir| dir.ends_with(APP_DIR)));
+    }
+}
@@ -1,6 +1,12 @@
 //! User Settings Module
 //! Handles persistence of user preferences (theme mode, background opacity) in a separate JSON file.
 
+use crate::clipboard_rules::ClipboardRule;
+use crate::config_manager::WindowPlacement;
+use crate::history_store::HistoryKeySource;
+use crate::input_simulator::PasteSettings;
+use crate::privacy_policy::PrivacySettings;
+use crate::shortcut_chord::ShortcutBindings;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -28,6 +34,49 @@ pub struct UserSettings {
     /// User-defined Kaomojis
     #[serde(default)]
     pub custom_kaomojis: Vec<CustomKaomoji>,
+
+    // --- Window ---
+    /// Where the history window opens
+    #[serde(default)]
+    pub window_placement: WindowPlacement,
+
+    // --- Global Shortcuts ---
+    /// Key combos registered with the desktop environment to open the history
+    #[serde(default)]
+    pub shortcuts: ShortcutBindings,
+
+    // --- Pasting ---
+    /// Put back what was on the clipboard after pasting an item, emoji or GIF
+    #[serde(default)]
+    pub restore_clipboard_after_paste: bool,
+    /// Paste chord and per-application overrides
+    #[serde(default)]
+    pub paste: PasteSettings,
+
+    // --- Clipboard Rules ---
+    /// Regex rules evaluated on copied text before it is recorded
+    #[serde(default)]
+    pub clipboard_rules: Vec<ClipboardRule>,
+
+    // --- History Storage ---
+    /// Keep history on disk between sessions
+    #[serde(default)]
+    pub persist_history: bool,
+    /// Encrypt stored history and images
+    #[serde(default)]
+    pub encrypt_history: bool,
+    /// Where the encryption key comes from
+    #[serde(default)]
+    pub history_key_source: HistoryKeySource,
+    /// Key file location (defaults to $XDG_RUNTIME_DIR/win11-clipboard-history/history.key,
+    /// which is emptied at logout). Must be outside the app data directory.
+    #[serde(default)]
+    pub history_key_file: Option<String>,
+
+    // --- Privacy ---
+    /// Expiring items and clear-on-lock / idle / quit policies
+    #[serde(default)]
+    pub privacy: PrivacySettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -51,6 +100,17 @@ impl Default for UserSettings {
 
             enable_ui_polish: true,
             custom_kaomojis: Vec::new(),
+            window_placement: WindowPlacement::default(),
+            shortcuts: ShortcutBindings::default(),
+            restore_clipboard_after_paste: false,
+            paste: PasteSettings::default(),
+            clipboard_rules: Vec::new(),
+
+            persist_history: false,
+            encrypt_history: false,
+            history_key_source: HistoryKeySource::default(),
+            history_key_file: None,
+            privacy: PrivacySettings::default(),
         }
     }
 }
@@ -65,6 +125,11 @@ impl UserSettings {
         if !["system", "dark", "light"].contains(&self.theme_mode.as_str()) {
             self.theme_mode = "system".to_string();
         }
+
+        // Fall back to the default shortcuts if a chord can't be parsed
+        if self.shortcuts.validate().is_err() {
+            self.shortcuts = ShortcutBindings::default();
+        }
     }
 }
 
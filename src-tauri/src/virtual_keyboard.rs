@@ -0,0 +1,233 @@
+//! Virtual Keyboard Module
+//! Injects key events through the `zwp_virtual_keyboard_v1` Wayland protocol, offered by
+//! wlroots compositors (Sway, Hyprland, river...). Unlike uinput it needs no access to
+//! /dev/uinput, so pasting works without the udev rule or the `input` group.
+
+use crate::keymap::{self, Keymap};
+use std::io::Write;
+use std::os::fd::{AsFd, FromRawFd, OwnedFd};
+use std::thread;
+use std::time::{Duration, Instant};
+use wayland_client::protocol::{wl_keyboard, wl_registry, wl_seat};
+use wayland_client::{delegate_noop, Connection, Dispatch, EventQueue, QueueHandle};
+use wayland_protocols_misc::zwp_virtual_keyboard_v1::client::{
+    zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1,
+    zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1,
+};
+
+const MANAGER_INTERFACE: &str = "zwp_virtual_keyboard_manager_v1";
+
+/// Real modifier bits; xkbcommon keeps the core modifiers at fixed indices
+const MOD_SHIFT: u32 = 1 << 0;
+const MOD_CONTROL: u32 = 1 << 2;
+
+/// wl_keyboard key states
+const KEY_RELEASED: u32 = 0;
+const KEY_PRESSED: u32 = 1;
+
+/// Modifier bit held down by an evdev key, if it is a modifier
+fn modifier_bit(evdev: u16) -> u32 {
+    match evdev {
+        29 | 97 => MOD_CONTROL, // KEY_LEFTCTRL, KEY_RIGHTCTRL
+        42 | 54 => MOD_SHIFT,   // KEY_LEFTSHIFT, KEY_RIGHTSHIFT
+        _ => 0,
+    }
+}
+
+#[derive(Default)]
+struct Globals {
+    seat: Option<wl_seat::WlSeat>,
+    manager: Option<ZwpVirtualKeyboardManagerV1>,
+}
+
+/// A virtual keyboard on the compositor's default seat, destroyed on drop
+pub struct VirtualKeyboard {
+    queue: EventQueue<Globals>,
+    globals: Globals,
+    keyboard: ZwpVirtualKeyboardV1,
+    keymap: Keymap,
+    /// Layout (XKB group) the next keys are sent on
+    layout: u32,
+    created: Instant,
+}
+
+/// Whether the compositor offers the virtual keyboard protocol
+pub fn is_available() -> bool {
+    let Ok(conn) = Connection::connect_to_env() else {
+        return false;
+    };
+    let mut queue = conn.new_event_queue();
+    conn.display().get_registry(&queue.handle(), ());
+
+    let mut globals = Globals::default();
+    queue.roundtrip(&mut globals).is_ok() && globals.manager.is_some()
+}
+
+impl VirtualKeyboard {
+    /// Creates the keyboard; fails when the compositor doesn't offer the protocol
+    pub fn connect() -> Result<Self, String> {
+        let conn =
+            Connection::connect_to_env().map_err(|e| format!("Wayland connect failed: {}", e))?;
+        let mut queue = conn.new_event_queue();
+        let qh = queue.handle();
+        conn.display().get_registry(&qh, ());
+
+        let mut globals = Globals::default();
+        queue
+            .roundtrip(&mut globals)
+            .map_err(|e| format!("Wayland roundtrip failed: {}", e))?;
+        let manager = globals
+            .manager
+            .clone()
+            .ok_or_else(|| format!("Compositor does not offer {}", MANAGER_INTERFACE))?;
+        let seat = globals.seat.clone().ok_or("Compositor has no seat")?;
+
+        // Reuse the user's keymap, so the compositor doesn't switch layouts when we type
+        let text = keymap::compositor_keymap_text().or_else(|e| {
+            eprintln!(
+                "[VirtualKeyboard] Compositor keymap unavailable ({}), using XKB defaults",
+                e
+            );
+            keymap::default_keymap_text()
+        })?;
+        let keymap = Keymap::from_xkb_text(&text)?;
+        let fd = keymap_fd(&text)?;
+
+        let keyboard = manager.create_virtual_keyboard(&seat, &qh, ());
+        // The size includes the terminating NUL written by keymap_fd
+        keyboard.keymap(
+            wl_keyboard::KeymapFormat::XkbV1.into(),
+            fd.as_fd(),
+            text.len() as u32 + 1,
+        );
+        // Surfaces protocol errors, e.g. a compositor refusing untrusted clients
+        queue
+            .roundtrip(&mut globals)
+            .map_err(|e| format!("Virtual keyboard setup failed: {}", e))?;
+
+        Ok(Self {
+            queue,
+            globals,
+            keyboard,
+            keymap,
+            layout: 0,
+            created: Instant::now(),
+        })
+    }
+
+    /// The keymap the keyboard was created with
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    /// Selects the layout keys are interpreted with
+    pub fn set_layout(&mut self, layout: usize) {
+        self.layout = layout as u32;
+    }
+
+    /// Presses the keys in order, then releases them in reverse
+    pub fn tap(&mut self, keys: &[u16], delay: Duration) -> Result<(), String> {
+        let mut mods = 0;
+        for &key in keys {
+            mods |= modifier_bit(key);
+            self.send_key(key, KEY_PRESSED, mods);
+            thread::sleep(delay);
+        }
+        for &key in keys.iter().rev() {
+            mods &= !modifier_bit(key);
+            self.send_key(key, KEY_RELEASED, mods);
+            thread::sleep(delay);
+        }
+
+        self.queue
+            .roundtrip(&mut self.globals)
+            .map(|_| ())
+            .map_err(|e| format!("Virtual keyboard roundtrip failed: {}", e))
+    }
+
+    fn send_key(&self, key: u16, state: u32, mods: u32) {
+        let time = self.created.elapsed().as_millis() as u32;
+        self.keyboard.key(time, u32::from(key), state);
+        // Sent explicitly; not every compositor derives them from the key events
+        self.keyboard.modifiers(mods, 0, 0, self.layout);
+        let _ = self.queue.flush();
+    }
+}
+
+impl Drop for VirtualKeyboard {
+    fn drop(&mut self) {
+        self.keyboard.destroy();
+        let _ = self.queue.flush();
+    }
+}
+
+/// Writes the keymap into an anonymous file the compositor can map
+fn keymap_fd(text: &str) -> Result<OwnedFd, String> {
+    let fd = unsafe { libc::memfd_create(c"xkb-keymap".as_ptr(), libc::MFD_CLOEXEC) };
+    if fd < 0 {
+        return Err(format!(
+            "memfd_create failed: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    let mut file = std::fs::File::from(unsafe { OwnedFd::from_raw_fd(fd) });
+    file.write_all(text.as_bytes())
+        .and_then(|()| file.write_all(&[0]))
+        .map_err(|e| format!("Failed to write keymap: {}", e))?;
+    Ok(file.into())
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for Globals {
+    fn event(
+        state: &mut Self,
+        registry: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _data: &(),
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            if interface == "wl_seat" && state.seat.is_none() {
+                state.seat = Some(registry.bind(name, version.min(5), qh, ()));
+            } else if interface == MANAGER_INTERFACE {
+                state.manager = Some(registry.bind(name, 1, qh, ()));
+            }
+        }
+    }
+}
+
+delegate_noop!(Globals: ignore wl_seat::WlSeat);
+delegate_noop!(Globals: ignore ZwpVirtualKeyboardManagerV1);
+delegate_noop!(Globals: ignore ZwpVirtualKeyboardV1);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modifier_bits() {
+        assert_eq!(modifier_bit(29) | modifier_bit(42), MOD_CONTROL | MOD_SHIFT);
+        assert_eq!(modifier_bit(47), 0);
+    }
+
+    /// Needs a wlroots compositor, e.g. `WLR_BACKENDS=headless sway` or
+    /// `WLR_BACKENDS=headless cage -- sleep 60`, with WAYLAND_DISPLAY pointing at it:
+    /// `cargo test virtual_keyboard -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_tap_on_headless_compositor() {
+        assert!(is_available());
+        let mut keyboard = VirtualKeyboard::connect().unwrap();
+        let v = keyboard.keymap().locate(keymap::XK_V).unwrap();
+        keyboard.set_layout(v.layout);
+        keyboard
+            .tap(&[29, v.evdev], Duration::from_millis(10))
+            .unwrap();
+    }
+}
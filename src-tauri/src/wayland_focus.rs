@@ -0,0 +1,1143 @@
+//! Wayland Focus Module
+//! Focus tracking for native Wayland sessions, where X11 window ids mean nothing.
+//! Each backend asks the compositor for the active toplevel and reactivates it before a paste:
+//! sway/i3 IPC, Hyprland's socket (or `hyprctl`), KWin scripts over D-Bus, and the
+//! `wlr-foreign-toplevel-management` protocol for other wlroots compositors.
+//! Each backend also records the layout bounds of the output the user was working on, so the
+//! history window can open on that monitor.
+
+use crate::config_manager::ScreenRect;
+use crate::focus_manager::FocusBackend;
+use crate::linux_shortcut_manager::{detect_desktop, DesktopEnvironment};
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+use std::time::Duration;
+use wayland_client::protocol::{wl_output, wl_registry, wl_seat};
+use wayland_client::{delegate_noop, event_created_child, Connection, Dispatch, EventQueue};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+
+/// How long to wait for a compositor to answer
+const IPC_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Toplevel recorded by an IPC backend
+#[derive(Debug, Clone, PartialEq)]
+struct SavedToplevel {
+    /// Compositor-specific id used to reactivate it
+    id: String,
+    classes: Vec<String>,
+}
+
+/// Picks the backend for the detected compositor; `None` falls back to X11 (XWayland)
+pub fn backend_for_session() -> Option<Box<dyn FocusBackend>> {
+    let desktop = detect_desktop();
+    let backend: Result<Box<dyn FocusBackend>, String> = match desktop {
+        DesktopEnvironment::Sway | DesktopEnvironment::I3 => {
+            SwayIpc::connect().map(|b| Box::new(b) as Box<dyn FocusBackend>)
+        }
+        DesktopEnvironment::Hyprland => {
+            HyprlandIpc::connect().map(|b| Box::new(b) as Box<dyn FocusBackend>)
+        }
+        DesktopEnvironment::Kde => {
+            KWinScript::connect().map(|b| Box::new(b) as Box<dyn FocusBackend>)
+        }
+        _ => Err(format!("no compositor IPC for {:?}", desktop)),
+    };
+
+    match backend {
+        Ok(backend) => Some(backend),
+        Err(e) => {
+            eprintln!("[WaylandFocus] {}, trying wlr-foreign-toplevel", e);
+            match WlrForeignToplevel::connect() {
+                Ok(backend) => Some(Box::new(backend)),
+                Err(e) => {
+                    eprintln!("[WaylandFocus] {}", e);
+                    None
+                }
+            }
+        }
+    }
+}
+
+// =============================================================================
+// sway / i3 IPC
+// =============================================================================
+
+const IPC_MAGIC: &[u8] = b"i3-ipc";
+const IPC_RUN_COMMAND: u32 = 0;
+const IPC_GET_TREE: u32 = 4;
+
+/// Sends one i3-ipc message and parses the JSON reply
+fn ipc_request(socket: &Path, msg_type: u32, payload: &str) -> Result<Value, String> {
+    let mut stream =
+        UnixStream::connect(socket).map_err(|e| format!("IPC connect failed: {}", e))?;
+    stream
+        .set_read_timeout(Some(IPC_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+
+    let mut message = Vec::with_capacity(IPC_MAGIC.len() + 8 + payload.len());
+    message.extend_from_slice(IPC_MAGIC);
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&msg_type.to_ne_bytes());
+    message.extend_from_slice(payload.as_bytes());
+    stream
+        .write_all(&message)
+        .map_err(|e| format!("IPC write failed: {}", e))?;
+
+    let mut header = [0u8; 14];
+    stream
+        .read_exact(&mut header)
+        .map_err(|e| format!("IPC read failed: {}", e))?;
+    if &header[..6] != IPC_MAGIC {
+        return Err("Invalid IPC reply".to_string());
+    }
+    let len = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]) as usize;
+    let mut body = vec![0; len];
+    stream
+        .read_exact(&mut body)
+        .map_err(|e| format!("IPC read failed: {}", e))?;
+
+    serde_json::from_slice(&body).map_err(|e| format!("Invalid IPC reply: {}", e))
+}
+
+/// Finds the focused container in a `get_tree` reply
+fn find_focused(node: &Value) -> Option<&Value> {
+    if node["focused"].as_bool() == Some(true) {
+        return Some(node);
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[*key].as_array())
+        .flatten()
+        .find_map(find_focused)
+}
+
+/// A `{x, y, width, height}` object in an IPC reply
+fn json_rect(rect: &Value) -> Option<ScreenRect> {
+    let field = |key: &str| rect[key].as_i64().map(|v| v as i32);
+    Some(ScreenRect {
+        x: field("x")?,
+        y: field("y")?,
+        width: field("width")?,
+        height: field("height")?,
+    })
+}
+
+/// Layout bounds of an output from its position and mode, which is scaled and possibly
+/// rotated into the layout
+fn logical_output_rect(x: i32, y: i32, mode: (i32, i32), scale: f64, rotated: bool) -> ScreenRect {
+    let (width, height) = if rotated { (mode.1, mode.0) } else { mode };
+    ScreenRect {
+        x,
+        y,
+        width: (width as f64 / scale).round() as i32,
+        height: (height as f64 / scale).round() as i32,
+    }
+}
+
+/// Layout bounds of the output holding the focused container (or empty workspace)
+fn focused_output_in_tree(tree: &Value) -> Option<ScreenRect> {
+    tree["nodes"]
+        .as_array()?
+        .iter()
+        .filter(|node| node["type"].as_str() == Some("output"))
+        .find(|output| find_focused(output).is_some())
+        .and_then(|output| json_rect(&output["rect"]))
+}
+
+/// Container id and class names of a focused window; `None` for an empty workspace
+fn focused_toplevel(tree: &Value) -> Option<SavedToplevel> {
+    let node = find_focused(tree)?;
+    if node["type"].as_str() == Some("workspace") {
+        return None;
+    }
+    let id = node["id"].as_u64()?;
+
+    // Native windows have an app_id, XWayland (and i3) windows have WM_CLASS
+    let mut classes: Vec<String> = node["app_id"]
+        .as_str()
+        .map(String::from)
+        .into_iter()
+        .collect();
+    for key in ["instance", "class"] {
+        if let Some(class) = node["window_properties"][key].as_str() {
+            classes.push(class.to_string());
+        }
+    }
+
+    Some(SavedToplevel {
+        id: id.to_string(),
+        classes,
+    })
+}
+
+/// Focus through the sway (or i3) IPC socket
+pub struct SwayIpc {
+    socket: PathBuf,
+    saved: Option<SavedToplevel>,
+    output: Option<ScreenRect>,
+}
+
+impl SwayIpc {
+    pub fn connect() -> Result<Self, String> {
+        let socket = ["SWAYSOCK", "I3SOCK"]
+            .iter()
+            .filter_map(std::env::var_os)
+            .map(PathBuf::from)
+            .chain(["sway", "i3"].iter().filter_map(|wm| socket_path_from(wm)))
+            .find(|path| path.exists())
+            .ok_or("sway/i3 IPC socket not found")?;
+
+        Ok(Self {
+            socket,
+            saved: None,
+            output: None,
+        })
+    }
+}
+
+/// Asks the window manager binary for its socket (`sway --get-socketpath`)
+fn socket_path_from(wm: &str) -> Option<PathBuf> {
+    let output = Command::new(wm).arg("--get-socketpath").output().ok()?;
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !path.is_empty()).then(|| PathBuf::from(path))
+}
+
+impl FocusBackend for SwayIpc {
+    fn name(&self) -> &'static str {
+        "sway IPC"
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        let tree = ipc_request(&self.socket, IPC_GET_TREE, "")?;
+        self.saved = focused_toplevel(&tree);
+        self.output = focused_output_in_tree(&tree);
+        eprintln!(
+            "[WaylandFocus] Saved sway container: {:?} on {:?}",
+            self.saved, self.output
+        );
+        Ok(())
+    }
+
+    fn restore(&mut self) -> Result<(), String> {
+        let saved = self.saved.as_ref().ok_or("No previous window saved")?;
+        let command = format!("[con_id={}] focus", saved.id);
+        let reply = ipc_request(&self.socket, IPC_RUN_COMMAND, &command)?;
+
+        let outcome = &reply[0];
+        if outcome["success"].as_bool() == Some(true) {
+            Ok(())
+        } else {
+            Err(format!(
+                "sway refused to focus container {}: {}",
+                saved.id,
+                outcome["error"].as_str().unwrap_or("unknown error")
+            ))
+        }
+    }
+
+    fn saved_class(&self) -> Option<Vec<String>> {
+        self.saved.as_ref().map(|saved| saved.classes.clone())
+    }
+
+    fn active_class(&mut self) -> Option<Vec<String>> {
+        let tree = ipc_request(&self.socket, IPC_GET_TREE, "").ok()?;
+        focused_toplevel(&tree).map(|toplevel| toplevel.classes)
+    }
+
+    fn saved_output(&self) -> Option<ScreenRect> {
+        self.output
+    }
+}
+
+// =============================================================================
+// Hyprland
+// =============================================================================
+
+/// Focus through Hyprland's request socket, falling back to `hyprctl`
+pub struct HyprlandIpc {
+    socket: Option<PathBuf>,
+    saved: Option<SavedToplevel>,
+    output: Option<ScreenRect>,
+}
+
+impl HyprlandIpc {
+    pub fn connect() -> Result<Self, String> {
+        let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+            .map_err(|_| "HYPRLAND_INSTANCE_SIGNATURE is not set")?;
+
+        // Hyprland 0.40 moved the sockets from /tmp into the runtime dir
+        let socket = dirs::runtime_dir()
+            .into_iter()
+            .chain([PathBuf::from("/tmp")])
+            .map(|dir| dir.join("hypr").join(&signature).join(".socket.sock"))
+            .find(|path| path.exists());
+        if socket.is_none() && !crate::linux_shortcut_manager::Utils::command_exists("hyprctl") {
+            return Err("Hyprland socket and hyprctl not found".to_string());
+        }
+
+        Ok(Self {
+            socket,
+            saved: None,
+            output: None,
+        })
+    }
+
+    fn request(&self, command: &str) -> Result<String, String> {
+        if let Some(socket) = &self.socket {
+            match hyprland_socket_request(socket, command) {
+                Ok(reply) => return Ok(reply),
+                Err(e) => eprintln!("[WaylandFocus] Hyprland socket failed: {}", e),
+            }
+        }
+
+        let output = Command::new("hyprctl")
+            .args(hyprctl_args(command))
+            .output()
+            .map_err(|e| format!("hyprctl failed: {}", e))?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+fn hyprland_socket_request(socket: &Path, command: &str) -> Result<String, String> {
+    let mut stream = UnixStream::connect(socket).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(IPC_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    stream
+        .write_all(command.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    // Hyprland closes the connection after replying
+    let mut reply = String::new();
+    stream
+        .read_to_string(&mut reply)
+        .map_err(|e| e.to_string())?;
+    Ok(reply)
+}
+
+/// `hyprctl` arguments for a socket command ("j/activewindow" -> "-j activewindow")
+fn hyprctl_args(command: &str) -> Vec<&str> {
+    match command.strip_prefix("j/") {
+        Some(rest) => std::iter::once("-j")
+            .chain(rest.split_whitespace())
+            .collect(),
+        None => command.split_whitespace().collect(),
+    }
+}
+
+/// Address and classes from `j/activewindow`; `None` when no window is focused
+fn parse_active_window(reply: &str) -> Option<SavedToplevel> {
+    let window: Value = serde_json::from_str(reply).ok()?;
+    let address = window["address"].as_str().filter(|a| !a.is_empty())?;
+
+    let mut classes = Vec::new();
+    for key in ["class", "initialClass"] {
+        if let Some(class) = window[key].as_str().filter(|c| !c.is_empty()) {
+            if !classes.iter().any(|c| c == class) {
+                classes.push(class.to_string());
+            }
+        }
+    }
+
+    Some(SavedToplevel {
+        id: address.to_string(),
+        classes,
+    })
+}
+
+/// Layout bounds of the focused monitor in a `j/monitors` reply, where the width and
+/// height are the mode's
+fn parse_focused_monitor(reply: &str) -> Option<ScreenRect> {
+    let monitors: Value = serde_json::from_str(reply).ok()?;
+    let monitor = monitors
+        .as_array()?
+        .iter()
+        .find(|monitor| monitor["focused"].as_bool() == Some(true))?;
+    let mode = json_rect(monitor)?;
+    let scale = monitor["scale"]
+        .as_f64()
+        .filter(|s| *s > 0.0)
+        .unwrap_or(1.0);
+    // Odd transforms turn the monitor by 90 or 270 degrees
+    let rotated = monitor["transform"].as_i64().unwrap_or(0) % 2 == 1;
+    Some(logical_output_rect(
+        mode.x,
+        mode.y,
+        (mode.width, mode.height),
+        scale,
+        rotated,
+    ))
+}
+
+impl FocusBackend for HyprlandIpc {
+    fn name(&self) -> &'static str {
+        "Hyprland IPC"
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        let reply = self.request("j/activewindow")?;
+        self.saved = parse_active_window(&reply);
+        self.output = self
+            .request("j/monitors")
+            .ok()
+            .and_then(|reply| parse_focused_monitor(&reply));
+        eprintln!(
+            "[WaylandFocus] Saved Hyprland window: {:?} on {:?}",
+            self.saved, self.output
+        );
+        Ok(())
+    }
+
+    fn restore(&mut self) -> Result<(), String> {
+        let saved = self.saved.as_ref().ok_or("No previous window saved")?;
+        let reply = self.request(&format!("dispatch focuswindow address:{}", saved.id))?;
+        if reply.trim() == "ok" {
+            Ok(())
+        } else {
+            Err(format!(
+                "Hyprland refused to focus {}: {}",
+                saved.id,
+                reply.trim()
+            ))
+        }
+    }
+
+    fn saved_class(&self) -> Option<Vec<String>> {
+        self.saved.as_ref().map(|saved| saved.classes.clone())
+    }
+
+    fn active_class(&mut self) -> Option<Vec<String>> {
+        let reply = self.request("j/activewindow").ok()?;
+        parse_active_window(&reply).map(|toplevel| toplevel.classes)
+    }
+
+    fn saved_output(&self) -> Option<ScreenRect> {
+        self.output
+    }
+}
+
+// =============================================================================
+// KWin scripting
+// =============================================================================
+
+const KWIN_BUS_NAME: &str = "org.kde.KWin";
+const KWIN_SCRIPTING_PATH: &str = "/Scripting";
+const KWIN_SCRIPTING_INTERFACE: &str = "org.kde.kwin.Scripting";
+const KWIN_SCRIPT_INTERFACE: &str = "org.kde.kwin.Script";
+const CALLBACK_PATH: &str = "/org/win11clipboard/FocusTracker";
+const CALLBACK_INTERFACE: &str = "org.win11clipboard.FocusTracker";
+const SAVE_SCRIPT: &str = "win11-clipboard-save-focus";
+const RESTORE_SCRIPT: &str = "win11-clipboard-restore-focus";
+
+/// What the KWin scripts report back over D-Bus
+#[derive(Debug)]
+enum ScriptReply {
+    Saved {
+        id: String,
+        class: String,
+        output: String,
+    },
+    Restored {
+        found: bool,
+    },
+}
+
+struct FocusCallback {
+    replies: mpsc::Sender<ScriptReply>,
+}
+
+#[zbus::interface(name = "org.win11clipboard.FocusTracker")]
+impl FocusCallback {
+    fn saved(&self, id: String, class: String, output: String) {
+        let _ = self.replies.send(ScriptReply::Saved { id, class, output });
+    }
+
+    fn restored(&self, found: bool) {
+        let _ = self.replies.send(ScriptReply::Restored { found });
+    }
+}
+
+/// Focus through short KWin scripts (Plasma 5 and 6) that report back over D-Bus
+pub struct KWinScript {
+    conn: zbus::blocking::Connection,
+    replies: mpsc::Receiver<ScriptReply>,
+    script_dir: PathBuf,
+    saved: Option<SavedToplevel>,
+    output: Option<ScreenRect>,
+}
+
+impl KWinScript {
+    pub fn connect() -> Result<Self, String> {
+        let (sender, replies) = mpsc::channel();
+        let conn = zbus::blocking::connection::Builder::session()
+            .and_then(|b| b.serve_at(CALLBACK_PATH, FocusCallback { replies: sender }))
+            .and_then(|b| b.build())
+            .map_err(|e| format!("D-Bus connect failed: {}", e))?;
+
+        let kwin_running = zbus::blocking::fdo::DBusProxy::new(&conn)
+            .ok()
+            .and_then(|proxy| proxy.name_has_owner(KWIN_BUS_NAME.try_into().ok()?).ok())
+            .unwrap_or(false);
+        if !kwin_running {
+            return Err("KWin is not on the session bus".to_string());
+        }
+
+        let script_dir = dirs::runtime_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("win11-clipboard-history");
+        std::fs::create_dir_all(&script_dir)
+            .map_err(|e| format!("Failed to create script dir: {}", e))?;
+
+        Ok(Self {
+            conn,
+            replies,
+            script_dir,
+            saved: None,
+            output: None,
+        })
+    }
+
+    fn unique_name(&self) -> Result<String, String> {
+        self.conn
+            .unique_name()
+            .map(|name| name.to_string())
+            .ok_or_else(|| "D-Bus connection has no unique name".to_string())
+    }
+
+    /// Loads and runs a script, then waits for its callback.
+    /// KWin reads the file asynchronously, so it is only unloaded once the script replied.
+    fn run_script(&self, name: &str, source: &str) -> Result<ScriptReply, String> {
+        let path = self.script_dir.join(format!("{}.js", name));
+        std::fs::write(&path, source).map_err(|e| format!("Failed to write script: {}", e))?;
+
+        // Drop replies from earlier scripts that timed out
+        while self.replies.try_recv().is_ok() {}
+        self.unload_script(name);
+
+        let result = self.load_and_run(&path, name).and_then(|()| {
+            self.replies
+                .recv_timeout(IPC_TIMEOUT)
+                .map_err(|_| "KWin script did not reply".to_string())
+        });
+        self.unload_script(name);
+        result
+    }
+
+    fn load_and_run(&self, path: &Path, name: &str) -> Result<(), String> {
+        let id: i32 = self
+            .conn
+            .call_method(
+                Some(KWIN_BUS_NAME),
+                KWIN_SCRIPTING_PATH,
+                Some(KWIN_SCRIPTING_INTERFACE),
+                "loadScript",
+                &(path.to_string_lossy().as_ref(), name),
+            )
+            .and_then(|reply| reply.body().deserialize())
+            .map_err(|e| format!("loadScript failed: {}", e))?;
+        if id < 0 {
+            return Err(format!("KWin rejected script {}", name));
+        }
+
+        // Plasma 6 exports scripts under /Scripting, Plasma 5 at the root
+        let mut last_error = String::new();
+        for script_path in [format!("/Scripting/Script{}", id), format!("/{}", id)] {
+            match self.conn.call_method(
+                Some(KWIN_BUS_NAME),
+                script_path.as_str(),
+                Some(KWIN_SCRIPT_INTERFACE),
+                "run",
+                &(),
+            ) {
+                Ok(_) => return Ok(()),
+                Err(e) => last_error = e.to_string(),
+            }
+        }
+        Err(format!("Running KWin script failed: {}", last_error))
+    }
+
+    fn unload_script(&self, name: &str) {
+        let _ = self.conn.call_method(
+            Some(KWIN_BUS_NAME),
+            KWIN_SCRIPTING_PATH,
+            Some(KWIN_SCRIPTING_INTERFACE),
+            "unloadScript",
+            &(name,),
+        );
+    }
+}
+
+/// JavaScript string literal (JSON strings are valid JS)
+fn js_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+fn callback_call(service: &str, method: &str, args: &str) -> String {
+    format!(
+        "callDBus({}, {}, {}, {}, {});",
+        js_string(service),
+        js_string(CALLBACK_PATH),
+        js_string(CALLBACK_INTERFACE),
+        js_string(method),
+        args
+    )
+}
+
+/// Plasma 6 calls the active window `activeWindow`, Plasma 5 `activeClient`.
+/// Only Plasma 6 outputs have a geometry; Plasma 5's `activeScreen` is a bare index.
+/// The geometry is sent as `x,y,width,height`, as JS numbers don't map to D-Bus integers.
+fn save_script(service: &str) -> String {
+    format!(
+        "var w = workspace.activeWindow || workspace.activeClient;\n\
+         var screen = (w && w.output) || workspace.activeScreen;\n\
+         var g = screen && screen.geometry;\n{}\n",
+        callback_call(
+            service,
+            "Saved",
+            "w ? String(w.internalId) : \"\", w ? String(w.resourceClass) : \"\", \
+             g ? [g.x, g.y, g.width, g.height].join(\",\") : \"\""
+        )
+    )
+}
+
+/// Output geometry sent by the save script
+fn parse_kwin_geometry(geometry: &str) -> Option<ScreenRect> {
+    let values: Vec<i32> = geometry
+        .split(',')
+        .map(|v| v.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match values[..] {
+        [x, y, width, height] => Some(ScreenRect {
+            x,
+            y,
+            width,
+            height,
+        }),
+        _ => None,
+    }
+}
+
+fn restore_script(service: &str, id: &str) -> String {
+    format!(
+        r#"var target = {};
+var windows = workspace.windowList ? workspace.windowList() : workspace.clientList();
+var found = false;
+for (var i = 0; i < windows.length; i++) {{
+    if (String(windows[i].internalId) === target) {{
+        if (workspace.activeWindow !== undefined) {{
+            workspace.activeWindow = windows[i];
+        }} else {{
+            workspace.activeClient = windows[i];
+        }}
+        found = true;
+    }}
+}}
+{}
+"#,
+        js_string(id),
+        callback_call(service, "Restored", "found")
+    )
+}
+
+impl FocusBackend for KWinScript {
+    fn name(&self) -> &'static str {
+        "KWin script"
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        let script = save_script(&self.unique_name()?);
+        match self.run_script(SAVE_SCRIPT, &script)? {
+            ScriptReply::Saved { id, class, output } => {
+                self.saved = (!id.is_empty()).then(|| SavedToplevel {
+                    id,
+                    classes: vec![class],
+                });
+                self.output = parse_kwin_geometry(&output);
+                eprintln!(
+                    "[WaylandFocus] Saved KWin window: {:?} on {:?}",
+                    self.saved, self.output
+                );
+                Ok(())
+            }
+            other => Err(format!("Unexpected KWin script reply: {:?}", other)),
+        }
+    }
+
+    fn restore(&mut self) -> Result<(), String> {
+        let saved = self.saved.as_ref().ok_or("No previous window saved")?;
+        let script = restore_script(&self.unique_name()?, &saved.id);
+        match self.run_script(RESTORE_SCRIPT, &script)? {
+            ScriptReply::Restored { found: true } => Ok(()),
+            ScriptReply::Restored { found: false } => {
+                Err(format!("KWin window {} no longer exists", saved.id))
+            }
+            other => Err(format!("Unexpected KWin script reply: {:?}", other)),
+        }
+    }
+
+    fn saved_class(&self) -> Option<Vec<String>> {
+        self.saved.as_ref().map(|saved| saved.classes.clone())
+    }
+
+    fn active_class(&mut self) -> Option<Vec<String>> {
+        // The script only reports through a save, so the recorded window is put back
+        let saved = self.saved.take();
+        let output = self.output.take();
+        let active = self.save().ok().and_then(|_| self.saved_class());
+        self.saved = saved;
+        self.output = output;
+        active
+    }
+
+    fn saved_output(&self) -> Option<ScreenRect> {
+        self.output
+    }
+}
+
+// =============================================================================
+// wlr-foreign-toplevel-management
+// =============================================================================
+
+const TOPLEVEL_MANAGER_INTERFACE: &str = "zwlr_foreign_toplevel_manager_v1";
+
+struct Toplevel {
+    handle: ZwlrForeignToplevelHandleV1,
+    app_id: String,
+    activated: bool,
+    /// Outputs the toplevel is shown on
+    outputs: Vec<wl_output::WlOutput>,
+}
+
+/// What wl_output announced about an output
+#[derive(Debug, Clone, Copy, Default)]
+struct OutputInfo {
+    x: i32,
+    y: i32,
+    /// Current mode, in pixels
+    mode: (i32, i32),
+    scale: i32,
+    rotated: bool,
+}
+
+impl OutputInfo {
+    fn layout_rect(&self) -> Option<ScreenRect> {
+        (self.mode.0 > 0 && self.mode.1 > 0).then(|| {
+            logical_output_rect(
+                self.x,
+                self.y,
+                self.mode,
+                self.scale.max(1) as f64,
+                self.rotated,
+            )
+        })
+    }
+}
+
+#[derive(Default)]
+struct ToplevelState {
+    seat: Option<wl_seat::WlSeat>,
+    manager: Option<ZwlrForeignToplevelManagerV1>,
+    toplevels: Vec<Toplevel>,
+    outputs: Vec<(wl_output::WlOutput, OutputInfo)>,
+}
+
+/// Focus through `zwlr_foreign_toplevel_manager_v1`. The connection stays open, since
+/// toplevel handles are only valid on the connection that received them.
+pub struct WlrForeignToplevel {
+    queue: EventQueue<ToplevelState>,
+    state: ToplevelState,
+    saved: Option<ZwlrForeignToplevelHandleV1>,
+}
+
+impl WlrForeignToplevel {
+    pub fn connect() -> Result<Self, String> {
+        let conn =
+            Connection::connect_to_env().map_err(|e| format!("Wayland connect failed: {}", e))?;
+        let mut queue = conn.new_event_queue();
+        conn.display().get_registry(&queue.handle(), ());
+
+        let mut state = ToplevelState::default();
+        // The first roundtrip binds the globals, the second receives the toplevel list
+        for _ in 0..2 {
+            queue
+                .roundtrip(&mut state)
+                .map_err(|e| format!("Wayland roundtrip failed: {}", e))?;
+        }
+        if state.manager.is_none() {
+            return Err(format!(
+                "Compositor does not offer {}",
+                TOPLEVEL_MANAGER_INTERFACE
+            ));
+        }
+        if state.seat.is_none() {
+            return Err("Compositor has no seat".to_string());
+        }
+
+        Ok(Self {
+            queue,
+            state,
+            saved: None,
+        })
+    }
+
+    /// Applies the toplevel events received since the last call
+    fn sync(&mut self) -> Result<(), String> {
+        self.queue
+            .roundtrip(&mut self.state)
+            .map(|_| ())
+            .map_err(|e| format!("Wayland roundtrip failed: {}", e))
+    }
+
+    fn saved_toplevel(&self) -> Option<&Toplevel> {
+        let saved = self.saved.as_ref()?;
+        self.state.toplevels.iter().find(|t| &t.handle == saved)
+    }
+}
+
+impl FocusBackend for WlrForeignToplevel {
+    fn name(&self) -> &'static str {
+        "wlr-foreign-toplevel"
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        self.sync()?;
+        self.saved = self
+            .state
+            .toplevels
+            .iter()
+            .find(|t| t.activated)
+            .map(|t| t.handle.clone());
+        eprintln!(
+            "[WaylandFocus] Saved toplevel: {:?}",
+            self.saved_toplevel().map(|t| &t.app_id)
+        );
+        Ok(())
+    }
+
+    fn restore(&mut self) -> Result<(), String> {
+        self.sync()?;
+        let seat = self.state.seat.as_ref().ok_or("Compositor has no seat")?;
+        let toplevel = self
+            .saved_toplevel()
+            .ok_or("Previous window was closed or never saved")?;
+        toplevel.handle.activate(seat);
+        self.sync()
+    }
+
+    fn saved_class(&self) -> Option<Vec<String>> {
+        self.saved_toplevel()
+            .filter(|t| !t.app_id.is_empty())
+            .map(|t| vec![t.app_id.clone()])
+    }
+
+    fn active_class(&mut self) -> Option<Vec<String>> {
+        self.sync().ok()?;
+        self.state
+            .toplevels
+            .iter()
+            .find(|t| t.activated && !t.app_id.is_empty())
+            .map(|t| vec![t.app_id.clone()])
+    }
+
+    fn saved_output(&self) -> Option<ScreenRect> {
+        let output = self.saved_toplevel()?.outputs.first()?;
+        self.state
+            .outputs
+            .iter()
+            .find(|(o, _)| o == output)
+            .and_then(|(_, info)| info.layout_rect())
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for ToplevelState {
+    fn event(
+        state: &mut Self,
+        registry: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _data: &(),
+        _conn: &Connection,
+        qh: &wayland_client::QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            if interface == "wl_seat" && state.seat.is_none() {
+                state.seat = Some(registry.bind(name, version.min(5), qh, ()));
+            } else if interface == TOPLEVEL_MANAGER_INTERFACE {
+                state.manager = Some(registry.bind(name, version.min(3), qh, ()));
+            } else if interface == "wl_output" {
+                // Bound before the toplevels arrive, so their output_enter events refer to it
+                let output: wl_output::WlOutput = registry.bind(name, version.min(4), qh, ());
+                state.outputs.push((output, OutputInfo::default()));
+            }
+        }
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for ToplevelState {
+    fn event(
+        state: &mut Self,
+        _manager: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &wayland_client::QueueHandle<Self>,
+    ) {
+        if let zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } = event {
+            state.toplevels.push(Toplevel {
+                handle: toplevel,
+                app_id: String::new(),
+                activated: false,
+                outputs: Vec::new(),
+            });
+        }
+    }
+
+    event_created_child!(ToplevelState, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for ToplevelState {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &wayland_client::QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                state.toplevels.retain(|t| &t.handle != handle);
+                handle.destroy();
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                if let Some(toplevel) = state.toplevels.iter_mut().find(|t| &t.handle == handle) {
+                    toplevel.app_id = app_id;
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::State { state: flags } => {
+                let activated = flags
+                    .chunks_exact(4)
+                    .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                    .any(|s| s == zwlr_foreign_toplevel_handle_v1::State::Activated as u32);
+                if let Some(toplevel) = state.toplevels.iter_mut().find(|t| &t.handle == handle) {
+                    toplevel.activated = activated;
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::OutputEnter { output } => {
+                if let Some(toplevel) = state.toplevels.iter_mut().find(|t| &t.handle == handle) {
+                    toplevel.outputs.push(output);
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::OutputLeave { output } => {
+                if let Some(toplevel) = state.toplevels.iter_mut().find(|t| &t.handle == handle) {
+                    toplevel.outputs.retain(|o| o != &output);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, ()> for ToplevelState {
+    fn event(
+        state: &mut Self,
+        output: &wl_output::WlOutput,
+        event: wl_output::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &wayland_client::QueueHandle<Self>,
+    ) {
+        let Some((_, info)) = state.outputs.iter_mut().find(|(o, _)| o == output) else {
+            return;
+        };
+        match event {
+            wl_output::Event::Geometry {
+                x, y, transform, ..
+            } => {
+                info.x = x;
+                info.y = y;
+                info.rotated = matches!(
+                    transform.into_result(),
+                    Ok(wl_output::Transform::_90
+                        | wl_output::Transform::_270
+                        | wl_output::Transform::Flipped90
+                        | wl_output::Transform::Flipped270)
+                );
+            }
+            wl_output::Event::Mode {
+                flags,
+                width,
+                height,
+                ..
+            } if flags
+                .into_result()
+                .is_ok_and(|flags| flags.contains(wl_output::Mode::Current)) =>
+            {
+                info.mode = (width, height);
+            }
+            wl_output::Event::Scale { factor } => info.scale = factor,
+            _ => {}
+        }
+    }
+}
+
+delegate_noop!(ToplevelState: ignore wl_seat::WlSeat);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn test_focused_toplevel_from_sway_tree() {
+        let tree = serde_json::json!({
+            "id": 1, "type": "root", "focused": false,
+            "nodes": [{
+                "id": 4, "type": "workspace", "focused": false,
+                "nodes": [{ "id": 7, "type": "con", "focused": false, "app_id": "foot" }],
+                "floating_nodes": [{
+                    "id": 9, "type": "floating_con", "focused": true, "app_id": null,
+                    "window_properties": { "class": "Gimp", "instance": "gimp" }
+                }]
+            }]
+        });
+        assert_eq!(
+            focused_toplevel(&tree),
+            Some(SavedToplevel {
+                id: "9".to_string(),
+                classes: vec!["gimp".to_string(), "Gimp".to_string()],
+            })
+        );
+
+        // Focus on an empty workspace
+        let empty = serde_json::json!({
+            "id": 1, "focused": false,
+            "nodes": [{ "id": 4, "type": "workspace", "focused": true, "nodes": [] }]
+        });
+        assert_eq!(focused_toplevel(&empty), None);
+    }
+
+    #[test]
+    fn test_focused_output() {
+        let tree = serde_json::json!({
+            "id": 1, "type": "root", "focused": false,
+            "nodes": [
+                { "id": 2, "type": "output", "name": "__i3", "focused": false, "nodes": [] },
+                {
+                    "id": 3, "type": "output", "name": "eDP-1", "focused": false,
+                    "rect": { "x": 0, "y": 0, "width": 1440, "height": 900 },
+                    "nodes": [{ "id": 4, "type": "workspace", "focused": false, "nodes": [] }]
+                },
+                {
+                    "id": 5, "type": "output", "name": "DP-2", "focused": false,
+                    "rect": { "x": 1440, "y": 0, "width": 1920, "height": 1080 },
+                    "nodes": [{ "id": 6, "type": "workspace", "focused": true, "nodes": [] }]
+                }
+            ]
+        });
+        assert_eq!(
+            focused_output_in_tree(&tree),
+            Some(ScreenRect {
+                x: 1440,
+                y: 0,
+                width: 1920,
+                height: 1080,
+            })
+        );
+
+        // Hyprland reports the mode: a 4K monitor at scale 2, turned to portrait
+        let monitors = r#"[
+            {"id": 0, "name": "eDP-1", "focused": false, "x": 0, "y": 0,
+             "width": 2880, "height": 1800, "scale": 2.0, "transform": 0},
+            {"id": 1, "name": "HDMI-A-1", "focused": true, "x": 1440, "y": 0,
+             "width": 3840, "height": 2160, "scale": 2.0, "transform": 1}
+        ]"#;
+        assert_eq!(
+            parse_focused_monitor(monitors),
+            Some(ScreenRect {
+                x: 1440,
+                y: 0,
+                width: 1080,
+                height: 1920,
+            })
+        );
+        assert_eq!(parse_focused_monitor("[]"), None);
+
+        assert_eq!(
+            parse_kwin_geometry("1920,0,2560,1440"),
+            Some(ScreenRect {
+                x: 1920,
+                y: 0,
+                width: 2560,
+                height: 1440,
+            })
+        );
+        assert_eq!(parse_kwin_geometry(""), None);
+        assert_eq!(parse_kwin_geometry("1,2,3"), None);
+    }
+
+    #[test]
+    fn test_ipc_request_framing() {
+        let socket =
+            std::env::temp_dir().join(format!("sway-ipc-test-{}.sock", uuid::Uuid::new_v4()));
+        let listener = UnixListener::bind(&socket).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut header = [0u8; 14];
+            stream.read_exact(&mut header).unwrap();
+            let len = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]);
+            let msg_type = u32::from_ne_bytes([header[10], header[11], header[12], header[13]]);
+            let mut payload = vec![0; len as usize];
+            stream.read_exact(&mut payload).unwrap();
+
+            let reply = br#"[{"success":true}]"#;
+            let mut message = IPC_MAGIC.to_vec();
+            message.extend_from_slice(&(reply.len() as u32).to_ne_bytes());
+            message.extend_from_slice(&msg_type.to_ne_bytes());
+            message.extend_from_slice(reply);
+            stream.write_all(&message).unwrap();
+            (msg_type, String::from_utf8(payload).unwrap())
+        });
+
+        let reply = ipc_request(&socket, IPC_RUN_COMMAND, "[con_id=9] focus").unwrap();
+        assert_eq!(reply[0]["success"], true);
+        assert_eq!(
+            server.join().unwrap(),
+            (IPC_RUN_COMMAND, "[con_id=9] focus".to_string())
+        );
+        let _ = std::fs::remove_file(socket);
+    }
+
+    #[test]
+    fn test_hyprland_active_window() {
+        let reply = r#"{"address": "0x55d1c0a8f2b0", "class": "kitty", "initialClass": "kitty"}"#;
+        assert_eq!(
+            parse_active_window(reply),
+            Some(SavedToplevel {
+                id: "0x55d1c0a8f2b0".to_string(),
+                classes: vec!["kitty".to_string()],
+            })
+        );
+        // No focused window
+        assert_eq!(parse_active_window("{}"), None);
+
+        assert_eq!(hyprctl_args("j/activewindow"), vec!["-j", "activewindow"]);
+        assert_eq!(
+            hyprctl_args("dispatch focuswindow address:0x1"),
+            vec!["dispatch", "focuswindow", "address:0x1"]
+        );
+    }
+}
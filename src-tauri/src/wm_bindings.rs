@@ -0,0 +1,337 @@
+//! Window Manager Bindings Module
+//! Reads key bindings back from window manager config files, so the shortcut manager can
+//! find the lines its chords collide with and the conflict detector can report them.
+
+use crate::shortcut_chord::{key_from_evdev, Chord};
+
+/// Config file syntaxes bindings can be read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSyntax {
+    /// i3 and sway: `bindsym $mod+v exec foo`
+    I3,
+    /// Hyprland: `bind = SUPER, V, exec, foo`
+    Hyprland,
+    /// niri (KDL): `Mod+V { spawn "foo"; }`
+    Niri,
+    /// river init script: `riverctl map normal Super V spawn foo`
+    River,
+    /// Openbox and labwc rc.xml: `<keybind key="W-v">`
+    Openbox,
+    /// wayfire.ini: `binding_foo = <super> KEY_V`
+    Wayfire,
+    /// sxhkdrc (bspwm): `super + v` followed by the indented command
+    Sxhkd,
+    /// awesome rc.lua: `awful.key({ modkey }, "v", ...)`
+    Awesome,
+    /// Qtile config.py: `Key([mod], "v", lazy.spawn("foo"))`
+    Qtile,
+}
+
+/// A key binding found in a config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigBinding {
+    /// 0-based line of the binding
+    pub line: usize,
+    pub chord: Chord,
+    /// What the binding runs, as written in the config
+    pub action: String,
+}
+
+impl ConfigSyntax {
+    /// Chord bound by a single config line, if it is a key binding
+    pub fn parse_line(self, line: &str) -> Option<Chord> {
+        self.parse_entry(line).map(|(chord, _)| chord)
+    }
+
+    /// All key bindings in a config
+    pub fn bindings(self, content: &str) -> Vec<ConfigBinding> {
+        let lines: Vec<&str> = content.lines().collect();
+        lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let (chord, mut action) = self.parse_entry(line)?;
+                // Multi-line formats keep the action below the chord
+                let following = lines[i + 1..].iter().map(|l| l.trim());
+                match self {
+                    Self::Sxhkd => {
+                        action = following.take(1).collect();
+                    }
+                    Self::Openbox => {
+                        // The action may share the <keybind> line
+                        let body: Vec<&str> = std::iter::once(line.trim())
+                            .chain(following.take_while(|l| !l.starts_with("</keybind")))
+                            .collect();
+                        let name = body.iter().find_map(|l| attribute(l, "<action", "name"));
+                        // Execute actions are shown with their command
+                        let command = body.iter().find_map(|l| {
+                            attribute(l, "<action", "command").or_else(|| {
+                                let text = l.strip_prefix("<command>")?;
+                                text.split_once("</command>").map(|(c, _)| c.to_string())
+                            })
+                        });
+                        action = match (name, command) {
+                            (Some(name), Some(command)) => format!("{}: {}", name, command),
+                            (name, command) => name.or(command).unwrap_or_default(),
+                        };
+                    }
+                    _ => {}
+                }
+                Some(ConfigBinding {
+                    line: i,
+                    chord,
+                    action,
+                })
+            })
+            .collect()
+    }
+
+    /// Chord and action of a binding line
+    fn parse_entry(self, line: &str) -> Option<(Chord, String)> {
+        let trimmed = line.trim();
+        if ["#", "//", "--", "<!--"]
+            .iter()
+            .any(|comment| trimmed.starts_with(comment))
+        {
+            return None;
+        }
+
+        match self {
+            Self::I3 => {
+                let mut words = trimmed.split_whitespace();
+                if words.next() != Some("bindsym") {
+                    return None;
+                }
+                let combo = words.find(|word| !word.starts_with("--"))?;
+                let chord = Chord::parse(combo).ok()?;
+                Some((chord, words.collect::<Vec<_>>().join(" ")))
+            }
+            Self::Hyprland => {
+                let (keyword, args) = trimmed.split_once('=')?;
+                if !keyword.trim().starts_with("bind") {
+                    return None;
+                }
+                let mut fields = args.split(',').map(str::trim);
+                let (mods, key) = (fields.next()?, fields.next()?);
+                let chord = chord_from(mods.split_whitespace(), key)?;
+                Some((chord, fields.collect::<Vec<_>>().join(", ")))
+            }
+            Self::Niri => {
+                let (head, body) = trimmed.split_once('{')?;
+                let combo = head.split_whitespace().next()?;
+                let mut parts: Vec<&str> = combo.split('+').collect();
+                let key = parts.pop()?;
+                let chord = chord_from(parts, key)?;
+                let action = body.trim_end().trim_end_matches('}').trim();
+                Some((chord, action.trim_end_matches(';').to_string()))
+            }
+            Self::River => {
+                let mut words = trimmed.split_whitespace();
+                if words.next() != Some("riverctl") || words.next() != Some("map") {
+                    return None;
+                }
+                // Flags: -release, -repeat, -layout <index>
+                let mut word = words.next()?;
+                while word.starts_with('-') {
+                    if word == "-layout" {
+                        words.next();
+                    }
+                    word = words.next()?;
+                }
+                let (mods, key) = (words.next()?, words.next()?);
+                let mods = mods.split('+').filter(|m| *m != "None");
+                let chord = chord_from(mods, key)?;
+                Some((chord, words.collect::<Vec<_>>().join(" ")))
+            }
+            Self::Openbox => {
+                let key = attribute(trimmed, "<keybind", "key")?;
+                // Key chains ("W-a W-b") can't collide with a single chord
+                if key.contains(' ') {
+                    return None;
+                }
+                let mut parts: Vec<&str> = key.split('-').collect();
+                let key = parts.pop()?;
+                let mods = parts
+                    .into_iter()
+                    .map(|m| match m {
+                        "C" => Some("Ctrl"),
+                        "A" => Some("Alt"),
+                        "S" => Some("Shift"),
+                        "W" => Some("Super"),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some((chord_from(mods, key)?, String::new()))
+            }
+            Self::Wayfire => {
+                let (name, value) = trimmed.split_once('=')?;
+                // Only the first of several alternatives (`a | b`) is read
+                let value = value.split('|').next()?;
+                let mut mods = Vec::new();
+                let mut key = None;
+                for token in value.split_whitespace() {
+                    match token.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+                        Some(modifier) => mods.push(modifier),
+                        None if key.is_none() => key = Some(key_from_evdev(token)?),
+                        None => return None,
+                    }
+                }
+                let chord = chord_from(mods, &key?)?;
+                Some((chord, name.trim().to_string()))
+            }
+            Self::Sxhkd => {
+                // Commands are indented; `{a,b}` sequences aren't expanded
+                if line.starts_with(char::is_whitespace) || trimmed.contains('{') {
+                    return None;
+                }
+                let mut parts: Vec<&str> = trimmed.split('+').map(str::trim).collect();
+                let key = parts.pop()?.trim_start_matches(['@', '~']);
+                Some((chord_from(parts, key)?, String::new()))
+            }
+            Self::Awesome => {
+                let args = trimmed.split_once("awful.key(")?.1;
+                let (mods, rest) = args.trim_start().strip_prefix('{')?.split_once('}')?;
+                let (key, action) = first_quoted(rest)?;
+                let mods = mods.split(',').map(|m| m.trim().trim_matches('"'));
+                let action = action
+                    .trim_start_matches([',', ' '])
+                    .trim_end_matches([')', ',']);
+                Some((chord_from(mods, key)?, action.to_string()))
+            }
+            Self::Qtile => {
+                let args = trimmed.split_once("Key(")?.1;
+                let (mods, rest) = args.trim_start().strip_prefix('[')?.split_once(']')?;
+                let (key, action) = first_quoted(rest)?;
+                let mods = mods.split(',').map(|m| m.trim().trim_matches(['"', '\'']));
+                let action = action
+                    .trim_start_matches([',', ' '])
+                    .trim_end_matches([')', ',']);
+                Some((chord_from(mods, key)?, action.to_string()))
+            }
+        }
+    }
+}
+
+/// Builds a chord from modifier names in any WM's vocabulary. Variables like `$mod`,
+/// `modkey` or `$mainMod` are assumed to be Super, as in the default configs.
+fn chord_from<'a>(mods: impl IntoIterator<Item = &'a str>, key: &str) -> Option<Chord> {
+    let mut combo = Vec::new();
+    for modifier in mods.into_iter().filter(|m| !m.is_empty()) {
+        let canonical = match modifier.to_lowercase().as_str() {
+            "mod" | "modkey" | "$mainmod" | "$mod" | "super" | "mod4" | "logo" | "win" => "Super",
+            "ctrl" | "control" | "primary" => "Ctrl",
+            "alt" | "mod1" => "Alt",
+            "shift" => "Shift",
+            _ => return None,
+        };
+        combo.push(canonical);
+    }
+    combo.push(key);
+    Chord::parse(&combo.join("+")).ok()
+}
+
+/// Value of `name="..."` in a tag starting with `tag`
+fn attribute(line: &str, tag: &str, name: &str) -> Option<String> {
+    let rest = line.split_once(tag)?.1;
+    let value = rest.split_once(&format!("{}=\"", name))?.1;
+    value.split_once('"').map(|(value, _)| value.to_string())
+}
+
+/// First quoted string in `text` and what follows it
+fn first_quoted(text: &str) -> Option<(&str, &str)> {
+    let start = text.find(['"', '\''])?;
+    let quote = text[start..].chars().next()?;
+    let (value, rest) = text[start + 1..].split_once(quote)?;
+    Some((value, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn super_v() -> Chord {
+        Chord::parse("Super+V").unwrap()
+    }
+
+    #[test]
+    fn test_single_line_syntaxes() {
+        let cases = [
+            (ConfigSyntax::I3, "bindsym --release $mod+v exec foo"),
+            (
+                ConfigSyntax::Hyprland,
+                "bind = $mainMod, V, togglefloating,",
+            ),
+            (
+                ConfigSyntax::Niri,
+                r#"    Mod+V hotkey-overlay-title="Float" { toggle-window-floating; }"#,
+            ),
+            (
+                ConfigSyntax::River,
+                "riverctl map -release normal Super V spawn foo",
+            ),
+            (ConfigSyntax::Wayfire, "binding_clip = <super> KEY_V"),
+            (ConfigSyntax::Sxhkd, "super + @v"),
+            (
+                ConfigSyntax::Awesome,
+                r#"    awful.key({ modkey }, "v", function () awful.spawn("foo") end,"#,
+            ),
+            (
+                ConfigSyntax::Qtile,
+                r#"    Key([mod], "v", lazy.window.toggle_floating()),"#,
+            ),
+        ];
+        for (syntax, line) in cases {
+            assert_eq!(syntax.parse_line(line), Some(super_v()), "{:?}", syntax);
+        }
+
+        // Other chords, comments and non-binding lines
+        assert!(ConfigSyntax::Niri
+            .parse_line("// Mod+V { spawn \"x\"; }")
+            .is_none());
+        assert!(ConfigSyntax::Sxhkd.parse_line("    super + v").is_none());
+        assert!(ConfigSyntax::River
+            .parse_line("riverctl map normal Super+Shift V spawn foo")
+            .is_some_and(|c| c != super_v()));
+        assert!(ConfigSyntax::Wayfire
+            .parse_line("command_clip = foo")
+            .is_none());
+        assert!(ConfigSyntax::Qtile
+            .parse_line(r#"Key([mod, "shift"], "v", lazy.spawn("x")),"#)
+            .is_some_and(|c| c.shift));
+    }
+
+    #[test]
+    fn test_bindings_with_actions() {
+        let niri =
+            "binds {\n    Mod+T { spawn \"alacritty\"; }\n    Mod+V { toggle-window-floating; }\n}";
+        let bindings = ConfigSyntax::Niri.bindings(niri);
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings[1].line, 2);
+        assert_eq!(bindings[1].action, "toggle-window-floating");
+
+        let sxhkd = "# terminal\nsuper + Return\n\talacritty\n\nsuper + v\n\tbspc node -t floating";
+        let bindings = ConfigSyntax::Sxhkd.bindings(sxhkd);
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings[1].chord, super_v());
+        assert_eq!(bindings[1].action, "bspc node -t floating");
+
+        let rc = r#"<keyboard>
+    <keybind key="W-v">
+      <action name="ToggleMaximize"/>
+    </keybind>
+    <keybind key="C-A-Left W-Right">
+      <action name="GoToDesktop"/>
+    </keybind>
+    <keybind key="C-A-v">
+      <action name="Execute">
+        <command>win11-clipboard-history</command>
+      </action>
+    </keybind>
+  </keyboard>"#;
+        let bindings = ConfigSyntax::Openbox.bindings(rc);
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings[0].chord, super_v());
+        assert_eq!(bindings[0].action, "ToggleMaximize");
+        assert_eq!(bindings[1].action, "Execute: win11-clipboard-history");
+    }
+}
@@ -4,6 +4,8 @@ import { getCurrentWindow } from '@tauri-apps/api/window'
 import { listen } from '@tauri-apps/api/event'
 import { invoke } from '@tauri-apps/api/core'
 import { useClipboardHistory } from './hooks/useClipboardHistory'
+import { useHistoryStore } from './hooks/useHistoryStore'
+import { useRecordingPaused } from './hooks/useRecordingPaused'
 import { TabBar, TabBarRef } from './components/TabBar'
 import { DragHandle } from './components/DragHandle'
 import { EmojiPicker } from './components/EmojiPicker'
@@ -13,6 +15,10 @@ import { SymbolPicker } from './components/SymbolPicker'
 import { calculateSecondaryOpacity, calculateTertiaryOpacity } from './utils/themeUtils'
 import type { ActiveTab, UserSettings } from './types/clipboard'
 import { ClipboardTab } from './components/ClipboardTab'
+import { UnlockHistoryPrompt } from './components/UnlockHistoryPrompt'
+import { DEFAULT_PRIVACY_SETTINGS } from './components/PrivacySection'
+import { DEFAULT_PASTE_SETTINGS } from './components/PasteSection'
+import { DEFAULT_SHORTCUT_BINDINGS } from './components/ShortcutSection'
 
 const DEFAULT_SETTINGS: UserSettings = {
   theme_mode: 'system',
@@ -21,6 +27,17 @@ const DEFAULT_SETTINGS: UserSettings = {
 
   enable_ui_polish: true,
   custom_kaomojis: [],
+  shortcuts: DEFAULT_SHORTCUT_BINDINGS,
+  restore_clipboard_after_paste: false,
+  paste: DEFAULT_PASTE_SETTINGS,
+  clipboard_rules: [],
+
+  persist_history: false,
+  encrypt_history: false,
+  history_key_source: 'passphrase',
+  history_key_file: null,
+
+  privacy: DEFAULT_PRIVACY_SETTINGS,
 }
 
 /**
@@ -93,8 +110,10 @@ function ClipboardApp() {
   const secondaryOpacity = calculateSecondaryOpacity(opacity)
   const tertiaryOpacity = calculateTertiaryOpacity(opacity)
 
-  const { history, isLoading, clearHistory, deleteItem, togglePin, pasteItem } =
+  const { history, isLoading, clearHistory, deleteItem, togglePin, pasteItem, typeItem } =
     useClipboardHistory()
+  const { status: storeStatus, unlock: unlockHistory } = useHistoryStore()
+  const { paused: recordingPaused, resume: resumeRecording } = useRecordingPaused()
 
   // Refs for focus management
   const tabBarRef = useRef<TabBarRef>(null)
@@ -180,6 +199,18 @@ function ClipboardApp() {
     }
   }, []) // Empty dependency array - listener is registered once
 
+  // Action shortcuts can open the window directly on a tab
+  useEffect(() => {
+    const unlistenPromise = listen<ActiveTab>('open-tab', (event) => {
+      activeTabRef.current = event.payload
+      setActiveTab(event.payload)
+    })
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten())
+    }
+  }, [])
+
   // Handle tab change
   const handleTabChange = useCallback((tab: ActiveTab) => {
     setActiveTab(tab)
@@ -198,19 +229,41 @@ function ClipboardApp() {
     switch (activeTab) {
       case 'clipboard':
         return (
-          <ClipboardTab
-            history={history}
-            isLoading={isLoading}
-            isDark={isDark}
-            tertiaryOpacity={tertiaryOpacity}
-            secondaryOpacity={secondaryOpacity}
-            clearHistory={clearHistory}
-            deleteItem={deleteItem}
-            togglePin={togglePin}
-            onPaste={pasteItem}
-            settings={settings}
-            tabBarRef={tabBarRef}
-          />
+          <>
+            {storeStatus?.locked && (
+              <UnlockHistoryPrompt status={storeStatus} onUnlock={unlockHistory} isDark={isDark} />
+            )}
+            {recordingPaused && (
+              <div
+                className={clsx(
+                  'mx-3 mt-2 p-3 rounded-win11 flex items-center justify-between text-sm',
+                  isDark ? 'bg-white/5' : 'bg-black/5'
+                )}
+              >
+                <span>Recording is paused</span>
+                <button
+                  onClick={() => resumeRecording().catch(console.error)}
+                  className="text-xs text-win11-bg-accent hover:underline"
+                >
+                  Resume
+                </button>
+              </div>
+            )}
+            <ClipboardTab
+              history={history}
+              isLoading={isLoading}
+              isDark={isDark}
+              tertiaryOpacity={tertiaryOpacity}
+              secondaryOpacity={secondaryOpacity}
+              clearHistory={clearHistory}
+              deleteItem={deleteItem}
+              togglePin={togglePin}
+              onPaste={pasteItem}
+              onTypeOut={typeItem}
+              settings={settings}
+              tabBarRef={tabBarRef}
+            />
+          </>
         )
 
       case 'emoji':
@@ -4,18 +4,47 @@ import { getCurrentWindow, Window } from '@tauri-apps/api/window'
 import { listen } from '@tauri-apps/api/event'
 import { emit } from '@tauri-apps/api/event'
 import { clsx } from 'clsx'
+import { ImportHistorySection } from './components/ImportHistorySection'
+import { ConfigChangesSection } from './components/ConfigChangesSection'
+import { HistoryStorageSection, HistoryStorageSettings } from './components/HistoryStorageSection'
+import { PrivacySection, DEFAULT_PRIVACY_SETTINGS } from './components/PrivacySection'
+import {
+  PasteSection,
+  PasteSectionSettings,
+  DEFAULT_PASTE_SETTINGS,
+} from './components/PasteSection'
+import { ShortcutSection, DEFAULT_SHORTCUT_BINDINGS } from './components/ShortcutSection'
+import { WindowPlacementSection } from './components/WindowPlacementSection'
+import type { PrivacySettings, ShortcutBindings, WindowPlacement } from './types/clipboard'
 
 /** User settings type matching the Rust struct */
-interface UserSettings {
+interface UserSettings extends HistoryStorageSettings, PasteSectionSettings {
   theme_mode: 'system' | 'dark' | 'light'
   dark_background_opacity: number
   light_background_opacity: number
+  window_placement: WindowPlacement
+  shortcuts: ShortcutBindings
+  privacy: PrivacySettings
 }
 
 const DEFAULT_SETTINGS: UserSettings = {
   theme_mode: 'system',
   dark_background_opacity: 0.7,
   light_background_opacity: 0.7,
+
+  window_placement: 'auto',
+
+  shortcuts: DEFAULT_SHORTCUT_BINDINGS,
+
+  restore_clipboard_after_paste: false,
+  paste: DEFAULT_PASTE_SETTINGS,
+
+  persist_history: false,
+  encrypt_history: false,
+  history_key_source: 'passphrase',
+  history_key_file: null,
+
+  privacy: DEFAULT_PRIVACY_SETTINGS,
 }
 
 type ThemeMode = 'system' | 'dark' | 'light'
@@ -207,6 +236,41 @@ function SettingsApp() {
     setSettings((prev) => ({ ...prev, light_background_opacity: value }))
   }
 
+  // Handle history storage changes
+  const handleStorageChange = (patch: Partial<HistoryStorageSettings>) => {
+    const newSettings = { ...settings, ...patch }
+    setSettings(newSettings)
+    saveSettings(newSettings)
+  }
+
+  // Handle window placement changes
+  const handlePlacementChange = (window_placement: WindowPlacement) => {
+    const newSettings = { ...settings, window_placement }
+    setSettings(newSettings)
+    saveSettings(newSettings)
+  }
+
+  // Handle global shortcut changes
+  const handleShortcutChange = (patch: Partial<ShortcutBindings>) => {
+    const newSettings = { ...settings, shortcuts: { ...settings.shortcuts, ...patch } }
+    setSettings(newSettings)
+    saveSettings(newSettings)
+  }
+
+  // Handle paste option changes
+  const handlePasteChange = (patch: Partial<PasteSectionSettings>) => {
+    const newSettings = { ...settings, ...patch }
+    setSettings(newSettings)
+    saveSettings(newSettings)
+  }
+
+  // Handle privacy policy changes
+  const handlePrivacyChange = (patch: Partial<PrivacySettings>) => {
+    const newSettings = { ...settings, privacy: { ...settings.privacy, ...patch } }
+    setSettings(newSettings)
+    saveSettings(newSettings)
+  }
+
   // Commit opacity changes to disk (called on mouseUp/touchEnd)
   const commitOpacityChange = () => {
     saveSettings(settings)
@@ -447,6 +511,39 @@ function SettingsApp() {
           </div>
         </section>
 
+        {/* Window Position Section */}
+        <WindowPlacementSection
+          isDark={isDark}
+          placement={settings.window_placement}
+          onChange={handlePlacementChange}
+        />
+
+        {/* Shortcuts Section */}
+        <ShortcutSection
+          isDark={isDark}
+          settings={settings.shortcuts}
+          onChange={handleShortcutChange}
+        />
+
+        {/* Desktop Configuration Section */}
+        <ConfigChangesSection isDark={isDark} />
+
+        {/* Pasting Section */}
+        <PasteSection isDark={isDark} settings={settings} onChange={handlePasteChange} />
+
+        {/* History Storage Section */}
+        <HistoryStorageSection isDark={isDark} settings={settings} onChange={handleStorageChange} />
+
+        {/* Privacy Section */}
+        <PrivacySection
+          isDark={isDark}
+          settings={settings.privacy}
+          onChange={handlePrivacyChange}
+        />
+
+        {/* Import Section */}
+        <ImportHistorySection isDark={isDark} />
+
         {/* Reset Section */}
         <div className="flex justify-end pt-2">
           <button
@@ -20,6 +20,7 @@ export function ClipboardTab(props: {
   deleteItem: (id: string) => void
   togglePin: (id: string) => void
   onPaste: (id: string) => void
+  onTypeOut: (id: string) => void
   settings: UserSettings
   tabBarRef: React.RefObject<TabBarRef | null>
 }) {
@@ -33,6 +34,7 @@ export function ClipboardTab(props: {
     deleteItem,
     togglePin,
     onPaste,
+    onTypeOut,
 
     tabBarRef,
   } = props
@@ -140,6 +142,7 @@ export function ClipboardTab(props: {
               index={index}
               isFocused={index === focusedIndex}
               onPaste={onPaste}
+              onTypeOut={onTypeOut}
               onDelete={deleteItem}
               onTogglePin={togglePin}
               onFocus={() => setFocusedIndex(index)}
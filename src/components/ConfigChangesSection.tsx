@@ -0,0 +1,117 @@
+import { useState, useEffect, useCallback } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { clsx } from 'clsx'
+
+/** A desktop config file or setting we changed (matches the Rust ConfigChange) */
+interface ConfigChange {
+  target: string
+  timestamp: number
+  reasons: string[]
+  diff: string
+}
+
+interface ConfigChangesSectionProps {
+  isDark: boolean
+}
+
+/**
+ * Settings card listing the desktop config we changed, with a rollback to the originals
+ */
+export function ConfigChangesSection({ isDark }: ConfigChangesSectionProps) {
+  const [changes, setChanges] = useState<ConfigChange[]>([])
+  const [expanded, setExpanded] = useState<string | null>(null)
+  const [isRestoring, setIsRestoring] = useState(false)
+  const [message, setMessage] = useState<string | null>(null)
+
+  const loadChanges = useCallback(() => {
+    invoke<ConfigChange[]>('get_config_changes').then(setChanges).catch(console.error)
+  }, [])
+
+  useEffect(() => {
+    loadChanges()
+  }, [loadChanges])
+
+  const handleRollback = async () => {
+    setIsRestoring(true)
+    setMessage(null)
+    try {
+      const restored = await invoke<string[]>('rollback_config_changes')
+      setMessage(
+        `Restored ${restored.length} item(s). Shortcuts are registered again on the next start.`
+      )
+    } catch (err) {
+      console.error('Rollback failed:', err)
+      setMessage(`Error: ${err}`)
+    } finally {
+      setIsRestoring(false)
+      loadChanges()
+    }
+  }
+
+  const mutedText = isDark ? 'text-gray-400' : 'text-gray-500'
+
+  return (
+    <section
+      className={clsx(
+        'rounded-xl border shadow-sm overflow-hidden',
+        isDark ? 'bg-win11-bg-secondary border-white/5' : 'bg-white border-gray-200/60'
+      )}
+    >
+      <div className="p-6 border-b border-inherit">
+        <h2 className="text-base font-semibold mb-1">Desktop Configuration</h2>
+        <p className={clsx('text-xs', mutedText)}>
+          Shortcut files and settings changed to register shortcuts or resolve conflicts
+        </p>
+      </div>
+
+      <div className="p-6 space-y-4">
+        {changes.length === 0 ? (
+          <p className={clsx('text-sm', mutedText)}>No changes have been made</p>
+        ) : (
+          <ul className="space-y-3">
+            {changes.map((change) => (
+              <li key={change.target} className="text-sm">
+                <button
+                  onClick={() => setExpanded(expanded === change.target ? null : change.target)}
+                  className="w-full text-left"
+                >
+                  <span className="font-medium break-all">{change.target}</span>
+                  <span className={clsx('block text-xs', mutedText)}>
+                    {new Date(change.timestamp * 1000).toLocaleString()} ·{' '}
+                    {change.reasons.join(', ')}
+                  </span>
+                </button>
+                {expanded === change.target && (
+                  <pre
+                    className={clsx(
+                      'mt-2 p-3 rounded-lg text-xs overflow-x-auto',
+                      isDark ? 'bg-black/20' : 'bg-gray-50'
+                    )}
+                  >
+                    {change.diff || 'No differences'}
+                  </pre>
+                )}
+              </li>
+            ))}
+          </ul>
+        )}
+
+        <div className="flex justify-end">
+          <button
+            onClick={handleRollback}
+            disabled={isRestoring || changes.length === 0}
+            className="px-4 py-2 bg-win11-bg-accent hover:opacity-90 disabled:opacity-50 text-white rounded-lg text-sm font-semibold transition-all"
+          >
+            {isRestoring ? 'Restoring...' : 'Restore original configuration'}
+          </button>
+        </div>
+
+        {message && (
+          <p className={clsx('text-xs', message.startsWith('Error') ? 'text-red-500' : mutedText)}>
+            {message}
+          </p>
+        )}
+      </div>
+    </section>
+  )
+}
@@ -1,12 +1,13 @@
 import { useCallback, forwardRef } from 'react'
 import { clsx } from 'clsx'
-import { Pin, X, Image as ImageIcon, Type } from 'lucide-react'
+import { Pin, X, Image as ImageIcon, Type, Keyboard } from 'lucide-react'
 import type { ClipboardItem } from '../types/clipboard'
 import { getCardBackgroundStyle, getTertiaryBackgroundStyle } from '../utils/themeUtils'
 
 interface HistoryItemProps {
   item: ClipboardItem
   onPaste: (id: string) => void
+  onTypeOut?: (id: string) => void
   onDelete: (id: string) => void
   onTogglePin: (id: string) => void
   onFocus?: () => void
@@ -20,6 +21,7 @@ export const HistoryItem = forwardRef<HTMLDivElement, HistoryItemProps>(function
   {
     item,
     onPaste,
+    onTypeOut,
     onDelete,
     onTogglePin,
     onFocus,
@@ -51,6 +53,15 @@ export const HistoryItem = forwardRef<HTMLDivElement, HistoryItemProps>(function
     onPaste(item.id)
   }, [item.id, onPaste])
 
+  // Type the item out instead of pasting it
+  const handleTypeOut = useCallback(
+    (e: React.SyntheticEvent) => {
+      e.stopPropagation()
+      onTypeOut?.(item.id)
+    },
+    [item.id, onTypeOut]
+  )
+
   // Handle delete with stopPropagation
   const handleDelete = useCallback(
     (e: React.MouseEvent) => {
@@ -92,7 +103,10 @@ export const HistoryItem = forwardRef<HTMLDivElement, HistoryItemProps>(function
       role="button"
       tabIndex={isFocused ? 0 : -1}
       onKeyDown={(e) => {
-        if (e.key === 'Enter' || e.key === ' ') {
+        if (e.key === 'Enter' && e.shiftKey && isText && onTypeOut) {
+          e.preventDefault()
+          handleTypeOut(e)
+        } else if (e.key === 'Enter' || e.key === ' ') {
           e.preventDefault()
           handleClick()
         }
@@ -170,6 +184,23 @@ export const HistoryItem = forwardRef<HTMLDivElement, HistoryItemProps>(function
             'transition-opacity duration-150'
           )}
         >
+          {/* Type-out button */}
+          {isText && onTypeOut && (
+            <button
+              onClick={handleTypeOut}
+              className={clsx(
+                'p-1.5 rounded-md transition-colors',
+                isDark
+                  ? 'text-win11-text-tertiary hover:bg-win11-bg-tertiary'
+                  : 'text-win11Light-text-secondary hover:bg-win11Light-bg-tertiary'
+              )}
+              title="Type out (Shift+Enter)"
+              tabIndex={-1}
+            >
+              <Keyboard className="w-4 h-4" />
+            </button>
+          )}
+
           {/* Pin button */}
           <button
             onClick={handleTogglePin}
@@ -0,0 +1,134 @@
+import { clsx } from 'clsx'
+import { useHistoryStore } from '../hooks/useHistoryStore'
+import type { HistoryKeySource } from '../types/clipboard'
+
+/** Storage-related subset of the user settings */
+export interface HistoryStorageSettings {
+  persist_history: boolean
+  encrypt_history: boolean
+  history_key_source: HistoryKeySource
+  history_key_file: string | null
+}
+
+interface HistoryStorageSectionProps {
+  isDark: boolean
+  settings: HistoryStorageSettings
+  onChange: (patch: Partial<HistoryStorageSettings>) => void
+}
+
+const KEY_SOURCES: { id: HistoryKeySource; label: string }[] = [
+  { id: 'passphrase', label: 'Passphrase' },
+  { id: 'key_file', label: 'Key file' },
+  { id: 'keyring', label: 'System keyring' },
+]
+
+/**
+ * Settings card for saving history to disk and encrypting it
+ */
+export function HistoryStorageSection({ isDark, settings, onChange }: HistoryStorageSectionProps) {
+  const { status, lock } = useHistoryStore()
+
+  const mutedText = isDark ? 'text-gray-400' : 'text-gray-500'
+  const inputClass = clsx(
+    'w-full px-3 py-2 rounded-lg text-sm border outline-none',
+    isDark ? 'bg-black/20 border-white/10' : 'bg-white border-gray-200'
+  )
+
+  return (
+    <section
+      className={clsx(
+        'rounded-xl border shadow-sm overflow-hidden',
+        isDark ? 'bg-win11-bg-secondary border-white/5' : 'bg-white border-gray-200/60'
+      )}
+    >
+      <div className="p-6 border-b border-inherit">
+        <h2 className="text-base font-semibold mb-1">History Storage</h2>
+        <p className={clsx('text-xs', mutedText)}>
+          Keep history between sessions, optionally encrypted at rest
+        </p>
+      </div>
+
+      <div className="p-6 space-y-4">
+        <label className="flex items-center gap-2 text-sm font-medium">
+          <input
+            type="checkbox"
+            checked={settings.persist_history}
+            onChange={(e) => onChange({ persist_history: e.target.checked })}
+            className="accent-win11-bg-accent"
+          />
+          Save history to disk
+        </label>
+
+        <label
+          className={clsx(
+            'flex items-center gap-2 text-sm font-medium',
+            !settings.persist_history && 'opacity-50'
+          )}
+        >
+          <input
+            type="checkbox"
+            checked={settings.encrypt_history}
+            disabled={!settings.persist_history}
+            onChange={(e) => onChange({ encrypt_history: e.target.checked })}
+            className="accent-win11-bg-accent"
+          />
+          Encrypt saved history
+        </label>
+
+        {settings.persist_history && settings.encrypt_history && (
+          <div className="space-y-4 pl-6">
+            <label className="block space-y-2 text-sm font-medium">
+              <span>Encryption key</span>
+              <select
+                value={settings.history_key_source}
+                onChange={(e) =>
+                  onChange({ history_key_source: e.target.value as HistoryKeySource })
+                }
+                className={inputClass}
+              >
+                {KEY_SOURCES.map((source) => (
+                  <option key={source.id} value={source.id}>
+                    {source.label}
+                  </option>
+                ))}
+              </select>
+            </label>
+
+            {settings.history_key_source === 'key_file' && (
+              <label className="block space-y-2 text-sm font-medium">
+                <span>Key file</span>
+                <input
+                  type="text"
+                  defaultValue={settings.history_key_file ?? ''}
+                  placeholder={status?.key_file ?? ''}
+                  onBlur={(e) => onChange({ history_key_file: e.target.value.trim() || null })}
+                  className={inputClass}
+                />
+                <p className={clsx('text-xs font-normal', mutedText)}>
+                  The default key is cleared at logout. To keep history across reboots, choose a
+                  path outside the app data folder.
+                </p>
+              </label>
+            )}
+
+            <div className="flex items-center justify-between">
+              <span className={clsx('text-xs', mutedText)}>
+                {status?.locked ? 'Locked — unlock from the clipboard window' : 'Unlocked'}
+              </span>
+              <button
+                onClick={() => lock().catch(console.error)}
+                disabled={!status || status.locked}
+                className={clsx(
+                  'px-4 py-2 rounded-lg text-sm font-medium transition-all disabled:opacity-50',
+                  isDark ? 'bg-white/10 hover:bg-white/15' : 'bg-gray-100 hover:bg-gray-200'
+                )}
+              >
+                Lock now
+              </button>
+            </div>
+          </div>
+        )}
+      </div>
+    </section>
+  )
+}
@@ -0,0 +1,150 @@
+import { useState, useEffect } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { clsx } from 'clsx'
+
+/** Clipboard manager history can be imported from (matches the Rust ImportSourceInfo) */
+interface ImportSourceInfo {
+  id: 'copyq' | 'gpaste' | 'klipper' | 'cliphist' | 'clipman'
+  name: string
+  default_path: string | null
+  available: boolean
+}
+
+interface ImportSummary {
+  added: number
+  duplicates: number
+  removed: number
+}
+
+interface ImportHistorySectionProps {
+  isDark: boolean
+}
+
+/**
+ * Settings card for importing history from CopyQ, GPaste, Klipper, cliphist or Clipman
+ */
+export function ImportHistorySection({ isDark }: ImportHistorySectionProps) {
+  const [sources, setSources] = useState<ImportSourceInfo[]>([])
+  const [source, setSource] = useState<ImportSourceInfo['id']>('copyq')
+  const [path, setPath] = useState('')
+  const [replace, setReplace] = useState(false)
+  const [isImporting, setIsImporting] = useState(false)
+  const [message, setMessage] = useState<string | null>(null)
+
+  useEffect(() => {
+    invoke<ImportSourceInfo[]>('list_import_sources')
+      .then((list) => {
+        setSources(list)
+        const found = list.find((s) => s.available)
+        if (found) setSource(found.id)
+      })
+      .catch(console.error)
+  }, [])
+
+  const selected = sources.find((s) => s.id === source)
+
+  const handleImport = async () => {
+    setIsImporting(true)
+    setMessage(null)
+    try {
+      const summary = await invoke<ImportSummary>('import_from_manager', {
+        source,
+        path: path.trim() || null,
+        mode: replace ? 'replace' : 'merge',
+      })
+      setMessage(`Imported ${summary.added} item(s), skipped ${summary.duplicates} duplicate(s)`)
+    } catch (err) {
+      console.error('Import failed:', err)
+      setMessage(`Error: ${err}`)
+    } finally {
+      setIsImporting(false)
+    }
+  }
+
+  const inputClass = clsx(
+    'w-full px-3 py-2 rounded-lg text-sm border outline-none',
+    isDark ? 'bg-black/20 border-white/10' : 'bg-white border-gray-200'
+  )
+
+  return (
+    <section
+      className={clsx(
+        'rounded-xl border shadow-sm overflow-hidden',
+        isDark ? 'bg-win11-bg-secondary border-white/5' : 'bg-white border-gray-200/60'
+      )}
+    >
+      <div className="p-6 border-b border-inherit">
+        <h2 className="text-base font-semibold mb-1">Import History</h2>
+        <p className={clsx('text-xs', isDark ? 'text-gray-400' : 'text-gray-500')}>
+          Bring over history and pins from another clipboard manager
+        </p>
+      </div>
+
+      <div className="p-6 space-y-4">
+        <div className="grid grid-cols-2 gap-4">
+          <label className="space-y-2 text-sm font-medium">
+            <span>Source</span>
+            <select
+              value={source}
+              onChange={(e) => setSource(e.target.value as ImportSourceInfo['id'])}
+              className={inputClass}
+            >
+              {sources.map((s) => (
+                <option key={s.id} value={s.id}>
+                  {s.name}
+                  {s.available ? '' : ' (not found)'}
+                </option>
+              ))}
+            </select>
+          </label>
+
+          <label className="space-y-2 text-sm font-medium">
+            <span>History file (optional)</span>
+            <input
+              type="text"
+              value={path}
+              placeholder={selected?.default_path ?? ''}
+              onChange={(e) => setPath(e.target.value)}
+              className={inputClass}
+            />
+          </label>
+        </div>
+
+        <div className="flex items-center justify-between">
+          <label className="flex items-center gap-2 text-sm">
+            <input
+              type="checkbox"
+              checked={replace}
+              onChange={(e) => setReplace(e.target.checked)}
+              className="accent-win11-bg-accent"
+            />
+            Replace current history
+          </label>
+
+          <button
+            onClick={handleImport}
+            disabled={isImporting}
+            className="px-4 py-2 bg-win11-bg-accent hover:opacity-90 disabled:opacity-50 text-white rounded-lg text-sm font-semibold transition-all"
+          >
+            {isImporting ? 'Importing...' : 'Import'}
+          </button>
+        </div>
+
+        {message && (
+          <p
+            className={clsx(
+              'text-xs',
+              message.startsWith('Error')
+                ? 'text-red-500'
+                : isDark
+                  ? 'text-gray-400'
+                  : 'text-gray-500'
+            )}
+          >
+            {message}
+          </p>
+        )}
+      </div>
+    </section>
+  )
+}
@@ -0,0 +1,198 @@
+import { useState } from 'react'
+import { clsx } from 'clsx'
+import type { PasteChord, PasteSettings } from '../types/clipboard'
+
+/** Matches PasteSettings::default() in Rust */
+export const DEFAULT_PASTE_SETTINGS: PasteSettings = {
+  chord: 'ctrl_v',
+  detect_terminals: true,
+  app_overrides: [],
+  type_delay_ms: 12,
+  type_abort_shortcut: 'Escape',
+}
+
+const CHORDS: { id: PasteChord; label: string }[] = [
+  { id: 'ctrl_v', label: 'Ctrl+V' },
+  { id: 'ctrl_shift_v', label: 'Ctrl+Shift+V' },
+  { id: 'shift_insert', label: 'Shift+Insert' },
+  { id: 'middle_click', label: 'Middle click' },
+  { id: 'none', label: 'Copy only (paste manually)' },
+]
+
+/** Pasting-related subset of the user settings */
+export interface PasteSectionSettings {
+  restore_clipboard_after_paste: boolean
+  paste: PasteSettings
+}
+
+interface PasteSectionProps {
+  isDark: boolean
+  settings: PasteSectionSettings
+  onChange: (patch: Partial<PasteSectionSettings>) => void
+}
+
+/**
+ * Settings card for the paste keystroke, per-app overrides, type-out mode and clipboard restoring
+ */
+export function PasteSection({ isDark, settings, onChange }: PasteSectionProps) {
+  const [newApp, setNewApp] = useState('')
+  const [newChord, setNewChord] = useState<PasteChord>('ctrl_shift_v')
+
+  const paste = settings.paste
+  const updatePaste = (patch: Partial<PasteSettings>) => onChange({ paste: { ...paste, ...patch } })
+
+  const addOverride = () => {
+    const app = newApp.trim()
+    if (!app) return
+    const others = paste.app_overrides.filter((o) => o.app.toLowerCase() !== app.toLowerCase())
+    updatePaste({ app_overrides: [...others, { app, chord: newChord }] })
+    setNewApp('')
+  }
+
+  const removeOverride = (app: string) => {
+    updatePaste({ app_overrides: paste.app_overrides.filter((o) => o.app !== app) })
+  }
+
+  const mutedText = isDark ? 'text-gray-400' : 'text-gray-500'
+  const inputClass = clsx(
+    'w-full px-3 py-2 rounded-lg text-sm border outline-none',
+    isDark ? 'bg-black/20 border-white/10' : 'bg-white border-gray-200'
+  )
+  const chordLabel = (id: PasteChord) => CHORDS.find((c) => c.id === id)?.label ?? id
+
+  return (
+    <section
+      className={clsx(
+        'rounded-xl border shadow-sm overflow-hidden',
+        isDark ? 'bg-win11-bg-secondary border-white/5' : 'bg-white border-gray-200/60'
+      )}
+    >
+      <div className="p-6 border-b border-inherit">
+        <h2 className="text-base font-semibold mb-1">Pasting</h2>
+        <p className={clsx('text-xs', mutedText)}>
+          Control how items are pasted and what happens to the clipboard
+        </p>
+      </div>
+
+      <div className="p-6 space-y-4">
+        <label className="block space-y-2 text-sm font-medium">
+          <span>Paste with</span>
+          <select
+            value={paste.chord}
+            onChange={(e) => updatePaste({ chord: e.target.value as PasteChord })}
+            className={inputClass}
+          >
+            {CHORDS.map((c) => (
+              <option key={c.id} value={c.id}>
+                {c.label}
+              </option>
+            ))}
+          </select>
+        </label>
+
+        <label className="flex items-center gap-2 text-sm font-medium">
+          <input
+            type="checkbox"
+            checked={paste.detect_terminals}
+            onChange={(e) => updatePaste({ detect_terminals: e.target.checked })}
+            className="accent-win11-bg-accent"
+          />
+          Use Ctrl+Shift+V in terminals
+        </label>
+
+        <div className="space-y-2">
+          <span className="text-sm font-medium">Per-application overrides</span>
+          <p className={clsx('text-xs', mutedText)}>
+            Matched on the window class (X11 and XWayland apps), e.g. &quot;kitty&quot; or
+            &quot;org.wezfurlong.wezterm&quot;
+          </p>
+
+          {paste.app_overrides.map((o) => (
+            <div key={o.app} className="flex items-center justify-between text-sm">
+              <span>
+                {o.app} <span className={mutedText}>→ {chordLabel(o.chord)}</span>
+              </span>
+              <button
+                onClick={() => removeOverride(o.app)}
+                className="text-xs text-red-500 hover:underline"
+              >
+                Remove
+              </button>
+            </div>
+          ))}
+
+          <div className="flex gap-2">
+            <input
+              type="text"
+              value={newApp}
+              placeholder="Window class"
+              onChange={(e) => setNewApp(e.target.value)}
+              onKeyDown={(e) => e.key === 'Enter' && addOverride()}
+              className={inputClass}
+            />
+            <select
+              value={newChord}
+              onChange={(e) => setNewChord(e.target.value as PasteChord)}
+              className={inputClass}
+            >
+              {CHORDS.map((c) => (
+                <option key={c.id} value={c.id}>
+                  {c.label}
+                </option>
+              ))}
+            </select>
+            <button
+              onClick={addOverride}
+              className="px-4 py-2 bg-win11-bg-accent hover:opacity-90 text-white rounded-lg text-sm font-semibold transition-all"
+            >
+              Add
+            </button>
+          </div>
+        </div>
+
+        <div className="space-y-2">
+          <span className="text-sm font-medium">Type-out mode</span>
+          <p className={clsx('text-xs', mutedText)}>
+            Shift+Enter or the keyboard button types an item key by key, for fields that block
+            pasting. On Wayland, bind a key to &quot;--abort-typing&quot; to stop it.
+          </p>
+          <div className="flex gap-2">
+            <label className="flex-1 space-y-1 text-sm">
+              <span className={mutedText}>Delay between keys (ms)</span>
+              <input
+                type="number"
+                min={0}
+                max={500}
+                value={paste.type_delay_ms}
+                onChange={(e) =>
+                  updatePaste({ type_delay_ms: Math.max(0, Number(e.target.value) || 0) })
+                }
+                className={inputClass}
+              />
+            </label>
+            <label className="flex-1 space-y-1 text-sm">
+              <span className={mutedText}>Abort shortcut</span>
+              <input
+                type="text"
+                value={paste.type_abort_shortcut}
+                placeholder="Escape"
+                onChange={(e) => updatePaste({ type_abort_shortcut: e.target.value })}
+                className={inputClass}
+              />
+            </label>
+          </div>
+        </div>
+
+        <label className="flex items-center gap-2 text-sm font-medium">
+          <input
+            type="checkbox"
+            checked={settings.restore_clipboard_after_paste}
+            onChange={(e) => onChange({ restore_clipboard_after_paste: e.target.checked })}
+            className="accent-win11-bg-accent"
+          />
+          Restore the previous clipboard contents after pasting
+        </label>
+      </div>
+    </section>
+  )
+}
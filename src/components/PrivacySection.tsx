@@ -0,0 +1,133 @@
+import { useState, useEffect } from 'react'
+import { clsx } from 'clsx'
+import type { PrivacySettings } from '../types/clipboard'
+
+/** Matches PrivacySettings::default() in Rust */
+export const DEFAULT_PRIVACY_SETTINGS: PrivacySettings = {
+  expire_secrets: false,
+  flagged_apps: [],
+  expiring_ttl_secs: 60,
+  clear_on_lock: false,
+  clear_on_idle_minutes: 0,
+  clear_on_quit: false,
+}
+
+interface PrivacySectionProps {
+  isDark: boolean
+  settings: PrivacySettings
+  onChange: (patch: Partial<PrivacySettings>) => void
+}
+
+/**
+ * Settings card for expiring items and automatic history clearing
+ */
+export function PrivacySection({ isDark, settings, onChange }: PrivacySectionProps) {
+  // Edited as free text, committed on blur
+  const [flaggedApps, setFlaggedApps] = useState(settings.flagged_apps.join(', '))
+
+  useEffect(() => {
+    setFlaggedApps(settings.flagged_apps.join(', '))
+  }, [settings.flagged_apps])
+
+  const commitFlaggedApps = () => {
+    const apps = flaggedApps
+      .split(',')
+      .map((app) => app.trim())
+      .filter(Boolean)
+    onChange({ flagged_apps: apps })
+  }
+
+  const mutedText = isDark ? 'text-gray-400' : 'text-gray-500'
+  const inputClass = clsx(
+    'w-full px-3 py-2 rounded-lg text-sm border outline-none',
+    isDark ? 'bg-black/20 border-white/10' : 'bg-white border-gray-200'
+  )
+
+  return (
+    <section
+      className={clsx(
+        'rounded-xl border shadow-sm overflow-hidden',
+        isDark ? 'bg-win11-bg-secondary border-white/5' : 'bg-white border-gray-200/60'
+      )}
+    >
+      <div className="p-6 border-b border-inherit">
+        <h2 className="text-base font-semibold mb-1">Privacy</h2>
+        <p className={clsx('text-xs', mutedText)}>
+          Forget sensitive items automatically. Pinned items are never removed.
+        </p>
+      </div>
+
+      <div className="p-6 space-y-4">
+        <label className="flex items-center gap-2 text-sm font-medium">
+          <input
+            type="checkbox"
+            checked={settings.expire_secrets}
+            onChange={(e) => onChange({ expire_secrets: e.target.checked })}
+            className="accent-win11-bg-accent"
+          />
+          Expire items that look like passwords or tokens
+        </label>
+
+        <div className="grid grid-cols-2 gap-4">
+          <label className="space-y-2 text-sm font-medium">
+            <span>Always expire copies from</span>
+            <input
+              type="text"
+              value={flaggedApps}
+              placeholder="KeePassXC, Bitwarden"
+              onChange={(e) => setFlaggedApps(e.target.value)}
+              onBlur={commitFlaggedApps}
+              className={inputClass}
+            />
+          </label>
+
+          <label className="space-y-2 text-sm font-medium">
+            <span>Expire after (seconds)</span>
+            <input
+              type="number"
+              min={1}
+              value={settings.expiring_ttl_secs}
+              onChange={(e) =>
+                onChange({ expiring_ttl_secs: Math.max(1, Number(e.target.value) || 1) })
+              }
+              className={inputClass}
+            />
+          </label>
+        </div>
+
+        <label className="flex items-center gap-2 text-sm font-medium">
+          <input
+            type="checkbox"
+            checked={settings.clear_on_lock}
+            onChange={(e) => onChange({ clear_on_lock: e.target.checked })}
+            className="accent-win11-bg-accent"
+          />
+          Clear history when the screen locks
+        </label>
+
+        <label className="flex items-center gap-2 text-sm font-medium">
+          <input
+            type="checkbox"
+            checked={settings.clear_on_quit}
+            onChange={(e) => onChange({ clear_on_quit: e.target.checked })}
+            className="accent-win11-bg-accent"
+          />
+          Clear history on quit
+        </label>
+
+        <label className="block space-y-2 text-sm font-medium">
+          <span>Clear history after being idle for (minutes, 0 = never)</span>
+          <input
+            type="number"
+            min={0}
+            value={settings.clear_on_idle_minutes}
+            onChange={(e) =>
+              onChange({ clear_on_idle_minutes: Math.max(0, Number(e.target.value) || 0) })
+            }
+            className={inputClass}
+          />
+        </label>
+      </div>
+    </section>
+  )
+}
@@ -19,9 +19,25 @@ interface PermissionStatus {
   uinput_accessible: boolean
   uinput_path: string
   user_in_input_group: boolean
+  virtual_keyboard_available: boolean
+  remote_desktop_available: boolean
+  remote_desktop_approved: boolean
   suggestion: string
 }
 
+type RegistrationOutcome =
+  | { status: 'registered' }
+  | { status: 'already_present' }
+  | { status: 'needs_reload'; detail: string }
+  | { status: 'failed'; detail: string }
+
+interface ShortcutStatus {
+  name: string
+  chord: string
+  outcome: RegistrationOutcome | null
+  verified: boolean | null
+}
+
 interface ShortcutToolsStatus {
   desktop_environment: string
   gsettings_available: boolean
@@ -32,6 +48,7 @@ interface ShortcutToolsStatus {
   has_conflicts: boolean
   conflict_count: number
   can_auto_resolve_conflicts: boolean
+  shortcuts?: ShortcutStatus[]
 }
 
 interface ShortcutConflict {
@@ -131,6 +148,8 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
   const [fixError, setFixError] = useState<string | null>(null)
   const [registeringShortcut, setRegisteringShortcut] = useState(false)
   const [shortcutRegistered, setShortcutRegistered] = useState(false)
+  const [registrationMessage, setRegistrationMessage] = useState<string | null>(null)
+  const [registrationError, setRegistrationError] = useState<string | null>(null)
   const [showManualInstructions, setShowManualInstructions] = useState(false)
   const [resolvingConflicts, setResolvingConflicts] = useState(false)
   const [conflictsResolved, setConflictsResolved] = useState(false)
@@ -182,6 +201,13 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
     }
   }
 
+  // uinput isn't needed when the compositor offers a virtual keyboard
+  const canPaste =
+    !!permissions &&
+    (permissions.uinput_accessible ||
+      permissions.virtual_keyboard_available ||
+      permissions.remote_desktop_available)
+
   const checkShortcutTools = async () => {
     try {
       const status = await invoke<ShortcutToolsStatus>('check_shortcut_tools')
@@ -233,15 +259,19 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
 
   const handleRegisterShortcut = async () => {
     setRegisteringShortcut(true)
+    setRegistrationError(null)
     try {
-      await invoke<string>('register_de_shortcut')
+      const message = await invoke<string>('register_de_shortcut')
+      setRegistrationMessage(message)
       setShortcutRegistered(true)
-      setTimeout(() => setStep(3), 1500)
     } catch (e) {
       console.error('Failed to register shortcut:', e)
+      setRegistrationError(String(e))
       setShowManualInstructions(true)
     } finally {
       setRegisteringShortcut(false)
+      // Show what the desktop environment reports for each shortcut now
+      await checkShortcutTools()
     }
   }
 
@@ -369,10 +399,10 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
         <div
           className={clsx(
             'mb-4',
-            statusCardClass(permissions.uinput_accessible ? 'success' : 'warning')
+            statusCardClass(canPaste ? 'success' : 'warning')
           )}
         >
-          {permissions.uinput_accessible ? (
+          {canPaste ? (
             <CheckCircle className="w-5 h-5 flex-shrink-0 mt-0.5" />
           ) : (
             <AlertTriangle className="w-5 h-5 flex-shrink-0 mt-0.5" />
@@ -384,13 +414,13 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
       {fixError && <div className={clsx('mb-4', statusCardClass('error'))}>{fixError}</div>}
 
       <div className="flex gap-3 justify-center">
-        {!permissions?.uinput_accessible && (
+        {!canPaste && (
           <Button id="fix" onClick={handleFixPermissions} disabled={fixing}>
             {fixing ? 'Fixing...' : 'Fix Now'}
           </Button>
         )}
         <Button id="perm-continue" onClick={() => setStep(2)} primary>
-          {permissions?.uinput_accessible ? 'Continue' : 'Skip'}
+          {canPaste ? 'Continue' : 'Skip'}
         </Button>
       </div>
     </div>,
@@ -456,6 +486,22 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
               </strong>
             </span>
           </div>
+          {(shortcutRegistered || registrationError) &&
+            shortcutTools.shortcuts?.map((shortcut) => (
+              <div key={shortcut.name} className="flex items-center justify-between mt-2 text-xs">
+                <span>
+                  {shortcut.name}{' '}
+                  <span className="font-mono opacity-75">{shortcut.chord}</span>
+                </span>
+                <span className="opacity-75">
+                  {shortcut.verified === null
+                    ? 'Unknown'
+                    : shortcut.verified
+                      ? 'Registered'
+                      : 'Not registered'}
+                </span>
+              </div>
+            ))}
         </div>
       )}
 
@@ -465,13 +511,18 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
           <AlertCircle className="w-5 h-5 flex-shrink-0 mt-0.5" />
           <div className="flex-1">
             <p className="font-medium mb-1">
-              {conflicts.conflicts.length} shortcut conflict
+              {conflicts.conflicts.length} conflict
               {conflicts.conflicts.length > 1 ? 's' : ''} detected
             </p>
-            <p className="text-xs opacity-90 mb-2">
-              Super+V is already used by {conflicts.conflicts[0].owner} for "
-              {conflicts.conflicts[0].current_action}"
-            </p>
+            <ul className="text-xs opacity-90 mb-2 space-y-0.5">
+              {conflicts.conflicts.map((conflict, i) => (
+                <li key={`${conflict.owner}-${i}`}>
+                  {conflict.binding
+                    ? `${conflict.binding} is already used by ${conflict.owner} for "${conflict.current_action}"`
+                    : `${conflict.owner} clipboard manager: ${conflict.current_action}`}
+                </li>
+              ))}
+            </ul>
             {conflicts.can_auto_resolve && (
               <div className="space-y-1">
                 <Button
@@ -501,7 +552,7 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
       {conflictsResolved && (
         <div className={clsx('mb-4', statusCardClass('success'))}>
           <CheckCircle className="w-5 h-5 flex-shrink-0 mt-0.5" />
-          <span>Conflicts resolved! Super+V is now available.</span>
+          <span>Conflicts resolved! Your shortcuts are now available.</span>
         </div>
       )}
 
@@ -518,7 +569,20 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
       {shortcutRegistered && (
         <div className={clsx('mb-4', statusCardClass('success'))}>
           <CheckCircle className="w-5 h-5 flex-shrink-0 mt-0.5" />
-          <span>Shortcut registered successfully!</span>
+          <div>
+            <p className="font-medium">Shortcut registered successfully!</p>
+            {registrationMessage && <p className="text-xs opacity-90">{registrationMessage}</p>}
+          </div>
+        </div>
+      )}
+
+      {registrationError && (
+        <div className={clsx('mb-4', statusCardClass('error'))}>
+          <AlertTriangle className="w-5 h-5 flex-shrink-0 mt-0.5" />
+          <div>
+            <p className="font-medium">Failed to register shortcut</p>
+            <p className="text-xs opacity-90 whitespace-pre-line">{registrationError}</p>
+          </div>
         </div>
       )}
 
@@ -0,0 +1,212 @@
+import { useState, useEffect } from 'react'
+import { clsx } from 'clsx'
+import type { ActionBinding, ActiveTab, ShortcutAction, ShortcutBindings } from '../types/clipboard'
+
+/** Matches ShortcutBindings::default() in Rust */
+export const DEFAULT_SHORTCUT_BINDINGS: ShortcutBindings = {
+  open: 'Super+V',
+  alternate: 'Ctrl+Alt+V',
+  actions: [],
+}
+
+const TABS: { id: ActiveTab; label: string }[] = [
+  { id: 'clipboard', label: 'Clipboard' },
+  { id: 'emoji', label: 'Emoji' },
+  { id: 'gifs', label: 'GIFs' },
+  { id: 'kaomoji', label: 'Kaomoji' },
+  { id: 'symbols', label: 'Symbols' },
+]
+
+/** Every action that can be bound, keyed by a select-friendly id */
+const ACTIONS: { id: string; label: string; action: ShortcutAction }[] = [
+  ...Array.from({ length: 9 }, (_, i) => ({
+    id: `paste_item:${i + 1}`,
+    label: `Paste item ${i + 1}`,
+    action: { kind: 'paste_item', index: i + 1 } as ShortcutAction,
+  })),
+  { id: 'paste_plain', label: 'Paste last item as plain text', action: { kind: 'paste_plain' } },
+  ...TABS.map((t) => ({
+    id: `open_tab:${t.id}`,
+    label: `Open ${t.label} tab`,
+    action: { kind: 'open_tab', tab: t.id } as ShortcutAction,
+  })),
+  { id: 'toggle_pause', label: 'Pause / resume recording', action: { kind: 'toggle_pause' } },
+]
+
+const actionId = (action: ShortcutAction) => {
+  switch (action.kind) {
+    case 'paste_item':
+      return `paste_item:${action.index}`
+    case 'open_tab':
+      return `open_tab:${action.tab}`
+    default:
+      return action.kind
+  }
+}
+
+const actionLabel = (action: ShortcutAction) =>
+  ACTIONS.find((a) => a.id === actionId(action))?.label ?? action.kind
+
+interface ShortcutSectionProps {
+  isDark: boolean
+  settings: ShortcutBindings
+  onChange: (patch: Partial<ShortcutBindings>) => void
+}
+
+/**
+ * Settings card for the key combos registered with the desktop environment
+ */
+export function ShortcutSection({ isDark, settings, onChange }: ShortcutSectionProps) {
+  // Edited as free text, committed on blur so every keystroke doesn't re-register
+  const [open, setOpen] = useState(settings.open)
+  const [alternate, setAlternate] = useState(settings.alternate)
+
+  useEffect(() => {
+    setOpen(settings.open)
+    setAlternate(settings.alternate)
+  }, [settings.open, settings.alternate])
+
+  const [newAction, setNewAction] = useState(ACTIONS[0].id)
+  const [newChord, setNewChord] = useState('')
+
+  const commit = () => {
+    const patch = { open: open.trim(), alternate: alternate.trim() }
+    if (patch.open !== settings.open || patch.alternate !== settings.alternate) {
+      onChange(patch)
+    }
+  }
+
+  // An action has at most one shortcut: adding it again replaces the old one
+  const setActions = (added: ActionBinding[]) => {
+    const addedIds = added.map((b) => actionId(b.action))
+    const others = settings.actions.filter((b) => !addedIds.includes(actionId(b.action)))
+    onChange({ actions: [...others, ...added] })
+  }
+
+  const addAction = () => {
+    const chord = newChord.trim()
+    const entry = ACTIONS.find((a) => a.id === newAction)
+    if (!chord || !entry) return
+    setActions([{ action: entry.action, chord }])
+    setNewChord('')
+  }
+
+  const addQuickPasteSlots = () => {
+    setActions(
+      ACTIONS.filter((a) => a.action.kind === 'paste_item').map((a, i) => ({
+        action: a.action,
+        chord: `Super+${i + 1}`,
+      }))
+    )
+  }
+
+  const removeAction = (id: string) => {
+    onChange({ actions: settings.actions.filter((b) => actionId(b.action) !== id) })
+  }
+
+  const mutedText = isDark ? 'text-gray-400' : 'text-gray-500'
+  const inputClass = clsx(
+    'w-full px-3 py-2 rounded-lg text-sm border outline-none',
+    isDark ? 'bg-black/20 border-white/10' : 'bg-white border-gray-200'
+  )
+
+  return (
+    <section
+      className={clsx(
+        'rounded-xl border shadow-sm overflow-hidden',
+        isDark ? 'bg-win11-bg-secondary border-white/5' : 'bg-white border-gray-200/60'
+      )}
+    >
+      <div className="p-6 border-b border-inherit">
+        <h2 className="text-base font-semibold mb-1">Shortcuts</h2>
+        <p className={clsx('text-xs', mutedText)}>
+          Key combos that open the clipboard history, e.g. &quot;Super+V&quot; or
+          &quot;Ctrl+Shift+F1&quot;. They are registered with your desktop environment.
+        </p>
+      </div>
+
+      <div className="p-6 flex gap-2">
+        <label className="flex-1 space-y-1 text-sm">
+          <span className={mutedText}>Shortcut</span>
+          <input
+            type="text"
+            value={open}
+            placeholder="Super+V"
+            onChange={(e) => setOpen(e.target.value)}
+            onBlur={commit}
+            onKeyDown={(e) => e.key === 'Enter' && commit()}
+            className={inputClass}
+          />
+        </label>
+        <label className="flex-1 space-y-1 text-sm">
+          <span className={mutedText}>Alternate (optional)</span>
+          <input
+            type="text"
+            value={alternate}
+            placeholder="Ctrl+Alt+V"
+            onChange={(e) => setAlternate(e.target.value)}
+            onBlur={commit}
+            onKeyDown={(e) => e.key === 'Enter' && commit()}
+            className={inputClass}
+          />
+        </label>
+      </div>
+
+      <div className="px-6 pb-6 space-y-2">
+        <span className="text-sm font-medium">Action shortcuts</span>
+        <p className={clsx('text-xs', mutedText)}>
+          Extra shortcuts that act without opening the history, e.g. Super+1 to paste the first
+          item or Super+Period for the emoji tab
+        </p>
+
+        {settings.actions.map((b) => (
+          <div key={actionId(b.action)} className="flex items-center justify-between text-sm">
+            <span>
+              {actionLabel(b.action)} <span className={mutedText}>→ {b.chord}</span>
+            </span>
+            <button
+              onClick={() => removeAction(actionId(b.action))}
+              className="text-xs text-red-500 hover:underline"
+            >
+              Remove
+            </button>
+          </div>
+        ))}
+
+        <div className="flex gap-2">
+          <select
+            value={newAction}
+            onChange={(e) => setNewAction(e.target.value)}
+            className={inputClass}
+          >
+            {ACTIONS.map((a) => (
+              <option key={a.id} value={a.id}>
+                {a.label}
+              </option>
+            ))}
+          </select>
+          <input
+            type="text"
+            value={newChord}
+            placeholder="Super+1"
+            onChange={(e) => setNewChord(e.target.value)}
+            onKeyDown={(e) => e.key === 'Enter' && addAction()}
+            className={inputClass}
+          />
+          <button
+            onClick={addAction}
+            className="px-4 py-2 bg-win11-bg-accent hover:opacity-90 text-white rounded-lg text-sm font-semibold transition-all"
+          >
+            Add
+          </button>
+        </div>
+        <button
+          onClick={addQuickPasteSlots}
+          className="text-xs text-win11-bg-accent hover:underline"
+        >
+          Bind Super+1 … Super+9 to the first nine items
+        </button>
+      </div>
+    </section>
+  )
+}
@@ -0,0 +1,85 @@
+import { useState } from 'react'
+import { clsx } from 'clsx'
+import type { HistoryStoreStatus } from '../types/clipboard'
+
+interface UnlockHistoryPromptProps {
+  status: HistoryStoreStatus
+  onUnlock: (passphrase?: string) => Promise<void>
+  isDark: boolean
+}
+
+/**
+ * Shown above the history while encrypted history is locked
+ */
+export function UnlockHistoryPrompt({ status, onUnlock, isDark }: UnlockHistoryPromptProps) {
+  const [passphrase, setPassphrase] = useState('')
+  const [error, setError] = useState<string | null>(null)
+  const [isUnlocking, setIsUnlocking] = useState(false)
+
+  const usesPassphrase = status.key_source === 'passphrase'
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault()
+    setIsUnlocking(true)
+    setError(null)
+    try {
+      await onUnlock(usesPassphrase ? passphrase : undefined)
+      setPassphrase('')
+    } catch (err) {
+      setError(String(err))
+    } finally {
+      setIsUnlocking(false)
+    }
+  }
+
+  return (
+    <form
+      onSubmit={handleSubmit}
+      className={clsx(
+        'mx-3 mt-2 p-3 rounded-win11 space-y-2 text-sm',
+        isDark ? 'bg-white/5' : 'bg-black/5'
+      )}
+    >
+      <p className="font-medium">
+        {status.needs_new_passphrase ? 'Set a passphrase for saved history' : 'History is locked'}
+      </p>
+      <p
+        className={clsx(
+          'text-xs',
+          isDark ? 'text-win11-text-secondary' : 'text-win11Light-text-secondary'
+        )}
+      >
+        {usesPassphrase
+          ? 'Saved items appear once the history is unlocked.'
+          : status.key_source === 'keyring'
+            ? 'The encryption key could not be read from the keyring.'
+            : `The key file ${status.key_file ?? ''} could not be read.`}
+      </p>
+
+      <div className="flex gap-2">
+        {usesPassphrase && (
+          <input
+            type="password"
+            value={passphrase}
+            autoFocus
+            placeholder="Passphrase"
+            onChange={(e) => setPassphrase(e.target.value)}
+            className={clsx(
+              'flex-1 px-2 py-1 rounded text-sm outline-none border',
+              isDark ? 'bg-black/20 border-white/10' : 'bg-white border-gray-200'
+            )}
+          />
+        )}
+        <button
+          type="submit"
+          disabled={isUnlocking || (usesPassphrase && !passphrase)}
+          className="px-3 py-1 rounded bg-win11-bg-accent text-white text-sm font-medium disabled:opacity-50"
+        >
+          {usesPassphrase ? 'Unlock' : 'Retry'}
+        </button>
+      </div>
+
+      {error && <p className="text-xs text-red-500">{error}</p>}
+    </form>
+  )
+}
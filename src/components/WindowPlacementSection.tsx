@@ -0,0 +1,79 @@
+import { clsx } from 'clsx'
+import type { WindowPlacement } from '../types/clipboard'
+
+const PLACEMENTS: { id: WindowPlacement; label: string; hint: string }[] = [
+  {
+    id: 'auto',
+    label: 'Automatic',
+    hint: 'At the mouse cursor on X11, where you last left it on Wayland',
+  },
+  {
+    id: 'caret',
+    label: 'At the text cursor',
+    hint: 'Below the caret of the focused app. Needs accessibility support (AT-SPI); falls back to the mouse cursor',
+  },
+  {
+    id: 'mouse',
+    label: 'At the mouse cursor',
+    hint: 'Falls back to the centre of the screen where the cursor position is unknown (Wayland)',
+  },
+  { id: 'monitor_center', label: 'Centre of the screen', hint: 'Centre of the active monitor' },
+  {
+    id: 'saved',
+    label: 'Where I last moved it',
+    hint: 'Remembered for each monitor; bottom-centre on monitors where you have not moved it yet',
+  },
+  { id: 'bottom_center', label: 'Bottom-centre', hint: 'Above the taskbar of the active monitor' },
+]
+
+interface WindowPlacementSectionProps {
+  isDark: boolean
+  placement: WindowPlacement
+  onChange: (placement: WindowPlacement) => void
+}
+
+/**
+ * Settings card for where the clipboard history window opens
+ */
+export function WindowPlacementSection({
+  isDark,
+  placement,
+  onChange,
+}: WindowPlacementSectionProps) {
+  const mutedText = isDark ? 'text-gray-400' : 'text-gray-500'
+  const current = PLACEMENTS.find((p) => p.id === placement) ?? PLACEMENTS[0]
+
+  const inputClass = clsx(
+    'w-full px-3 py-2 rounded-lg text-sm border outline-none',
+    isDark ? 'bg-black/20 border-white/10' : 'bg-white border-gray-200'
+  )
+
+  return (
+    <section
+      className={clsx(
+        'rounded-xl border shadow-sm overflow-hidden',
+        isDark ? 'bg-win11-bg-secondary border-white/5' : 'bg-white border-gray-200/60'
+      )}
+    >
+      <div className="p-6 border-b border-inherit">
+        <h2 className="text-base font-semibold mb-1">Window Position</h2>
+        <p className={clsx('text-xs', mutedText)}>Where the clipboard history opens</p>
+      </div>
+
+      <div className="p-6 space-y-2">
+        <select
+          value={current.id}
+          onChange={(e) => onChange(e.target.value as WindowPlacement)}
+          className={inputClass}
+        >
+          {PLACEMENTS.map((p) => (
+            <option key={p.id} value={p.id}>
+              {p.label}
+            </option>
+          ))}
+        </select>
+        <p className={clsx('text-xs', mutedText)}>{current.hint}</p>
+      </div>
+    </section>
+  )
+}
@@ -83,6 +83,15 @@ export function useClipboardHistory() {
     [fetchHistory]
   )
 
+  // Type an item out key by key (for fields that block pasting)
+  const typeItem = useCallback(async (id: string) => {
+    try {
+      await invoke('type_item', { id })
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err))
+    }
+  }, [])
+
   // Listen for clipboard changes
   useEffect(() => {
     fetchHistory()
@@ -150,5 +159,6 @@ export function useClipboardHistory() {
     deleteItem,
     togglePin,
     pasteItem,
+    typeItem,
   }
 }
@@ -0,0 +1,40 @@
+import { useState, useEffect, useCallback } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import type { HistoryStoreStatus } from '../types/clipboard'
+
+/**
+ * Hook for the on-disk history store (persistence, encryption and lock state)
+ */
+export function useHistoryStore() {
+  const [status, setStatus] = useState<HistoryStoreStatus | null>(null)
+
+  useEffect(() => {
+    invoke<HistoryStoreStatus>('get_history_store_status')
+      .then(setStatus)
+      .catch(console.error)
+
+    const unlistenPromise = listen<HistoryStoreStatus>('history-store-changed', (event) => {
+      setStatus(event.payload)
+    })
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten())
+    }
+  }, [])
+
+  // Unlock with a passphrase, or with the key file / keyring when omitted
+  const unlock = useCallback(async (passphrase?: string) => {
+    const newStatus = await invoke<HistoryStoreStatus>('unlock_history', {
+      passphrase: passphrase ?? null,
+    })
+    setStatus(newStatus)
+  }, [])
+
+  const lock = useCallback(async () => {
+    const newStatus = await invoke<HistoryStoreStatus>('lock_history')
+    setStatus(newStatus)
+  }, [])
+
+  return { status, unlock, lock }
+}
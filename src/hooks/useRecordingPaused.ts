@@ -0,0 +1,29 @@
+import { useState, useEffect, useCallback } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+
+/**
+ * Hook for the paused state of clipboard recording (toggled by a shortcut or from the UI)
+ */
+export function useRecordingPaused() {
+  const [paused, setPaused] = useState(false)
+
+  useEffect(() => {
+    invoke<boolean>('get_recording_paused').then(setPaused).catch(console.error)
+
+    const unlistenPromise = listen<boolean>('recording-paused-changed', (event) => {
+      setPaused(event.payload)
+    })
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten())
+    }
+  }, [])
+
+  const resume = useCallback(async () => {
+    await invoke('set_recording_paused', { paused: false })
+    setPaused(false)
+  }, [])
+
+  return { paused, resume }
+}
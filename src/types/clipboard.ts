@@ -27,6 +27,10 @@ export interface ClipboardItem {
   timestamp: string
   pinned: boolean
   preview: string
+  /** Collections assigned by clipboard rules */
+  collections?: string[]
+  /** When the item is removed automatically (secrets, copies from flagged apps) */
+  expires_at?: string
 }
 
 /** Active tab in the UI */
@@ -51,4 +55,116 @@ export interface UserSettings {
 
   enable_ui_polish: boolean
   custom_kaomojis: CustomKaomoji[]
+  window_placement: WindowPlacement
+  shortcuts: ShortcutBindings
+  restore_clipboard_after_paste: boolean
+  paste: PasteSettings
+  clipboard_rules: ClipboardRule[]
+
+  persist_history: boolean
+  encrypt_history: boolean
+  history_key_source: HistoryKeySource
+  history_key_file: string | null
+
+  privacy: PrivacySettings
+}
+
+/** Where the history window opens; matches WindowPlacement in Rust */
+export type WindowPlacement =
+  | 'auto'
+  | 'caret'
+  | 'mouse'
+  | 'monitor_center'
+  | 'saved'
+  | 'bottom_center'
+
+/** Global shortcuts in canonical form ("Super+V"); an empty alternate is disabled */
+export interface ShortcutBindings {
+  open: string
+  alternate: string
+  actions: ActionBinding[]
+}
+
+/** What an extra shortcut does; matches ShortcutAction in Rust */
+export type ShortcutAction =
+  | { kind: 'paste_item'; index: number }
+  | { kind: 'open_tab'; tab: ActiveTab }
+  | { kind: 'paste_plain' }
+  | { kind: 'toggle_pause' }
+
+export interface ActionBinding {
+  action: ShortcutAction
+  chord: string
+}
+
+/** What is sent to the focused window to paste */
+export type PasteChord = 'ctrl_v' | 'ctrl_shift_v' | 'shift_insert' | 'middle_click' | 'none'
+
+/** Paste chord, optionally overridden per application (matched on WM_CLASS) */
+export interface PasteSettings {
+  chord: PasteChord
+  detect_terminals: boolean
+  app_overrides: { app: string; chord: PasteChord }[]
+  type_delay_ms: number
+  type_abort_shortcut: string
+}
+
+/** Expiring items and clear-on-lock / idle / quit policies */
+export interface PrivacySettings {
+  expire_secrets: boolean
+  flagged_apps: string[]
+  expiring_ttl_secs: number
+  clear_on_lock: boolean
+  /** 0 disables clearing on idle */
+  clear_on_idle_minutes: number
+  clear_on_quit: boolean
+}
+
+/** Where the history encryption key comes from */
+export type HistoryKeySource = 'passphrase' | 'key_file' | 'keyring'
+
+/** State of the on-disk history store */
+export interface HistoryStoreStatus {
+  persist: boolean
+  encrypted: boolean
+  key_source: HistoryKeySource
+  locked: boolean
+  needs_new_passphrase: boolean
+  key_file: string | null
+}
+
+/** Action executed when a clipboard rule matches */
+export type RuleAction =
+  | { type: 'StripTrackingParams' }
+  | { type: 'Replace'; data: { with: string } }
+  | { type: 'Trim' }
+  | { type: 'SkipRecording' }
+  | { type: 'AutoPin' }
+  | { type: 'Tag'; data: { collection: string } }
+
+/** Regex rule evaluated on copied text before it is recorded */
+export interface ClipboardRule {
+  id: string
+  name: string
+  enabled: boolean
+  pattern: string
+  action: RuleAction
+}
+
+/** Result of the `preview_clipboard_rules` dry run */
+export interface RuleDryRun {
+  rules: {
+    id: string
+    name: string
+    enabled: boolean
+    matched: boolean
+    error: string | null
+    text_after: string | null
+  }[]
+  outcome: {
+    text: string
+    skip: boolean
+    pin: boolean
+    collections: string[]
+  }
 }